@@ -0,0 +1,54 @@
+//! Transaction signing benchmark, with no network involved
+//!
+//! Measures `LighterClient::sign_transaction` on one fixed CREATE_ORDER
+//! transaction: building the Poseidon2 message hash plus the Schnorr signature
+//! with the client's prepared signer. Alongside it, the bare signature over a
+//! fixed message hash, cold (`KeyManager::sign`, computing `nonce * G` from
+//! scratch) and prepared (`PreparedSigner::sign`, from the generator table).
+//! Criterion reports the time per signature (ns/op) and signatures per second.
+//! Run with:
+//!
+//! ```sh
+//! cargo bench -p api-client --bench signing
+//! ```
+
+use api_client::{CreateOrderRequest, LighterClient, Side};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use signer::KeyManager;
+
+/// Fixed private key (32 bytes of key material, zero-padded to 40)
+const PRIVATE_KEY_HEX: &str = "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f200000000000000000";
+const BASE_URL: &str = "https://testnet.zklighter.elliot.ai";
+
+fn sign_transaction(c: &mut Criterion) {
+    let client = LighterClient::new(BASE_URL.to_string(), PRIVATE_KEY_HEX, 42, 3).expect("valid client");
+    let mut tx = CreateOrderRequest {
+        client_order_index: 99,
+        ..CreateOrderRequest::limit(1, Side::Sell, 1_000, 305_000)
+    }
+    .to_tx_at(1_700_000_000_000)
+    .expect("legal order");
+    tx.header = client.tx_header(7).expect("header");
+
+    let mut group = c.benchmark_group("signing");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("sign_transaction/create_order", |b| {
+        b.iter(|| client.sign_transaction(black_box(&tx)).expect("signed"))
+    });
+    group.finish();
+}
+
+fn sign_message(c: &mut Criterion) {
+    let key_manager = KeyManager::from_hex(PRIVATE_KEY_HEX).expect("valid key");
+    let prepared = key_manager.prepare();
+    let message: [u8; 40] = std::array::from_fn(|i| i as u8);
+
+    let mut group = c.benchmark_group("signing");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("sign/cold", |b| b.iter(|| key_manager.sign(black_box(&message)).expect("signed")));
+    group.bench_function("sign/prepared", |b| b.iter(|| prepared.sign(black_box(&message)).expect("signed")));
+    group.finish();
+}
+
+criterion_group!(benches, sign_transaction, sign_message);
+criterion_main!(benches);
@@ -34,23 +34,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  Time: {}", time);
     println!();
 
-    let response = client.cancel_all_orders(time_in_force, time).await?;
+    let response = client.cancel_all_orders(time_in_force, time).await;
 
-    println!("✅ Cancel all orders submitted!");
-    println!("📥 Response:");
-    println!("{}", serde_json::to_string_pretty(&response)?);
-
-    let code = response["code"].as_i64().unwrap_or_default();
-    if code == 200 {
-        println!("\n✅ All orders canceled successfully!");
-        if let Some(tx_hash) = response["tx_hash"].as_str() {
-            println!("  Transaction Hash: {}", tx_hash);
-        }
-    } else {
-        println!("\n⚠️  Cancel all orders returned code: {}", code);
-        if let Some(msg) = response["message"].as_str() {
-            println!("  Message: {}", msg);
+    match response {
+        Ok(accepted) => {
+            println!("✅ All orders canceled successfully!");
+            println!("  Transaction Hash: {}", accepted.tx_hash);
         }
+        Err(e) => println!("⚠️  Cancel all orders rejected: {}", e),
     }
 
     Ok(())
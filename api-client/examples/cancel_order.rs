@@ -34,23 +34,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  Order Index: {}", order_index);
     println!();
 
-    let response = client.cancel_order(0, order_index).await?;
+    let response = client.cancel_order(0, order_index).await;
 
-    println!("✅ Cancel order submitted!");
-    println!("📥 Response:");
-    println!("{}", serde_json::to_string_pretty(&response)?);
-
-    let code = response["code"].as_i64().unwrap_or_default();
-    if code == 200 {
-        println!("\n✅ Order canceled successfully!");
-        if let Some(tx_hash) = response["tx_hash"].as_str() {
-            println!("  Transaction Hash: {}", tx_hash);
-        }
-    } else {
-        println!("\n⚠️  Cancel order returned code: {}", code);
-        if let Some(msg) = response["message"].as_str() {
-            println!("  Message: {}", msg);
+    match response {
+        Ok(accepted) => {
+            println!("✅ Order canceled successfully!");
+            println!("  Transaction Hash: {}", accepted.tx_hash);
         }
+        Err(e) => println!("⚠️  Cancel order rejected: {}", e),
     }
 
     Ok(())
@@ -1,4 +1,4 @@
-use api_client::{LighterClient, CreateOrderRequest};
+use api_client::{LighterClient, CreateOrderRequest, OrderExpiry, Side};
 use std::env;
 
 #[tokio::main]
@@ -31,30 +31,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         client_order_index: 12345, // unique identifier
         base_amount: 1000,         // 0.001 tokens in smallest unit
         price: 349659,             // limit price in cents
-        is_ask: false,             // false = buy order
+        side: Side::Buy,
         order_type: 0,             // 0 = LimitOrder
         time_in_force: 1,          // 1 = GoodTillTime
         reduce_only: false,
         trigger_price: 0,
+        order_expiry: OrderExpiry::None,
+        price_protection: None,
     };
 
-    let response = client.create_order(order).await?;
+    let response = client.create_order(order).await;
 
-    println!("✅ Limit order submitted!");
-    println!("📥 Response:");
-    println!("{}", serde_json::to_string_pretty(&response)?);
-
-    let code = response["code"].as_i64().unwrap_or_default();
-    if code == 200 {
-        println!("\n✅ Order created successfully!");
-        if let Some(tx_hash) = response["tx_hash"].as_str() {
-            println!("  Transaction Hash: {}", tx_hash);
-        }
-    } else {
-        println!("\n⚠️  Order submission returned code: {}", code);
-        if let Some(msg) = response["message"].as_str() {
-            println!("  Message: {}", msg);
+    match response {
+        Ok(accepted) => {
+            println!("✅ Order created successfully!");
+            println!("  Transaction Hash: {}", accepted.tx_hash);
         }
+        Err(e) => println!("⚠️  Order submission rejected: {}", e),
     }
 
     Ok(())
@@ -1,4 +1,4 @@
-use api_client::{LighterClient, CreateOrderRequest};
+use api_client::{LighterClient, Side};
 use std::env;
 
 #[tokio::main]
@@ -30,24 +30,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         12345,                // client_order_index (unique identifier)
         1000,                 // base_amount (0.001 tokens in smallest unit)
         349659,               // avg_execution_price (max price in cents)
-        false,                // is_ask (false = buy order)
-    ).await?;
-
-    println!("✅ Market order submitted!");
-    println!("📥 Response:");
-    println!("{}", serde_json::to_string_pretty(&response)?);
-
-    let code = response["code"].as_i64().unwrap_or_default();
-    if code == 200 {
-        println!("\n✅ Order created successfully!");
-        if let Some(tx_hash) = response["tx_hash"].as_str() {
-            println!("  Transaction Hash: {}", tx_hash);
-        }
-    } else {
-        println!("\n⚠️  Order submission returned code: {}", code);
-        if let Some(msg) = response["message"].as_str() {
-            println!("  Message: {}", msg);
+        Side::Buy,            // side
+    ).await;
+
+    match response {
+        Ok(accepted) => {
+            println!("✅ Order created successfully!");
+            println!("  Transaction Hash: {}", accepted.tx_hash);
         }
+        Err(e) => println!("⚠️  Order submission rejected: {}", e),
     }
 
     Ok(())
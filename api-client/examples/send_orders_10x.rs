@@ -1,4 +1,4 @@
-use api_client::LighterClient;
+use api_client::{LighterClient, Side};
 use std::env;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -47,24 +47,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             0,
             client_order_index,
             1000,
-            50000_0000,
-            false,
+            500_000_000,
+            Side::Buy,
         ).await {
-            Ok(response) => {
-                let code = response["code"].as_i64().unwrap_or_default();
-                
-                if code == 200 {
-                    success_count += 1;
-                    println!("  ✅ [{}] SUCCESS", i);
-                } else {
-                    fail_count += 1;
-                    let msg = response["message"]
-                        .as_str()
-                        .unwrap_or("Unknown error")
-                        .to_string();
-                    println!("  ❌ [{}] FAILED - Code: {} - {}", i, code, msg);
-                    error_messages.push(format!("Order {}: Code {} - {}", i, code, msg));
-                }
+            Ok(_) => {
+                success_count += 1;
+                println!("  ✅ [{}] SUCCESS", i);
             }
             Err(e) => {
                 fail_count += 1;
@@ -1,4 +1,3 @@
-use api_client::LighterClient;
 use signer::KeyManager;
 use std::env;
 
@@ -14,7 +13,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let base_url = env::var("BASE_URL")?;
     let account_index: i64 = env::var("ACCOUNT_INDEX")?.parse()?;
     let api_key_index: u8 = env::var("API_KEY_INDEX")?.parse()?;
-    let api_key = env::var("API_PRIVATE_KEY")?;
+    let _api_key = env::var("API_PRIVATE_KEY")?;
 
     println!("📋 Configuration:");
     println!("  Base URL: {}", base_url);
@@ -29,8 +28,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let new_public_key = new_key_manager.public_key_bytes();
 
     println!("✅ New API key generated!");
-    println!("  Private Key (hex): {}", hex::encode(&new_private_key));
-    println!("  Public Key (hex): {}", hex::encode(&new_public_key));
+    println!("  Private Key (hex): {}", hex::encode(new_private_key));
+    println!("  Public Key (hex): {}", hex::encode(new_public_key));
     println!();
 
     // Determine the new API key index (typically current + 1)
@@ -57,7 +56,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Example: Show how to create the client with new key
     println!("📝 Example: Using new API key for future transactions:");
     println!("  Update your .env file:");
-    println!("    API_PRIVATE_KEY={}", hex::encode(&new_private_key));
+    println!("    API_PRIVATE_KEY={}", hex::encode(new_private_key));
     println!("    API_KEY_INDEX={}", new_api_key_index);
     println!();
 
@@ -67,6 +66,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  1. Generate L1 signature using your ETH private key");
     println!("  2. Use the new API key manager to sign the change_pub_key transaction");
     println!("  3. Submit the transaction with both signatures");
+    println!("  4. Call wait_for_key_active({}, timeout) on a client with the new key before trading", new_api_key_index);
 
     Ok(())
 }
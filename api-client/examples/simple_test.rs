@@ -1,4 +1,4 @@
-use api_client::{LighterClient, CreateOrderRequest};
+use api_client::{LighterClient, CreateOrderRequest, OrderExpiry, Side};
 use std::env;
 
 #[tokio::main]
@@ -40,19 +40,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         order_book_index: 0, // BTC-USD
         client_order_index: 12345,
         base_amount: 1000, // 0.0001 BTC
-        price: 50000_0000, // $50,000
-        is_ask: false, // Buy
+        price: 500_000_000, // $50,000
+        side: Side::Buy,
         order_type: 0, // Market (MarketOrder = 0)
         time_in_force: 0, // ImmediateOrCancel
         reduce_only: false,
         trigger_price: 0,
+        order_expiry: OrderExpiry::None,
+        price_protection: None,
     };
     
     println!("Submitting order...");
     match client.create_order(order).await {
-        Ok(response) => {
+        Ok(accepted) => {
             println!("✅ Order submitted successfully!");
-            println!("Response: {}", serde_json::to_string_pretty(&response)?);
+            println!("Transaction Hash: {}", accepted.tx_hash);
         }
         Err(e) => {
             println!("❌ Order failed: {}", e);
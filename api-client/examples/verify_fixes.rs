@@ -1,7 +1,6 @@
-use api_client::{LighterClient, CreateOrderRequest};
+use api_client::{LighterClient, CreateOrderRequest, OrderExpiry, Side};
 use std::env;
 use serde_json::json;
-use base64::Engine;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -51,7 +50,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let nonce_json: serde_json::Value = serde_json::from_str(&nonce_text)?;
     let nonce = nonce_json["nonce"]
         .as_i64()
-        .ok_or_else(|| "Invalid nonce response format")?;
+        .ok_or("Invalid nonce response format")?;
     println!("  Nonce: {}", nonce);
     println!();
     
@@ -62,11 +61,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         client_order_index: 12345,
         base_amount: 10, // 0.001 tokens
         price: 348400, // Current market price
-        is_ask: false, // Buy order
+        side: Side::Buy,
         order_type: 0, // MarketOrder
         time_in_force: 0, // ImmediateOrCancel
         reduce_only: false,
         trigger_price: 0,
+        order_expiry: OrderExpiry::None,
+        price_protection: None,
     };
     
     println!("📝 Order Details:");
@@ -74,7 +75,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  Client Order Index: {}", order.client_order_index);
     println!("  Base Amount: {}", order.base_amount);
     println!("  Price: {}", order.price);
-    println!("  Is Ask: {}", order.is_ask);
+    println!("  Side: {}", order.side);
     println!("  Order Type: {} (0 = Market)", order.order_type);
     println!("  Time In Force: {} (0 = IOC)", order.time_in_force);
     println!("  Reduce Only: {}", order.reduce_only);
@@ -85,11 +86,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)?
         .as_millis() as i64;
-    let expired_at = now + 599_000; // 10 minutes - 1 second (matches Go)
+    let expiry_ms = api_client::DEFAULT_TX_EXPIRY.as_millis() as i64;
+    let expired_at = now + expiry_ms; // 10 minutes - 1 second (matches Go)
     
     println!("⏰ Timing:");
     println!("  Current Time (ms): {}", now);
-    println!("  ExpiredAt: {} ({} minutes from now)", expired_at, 599_000 / 60_000);
+    println!("  ExpiredAt: {} ({} seconds from now)", expired_at, expiry_ms / 1000);
     println!();
     
     // Build transaction JSON (before signing)
@@ -100,7 +102,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "ClientOrderIndex": order.client_order_index,
         "BaseAmount": order.base_amount,
         "Price": order.price,
-        "IsAsk": if order.is_ask { 1 } else { 0 },
+        "IsAsk": order.side.as_is_ask(),
         "Type": order.order_type,
         "TimeInForce": order.time_in_force,
         "ReduceOnly": if order.reduce_only { 1 } else { 0 },
@@ -120,7 +122,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🔐 Signing transaction...");
     
     // Sign the transaction
-    let signature = match client.sign_transaction(&tx_json) {
+    let signature = match client.sign_raw(&tx_json) {
         Ok(sig) => {
             println!("  ✅ Signature generated successfully");
             sig
@@ -134,13 +136,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Calculate transaction hash manually to display
     println!();
     println!("🔑 Signature Details:");
-    println!("  Signature (hex): {}", hex::encode(&signature));
-    println!("  Signature (base64): {}", base64::engine::general_purpose::STANDARD.encode(&signature));
+    println!("  Signature (hex): {}", signature.to_hex());
+    println!("  Signature (base64): {}", signature.to_base64());
     println!();
     
     // Add signature to transaction
     let mut final_tx_info = tx_info.clone();
-    final_tx_info["Sig"] = json!(base64::engine::general_purpose::STANDARD.encode(&signature));
+    final_tx_info["Sig"] = json!(signature.to_base64());
     
     println!("📤 Final Transaction JSON (with signature):");
     println!("{}", serde_json::to_string_pretty(&final_tx_info)?);
@@ -160,7 +162,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("{}", "═".repeat(80));
     println!("📊 Verification Checklist:");
     println!("  ✅ Chain ID: {} ({})", chain_id, if chain_id == 304 { "mainnet" } else { "testnet" });
-    println!("  ✅ ExpiredAt: {} (current + 599 seconds)", expired_at);
+    println!("  ✅ ExpiredAt: {} (current + {} seconds)", expired_at, expiry_ms / 1000);
     println!("  ✅ Transaction JSON field order and values");
     println!("  ✅ Signature (base64)");
     println!("  ✅ Final JSON structure");
@@ -171,28 +173,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!();
     
     match client.create_order(order).await {
-        Ok(response) => {
+        Ok(accepted) => {
             println!("{}", "═".repeat(80));
             println!("✅ ORDER SUBMITTED SUCCESSFULLY!");
             println!("{}", "═".repeat(80));
             println!();
-            println!("📥 Response from exchange:");
-            println!("{}", serde_json::to_string_pretty(&response)?);
-            println!();
-            
-            // Check response code
-            if let Some(code) = response.get("code") {
-                if let Some(code_num) = code.as_i64() {
-                    if code_num == 0 {
-                        println!("🎉 SUCCESS: Order was accepted by exchange!");
-                    } else {
-                        println!("⚠️  Order was processed with code: {}", code_num);
-                        if let Some(message) = response.get("message") {
-                            println!("   Message: {}", message);
-                        }
-                    }
-                }
-            }
+            println!("🎉 SUCCESS: Order was accepted by exchange! ({})", accepted.tx_hash);
         }
         Err(e) => {
             println!("{}", "═".repeat(80));
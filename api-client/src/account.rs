@@ -0,0 +1,945 @@
+use crate::side::side_from_is_ask;
+use crate::{ApiError, LighterClient, Result, Side};
+use futures::stream::{self, Stream, StreamExt};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::time::{Duration, Instant};
+
+/// Maximum page size accepted by the order history endpoint
+const ORDER_HISTORY_PAGE_LIMIT: u32 = 100;
+
+/// Maximum page size accepted by the trades endpoint
+const TRADES_PAGE_LIMIT: u32 = 100;
+
+/// How often `wait_for_key_active` asks whether a new API key is registered
+pub const KEY_ACTIVE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How collateral backs a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MarginMode {
+    /// Shares the account's collateral with all other cross positions
+    #[default]
+    Cross,
+    /// Backed only by margin allocated to this position
+    Isolated,
+}
+
+impl MarginMode {
+    /// The `MarginMode` value used on the wire
+    pub fn as_u8(self) -> u8 {
+        match self {
+            MarginMode::Cross => 0,
+            MarginMode::Isolated => 1,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(MarginMode::Cross),
+            1 => Some(MarginMode::Isolated),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MarginMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = u8::deserialize(deserializer)?;
+        MarginMode::from_u8(value)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid margin mode {}", value)))
+    }
+}
+
+/// Whether [`LighterClient::update_margin`] adds or removes isolated margin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MarginDirection {
+    Remove,
+    Add,
+}
+
+impl MarginDirection {
+    /// The `Direction` value used on the wire
+    pub fn as_u8(self) -> u8 {
+        match self {
+            MarginDirection::Remove => 0,
+            MarginDirection::Add => 1,
+        }
+    }
+}
+
+/// Leverage configured for one market
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Leverage {
+    pub market_index: u8,
+    /// Leverage multiplier, e.g. 5 for 5x (`100 / initial margin fraction %`)
+    pub leverage: Decimal,
+    pub margin_mode: MarginMode,
+    /// `true` if the account never configured this market and the market default applies
+    pub is_default: bool,
+}
+
+/// An order as reported by the account order endpoints
+///
+/// A view over the exchange's order objects, which carry more fields, so the
+/// `strict-json` feature does not apply to it.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Order {
+    pub order_index: i64,
+    pub client_order_index: i64,
+    pub market_index: u8,
+    pub owner_account_index: i64,
+    pub initial_base_amount: Decimal,
+    pub remaining_base_amount: Decimal,
+    pub filled_base_amount: Decimal,
+    pub filled_quote_amount: Decimal,
+    pub price: Decimal,
+    pub trigger_price: Decimal,
+    #[serde(rename = "is_ask", deserialize_with = "side_from_is_ask")]
+    pub side: Side,
+    #[serde(rename = "type")]
+    pub order_type: String,
+    pub time_in_force: String,
+    pub reduce_only: bool,
+    pub order_expiry: i64,
+    /// e.g. "open", "filled", "canceled", "canceled-expired"
+    pub status: String,
+    pub nonce: i64,
+    pub timestamp: i64,
+}
+
+impl Default for Order {
+    fn default() -> Self {
+        Self {
+            order_index: 0,
+            client_order_index: 0,
+            market_index: 0,
+            owner_account_index: 0,
+            initial_base_amount: Decimal::ZERO,
+            remaining_base_amount: Decimal::ZERO,
+            filled_base_amount: Decimal::ZERO,
+            filled_quote_amount: Decimal::ZERO,
+            price: Decimal::ZERO,
+            trigger_price: Decimal::ZERO,
+            side: Side::Buy,
+            order_type: String::new(),
+            time_in_force: String::new(),
+            reduce_only: false,
+            order_expiry: 0,
+            status: String::new(),
+            nonce: 0,
+            timestamp: 0,
+        }
+    }
+}
+
+impl Order {
+    /// Average price of the filled part, `None` while nothing has filled
+    pub fn average_fill_price(&self) -> Option<Decimal> {
+        if self.filled_base_amount.is_zero() {
+            return None;
+        }
+        Some(self.filled_quote_amount / self.filled_base_amount)
+    }
+
+    /// Whether the order's `OrderExpiry` has passed at `now_ms`; never for orders without one
+    pub fn is_expired_at(&self, now_ms: i64) -> bool {
+        self.order_expiry > 0 && self.order_expiry <= now_ms
+    }
+}
+
+/// One page of historical (filled / canceled) orders
+///
+/// Holds [`Order`] views, so like them it is exempt from the `strict-json` feature.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct OrderHistoryPage {
+    pub orders: Vec<Order>,
+    /// Cursor for the next page, `None` once the end of history is reached
+    pub next_cursor: Option<String>,
+}
+
+/// Locally tracked orders compared with the orders resting on the exchange
+#[derive(Debug, Clone, Default)]
+pub struct OrderReconciliation {
+    /// Tracked locally and resting on the exchange
+    pub matched: Vec<Order>,
+    /// Tracked locally but not resting on the exchange (filled, canceled, expired or never accepted)
+    pub missing: Vec<u64>,
+    /// Resting on the exchange but not tracked locally
+    pub unknown: Vec<Order>,
+    /// Still listed as open although their `OrderExpiry` has passed, tracked or not
+    pub expired: Vec<Order>,
+}
+
+impl OrderReconciliation {
+    /// `true` when every local order rests on the exchange and nothing else does
+    pub fn is_consistent(&self) -> bool {
+        self.missing.is_empty() && self.unknown.is_empty()
+    }
+}
+
+/// An open perpetual position
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    pub market_index: u8,
+    pub symbol: String,
+    /// Signed size in base units: positive for long, negative for short
+    pub size: Decimal,
+    pub avg_entry_price: Decimal,
+    /// Mark price the unrealized PnL was computed at
+    pub mark_price: Decimal,
+    /// PnL if the position were closed at `mark_price`
+    pub unrealized_pnl: Decimal,
+    pub realized_pnl: Decimal,
+    pub liquidation_price: Decimal,
+    pub margin_mode: MarginMode,
+}
+
+impl Position {
+    /// Unrealized PnL at `mark_price`: `size * (mark - entry)`
+    ///
+    /// The signed size makes a long gain and a short lose when the price rises.
+    pub fn unrealized_pnl_at(&self, mark_price: Decimal) -> Decimal {
+        self.size * (mark_price - self.avg_entry_price)
+    }
+
+    /// Recompute `mark_price` and `unrealized_pnl` for a new mark price
+    pub fn mark_to(&mut self, mark_price: Decimal) {
+        self.mark_price = mark_price;
+        self.unrealized_pnl = self.unrealized_pnl_at(mark_price);
+    }
+}
+
+/// Raw position record from the account endpoint
+///
+/// Only the fields positions are built from; the exchange sends more (e.g.
+/// `unrealized_pnl`, `total_funding_paid_out`), so `strict-json` does not apply.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct PositionRecord {
+    market_id: u8,
+    symbol: String,
+    /// 1 = long, -1 = short
+    sign: i32,
+    /// Absolute size
+    position: Decimal,
+    avg_entry_price: Decimal,
+    /// Absolute notional at the exchange's mark price
+    position_value: Decimal,
+    realized_pnl: Decimal,
+    liquidation_price: Decimal,
+    margin_mode: MarginMode,
+    /// Initial margin fraction in percent ("20.00" = 5x)
+    initial_margin_fraction: Decimal,
+    open_order_count: u32,
+    /// Collateral allocated to an isolated position
+    allocated_margin: Decimal,
+}
+
+impl PositionRecord {
+    fn leverage(&self) -> Option<Leverage> {
+        if self.initial_margin_fraction <= Decimal::ZERO {
+            return None;
+        }
+        Some(Leverage {
+            market_index: self.market_id,
+            leverage: Decimal::ONE_HUNDRED / self.initial_margin_fraction,
+            margin_mode: self.margin_mode,
+            is_default: false,
+        })
+    }
+}
+
+impl PositionRecord {
+    /// Convert to a [`Position`], or `None` for a flat market
+    fn into_position(self) -> Option<Position> {
+        if self.position.is_zero() {
+            return None;
+        }
+        let size = if self.sign < 0 { -self.position } else { self.position };
+        // The exchange reports notional at its mark price rather than the price itself
+        let mark_price = self.position_value / self.position;
+
+        let mut position = Position {
+            market_index: self.market_id,
+            symbol: self.symbol,
+            size,
+            avg_entry_price: self.avg_entry_price,
+            mark_price,
+            unrealized_pnl: Decimal::ZERO,
+            realized_pnl: self.realized_pnl,
+            liquidation_price: self.liquidation_price,
+            margin_mode: self.margin_mode,
+        };
+        position.mark_to(mark_price);
+        Some(position)
+    }
+
+    /// Convert to a [`Position`], reporting a flat market as one of size zero
+    pub(crate) fn into_position_or_flat(self) -> Position {
+        let (market_index, symbol, realized_pnl, margin_mode) =
+            (self.market_id, self.symbol.clone(), self.realized_pnl, self.margin_mode);
+        self.into_position().unwrap_or(Position {
+            market_index,
+            symbol,
+            size: Decimal::ZERO,
+            avg_entry_price: Decimal::ZERO,
+            mark_price: Decimal::ZERO,
+            unrealized_pnl: Decimal::ZERO,
+            realized_pnl,
+            liquidation_price: Decimal::ZERO,
+            margin_mode,
+        })
+    }
+}
+
+/// A fill executed by one account, seen from that account's side of the trade
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountTrade {
+    pub trade_id: i64,
+    pub market_index: u8,
+    pub timestamp: i64,
+    pub price: Decimal,
+    pub size: Decimal,
+    /// Side the account took in this fill
+    pub side: Side,
+    pub is_maker: bool,
+    /// Fee charged to the account for this fill
+    pub fee: Decimal,
+    /// PnL realized by this fill (non-zero only when it reduced an open position)
+    pub realized_pnl: Decimal,
+}
+
+/// Raw trade record from the trades endpoint
+///
+/// Only the fields fills are built from; the exchange sends more (e.g. `tx_hash`,
+/// `usd_amount`, the order ids), so `strict-json` does not apply.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct TradeRecord {
+    trade_id: i64,
+    market_id: u8,
+    timestamp: i64,
+    price: Decimal,
+    size: Decimal,
+    ask_account_id: i64,
+    bid_account_id: i64,
+    is_maker_ask: bool,
+    taker_fee: Decimal,
+    maker_fee: Decimal,
+    taker_position_size_before: Decimal,
+    taker_entry_quote_before: Decimal,
+    maker_position_size_before: Decimal,
+    maker_entry_quote_before: Decimal,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+struct TradesPage {
+    trades: Vec<TradeRecord>,
+    next_cursor: Option<String>,
+}
+
+impl TradeRecord {
+    /// View the trade from `account_index`'s side, or `None` if it was not a party
+    pub(crate) fn for_account(&self, account_index: i64) -> Option<AccountTrade> {
+        self.with_position_before(account_index).map(|(trade, ..)| trade)
+    }
+
+    /// [`Self::for_account`], with the account's signed position size and entry quote before the fill
+    pub(crate) fn with_position_before(&self, account_index: i64) -> Option<(AccountTrade, Decimal, Decimal)> {
+        let side = if self.ask_account_id == account_index {
+            Side::Sell
+        } else if self.bid_account_id == account_index {
+            Side::Buy
+        } else {
+            return None;
+        };
+        let is_maker = side.is_ask() == self.is_maker_ask;
+        let (fee, position_before, entry_quote_before) = if is_maker {
+            (self.maker_fee, self.maker_position_size_before, self.maker_entry_quote_before)
+        } else {
+            (self.taker_fee, self.taker_position_size_before, self.taker_entry_quote_before)
+        };
+
+        let trade = AccountTrade {
+            trade_id: self.trade_id,
+            market_index: self.market_id,
+            timestamp: self.timestamp,
+            price: self.price,
+            size: self.size,
+            side,
+            is_maker,
+            fee,
+            realized_pnl: realized_pnl(side, self.price, self.size, position_before, entry_quote_before),
+        };
+        Some((trade, position_before, entry_quote_before))
+    }
+}
+
+/// PnL realized by a fill against the position held before it
+///
+/// `position_before` is signed (negative = short) and `entry_quote_before` is the
+/// absolute quote value paid to open it, so the average entry price is
+/// `entry_quote_before / |position_before|`. Only the part of the fill that
+/// reduces the position realizes PnL:
+/// - closing a long (sell): `closed * (price - entry)`
+/// - closing a short (buy): `closed * (entry - price)`
+pub(crate) fn realized_pnl(
+    side: Side,
+    price: Decimal,
+    size: Decimal,
+    position_before: Decimal,
+    entry_quote_before: Decimal,
+) -> Decimal {
+    let reduces = match side {
+        Side::Sell => position_before > Decimal::ZERO,
+        Side::Buy => position_before < Decimal::ZERO,
+    };
+    if !reduces {
+        return Decimal::ZERO;
+    }
+    let open_size = position_before.abs();
+    let closed = size.min(open_size);
+    let entry_price = entry_quote_before / open_size;
+    match side {
+        Side::Sell => closed * (price - entry_price),
+        Side::Buy => closed * (entry_price - price),
+    }
+}
+
+impl LighterClient {
+    /// Resolve the Lighter account indices owned by an L1 wallet address
+    ///
+    /// Queries the accounts-by-address endpoint, which lists the main account and
+    /// every sub-account registered to the address. Useful for first-time setup
+    /// when the account index is not yet known; the account index the client was
+    /// constructed with is not used by this call.
+    ///
+    /// # Arguments
+    /// * `l1_address` - L1 wallet address (hex, with or without `0x` prefix)
+    ///
+    /// # Returns
+    /// All account indices for the address, in the order returned by the API.
+    /// Returns `ApiError::NotFound` if the address has no Lighter account.
+    pub async fn get_account_index(&self, l1_address: &str) -> Result<Vec<i64>> {
+        let l1_address = if l1_address.starts_with("0x") {
+            l1_address.to_string()
+        } else {
+            format!("0x{}", l1_address)
+        };
+
+        let response = self
+            .get_json("/api/v1/accountsByL1Address", &[("l1_address", l1_address.clone())])
+            .await?;
+
+        let code = response["code"].as_i64().unwrap_or(200);
+        if code != 200 {
+            let message = response["message"].as_str().unwrap_or_default();
+            // 21100: account not found
+            if code == 21100 || message.contains("not found") {
+                return Err(ApiError::NotFound(format!("no account for L1 address {}", l1_address)));
+            }
+            return Err(ApiError::Api(format!("code {}: {}", code, message)));
+        }
+
+        let indices: Vec<i64> = response["sub_accounts"]
+            .as_array()
+            .map(|accounts| accounts.iter().filter_map(|a| a["index"].as_i64()).collect())
+            .unwrap_or_default();
+
+        if indices.is_empty() {
+            return Err(ApiError::NotFound(format!("no account for L1 address {}", l1_address)));
+        }
+
+        Ok(indices)
+    }
+
+    /// Check that the configured account and API key match what the exchange has registered
+    ///
+    /// Call at startup to fail fast on misconfiguration instead of seeing opaque
+    /// signature rejections later. Fails with `ApiError::NotFound` if the account
+    /// index does not exist, and with `ApiError::InvalidConfig` if no API key is
+    /// registered at the API key index or the registered public key differs from
+    /// the one derived from the configured private key.
+    pub async fn verify_credentials(&self) -> Result<()> {
+        let response = self
+            .get_json(
+                "/api/v1/account",
+                &[("by", "index".to_string()), ("value", self.account_index.to_string())],
+            )
+            .await?;
+        // 21100: account not found
+        let account_missing = response["code"].as_i64() == Some(21100)
+            || response["accounts"].as_array().is_some_and(|accounts| accounts.is_empty());
+        if account_missing {
+            return Err(ApiError::NotFound(format!("account {} does not exist", self.account_index)));
+        }
+        check_response_code(&response)?;
+
+        let registered = self.registered_public_key(self.api_key_index).await?.ok_or_else(|| {
+            ApiError::InvalidConfig(format!(
+                "no API key registered at index {} for account {}",
+                self.api_key_index, self.account_index
+            ))
+        })?;
+
+        let derived = hex::encode(self.key_manager.public_key_bytes());
+        if registered != derived {
+            return Err(ApiError::InvalidConfig(format!(
+                "API key {} of account {} is registered with public key {}, but the configured private key \
+                 derives {}; check the private key and API key index",
+                self.api_key_index, self.account_index, registered, derived
+            )));
+        }
+        Ok(())
+    }
+
+    /// Wait until the exchange has registered this client's key at `api_key_index`
+    ///
+    /// A key set with `change_api_key` takes a while to propagate before
+    /// transactions signed with it are accepted. This polls the account's API
+    /// keys every [`KEY_ACTIVE_POLL_INTERVAL`] until the key at `api_key_index`
+    /// is the public key derived from this client's private key, so call it on
+    /// the client holding the new key. Rejections of the lookup while the key
+    /// propagates count as not active yet; network errors are returned at once.
+    /// Fails with `ApiError::KeyActivationTimeout` if the key is not active
+    /// within `timeout`.
+    pub async fn wait_for_key_active(&self, api_key_index: u8, timeout: Duration) -> Result<()> {
+        let derived = hex::encode(self.key_manager.public_key_bytes());
+        let started = Instant::now();
+        loop {
+            match self.registered_public_key(api_key_index).await {
+                Ok(Some(registered)) if registered == derived => return Ok(()),
+                Ok(_) | Err(ApiError::Api(_)) => {}
+                Err(e) => return Err(e),
+            }
+            let remaining = timeout.saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                return Err(ApiError::KeyActivationTimeout { api_key_index, waited: started.elapsed() });
+            }
+            tokio::time::sleep(remaining.min(KEY_ACTIVE_POLL_INTERVAL)).await;
+        }
+    }
+
+    /// Public key registered at `api_key_index` of this client's account, lowercase hex without `0x`
+    ///
+    /// `None` if the slot is empty (absent or all zeros).
+    async fn registered_public_key(&self, api_key_index: u8) -> Result<Option<String>> {
+        let response = self
+            .get_json(
+                "/api/v1/apikeys",
+                &[
+                    ("account_index", self.account_index.to_string()),
+                    ("api_key_index", api_key_index.to_string()),
+                ],
+            )
+            .await?;
+        check_response_code(&response)?;
+        Ok(response["api_keys"]
+            .as_array()
+            .and_then(|keys| keys.iter().find(|key| key["api_key_index"].as_u64() == Some(api_key_index as u64)))
+            .and_then(|key| key["public_key"].as_str())
+            .map(|key| key.trim_start_matches("0x").to_ascii_lowercase())
+            .filter(|key| !key.is_empty() && key.bytes().any(|b| b != b'0')))
+    }
+
+    /// Fetch the open positions of an account
+    ///
+    /// Unrealized PnL is computed at the exchange's current mark price; call
+    /// [`Position::mark_to`] to revalue at a price of your own. Flat markets are
+    /// omitted, so a flat account yields an empty vector.
+    pub async fn get_positions(&self, account_index: i64) -> Result<Vec<Position>> {
+        let records = self.fetch_position_records(account_index).await?;
+        Ok(records.into_iter().filter_map(PositionRecord::into_position).collect())
+    }
+
+    /// Read the leverage and margin mode configured for one market
+    ///
+    /// Markets the account has never configured or traded fall back to the
+    /// market's default initial margin fraction in cross margin, flagged with
+    /// `is_default`, rather than failing.
+    pub async fn get_leverage(&self, account_index: i64, market_index: u8) -> Result<Leverage> {
+        let records = self.fetch_position_records(account_index).await?;
+        if let Some(leverage) = records
+            .iter()
+            .find(|record| record.market_id == market_index)
+            .and_then(PositionRecord::leverage)
+        {
+            return Ok(leverage);
+        }
+
+        let response = self
+            .get_json("/api/v1/orderBookDetails", &[("market_id", market_index.to_string())])
+            .await?;
+        check_response_code(&response)?;
+        // Basis points: 10_000 = 100% = 1x
+        let default_imf = response["order_book_details"]
+            .as_array()
+            .and_then(|details| details.iter().find(|d| d["market_id"].as_u64() == Some(market_index as u64)))
+            .and_then(|details| details["default_initial_margin_fraction"].as_i64())
+            .filter(|imf| *imf > 0)
+            .ok_or_else(|| ApiError::NotFound(format!("default leverage for market {}", market_index)))?;
+
+        Ok(Leverage {
+            market_index,
+            leverage: Decimal::from(10_000) / Decimal::from(default_imf),
+            margin_mode: MarginMode::Cross,
+            is_default: true,
+        })
+    }
+
+    async fn fetch_position_records(&self, account_index: i64) -> Result<Vec<PositionRecord>> {
+        let account = self.fetch_account(account_index).await?;
+        self.piggyback_nonce(account_index, &account);
+        decode_list(&account, "positions")
+    }
+
+    /// The account object of the account query
+    pub(crate) async fn fetch_account(&self, account_index: i64) -> Result<Value> {
+        let mut response = self
+            .get_json(
+                "/api/v1/account",
+                &[("by", "index".to_string()), ("value", account_index.to_string())],
+            )
+            .await?;
+        check_response_code(&response)?;
+
+        response["accounts"]
+            .as_array_mut()
+            .filter(|accounts| !accounts.is_empty())
+            .map(|accounts| accounts.swap_remove(0))
+            .ok_or_else(|| ApiError::NotFound(format!("account {}", account_index)))
+    }
+
+    /// Refuse to remove `usdc_amount` (6 decimals) of isolated margin if that
+    /// would leave the position at or below maintenance margin
+    pub(crate) async fn check_margin_removal(&self, market_index: u8, usdc_amount: i64) -> Result<()> {
+        let record = self
+            .fetch_position_records(self.account_index)
+            .await?
+            .into_iter()
+            .find(|record| record.market_id == market_index);
+        let Some(record) = record else {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "no isolated position on market {}",
+                market_index
+            )));
+        };
+        if record.margin_mode != MarginMode::Isolated {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "market {} is not in isolated margin",
+                market_index
+            )));
+        }
+        let allocated_margin = record.allocated_margin;
+        let Some(position) = record.into_position() else {
+            // Flat: nothing can be liquidated
+            return Ok(());
+        };
+
+        let response = self
+            .get_json("/api/v1/orderBookDetails", &[("market_id", market_index.to_string())])
+            .await?;
+        check_response_code(&response)?;
+        // Basis points: 10_000 = 100%
+        let maintenance_fraction = response["order_book_details"]
+            .as_array()
+            .and_then(|details| details.iter().find(|d| d["market_id"].as_u64() == Some(market_index as u64)))
+            .and_then(|details| details["maintenance_margin_fraction"].as_i64())
+            .ok_or_else(|| ApiError::NotFound(format!("maintenance margin for market {}", market_index)))?;
+
+        let remaining = allocated_margin + position.unrealized_pnl - Decimal::new(usdc_amount, 6);
+        let maintenance = (position.size * position.mark_price).abs() * Decimal::from(maintenance_fraction)
+            / Decimal::from(10_000);
+        if remaining <= maintenance {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "removing {} USDC leaves {} margin on market {}, at or below the maintenance margin of {}",
+                Decimal::new(usdc_amount, 6),
+                remaining,
+                market_index,
+                maintenance
+            )));
+        }
+        Ok(())
+    }
+
+    /// Fetch one page of inactive (filled / canceled / expired) orders
+    ///
+    /// # Arguments
+    /// * `account_index` - Account to query (the auth token is created for the client's account)
+    /// * `market_index` - Restrict to one market, or `None` for all markets
+    /// * `cursor` - `None` for the first page, then the previous page's `next_cursor`
+    pub async fn get_order_history(
+        &self,
+        account_index: i64,
+        market_index: Option<u8>,
+        cursor: Option<&str>,
+    ) -> Result<OrderHistoryPage> {
+        let mut query = vec![
+            ("account_index", account_index.to_string()),
+            ("limit", ORDER_HISTORY_PAGE_LIMIT.to_string()),
+        ];
+        if let Some(market_index) = market_index {
+            query.push(("market_id", market_index.to_string()));
+        }
+        if let Some(cursor) = cursor {
+            query.push(("cursor", cursor.to_string()));
+        }
+
+        let response = self.get_json_auth("/api/v1/accountInactiveOrders", &query).await?;
+        check_response_code(&response)?;
+
+        let mut page: OrderHistoryPage = decode(response)?;
+        if page.next_cursor.as_deref() == Some("") {
+            page.next_cursor = None;
+        }
+        Ok(page)
+    }
+
+    /// Fetch the open (resting and untriggered) orders of an account in one market (authenticated)
+    pub async fn get_active_orders(&self, account_index: i64, market_index: u8) -> Result<Vec<Order>> {
+        let query = [
+            ("account_index", account_index.to_string()),
+            ("market_id", market_index.to_string()),
+        ];
+        let response = self.get_json_auth("/api/v1/accountActiveOrders", &query).await?;
+        check_response_code(&response)?;
+        decode_list(&response, "orders")
+    }
+
+    /// Compare locally tracked client order indices with the client account's open orders
+    ///
+    /// Active orders are fetched for every market where the account reports open
+    /// orders. Use after a restart or a dropped connection to find orders that
+    /// left the book unnoticed (`missing`) and orders placed elsewhere or
+    /// forgotten locally (`unknown`). Orders the exchange still lists after their
+    /// `OrderExpiry` passed (see [`Self::get_expired_orders`]) go to `expired`
+    /// only, so a tracked one also counts as `missing`.
+    pub async fn reconcile_orders(&self, local: &[u64]) -> Result<OrderReconciliation> {
+        let active = self.active_orders_all_markets(self.account_index).await?;
+        let now = self.clock.now_ms();
+
+        let mut reconciliation = OrderReconciliation::default();
+        for order in active {
+            if order.is_expired_at(now) {
+                reconciliation.expired.push(order);
+            } else if local.contains(&(order.client_order_index as u64)) {
+                reconciliation.matched.push(order);
+            } else {
+                reconciliation.unknown.push(order);
+            }
+        }
+        reconciliation.missing = local
+            .iter()
+            .copied()
+            .filter(|index| {
+                !reconciliation
+                    .matched
+                    .iter()
+                    .any(|order| order.client_order_index as u64 == *index)
+            })
+            .collect();
+        Ok(reconciliation)
+    }
+
+    /// Open orders of an account whose `OrderExpiry` has already passed (authenticated)
+    ///
+    /// After downtime the exchange can still list good-till-date orders that
+    /// expired in the meantime; they will never fill, so do not count them as
+    /// live. Active orders are fetched for every market where the account
+    /// reports open orders and compared with the client's [`crate::Clock`]:
+    /// install a server-synced clock with `LighterClientBuilder::clock` so a
+    /// drifting local clock does not misjudge orders near their expiry.
+    pub async fn get_expired_orders(&self, account_index: i64) -> Result<Vec<Order>> {
+        let active = self.active_orders_all_markets(account_index).await?;
+        let now = self.clock.now_ms();
+        Ok(active.into_iter().filter(|order| order.is_expired_at(now)).collect())
+    }
+
+    /// Look up one order of an account by its client order index (authenticated)
+    ///
+    /// The API has no single-order query, so this searches the open orders of
+    /// every market where the account has any, then the latest page of order
+    /// history. Returns `None` for an index in neither, including orders that
+    /// closed long enough ago to have left the first history page.
+    pub async fn get_order(&self, account_index: i64, client_order_index: u64) -> Result<Option<Order>> {
+        let matches = |order: &Order| order.client_order_index as u64 == client_order_index;
+
+        let active = self.active_orders_all_markets(account_index).await?;
+        if let Some(order) = active.into_iter().find(matches) {
+            return Ok(Some(order));
+        }
+        let history = self.get_order_history(account_index, None, None).await?;
+        Ok(history.orders.into_iter().find(matches))
+    }
+
+    /// Open orders across every market where the account reports any
+    async fn active_orders_all_markets(&self, account_index: i64) -> Result<Vec<Order>> {
+        let records = self.fetch_position_records(account_index).await?;
+        let mut active = Vec::new();
+        for record in records.iter().filter(|record| record.open_order_count > 0) {
+            active.extend(self.get_active_orders(account_index, record.market_id).await?);
+        }
+        Ok(active)
+    }
+
+    /// Fetch the fills executed by an account within a time range (authenticated)
+    ///
+    /// Unlike public recent trades, this returns the account's own fills with the
+    /// fee and realized PnL from the account's side. Pages are followed until the
+    /// end of the range or the end of the data.
+    ///
+    /// # Arguments
+    /// * `account_index` - Account whose fills to return
+    /// * `market_index` - Restrict to one market, or `None` for all markets
+    /// * `start` - Inclusive start timestamp (unix ms), `None` for no lower bound
+    /// * `end` - Inclusive end timestamp (unix ms), `None` for no upper bound
+    ///
+    /// # Returns
+    /// Fills in ascending timestamp order
+    pub async fn get_my_trades(
+        &self,
+        account_index: i64,
+        market_index: Option<u8>,
+        start: Option<i64>,
+        end: Option<i64>,
+    ) -> Result<Vec<AccountTrade>> {
+        let records = self.fetch_trade_records(account_index, market_index, start, end).await?;
+        Ok(records.iter().filter_map(|record| record.for_account(account_index)).collect())
+    }
+
+    /// Trade records of an account within a time range (inclusive), following pages
+    pub(crate) async fn fetch_trade_records(
+        &self,
+        account_index: i64,
+        market_index: Option<u8>,
+        start: Option<i64>,
+        end: Option<i64>,
+    ) -> Result<Vec<TradeRecord>> {
+        let mut records = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let mut query = vec![
+                ("account_index", account_index.to_string()),
+                ("sort_by", "timestamp".to_string()),
+                ("sort_dir", "asc".to_string()),
+                ("limit", TRADES_PAGE_LIMIT.to_string()),
+            ];
+            if let Some(market_index) = market_index {
+                query.push(("market_id", market_index.to_string()));
+            }
+            if let Some(start) = start {
+                query.push(("from", start.to_string()));
+            }
+            if let Some(cursor) = &cursor {
+                query.push(("cursor", cursor.clone()));
+            }
+
+            let response = self.get_json_auth("/api/v1/trades", &query).await?;
+            check_response_code(&response)?;
+            let page: TradesPage = decode(response)?;
+
+            let mut past_end = false;
+            for record in &page.trades {
+                if start.is_some_and(|start| record.timestamp < start) {
+                    continue;
+                }
+                if end.is_some_and(|end| record.timestamp > end) {
+                    past_end = true;
+                    break;
+                }
+                records.push(record.clone());
+            }
+
+            cursor = match page.next_cursor {
+                Some(next)
+                    if !next.is_empty()
+                        && !past_end
+                        && !page.trades.is_empty()
+                        && Some(&next) != cursor.as_ref() =>
+                {
+                    Some(next)
+                }
+                _ => break,
+            };
+        }
+
+        Ok(records)
+    }
+
+    /// Stream every historical order, transparently following pagination cursors
+    ///
+    /// The stream ends after the last page (no cursor, or an empty page) and
+    /// also stops after yielding the first error.
+    pub fn order_history_stream(
+        &self,
+        account_index: i64,
+        market_index: Option<u8>,
+    ) -> impl Stream<Item = Result<Order>> + '_ {
+        enum Page {
+            First,
+            Next(String),
+            Done,
+        }
+
+        stream::unfold(Page::First, move |state| async move {
+            let cursor = match state {
+                Page::First => None,
+                Page::Next(cursor) => Some(cursor),
+                Page::Done => return None,
+            };
+
+            match self.get_order_history(account_index, market_index, cursor.as_deref()).await {
+                Ok(page) => {
+                    let next = match page.next_cursor {
+                        // Guard against a server echoing the same cursor forever
+                        Some(next) if !page.orders.is_empty() && Some(&next) != cursor.as_ref() => {
+                            Page::Next(next)
+                        }
+                        _ => Page::Done,
+                    };
+                    let orders: Vec<Result<Order>> = page.orders.into_iter().map(Ok).collect();
+                    Some((orders, next))
+                }
+                Err(e) => Some((vec![Err(e)], Page::Done)),
+            }
+        })
+        .flat_map(stream::iter)
+    }
+}
+
+/// Deserialize a response body into a typed response
+///
+/// Response types ignore fields they do not know and default missing ones.
+/// With the `strict-json` feature they reject unknown fields instead, to
+/// detect API drift; the `code` / `message` envelope is dropped first either way.
+pub(crate) fn decode<T: DeserializeOwned>(mut body: Value) -> Result<T> {
+    if let Value::Object(fields) = &mut body {
+        fields.remove("code");
+        fields.remove("message");
+    }
+    Ok(serde_json::from_value(body)?)
+}
+
+/// Deserialize the list under `key`, empty if the response has none
+pub(crate) fn decode_list<T: DeserializeOwned>(response: &Value, key: &str) -> Result<Vec<T>> {
+    match response.get(key) {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(list) => Ok(serde_json::from_value(list.clone())?),
+    }
+}
+
+/// Turn a non-200 `code` in a query response into an `ApiError::Api`
+pub(crate) fn check_response_code(response: &Value) -> Result<()> {
+    let code = response["code"].as_i64().unwrap_or(200);
+    if code != 200 {
+        let message = response["message"].as_str().unwrap_or_default();
+        return Err(ApiError::Api(format!("code {}: {}", code, message)));
+    }
+    Ok(())
+}
@@ -0,0 +1,248 @@
+use crate::account::{PositionRecord, TradeRecord};
+use crate::ws::{WsFrame, WsSubscription};
+use crate::{AccountTrade, ApiError, LighterClient, Order, Position, Result};
+use futures::stream::{self, BoxStream};
+use futures::{future, StreamExt};
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+/// Fill ids remembered by [`LighterClient::subscribe_account_from`] to drop repeats
+const SEEN_FILLS_CAPACITY: usize = 10_000;
+
+/// One event of an account's WebSocket streams, typed
+///
+/// See [`LighterClient::subscribe_account_events`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum AccountEvent {
+    /// An order was placed, changed, filled or canceled; carries its new state
+    OrderUpdate(Order),
+    /// A fill of one of the account's orders, from the account's side
+    Fill(AccountTrade),
+    /// New collateral and margin figures of the account
+    BalanceUpdate(BalanceUpdate),
+    /// New state of a position; a size of zero means the position was closed
+    PositionUpdate(Position),
+    /// The exchange warned that the account is at or near liquidation
+    LiquidationWarning(LiquidationWarning),
+    /// A message or notification this client does not model, as received
+    Unknown(Value),
+}
+
+/// Account-wide collateral and margin, from the `user_stats` channel
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+pub struct BalanceUpdate {
+    /// USDC deposited, before unrealized PnL
+    pub collateral: Decimal,
+    /// Collateral plus unrealized PnL of all positions
+    pub portfolio_value: Decimal,
+    /// USDC free for new orders and withdrawals
+    pub available_balance: Decimal,
+    /// Used margin as a percentage of portfolio value
+    pub margin_usage: Decimal,
+    pub leverage: Decimal,
+    pub buying_power: Decimal,
+}
+
+/// Liquidation notification of the account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiquidationWarning {
+    /// Notification id
+    pub id: String,
+    /// Market of the affected position, if the warning concerns one
+    pub market_index: Option<u8>,
+    pub message: String,
+    /// unix ms
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+struct WsNotification {
+    id: String,
+    kind: String,
+    created_at: i64,
+    content: WsNotificationContent,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+struct WsNotificationContent {
+    market_index: Option<u8>,
+    message: String,
+}
+
+/// Read `message[field]`, treating a missing field as empty
+fn field<T: DeserializeOwned + Default>(message: &Value, name: &str) -> Result<T> {
+    match message.get(name) {
+        None | Some(Value::Null) => Ok(T::default()),
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|e| ApiError::Api(format!("unreadable {} in account message: {}", name, e))),
+    }
+}
+
+impl AccountEvent {
+    /// Read the events of one account channel message of `account_index`
+    ///
+    /// Orders, trades and positions of an `account_all` message become one
+    /// event each (the subscription's first message lists the current state),
+    /// `user_stats` messages a [`BalanceUpdate`], and liquidation notifications
+    /// a [`LiquidationWarning`]. Messages of other types, and notifications of
+    /// other kinds, are returned whole as `Unknown`. Fails if a message of a
+    /// modeled type cannot be read, and for `error` messages.
+    pub fn from_message(message: &Value, account_index: i64) -> Result<Vec<Self>> {
+        let kind = message["type"].as_str().unwrap_or_default();
+        match kind.split_once('/').map_or(kind, |(_, channel)| channel) {
+            "account_all" => {
+                let orders: BTreeMap<u8, Vec<Order>> = field(message, "orders")?;
+                let trades: BTreeMap<u8, Vec<TradeRecord>> = field(message, "trades")?;
+                let positions: BTreeMap<u8, PositionRecord> = field(message, "positions")?;
+
+                let orders = orders.into_values().flatten().map(AccountEvent::OrderUpdate);
+                let fills = trades
+                    .into_values()
+                    .flatten()
+                    .filter_map(|trade| trade.for_account(account_index))
+                    .map(AccountEvent::Fill);
+                let positions = positions
+                    .into_values()
+                    .map(|record| AccountEvent::PositionUpdate(record.into_position_or_flat()));
+                Ok(orders.chain(fills).chain(positions).collect())
+            }
+            "user_stats" => Ok(vec![AccountEvent::BalanceUpdate(field(message, "stats")?)]),
+            "notification" => {
+                let notifications: Vec<Value> = field(message, "notifs")?;
+                notifications
+                    .into_iter()
+                    .map(|notification| {
+                        if notification["kind"] != "liquidation" {
+                            return Ok(AccountEvent::Unknown(notification));
+                        }
+                        let notification: WsNotification = serde_json::from_value(notification)
+                            .map_err(|e| ApiError::Api(format!("unreadable liquidation notification: {}", e)))?;
+                        Ok(AccountEvent::LiquidationWarning(LiquidationWarning {
+                            id: notification.id,
+                            market_index: notification.content.market_index,
+                            message: notification.content.message,
+                            timestamp: notification.created_at,
+                        }))
+                    })
+                    .collect()
+            }
+            _ if kind == "error" => Err(ApiError::Api(format!("WebSocket: {}", message["message"]))),
+            _ => Ok(vec![AccountEvent::Unknown(message.clone())]),
+        }
+    }
+}
+
+/// Most recent fill ids, forgetting the oldest beyond [`SEEN_FILLS_CAPACITY`]
+#[derive(Debug, Default)]
+struct SeenFills {
+    ids: HashSet<i64>,
+    order: VecDeque<i64>,
+}
+
+impl SeenFills {
+    /// Remember `trade_id`; false if it was already seen
+    fn insert(&mut self, trade_id: i64) -> bool {
+        if !self.ids.insert(trade_id) {
+            return false;
+        }
+        self.order.push_back(trade_id);
+        if self.order.len() > SEEN_FILLS_CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Events of one account channel subscription
+fn account_channel(subscription: WsSubscription, account_index: i64) -> BoxStream<'static, Result<AccountEvent>> {
+    stream::unfold(Some(subscription), move |state| async move {
+        let mut subscription = state?;
+        loop {
+            let message = match subscription.next().await? {
+                Ok(WsFrame::Json(message)) => message,
+                // The new subscription starts with the current state again
+                Ok(WsFrame::Reconnected) => continue,
+                Err(e) => return Some((Err(e), None)),
+            };
+            match AccountEvent::from_message(&message, account_index) {
+                Ok(events) => return Some((Ok(events), Some(subscription))),
+                Err(e) => return Some((Err(e), None)),
+            }
+        }
+    })
+    .flat_map(|events| match events {
+        Ok(events) => stream::iter(events.into_iter().map(Ok).collect::<Vec<_>>()),
+        Err(e) => stream::iter(vec![Err(e)]),
+    })
+    .boxed()
+}
+
+impl LighterClient {
+    /// Stream typed events of one account
+    ///
+    /// Subscribes to the account's `account_all` (orders, fills, positions)
+    /// and `user_stats` (balances) channels and yields their messages as
+    /// [`AccountEvent`]s, see [`AccountEvent::from_message`]. Both channels
+    /// start with the account's current state, and again after a heartbeat
+    /// reconnect. Messages the client does not model arrive as
+    /// `AccountEvent::Unknown` instead of being dropped. Fills are seen from
+    /// `account_index`'s side. The stream ends once either channel is closed
+    /// by the server or fails; an error is yielded before it ends.
+    pub async fn subscribe_account_events(&self, account_index: i64) -> Result<BoxStream<'static, Result<AccountEvent>>> {
+        let account = WsSubscription::open(self.ws_config(), format!("account_all/{}", account_index)).await?;
+        let stats = WsSubscription::open(self.ws_config(), format!("user_stats/{}", account_index)).await?;
+        let merged = stream::select(
+            account_channel(account, account_index).map(Some).chain(stream::iter([None])),
+            account_channel(stats, account_index).map(Some).chain(stream::iter([None])),
+        );
+        // Stop at the end of the first channel to close rather than waiting on the other
+        Ok(merged.take_while(|event| future::ready(event.is_some())).filter_map(future::ready).boxed())
+    }
+
+    /// [`Self::subscribe_account_events`], starting with the account's fills since `since`
+    ///
+    /// The WebSocket channels cannot replay from a point in time, so the live
+    /// subscription is opened first and the fills from `since` (unix ms,
+    /// inclusive) are then fetched over REST with [`Self::get_my_trades`] and
+    /// yielded ahead of the live events, oldest first. Fills are deduplicated by
+    /// trade id across both sources, including the recent trades that each
+    /// channel snapshot repeats, and live fills older than `since` are dropped.
+    /// Other events pass through unchanged.
+    ///
+    /// To track fills without gaps across your own reconnects, pass the
+    /// timestamp of the last fill you processed: fills at that millisecond come
+    /// again and can be told apart by `trade_id`.
+    pub async fn subscribe_account_from(
+        &self,
+        account_index: i64,
+        since: i64,
+    ) -> Result<BoxStream<'static, Result<AccountEvent>>> {
+        let live = self.subscribe_account_events(account_index).await?;
+        let recent = self.get_my_trades(account_index, None, Some(since), None).await?;
+
+        let mut seen = SeenFills::default();
+        let backlog: Vec<_> = recent
+            .into_iter()
+            .filter(|trade| seen.insert(trade.trade_id))
+            .map(|trade| Ok(AccountEvent::Fill(trade)))
+            .collect();
+        let live = live.filter(move |event| {
+            future::ready(match event {
+                Ok(AccountEvent::Fill(trade)) => trade.timestamp >= since && seen.insert(trade.trade_id),
+                _ => true,
+            })
+        });
+        Ok(stream::iter(backlog).chain(live).boxed())
+    }
+}
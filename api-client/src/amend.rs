@@ -0,0 +1,172 @@
+use crate::transaction::{ModifyOrderTx, TxHeader};
+use crate::{ApiError, LighterClient, MarketInfo, Order, Result};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+/// Outcome of [`LighterClient::amend_order`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Amendment {
+    /// Client order index of the amended order (unchanged by the amendment)
+    pub client_order_index: u64,
+    /// Whether the order kept its place in the queue at its price level
+    pub priority_preserved: bool,
+}
+
+/// `amount` in integer units of `decimals` decimals, if it fits exactly
+fn to_units(amount: Decimal, decimals: u32) -> Option<i64> {
+    let units = amount * Decimal::from(10u64.pow(decimals));
+    if units.fract() != Decimal::ZERO {
+        return None;
+    }
+    units.to_i64()
+}
+
+/// Price, trigger price and remaining size of `order` in integer units of `market`
+fn order_units(order: &Order, market: &MarketInfo) -> Result<(i64, i64, i64)> {
+    let unrepresentable = |field: &str, value: Decimal| {
+        ApiError::Api(format!(
+            "order {} {} {} does not fit market {}",
+            order.client_order_index, field, value, market.symbol
+        ))
+    };
+    let price = to_units(order.price, market.price_decimals).ok_or_else(|| unrepresentable("price", order.price))?;
+    let trigger_price = to_units(order.trigger_price, market.price_decimals)
+        .ok_or_else(|| unrepresentable("trigger price", order.trigger_price))?;
+    let remaining = to_units(order.remaining_base_amount, market.size_decimals)
+        .ok_or_else(|| unrepresentable("remaining size", order.remaining_base_amount))?;
+    Ok((price, trigger_price, remaining))
+}
+
+impl LighterClient {
+    /// Change a resting order, keeping its queue priority where the exchange allows
+    ///
+    /// Lighter's matching engine keeps an order's time priority when a modify
+    /// (tx_type 17) leaves the price unchanged and does not increase the
+    /// remaining size. Such amendments are sent as a modify carrying the
+    /// order's current price and trigger price. Any other change (a new price,
+    /// or a larger size) goes through [`Self::replace_order`] and the order
+    /// moves to the back of the queue; [`Amendment::priority_preserved`]
+    /// reports which path was taken.
+    ///
+    /// The order is looked up first (see [`Self::get_order`]) to learn its
+    /// market, current price and remaining size, so this costs extra requests
+    /// over `replace_order`. Fails with `ApiError::NotFound` if the account has
+    /// no open order with this client order index, and with
+    /// `ApiError::InvalidOrderParams` if the amendment would change nothing.
+    ///
+    /// # Arguments
+    /// * `client_order_index` - Client order index the order was placed with
+    /// * `new_size` - New remaining base amount (integer, market size decimals)
+    /// * `new_price` - New limit price (integer, market price decimals), `None` to keep it
+    pub async fn amend_order(
+        &self,
+        client_order_index: u64,
+        new_size: i64,
+        new_price: Option<i64>,
+    ) -> Result<Amendment> {
+        if new_size <= 0 {
+            return Err(ApiError::InvalidOrderParams(format!("order size must be positive, got {}", new_size)));
+        }
+        let order = self
+            .get_order(self.account_index, client_order_index)
+            .await?
+            .filter(|order| order.status == "open")
+            .ok_or_else(|| {
+                ApiError::NotFound(format!("no open order with client order index {}", client_order_index))
+            })?;
+        let market_index = order.market_index;
+
+        let market = self.cached_market_info(market_index).await?;
+        let (price, trigger_price, remaining) = order_units(&order, &market)?;
+
+        let new_price = new_price.unwrap_or(price);
+        if new_price == price && new_size == remaining {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "order {} already has price {} and size {}",
+                client_order_index, price, remaining
+            )));
+        }
+        if new_price != price || new_size > remaining {
+            self.replace_order(market_index, client_order_index, new_price, new_size).await?;
+            return Ok(Amendment { client_order_index, priority_preserved: false });
+        }
+
+        let nonce = self.get_next_nonce_from_cache().await?;
+        self.submit_with_nonce(nonce, ModifyOrderTx {
+            header: TxHeader::default(),
+            market_index,
+            index: client_order_index as i64,
+            base_amount: new_size,
+            price,
+            trigger_price,
+        }).await?;
+        Ok(Amendment { client_order_index, priority_preserved: true })
+    }
+    /// Change the price and/or size of an open order in one modify (tx_type 17)
+    ///
+    /// `None` keeps a field as it is: the order is looked up first (see
+    /// [`Self::get_order`]), and the modify carries its current price, trigger
+    /// price and remaining size for the fields left out, since the transaction
+    /// always sets all three. Fails with `ApiError::InvalidOrderParams` if
+    /// neither field is given or neither differs from the order, with
+    /// `ApiError::NotFound` if the account has no open order with this client
+    /// order index, and with the exchange's error if it rejects the modify.
+    /// Like [`Self::replace_order`], a new price or larger size moves the order
+    /// to the back of the queue (see [`Self::amend_order`]).
+    ///
+    /// # Returns
+    /// The order as modified: the fetched order with the new price and
+    /// remaining size, and the initial size grown or shrunk by the same amount.
+    pub async fn modify_order(
+        &self,
+        client_order_index: u64,
+        new_price: Option<i64>,
+        new_size: Option<i64>,
+    ) -> Result<Order> {
+        if new_price.is_none() && new_size.is_none() {
+            return Err(ApiError::InvalidOrderParams("modify_order needs a new price or size".to_string()));
+        }
+        if let Some(value) = new_price.filter(|price| *price <= 0) {
+            return Err(ApiError::InvalidOrderParams(format!("order price must be positive, got {}", value)));
+        }
+        if let Some(value) = new_size.filter(|size| *size <= 0) {
+            return Err(ApiError::InvalidOrderParams(format!("order size must be positive, got {}", value)));
+        }
+        let mut order = self
+            .get_order(self.account_index, client_order_index)
+            .await?
+            .filter(|order| order.status == "open")
+            .ok_or_else(|| {
+                ApiError::NotFound(format!("no open order with client order index {}", client_order_index))
+            })?;
+
+        let market = self.cached_market_info(order.market_index).await?;
+        let (price, trigger_price, remaining) = order_units(&order, &market)?;
+        let (new_price, new_size) = (new_price.unwrap_or(price), new_size.unwrap_or(remaining));
+        if new_price == price && new_size == remaining {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "order {} already has price {} and size {}",
+                client_order_index, price, remaining
+            )));
+        }
+        if self.validate_orders {
+            self.check_order_params(order.market_index, new_price, new_size).await?;
+        }
+
+        let nonce = self.get_next_nonce_from_cache().await?;
+        self.submit_with_nonce(nonce, ModifyOrderTx {
+            header: TxHeader::default(),
+            market_index: order.market_index,
+            index: client_order_index as i64,
+            base_amount: new_size,
+            price: new_price,
+            trigger_price,
+        }).await?;
+
+        let remaining = Decimal::new(new_size, market.size_decimals);
+        order.initial_base_amount += remaining - order.remaining_base_amount;
+        order.remaining_base_amount = remaining;
+        order.price = Decimal::new(new_price, market.price_decimals);
+        Ok(order)
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{ApiError, Result};
+use futures::stream::{self, BoxStream, Stream};
+use futures::StreamExt;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+/// What a [`BufferedStream`] does when its consumer falls behind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backpressure {
+    /// Read the socket only as fast as items are consumed
+    ///
+    /// Nothing is lost, but a slow consumer leaves messages queued in the
+    /// server and OS buffers, and the data it sees grows stale.
+    Block,
+    /// Keep at most this many unread items (at least 1), discarding the oldest
+    ///
+    /// Data loss the caller opts into; for trade feeds prefer `Block` unless
+    /// freshness matters more than completeness.
+    DropOldest(usize),
+    /// Keep only the newest unread item
+    ///
+    /// For streams whose items are full states, such as order books and BBOs,
+    /// where an older state is worthless once a newer one exists.
+    LatestOnly,
+}
+
+/// A subscription stream with a chosen [`Backpressure`] strategy
+///
+/// Wrap any subscription, e.g.
+/// `BufferedStream::new(client.subscribe_order_book(0, None).await?, Backpressure::LatestOnly)`.
+/// Except with `Block`, a background task reads the subscription as fast as it
+/// produces items and buffers them; [`Self::dropped`] counts the items
+/// discarded. Errors are never discarded: one ends the stream after the items
+/// buffered before it. Must be created within a Tokio runtime; dropping the
+/// stream stops the background task and closes the subscription.
+pub struct BufferedStream<T> {
+    inner: BoxStream<'static, Result<T>>,
+    dropped: Arc<AtomicU64>,
+}
+
+struct Shared<T> {
+    buffer: Mutex<Buffer<T>>,
+    notify: Notify,
+}
+
+struct Buffer<T> {
+    items: VecDeque<T>,
+    /// Set when the source ended, holding the error it ended with, if any
+    end: Option<Option<ApiError>>,
+}
+
+/// Stops the reading task once the consumer side is gone
+struct AbortOnDrop(JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl<T: Send + 'static> BufferedStream<T> {
+    pub fn new(source: BoxStream<'static, Result<T>>, strategy: Backpressure) -> Self {
+        let dropped = Arc::new(AtomicU64::new(0));
+        let capacity = match strategy {
+            Backpressure::Block => return Self { inner: source, dropped },
+            Backpressure::DropOldest(capacity) => capacity.max(1),
+            Backpressure::LatestOnly => 1,
+        };
+
+        let shared = Arc::new(Shared {
+            buffer: Mutex::new(Buffer { items: VecDeque::new(), end: None }),
+            notify: Notify::new(),
+        });
+        let reader = tokio::spawn(fill(source, shared.clone(), capacity, dropped.clone()));
+
+        let inner = stream::unfold((shared, AbortOnDrop(reader)), |(shared, reader)| async move {
+            loop {
+                {
+                    let mut buffer = shared.buffer.lock().expect("stream buffer poisoned");
+                    if let Some(item) = buffer.items.pop_front() {
+                        drop(buffer);
+                        return Some((Ok(item), (shared, reader)));
+                    }
+                    match buffer.end.take() {
+                        Some(Some(e)) => {
+                            buffer.end = Some(None);
+                            drop(buffer);
+                            return Some((Err(e), (shared, reader)));
+                        }
+                        Some(None) => {
+                            buffer.end = Some(None);
+                            return None;
+                        }
+                        None => {}
+                    }
+                }
+                shared.notify.notified().await;
+            }
+        })
+        .boxed();
+
+        Self { inner, dropped }
+    }
+
+    /// Items discarded so far because the consumer fell behind
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Move items from `source` into the buffer, discarding the oldest beyond `capacity`
+async fn fill<T>(mut source: BoxStream<'static, Result<T>>, shared: Arc<Shared<T>>, capacity: usize, dropped: Arc<AtomicU64>) {
+    let end = loop {
+        match source.next().await {
+            Some(Ok(item)) => {
+                let mut buffer = shared.buffer.lock().expect("stream buffer poisoned");
+                if buffer.items.len() == capacity {
+                    buffer.items.pop_front();
+                    dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                buffer.items.push_back(item);
+            }
+            Some(Err(e)) => break Some(e),
+            None => break None,
+        }
+        shared.notify.notify_one();
+    };
+    shared.buffer.lock().expect("stream buffer poisoned").end = Some(end);
+    shared.notify.notify_one();
+}
+
+impl<T> Stream for BufferedStream<T> {
+    type Item = Result<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
@@ -0,0 +1,276 @@
+use crate::encoding;
+use crate::response::{is_accepted, send_tx_error, send_tx_result};
+use crate::signature::check_signed;
+use crate::{ApiError, CreateOrderRequest, CreateOrderTx, LighterClient, Result, SendTxResponse, Signature, Transaction};
+use futures::future::BoxFuture;
+use serde_json::Value;
+
+/// A CREATE_ORDER transaction signed and ready for submission
+#[derive(Debug, Clone)]
+pub struct SignedOrder {
+    pub client_order_index: u64,
+    pub market_index: u8,
+    /// Nonce reserved for this order; orders must reach the exchange in nonce order
+    pub nonce: i64,
+    pub tx_type: u32,
+    /// Complete `tx_info`, including the base64 `Sig`
+    pub tx_info: Value,
+    /// The signature in `tx_info`'s `Sig`, as bytes
+    pub signature: Signature,
+}
+
+/// Per-order outcome of [`LighterClient::send_tx_batch`], in input order
+#[derive(Debug)]
+pub struct BatchResult {
+    /// `items[i]` is the outcome of the `i`-th input order: the accepted
+    /// response with its `tx_hash`, or why it was not accepted
+    pub items: Vec<Result<SendTxResponse>>,
+}
+
+impl BatchResult {
+    /// Input positions and errors of the orders that were not accepted
+    ///
+    /// Retry exactly these positions; every other order is on the exchange.
+    pub fn failed_items(&self) -> Vec<(usize, &ApiError)> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| item.as_ref().err().map(|e| (index, e)))
+            .collect()
+    }
+
+    /// Input positions and responses of the accepted orders
+    pub fn succeeded_items(&self) -> Vec<(usize, &SendTxResponse)> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| item.as_ref().ok().map(|response| (index, response)))
+            .collect()
+    }
+
+    /// Whether every order was accepted
+    pub fn is_success(&self) -> bool {
+        self.items.iter().all(Result::is_ok)
+    }
+}
+
+impl LighterClient {
+    /// Sign many orders in parallel without submitting them
+    ///
+    /// Each order is validated (unless disabled on the builder) and reserves the
+    /// next nonce in input order; the signing itself runs in parallel on the
+    /// signing pool (see `LighterClientBuilder::signing_threads`), tokio's
+    /// blocking thread pool by default. The result has one entry per input order, in the same order.
+    ///
+    /// An order that fails validation reserves no nonce, so the failure affects
+    /// only its own entry. An order whose signing fails after its nonce was
+    /// reserved leaves a gap the orders after it cannot cross, so they fail
+    /// too, and all of these nonces are handed back to the cache, latest first.
+    ///
+    /// Submit the signed orders in the returned order: nonces are consecutive,
+    /// and an order sent ahead of a lower nonce is rejected.
+    pub async fn sign_orders(&self, orders: &[CreateOrderRequest]) -> Vec<Result<SignedOrder>> {
+        let mut tasks = Vec::with_capacity(orders.len());
+        for order in orders {
+            tasks.push(self.spawn_order_signing(order).await);
+        }
+
+        let mut signed = Vec::with_capacity(tasks.len());
+        // Position of the first order that failed to sign, and the nonces reserved from there on
+        let mut failed_at = None;
+        let mut unused = Vec::new();
+        for (position, task) in tasks.into_iter().enumerate() {
+            let result = match task {
+                Ok((nonce, signing)) => match (signing.await, failed_at) {
+                    (Ok(order), None) => Ok(order),
+                    (result, _) => {
+                        unused.push(nonce);
+                        let failed = *failed_at.get_or_insert(position);
+                        result.and_then(|_| {
+                            Err(ApiError::Api(format!("not signed: order {} before it failed to sign", failed)))
+                        })
+                    }
+                },
+                Err(e) => Err(e),
+            };
+            signed.push(result);
+        }
+        for nonce in unused.into_iter().rev() {
+            self.release_nonce(nonce, None).await;
+        }
+        signed
+    }
+
+    /// Most transactions this client sends in one `sendTxBatch` request
+    ///
+    /// See `LighterClientBuilder::max_batch_size`.
+    pub fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+
+    /// Sign `orders` and submit them in `sendTxBatch` requests
+    ///
+    /// Orders that fail validation or signing keep their own error and are left
+    /// out, as do the orders after a signing failure (see [`Self::sign_orders`]);
+    /// the rest are sent in input order with consecutive nonces, split into
+    /// requests of at most [`Self::max_batch_size`] transactions. The requests
+    /// go one after another, each waiting for a `max_in_flight` slot, so a large
+    /// batch never bursts past the client's limit. If the exchange rejects a
+    /// request (or it fails in transit) its orders get an error, the requests
+    /// after it are not sent (their nonces would follow a gap) and their orders
+    /// get an error too; all of these nonces are handed back. An order of an
+    /// accepted request that the response lists no `tx_hash` for fails as well,
+    /// but keeps its nonce, since the exchange may have taken it. Results stay
+    /// in input order across requests: use [`BatchResult::failed_items`] to
+    /// retry only what did not go through.
+    pub async fn send_tx_batch(&self, orders: &[CreateOrderRequest]) -> BatchResult {
+        let mut items: Vec<Result<SendTxResponse>> = Vec::with_capacity(orders.len());
+        let mut submitted = Vec::new();
+        for (index, signed) in self.sign_orders(orders).await.into_iter().enumerate() {
+            match signed {
+                Ok(signed) => {
+                    submitted.push((index, signed));
+                    items.push(Err(ApiError::Api("not sent".to_string())));
+                }
+                Err(e) => items.push(Err(e)),
+            }
+        }
+        if submitted.is_empty() {
+            return BatchResult { items };
+        }
+
+        let chunks: Vec<_> = submitted.chunks(self.max_batch_size).collect();
+        for (position, chunk) in chunks.iter().enumerate() {
+            let txs: Vec<_> = chunk.iter().map(|(_, signed)| (signed.tx_type, &signed.tx_info)).collect();
+            let (reason, response) = match self.post_tx_batch(&txs).await {
+                Ok(response) if is_accepted(&response) => {
+                    self.nonce_gaps.record_success();
+                    let hashes = response["tx_hash"].as_array().cloned().unwrap_or_default();
+                    let message = response["message"].as_str().unwrap_or_default();
+                    for (offset, (index, _)) in chunk.iter().enumerate() {
+                        items[*index] = match hashes.get(offset).and_then(Value::as_str) {
+                            Some(tx_hash) => Ok(SendTxResponse {
+                                tx_hash: tx_hash.to_string(),
+                                code: 200,
+                                message: message.to_string(),
+                            }),
+                            None => Err(ApiError::Api(format!(
+                                "sendTxBatch accepted the request but returned no tx_hash for its transaction {}",
+                                offset
+                            ))),
+                        };
+                    }
+                    continue;
+                }
+                Ok(response) => (format!("sendTxBatch rejected ({})", send_tx_error(&response, None)), Some(response)),
+                Err(e) => (format!("sendTxBatch failed: {}", e), None),
+            };
+
+            let unsent = chunks[position + 1..].iter().flat_map(|chunk| chunk.iter());
+            for (index, _) in chunk.iter() {
+                items[*index] = Err(ApiError::Api(reason.clone()));
+            }
+            for (index, _) in unsent.clone() {
+                items[*index] = Err(ApiError::Api(format!("not sent: an earlier batch failed ({})", reason)));
+            }
+            // Latest nonce first, so each one is handed back in turn
+            for (_, signed) in chunk.iter().chain(unsent).rev() {
+                self.release_nonce(signed.nonce, response.as_ref()).await;
+            }
+            break;
+        }
+        BatchResult { items }
+    }
+
+    pub(crate) async fn post_tx_batch(&self, txs: &[(u32, &Value)]) -> Result<Value> {
+        for (tx_type, tx_info) in txs {
+            check_signed(*tx_type, tx_info)?;
+        }
+        let request = self.client.post(format!("{}/api/v1/sendTxBatch", self.base_url));
+        let request = encoding::send_tx_batch_body(request, self.send_tx_encoding, txs)?;
+        let _slot = self.in_flight.acquire().await;
+        self.send_json(request).await
+    }
+
+    /// Sign `orders` and submit them in a single `sendTxBatch` request, all or nothing
+    ///
+    /// Nothing is sent unless every order signs; a signing failure is reported
+    /// as `ApiError::InvalidOrderParams` prefixed with `name` of the order. On
+    /// any failure every reserved nonce is handed back.
+    pub(crate) async fn place_together(
+        &self,
+        orders: &[CreateOrderRequest],
+        name: impl Fn(usize) -> String,
+    ) -> Result<()> {
+        let mut signed = Vec::with_capacity(orders.len());
+        let mut failure = None;
+        for (number, result) in self.sign_orders(orders).await.into_iter().enumerate() {
+            match result {
+                Ok(order) => signed.push(order),
+                Err(e) => {
+                    failure.get_or_insert(ApiError::InvalidOrderParams(format!("{}: {}", name(number), e)));
+                }
+            }
+        }
+
+        let response = match failure {
+            Some(e) => Err(e),
+            None => {
+                let txs: Vec<_> = signed.iter().map(|order| (order.tx_type, &order.tx_info)).collect();
+                self.post_tx_batch(&txs).await
+            }
+        };
+        let (error, response) = match response {
+            Ok(response) if is_accepted(&response) => {
+                self.nonce_gaps.record_success();
+                return Ok(());
+            }
+            Ok(response) => (send_tx_error(&response, None), Some(response)),
+            Err(e) => (e, None),
+        };
+        // Latest nonce first, so each one is handed back in turn
+        for order in signed.iter().rev() {
+            self.release_nonce(order.nonce, response.as_ref()).await;
+        }
+        Err(error)
+    }
+
+    /// Submit a transaction signed elsewhere, e.g. by [`Self::sign_orders`] in another process
+    ///
+    /// `tx_info` is sent exactly as given: nothing is re-signed and no nonce is
+    /// fetched or reserved, so the payload's nonce and expiry must still be valid
+    /// when it arrives. Fails with `ApiError::Unsigned` before any network call
+    /// if `tx_info` has no well-formed `Sig`. Rejections are errors, mapped like
+    /// those of every other transaction (see [`crate::check_send_tx_response`]).
+    pub async fn submit_signed(&self, tx_type: u32, tx_info: &Value) -> Result<SendTxResponse> {
+        send_tx_result(self.send_tx(tx_type, tx_info).await?, Some(tx_info))
+    }
+
+    /// Validate `order`, reserve its nonce and start signing it on the signing pool
+    async fn spawn_order_signing(
+        &self,
+        order: &CreateOrderRequest,
+    ) -> Result<(i64, BoxFuture<'static, Result<SignedOrder>>)> {
+        if self.validate_orders {
+            self.check_order_params(order.order_book_index, order.price, order.base_amount).await?;
+        }
+        let mut tx = order.to_tx_at(self.clock.now_ms())?;
+        let nonce = self.get_nonce(false).await?;
+
+        let client = self.clone();
+        let client_order_index = order.client_order_index;
+        let market_index = order.order_book_index;
+        let signing = self.spawn_signing(move || {
+            let (tx_info, signature) = client.build_and_sign_with_signature(nonce, &mut tx)?;
+            Ok(SignedOrder {
+                client_order_index,
+                market_index,
+                nonce,
+                tx_type: CreateOrderTx::TX_TYPE,
+                tx_info,
+                signature,
+            })
+        });
+        Ok((nonce, signing))
+    }
+}
@@ -0,0 +1,91 @@
+//! Synchronous facade over [`crate::LighterClient`] (feature `blocking`)
+//!
+//! Each call runs the async method to completion on a runtime owned by the
+//! client, so no async setup is needed. Do not call these methods from inside
+//! an async runtime: blocking on one runtime from within another panics. Use
+//! the async client there instead.
+
+use crate::{ApiError, CreateOrderRequest, LighterClientBuilder, Order, Position, Result, SendTxResponse, Side};
+use tokio::runtime::Runtime;
+
+/// Blocking Lighter client; see the [module documentation](self)
+#[derive(Debug)]
+pub struct LighterClient {
+    inner: crate::LighterClient,
+    runtime: Runtime,
+}
+
+impl LighterClient {
+    /// Create a client with default settings, as [`crate::LighterClient::new`]
+    pub fn new(base_url: String, private_key_hex: &str, account_index: i64, api_key_index: u8) -> Result<Self> {
+        Self::from_builder(crate::LighterClient::builder(base_url, private_key_hex, account_index, api_key_index))
+    }
+
+    /// Create a client from a configured async builder
+    pub fn from_builder(builder: LighterClientBuilder) -> Result<Self> {
+        let inner = builder.build()?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| ApiError::Api(format!("failed to start runtime: {}", e)))?;
+        Ok(Self { inner, runtime })
+    }
+
+    /// The underlying async client, e.g. for methods without a blocking counterpart
+    pub fn inner(&self) -> &crate::LighterClient {
+        &self.inner
+    }
+
+    /// Blocking [`crate::LighterClient::create_order`]
+    pub fn create_order(&self, order: CreateOrderRequest) -> Result<SendTxResponse> {
+        self.runtime.block_on(self.inner.create_order(order))
+    }
+
+    /// Blocking [`crate::LighterClient::create_market_order`]
+    pub fn create_market_order(
+        &self,
+        order_book_index: u8,
+        client_order_index: u64,
+        base_amount: i64,
+        avg_execution_price: i64,
+        side: Side,
+    ) -> Result<SendTxResponse> {
+        self.runtime.block_on(self.inner.create_market_order(
+            order_book_index,
+            client_order_index,
+            base_amount,
+            avg_execution_price,
+            side,
+        ))
+    }
+
+    /// Blocking [`crate::LighterClient::cancel_order`]
+    pub fn cancel_order(&self, order_book_index: u8, order_index: i64) -> Result<SendTxResponse> {
+        self.runtime.block_on(self.inner.cancel_order(order_book_index, order_index))
+    }
+
+    /// Blocking [`crate::LighterClient::cancel_all_orders`]
+    pub fn cancel_all_orders(&self, time_in_force: u8, time: i64) -> Result<SendTxResponse> {
+        self.runtime.block_on(self.inner.cancel_all_orders(time_in_force, time))
+    }
+
+    /// Blocking [`crate::LighterClient::get_nonce`]
+    pub fn get_nonce(&self, force: bool) -> Result<i64> {
+        self.runtime.block_on(self.inner.get_nonce(force))
+    }
+
+    /// Blocking [`crate::LighterClient::get_positions`]
+    pub fn get_positions(&self, account_index: i64) -> Result<Vec<Position>> {
+        self.runtime.block_on(self.inner.get_positions(account_index))
+    }
+
+    /// Blocking [`crate::LighterClient::get_active_orders`]
+    pub fn get_active_orders(&self, account_index: i64, market_index: u8) -> Result<Vec<Order>> {
+        self.runtime.block_on(self.inner.get_active_orders(account_index, market_index))
+    }
+
+    /// Blocking [`crate::LighterClient::get_account_index`]
+    pub fn get_account_index(&self, l1_address: &str) -> Result<Vec<i64>> {
+        self.runtime.block_on(self.inner.get_account_index(l1_address))
+    }
+}
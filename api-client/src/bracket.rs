@@ -0,0 +1,86 @@
+use crate::order_rules::{ORDER_TYPE_STOP_LOSS, ORDER_TYPE_STOP_LOSS_LIMIT, ORDER_TYPE_TAKE_PROFIT, ORDER_TYPE_TAKE_PROFIT_LIMIT};
+use crate::{ApiError, CreateOrderRequest, LighterClient, Result};
+
+/// Client order indices of a bracket placed with [`LighterClient::place_bracket`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bracket {
+    pub market_index: u8,
+    pub entry: u64,
+    pub stop_loss: u64,
+    pub take_profit: u64,
+}
+
+/// Check that the exit legs close the entry on the right side of its price
+fn validate_bracket(entry: &CreateOrderRequest, stop_loss: &CreateOrderRequest, take_profit: &CreateOrderRequest) -> Result<()> {
+    let invalid = |reason: String| Err(ApiError::InvalidOrderParams(format!("bracket: {}", reason)));
+    let exit_side = entry.side.opposite();
+    for (name, leg, types) in [
+        ("stop-loss", stop_loss, [ORDER_TYPE_STOP_LOSS, ORDER_TYPE_STOP_LOSS_LIMIT]),
+        ("take-profit", take_profit, [ORDER_TYPE_TAKE_PROFIT, ORDER_TYPE_TAKE_PROFIT_LIMIT]),
+    ] {
+        if leg.order_book_index != entry.order_book_index {
+            return invalid(format!("{} is on market {}, the entry on {}", name, leg.order_book_index, entry.order_book_index));
+        }
+        if leg.side != exit_side {
+            return invalid(format!("{} must be a {:?} to close a {:?} entry", name, exit_side, entry.side));
+        }
+        if !types.contains(&leg.order_type) {
+            return invalid(format!("{} has order type {}, expected one of {:?}", name, leg.order_type, types));
+        }
+        if leg.trigger_price <= 0 {
+            return invalid(format!("{} needs a positive trigger price", name));
+        }
+    }
+
+    // A long is stopped out below the entry and takes profit above it; a short the other way round
+    for leg in [stop_loss, take_profit] {
+        leg.validate_trigger(entry.price).or_else(|e| match e {
+            ApiError::InvalidOrderParams(reason) => invalid(reason),
+            e => Err(e),
+        })?;
+    }
+
+    let indices = [entry.client_order_index, stop_loss.client_order_index, take_profit.client_order_index];
+    if indices[0] == indices[1] || indices[0] == indices[2] || indices[1] == indices[2] {
+        return invalid(format!("client order indices {:?} must be distinct", indices));
+    }
+    Ok(())
+}
+
+impl LighterClient {
+    /// Place an entry order together with its stop-loss and take-profit
+    ///
+    /// The legs are checked first: the exits must be on the entry's market and
+    /// opposite side, of stop-loss (2, 3) and take-profit (4, 5) order types,
+    /// with triggers on either side of the entry price (below and above it for a
+    /// buy, the reverse for a sell), and all three client order indices must
+    /// differ. All three are signed (see [`Self::sign_orders`]) before anything
+    /// is sent, then submitted in a single `sendTxBatch` request, so the
+    /// exchange accepts or rejects them together; on any failure every
+    /// reserved nonce is handed back and nothing is placed.
+    ///
+    /// Once placed the legs are not linked on the exchange: this client has no
+    /// grouped (OCO) order transaction, so they are three independent orders.
+    /// When one exit fills, cancel the other with [`Self::cancel_order`] using
+    /// the returned indices, and make the exits reduce-only so a stale one
+    /// cannot open a new position.
+    pub async fn place_bracket(
+        &self,
+        entry: CreateOrderRequest,
+        stop_loss: CreateOrderRequest,
+        take_profit: CreateOrderRequest,
+    ) -> Result<Bracket> {
+        validate_bracket(&entry, &stop_loss, &take_profit)?;
+        let bracket = Bracket {
+            market_index: entry.order_book_index,
+            entry: entry.client_order_index,
+            stop_loss: stop_loss.client_order_index,
+            take_profit: take_profit.client_order_index,
+        };
+
+        let legs = ["entry", "stop-loss", "take-profit"];
+        self.place_together(&[entry, stop_loss, take_profit], |number| format!("bracket {}", legs[number]))
+            .await?;
+        Ok(bracket)
+    }
+}
@@ -0,0 +1,597 @@
+use crate::circuit::{CircuitBreaker, CircuitConfig};
+use crate::failover::{Endpoints, DEFAULT_FAILOVER_THRESHOLD};
+use crate::latency::LatencyTracker;
+use crate::{
+    ApiError, Clock, HttpExchange, HttpLogHandler, InFlightLimit, LighterClient, NonceCache, NonceGapTracker,
+    NonceSource, RejectionHandler, RejectionRecord, Result, SendTxEncoding, SigningPool, SystemClock, WsFrameHandler,
+    DEFAULT_LATENCY_WINDOW,
+};
+use crate::tx;
+use reqwest::Client;
+use signer::KeyManager;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicI64;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::sync::Mutex as AsyncMutex;
+use zeroize::Zeroizing;
+
+/// Default transaction validity window: 10 minutes - 1 second (matches Go)
+pub const DEFAULT_TX_EXPIRY: Duration = Duration::from_millis(599_000);
+
+/// Maximum transaction validity window accepted by the server
+pub const MAX_TX_EXPIRY: Duration = Duration::from_millis(600_000);
+
+/// Default limit on establishing a TCP/TLS connection
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default limit on a whole HTTP request, from sending to the last body byte
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default interval between WebSocket heartbeat pings
+pub const DEFAULT_WS_PING_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Default time allowed for a heartbeat pong before the connection is replaced
+pub const DEFAULT_WS_PONG_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Consecutive nonce rejections after which a nonce gap is assumed
+pub const DEFAULT_NONCE_GAP_THRESHOLD: u32 = 3;
+
+/// Transactions per `sendTxBatch` request the exchange accepts
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 50;
+
+/// Whether `sendTx` asks the exchange to reject orders far from the mark price, unless configured
+pub const DEFAULT_PRICE_PROTECTION: bool = true;
+
+/// Client identifier sent in the User-Agent header of every request
+pub const DEFAULT_USER_AGENT: &str = concat!("lighter-rust/", env!("CARGO_PKG_VERSION"));
+
+/// Builder for [`LighterClient`] with optional configuration
+///
+/// ```rust,no_run
+/// use api_client::LighterClient;
+/// use std::time::Duration;
+///
+/// # fn main() -> Result<(), api_client::ApiError> {
+/// let client = LighterClient::builder(
+///     "https://mainnet.zklighter.elliot.ai".to_string(),
+///     "your_private_key_hex",
+///     0,
+///     0,
+/// )
+/// .default_expiry(Duration::from_secs(120))
+/// .build()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct LighterClientBuilder {
+    base_url: String,
+    base_urls: Option<Vec<String>>,
+    failover_threshold: u32,
+    circuit_breaker: Option<CircuitConfig>,
+    private_key_hex: Zeroizing<String>,
+    account_index: i64,
+    api_key_index: u8,
+    default_expiry: Duration,
+    nonce_ttl: Option<Duration>,
+    nonce_source: NonceSource,
+    nonce_gap_threshold: u32,
+    expiry_resigns: u32,
+    max_in_flight: Option<usize>,
+    latency_window: usize,
+    max_batch_size: usize,
+    send_tx_encoding: SendTxEncoding,
+    price_protection: bool,
+    rejection_handler: Option<RejectionHandler>,
+    http_log_handler: Option<HttpLogHandler>,
+    signing_threads: Option<usize>,
+    signing_pool: Option<Arc<SigningPool>>,
+    validate_orders: bool,
+    check_book_consistency: bool,
+    connect_timeout: Duration,
+    request_timeout: Duration,
+    ws_idle_timeout: Option<Duration>,
+    ws_heartbeat: Option<(Duration, Duration)>,
+    ws_frame_handler: Option<WsFrameHandler>,
+    clock: Arc<dyn Clock>,
+    user_agent: Option<String>,
+}
+
+impl std::fmt::Debug for LighterClientBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LighterClientBuilder")
+            .field("base_url", &self.base_url)
+            .field("base_urls", &self.base_urls)
+            .field("failover_threshold", &self.failover_threshold)
+            .field("circuit_breaker", &self.circuit_breaker)
+            .field("private_key_hex", &"<redacted>")
+            .field("account_index", &self.account_index)
+            .field("api_key_index", &self.api_key_index)
+            .field("default_expiry", &self.default_expiry)
+            .field("nonce_ttl", &self.nonce_ttl)
+            .field("nonce_source", &self.nonce_source)
+            .field("nonce_gap_threshold", &self.nonce_gap_threshold)
+            .field("expiry_resigns", &self.expiry_resigns)
+            .field("max_in_flight", &self.max_in_flight)
+            .field("latency_window", &self.latency_window)
+            .field("max_batch_size", &self.max_batch_size)
+            .field("send_tx_encoding", &self.send_tx_encoding)
+            .field("price_protection", &self.price_protection)
+            .field("on_rejection", &self.rejection_handler.is_some())
+            .field("on_http_exchange", &self.http_log_handler.is_some())
+            .field("signing_threads", &self.signing_threads)
+            .field("signing_pool", &self.signing_pool)
+            .field("validate_orders", &self.validate_orders)
+            .field("check_book_consistency", &self.check_book_consistency)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("request_timeout", &self.request_timeout)
+            .field("ws_idle_timeout", &self.ws_idle_timeout)
+            .field("ws_heartbeat", &self.ws_heartbeat)
+            .field("on_ws_frame", &self.ws_frame_handler.is_some())
+            .field("clock", &self.clock)
+            .field("user_agent", &self.user_agent)
+            .finish()
+    }
+}
+
+impl LighterClientBuilder {
+    pub(crate) fn new(
+        base_url: String,
+        private_key_hex: &str,
+        account_index: i64,
+        api_key_index: u8,
+    ) -> Self {
+        Self {
+            base_url,
+            base_urls: None,
+            failover_threshold: DEFAULT_FAILOVER_THRESHOLD,
+            circuit_breaker: None,
+            private_key_hex: Zeroizing::new(private_key_hex.to_string()),
+            account_index,
+            api_key_index,
+            default_expiry: DEFAULT_TX_EXPIRY,
+            nonce_ttl: None,
+            nonce_source: NonceSource::NextNonce,
+            nonce_gap_threshold: DEFAULT_NONCE_GAP_THRESHOLD,
+            expiry_resigns: 0,
+            max_in_flight: None,
+            latency_window: DEFAULT_LATENCY_WINDOW,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            send_tx_encoding: SendTxEncoding::Form,
+            price_protection: DEFAULT_PRICE_PROTECTION,
+            rejection_handler: None,
+            http_log_handler: None,
+            signing_threads: None,
+            signing_pool: None,
+            validate_orders: true,
+            check_book_consistency: true,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            ws_idle_timeout: None,
+            ws_heartbeat: Some((DEFAULT_WS_PING_INTERVAL, DEFAULT_WS_PONG_TIMEOUT)),
+            ws_frame_handler: None,
+            clock: Arc::new(SystemClock),
+            user_agent: None,
+        }
+    }
+
+    /// Validity window applied to `ExpiredAt` on every transaction
+    ///
+    /// Defaults to [`DEFAULT_TX_EXPIRY`]. Must be non-zero and no longer than
+    /// [`MAX_TX_EXPIRY`], otherwise `build` fails before anything is signed.
+    pub fn default_expiry(mut self, expiry: Duration) -> Self {
+        self.default_expiry = expiry;
+        self
+    }
+
+    /// Maximum age of a nonce fetched from the API before it is refetched
+    ///
+    /// Without a TTL (the default) the nonce is fetched once and then incremented
+    /// locally, which is fastest for continuous order flow. Strategies that trade
+    /// sporadically can set a short TTL so a long-idle counter, possibly moved by
+    /// another client using the same API key, is resynced before the next
+    /// transaction.
+    pub fn nonce_ttl(mut self, ttl: Duration) -> Self {
+        self.nonce_ttl = Some(ttl);
+        self
+    }
+
+    /// Endpoint the nonce is fetched from (default [`NonceSource::NextNonce`])
+    ///
+    /// With [`NonceSource::Account`] the nonce is read from the account query,
+    /// and account reads made for other reasons reseed an empty or expired
+    /// counter without a separate request; see [`NonceSource`] for the tradeoff.
+    pub fn nonce_source(mut self, source: NonceSource) -> Self {
+        self.nonce_source = source;
+        self
+    }
+
+    /// Consecutive nonce rejections that trigger nonce-gap recovery
+    ///
+    /// A submission that is silently dropped leaves the local counter ahead of
+    /// the server and every later transaction fails its nonce check. After this
+    /// many such rejections in a row the client refetches the server nonce and
+    /// replays the transaction that reached the threshold once with a corrected
+    /// nonce; see [`LighterClient::recovered_transactions`]. The transactions
+    /// rejected before it fail with `ApiError::NonceTooHigh` and are not
+    /// replayed. A nonce rejected as too low needs no streak and is replayed at
+    /// once, except for orders. Defaults to
+    /// [`DEFAULT_NONCE_GAP_THRESHOLD`]; `0` disables recovery.
+    pub fn nonce_gap_threshold(mut self, threshold: u32) -> Self {
+        self.nonce_gap_threshold = threshold;
+        self
+    }
+
+    /// Re-sign orders rejected for an expired `ExpiredAt` up to `retries` times (default 0, off)
+    ///
+    /// Resending the same signed bytes after an expiry rejection cannot succeed,
+    /// so the order is rebuilt from the request and signed again with a fresh
+    /// expiry and nonce. An expiry rejection is recognized by its exchange code
+    /// (`ErrorCode::TransactionExpired`) and fails with
+    /// `ApiError::TransactionExpired` once the re-signs are used up. Only
+    /// reduce-only and post-only orders are re-signed, since the market may have
+    /// moved while the first one waited (see `CreateOrderRequest::is_idempotent`).
+    /// Other rejections are not re-signed.
+    pub fn resign_on_expiry(mut self, retries: u32) -> Self {
+        self.expiry_resigns = retries;
+        self
+    }
+
+    /// Endpoints to use in order of preference, replacing the constructor's base URL
+    ///
+    /// Requests go to the first URL until it fails: a connection that cannot be
+    /// established moves on to the next URL at once and resends the request
+    /// there. A 5xx is never resent, since the request may have been executed;
+    /// it is returned, and later requests move on after
+    /// [`Self::failover_threshold`] consecutive ones, or right after one on a
+    /// transaction. After the last URL the first is tried again. All URLs must point to the
+    /// same network (mainnet or testnet) and at least one is required. See
+    /// [`LighterClient::active_base_url`].
+    pub fn base_urls(mut self, urls: Vec<String>) -> Self {
+        self.base_urls = Some(urls);
+        self
+    }
+
+    /// Consecutive 5xx responses to queries before failing over to the next base URL
+    ///
+    /// Defaults to [`DEFAULT_FAILOVER_THRESHOLD`]; must be non-zero. Only
+    /// matters with several [`Self::base_urls`].
+    pub fn failover_threshold(mut self, responses: u32) -> Self {
+        self.failover_threshold = responses;
+        self
+    }
+
+    /// Stop sending requests for `cooldown` after `failures` consecutive failures within `window`
+    ///
+    /// A failure is a request that got no response (connection error or
+    /// timeout) or a 5xx response; any other response resets the count. While
+    /// the breaker is open every request, retries included, fails at once with
+    /// `ApiError::CircuitOpen` instead of adding load to a degraded exchange.
+    /// After the cooldown one trial request is let through: its success closes
+    /// the breaker, its failure opens it again. Off by default; all three
+    /// values must be non-zero. See [`LighterClient::circuit_state`].
+    pub fn circuit_breaker(mut self, failures: u32, window: Duration, cooldown: Duration) -> Self {
+        self.circuit_breaker = Some(CircuitConfig { failures, window, cooldown });
+        self
+    }
+
+    /// Cap on concurrent `sendTx` requests
+    ///
+    /// Once `limit` submissions await a response, further submissions wait for
+    /// one to finish instead of opening more requests, which bounds the load an
+    /// order burst puts on the exchange and on the connection pool. Unlimited by
+    /// default; `limit` must be non-zero. See
+    /// [`LighterClient::in_flight_requests`].
+    pub fn max_in_flight(mut self, limit: usize) -> Self {
+        self.max_in_flight = Some(limit);
+        self
+    }
+
+    /// Number of recent samples per request kind the latency percentiles cover
+    ///
+    /// Defaults to [`DEFAULT_LATENCY_WINDOW`]; must be non-zero. See
+    /// [`LighterClient::latency_stats`].
+    pub fn latency_window(mut self, samples: usize) -> Self {
+        self.latency_window = samples;
+        self
+    }
+
+    /// Most transactions sent in one `sendTxBatch` request
+    ///
+    /// Defaults to [`DEFAULT_MAX_BATCH_SIZE`], the exchange's limit; must be
+    /// non-zero. Larger batches are split, see [`LighterClient::send_tx_batch`].
+    pub fn max_batch_size(mut self, size: usize) -> Self {
+        self.max_batch_size = size;
+        self
+    }
+
+    /// Body encoding of `sendTx` requests (default [`SendTxEncoding::Form`])
+    ///
+    /// The exchange currently expects a form-encoded body; switch to JSON only
+    /// for an endpoint or proxy that requires it.
+    pub fn send_tx_encoding(mut self, encoding: SendTxEncoding) -> Self {
+        self.send_tx_encoding = encoding;
+        self
+    }
+
+    /// Client-wide default of the `price_protection` flag sent with transactions
+    ///
+    /// With price protection the exchange rejects orders priced too far from
+    /// the mark price. Defaults to [`DEFAULT_PRICE_PROTECTION`] (on). An order's
+    /// own `CreateOrderRequest::price_protection` takes precedence; other
+    /// `sendTx` transactions, such as cancels, always use this default.
+    pub fn price_protection(mut self, enabled: bool) -> Self {
+        self.price_protection = enabled;
+        self
+    }
+
+    /// Register a callback receiving a [`RejectionRecord`] for every rejected order
+    ///
+    /// Called for orders the exchange answers with a non-200 code (after signature
+    /// retries are exhausted) and for submissions that fail outright. Runs inline
+    /// on the submitting task, so keep it cheap (e.g. push to a channel).
+    pub fn on_rejection<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&RejectionRecord) + Send + Sync + 'static,
+    {
+        self.rejection_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Register a callback receiving an [`HttpExchange`] for every HTTP request (off by default)
+    ///
+    /// For debugging: the exchange holds the method, URL and body exactly as
+    /// sent (including the `sendTx` form), the response status, `code` and
+    /// `message`, and the time taken. Auth tokens are redacted. Called once per
+    /// request after the response is read, including failed ones, and inline
+    /// on the requesting task, so keep it cheap (e.g. print or push to a channel).
+    pub fn on_http_exchange<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&HttpExchange) + Send + Sync + 'static,
+    {
+        self.http_log_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Sign on a dedicated pool of `threads` threads instead of tokio's blocking pool
+    ///
+    /// Used by `sign_transaction_async` and batch signing (`sign_orders`,
+    /// `send_tx_batch`, `place_bracket`). Size it to the cores set aside for
+    /// signing; see [`SigningPool`]. `build` fails with `ApiError::InvalidConfig`
+    /// for 0. By default signing runs on tokio's blocking pool.
+    pub fn signing_threads(mut self, threads: usize) -> Self {
+        self.signing_threads = Some(threads);
+        self.signing_pool = None;
+        self
+    }
+
+    /// Sign on an existing [`SigningPool`], e.g. one shared by several clients
+    ///
+    /// Replaces an earlier `signing_threads`; see there for what runs on it.
+    pub fn signing_pool(mut self, pool: Arc<SigningPool>) -> Self {
+        self.signing_pool = Some(pool);
+        self.signing_threads = None;
+        self
+    }
+
+    /// Check orders against market limits before signing (default `true`)
+    ///
+    /// Prices and sizes are checked against the market's metadata so violations
+    /// fail locally with `ApiError::InvalidOrderParams` instead of costing a round
+    /// trip. Disable when deliberately sending values the metadata does not
+    /// describe; the exchange still validates every order.
+    pub fn validate_orders(mut self, enabled: bool) -> Self {
+        self.validate_orders = enabled;
+        self
+    }
+
+    /// Check WebSocket order books for corruption after every change (default `true`)
+    ///
+    /// The exchange sends no checksum, so the local book is checked for crossed
+    /// or invalid levels instead; see [`crate::LighterClient::subscribe_order_book`].
+    pub fn check_book_consistency(mut self, enabled: bool) -> Self {
+        self.check_book_consistency = enabled;
+        self
+    }
+
+    /// Limit on establishing a connection, for HTTP requests and WebSocket subscriptions
+    ///
+    /// Keep it short so an unreachable exchange fails fast with
+    /// `ApiError::ConnectTimeout`. Defaults to [`DEFAULT_CONNECT_TIMEOUT`].
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Limit on a whole HTTP request, connection included
+    ///
+    /// Can be longer than the connect timeout to tolerate slow responses; an
+    /// expired request fails with `ApiError::RequestTimeout`. Defaults to
+    /// [`DEFAULT_REQUEST_TIMEOUT`].
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    /// End a WebSocket subscription that receives no frame for this long
+    ///
+    /// The stream then yields `ApiError::WebSocketIdle` and ends. Off by default,
+    /// since a quiet market can legitimately go without updates.
+    pub fn ws_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.ws_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Keepalive for WebSocket subscriptions: ping every `interval`, expect the pong within `pong_timeout`
+    ///
+    /// Intermediaries drop idle connections, and a socket can look open while
+    /// delivering nothing. A pong that misses its deadline is treated as a
+    /// disconnect: the subscription reconnects and resubscribes. On by default
+    /// with [`DEFAULT_WS_PING_INTERVAL`] and [`DEFAULT_WS_PONG_TIMEOUT`].
+    pub fn ws_heartbeat(mut self, interval: Duration, pong_timeout: Duration) -> Self {
+        self.ws_heartbeat = Some((interval, pong_timeout));
+        self
+    }
+
+    /// Turn off the WebSocket heartbeat
+    pub fn disable_ws_heartbeat(mut self) -> Self {
+        self.ws_heartbeat = None;
+        self
+    }
+
+    /// Register a callback receiving every raw text frame of every WebSocket subscription
+    ///
+    /// The callback sees each frame before it is parsed, including messages the
+    /// typed streams do not recognize and skip, so new server message shapes
+    /// can be logged and reported. Runs inline on the subscription's task, so
+    /// keep it cheap.
+    pub fn on_ws_frame<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.ws_frame_handler = Some(Arc::new(handler));
+        self
+    }
+
+    /// Time source for `ExpiredAt`, `OrderExpiry` dates, auth tokens and rejection records
+    ///
+    /// Defaults to [`SystemClock`]. Tests can pass a fixed clock to assert exact
+    /// expiry values.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Identify your application in the User-Agent header
+    ///
+    /// The crate identifier is always appended, so `user_agent("my-bot/2.1")`
+    /// sends `my-bot/2.1 lighter-rust/<version>`. Without this option the header
+    /// is just [`DEFAULT_USER_AGENT`].
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Validate the configuration and construct the client
+    pub fn build(self) -> Result<LighterClient> {
+        validate_expiry(self.default_expiry)?;
+        for (name, timeout) in [
+            ("connect", Some(self.connect_timeout)),
+            ("request", Some(self.request_timeout)),
+            ("WebSocket idle", self.ws_idle_timeout),
+            ("WebSocket ping interval", self.ws_heartbeat.map(|(interval, _)| interval)),
+            ("WebSocket pong", self.ws_heartbeat.map(|(_, pong_timeout)| pong_timeout)),
+        ] {
+            if timeout.is_some_and(|timeout| timeout.is_zero()) {
+                return Err(ApiError::InvalidConfig(format!("{} timeout must be non-zero", name)));
+            }
+        }
+        let signing_pool = match self.signing_threads {
+            Some(threads) => Some(Arc::new(SigningPool::new(threads)?)),
+            None => self.signing_pool,
+        };
+
+        if self.max_in_flight == Some(0) {
+            return Err(ApiError::InvalidConfig("max_in_flight must be non-zero".to_string()));
+        }
+        if self.latency_window == 0 {
+            return Err(ApiError::InvalidConfig("latency_window must be non-zero".to_string()));
+        }
+        if self.failover_threshold == 0 {
+            return Err(ApiError::InvalidConfig("failover_threshold must be non-zero".to_string()));
+        }
+        if let Some(circuit) = self.circuit_breaker {
+            if circuit.failures == 0 || circuit.window.is_zero() || circuit.cooldown.is_zero() {
+                return Err(ApiError::InvalidConfig(
+                    "circuit breaker failures, window and cooldown must be non-zero".to_string(),
+                ));
+            }
+        }
+        let urls = self.base_urls.unwrap_or_else(|| vec![self.base_url]);
+        let Some(primary) = urls.first().cloned() else {
+            return Err(ApiError::InvalidConfig("base_urls must not be empty".to_string()));
+        };
+        if let Some(other) = urls.iter().find(|url| tx::chain_id(url) != tx::chain_id(&primary)) {
+            return Err(ApiError::InvalidConfig(format!(
+                "base URL {} is on a different network than {}",
+                other, primary
+            )));
+        }
+
+        if self.max_batch_size == 0 {
+            return Err(ApiError::InvalidConfig("max_batch_size must be non-zero".to_string()));
+        }
+
+        let key_manager = KeyManager::from_hex(&self.private_key_hex)?;
+
+        let user_agent = match &self.user_agent {
+            Some(custom) => format!("{} {}", custom, DEFAULT_USER_AGENT),
+            None => DEFAULT_USER_AGENT.to_string(),
+        };
+        let http = Client::builder()
+            .user_agent(user_agent)
+            .connect_timeout(self.connect_timeout)
+            .timeout(self.request_timeout);
+        // When both TLS features end up enabled (feature unification), prefer rustls
+        #[cfg(feature = "rustls-tls")]
+        let http = http.use_rustls_tls();
+
+        Ok(LighterClient {
+            client: http.build()?,
+            base_url: primary.into(),
+            endpoints: Arc::new(Endpoints::new(
+                urls.into_iter().map(Into::into).collect(),
+                self.failover_threshold,
+            )),
+            circuit: Arc::new(CircuitBreaker::new(self.circuit_breaker)),
+            rate_limits: Arc::default(),
+            signer: Arc::new(key_manager.prepare()),
+            key_manager: Arc::new(key_manager),
+            account_index: self.account_index,
+            api_key_index: self.api_key_index,
+            default_expiry: self.default_expiry,
+            nonce_cache: Arc::new(AsyncMutex::new(NonceCache::new(self.nonce_ttl))),
+            nonce_ttl: self.nonce_ttl,
+            nonce_source: self.nonce_source,
+            rejection_handler: self.rejection_handler,
+            http_log_handler: self.http_log_handler,
+            signing_pool,
+            market_cache: Arc::new(RwLock::new(HashMap::new())),
+            markets_refreshed_at: Arc::new(AtomicI64::new(0)),
+            validate_orders: self.validate_orders,
+            connect_timeout: self.connect_timeout,
+            request_timeout: self.request_timeout,
+            ws_idle_timeout: self.ws_idle_timeout,
+            ws_heartbeat: self.ws_heartbeat,
+            ws_frame_handler: self.ws_frame_handler,
+            clock: self.clock,
+            check_book_consistency: self.check_book_consistency,
+            nonce_gaps: Arc::new(NonceGapTracker::new(self.nonce_gap_threshold)),
+            in_flight: Arc::new(InFlightLimit::new(self.max_in_flight)),
+            send_tx_encoding: self.send_tx_encoding,
+            price_protection: self.price_protection,
+            expiry_resigns: self.expiry_resigns,
+            latency: Arc::new(LatencyTracker::new(self.latency_window)),
+            max_batch_size: self.max_batch_size,
+        })
+    }
+
+    /// Construct the client and warm up its connection; see [`LighterClient::warm_up`]
+    pub async fn connect(self) -> Result<LighterClient> {
+        let client = self.build()?;
+        client.warm_up().await?;
+        Ok(client)
+    }
+}
+
+/// Check a transaction validity window against the server limit
+pub(crate) fn validate_expiry(expiry: Duration) -> Result<()> {
+    if expiry.is_zero() || expiry > MAX_TX_EXPIRY {
+        return Err(ApiError::InvalidConfig(format!(
+            "transaction expiry must be between 1ms and {}ms, got {}ms",
+            MAX_TX_EXPIRY.as_millis(),
+            expiry.as_millis()
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use crate::{LighterClient, Result, SendTxResponse};
+use std::time::Duration;
+use tokio::runtime::{Handle, RuntimeFlavor};
+
+/// `cancel_all_orders` time in force: cancel every resting order now
+pub const CANCEL_ALL_TIF_IMMEDIATE: u8 = 0;
+/// `cancel_all_orders` time in force: cancel every resting order at `time` (unix ms)
+pub const CANCEL_ALL_TIF_SCHEDULED: u8 = 1;
+/// `cancel_all_orders` time in force: abort a scheduled cancellation
+pub const CANCEL_ALL_TIF_ABORT: u8 = 2;
+
+/// Longest a [`CancelGuard`] waits for its cancellation on drop unless configured otherwise
+pub const DEFAULT_CANCEL_GUARD_TIMEOUT: Duration = Duration::from_secs(5);
+
+impl LighterClient {
+    /// Have the exchange cancel every resting order `after` from now
+    ///
+    /// A server-side dead man's switch: the cancellation happens even if this
+    /// process dies. Call again before it fires to push it back (a heartbeat),
+    /// or [`Self::abort_scheduled_cancel_all`] to call it off. The exchange has
+    /// no cancel-on-disconnect for WebSocket sessions; this is its equivalent.
+    pub async fn schedule_cancel_all(&self, after: Duration) -> Result<SendTxResponse> {
+        let time = self.clock.now_ms() + after.as_millis() as i64;
+        self.cancel_all_orders(CANCEL_ALL_TIF_SCHEDULED, time).await
+    }
+
+    /// Call off a cancellation set up with [`Self::schedule_cancel_all`]
+    pub async fn abort_scheduled_cancel_all(&self) -> Result<SendTxResponse> {
+        self.cancel_all_orders(CANCEL_ALL_TIF_ABORT, 0).await
+    }
+}
+
+/// Cancels every resting order of its client's account when dropped
+///
+/// Best effort and client-side: it runs only if the guard is dropped, so not
+/// when the process is killed, crashes without unwinding or loses its network,
+/// and the cancellation itself can fail or time out (the outcome is not
+/// reported). For a guarantee that holds when the process is gone, use
+/// [`LighterClient::schedule_cancel_all`].
+///
+/// On a multi-threaded runtime the drop waits up to the timeout for the
+/// cancellation; on a current-thread runtime it can only spawn it, so it runs
+/// only if the runtime keeps going. Outside a runtime a temporary one is used.
+#[derive(Debug)]
+pub struct CancelGuard {
+    client: LighterClient,
+    timeout: Duration,
+    armed: bool,
+}
+
+impl CancelGuard {
+    pub fn new(client: LighterClient) -> Self {
+        Self { client, timeout: DEFAULT_CANCEL_GUARD_TIMEOUT, armed: true }
+    }
+
+    /// Longest the drop waits for the cancellation (default [`DEFAULT_CANCEL_GUARD_TIMEOUT`])
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Leave orders in place when the guard is dropped, e.g. on an orderly shutdown
+    pub fn disarm(&mut self) {
+        self.armed = false;
+    }
+
+    /// Cancel now and report the outcome; nothing more happens on drop
+    pub async fn cancel_now(mut self) -> Result<SendTxResponse> {
+        self.armed = false;
+        self.client.cancel_all_orders(CANCEL_ALL_TIF_IMMEDIATE, 0).await
+    }
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let client = self.client.clone();
+        let timeout = self.timeout;
+        let cancel = async move {
+            let _ = tokio::time::timeout(timeout, client.cancel_all_orders(CANCEL_ALL_TIF_IMMEDIATE, 0)).await;
+        };
+        match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(|| handle.block_on(cancel));
+            }
+            Ok(handle) => {
+                handle.spawn(cancel);
+            }
+            Err(_) => {
+                if let Ok(runtime) = tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                    runtime.block_on(cancel);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,370 @@
+use crate::account::{check_response_code, decode_list};
+use crate::ws::{WsFrame, WsSubscription};
+use crate::{ApiError, LighterClient, Result};
+use futures::stream::{self, BoxStream};
+use futures::StreamExt;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Most candles the `candlesticks` endpoint returns for one request
+///
+/// A longer range is not rejected by the server but cut down to its most
+/// recent candles, which is why [`LighterClient::get_candles`] refuses it.
+pub const MAX_CANDLES_PER_REQUEST: i64 = 500;
+
+/// Period covered by one candle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CandleResolution {
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+    OneHour,
+    FourHours,
+    OneDay,
+}
+
+impl CandleResolution {
+    pub const ALL: [CandleResolution; 6] = [
+        CandleResolution::OneMinute,
+        CandleResolution::FiveMinutes,
+        CandleResolution::FifteenMinutes,
+        CandleResolution::OneHour,
+        CandleResolution::FourHours,
+        CandleResolution::OneDay,
+    ];
+
+    /// Name used by the API ("1m", "5m", "15m", "1h", "4h", "1d")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CandleResolution::OneMinute => "1m",
+            CandleResolution::FiveMinutes => "5m",
+            CandleResolution::FifteenMinutes => "15m",
+            CandleResolution::OneHour => "1h",
+            CandleResolution::FourHours => "4h",
+            CandleResolution::OneDay => "1d",
+        }
+    }
+
+    /// Length of one candle in milliseconds
+    pub fn as_millis(&self) -> i64 {
+        const MINUTE: i64 = 60_000;
+        match self {
+            CandleResolution::OneMinute => MINUTE,
+            CandleResolution::FiveMinutes => 5 * MINUTE,
+            CandleResolution::FifteenMinutes => 15 * MINUTE,
+            CandleResolution::OneHour => 60 * MINUTE,
+            CandleResolution::FourHours => 240 * MINUTE,
+            CandleResolution::OneDay => 1_440 * MINUTE,
+        }
+    }
+
+    /// Start (unix ms) of the candle containing `timestamp`
+    pub fn open_time(&self, timestamp: i64) -> i64 {
+        timestamp - timestamp.rem_euclid(self.as_millis())
+    }
+}
+
+impl fmt::Display for CandleResolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parses the API names ("1m", "5m", "15m", "1h", "4h", "1d"); anything else is `ApiError::Unsupported`
+impl FromStr for CandleResolution {
+    type Err = ApiError;
+
+    fn from_str(name: &str) -> Result<Self> {
+        CandleResolution::ALL.into_iter().find(|resolution| resolution.as_str() == name).ok_or_else(|| {
+            let names: Vec<_> = CandleResolution::ALL.iter().map(CandleResolution::as_str).collect();
+            ApiError::Unsupported(format!("candle resolution {:?}; expected one of {}", name, names.join(", ")))
+        })
+    }
+}
+
+/// Longest time range one candle request can cover at `resolution`
+///
+/// [`MAX_CANDLES_PER_REQUEST`] candles: e.g. 500 minutes at one minute, 500
+/// days at one day. Split longer ranges into requests of at most this length.
+pub fn max_range_for(resolution: CandleResolution) -> Duration {
+    Duration::from_millis((resolution.as_millis() * MAX_CANDLES_PER_REQUEST) as u64)
+}
+
+/// Number of candles of `resolution` with an open time in `start..end` (unix ms)
+fn candle_count(resolution: CandleResolution, start: i64, end: i64) -> i64 {
+    let step = resolution.as_millis();
+    let first = start + (step - start.rem_euclid(step)) % step;
+    if end <= first {
+        return 0;
+    }
+    (end - first - 1) / step + 1
+}
+
+/// OHLCV of one market over one period
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candle {
+    pub market_index: u8,
+    pub resolution: CandleResolution,
+    /// Start of the period, unix ms
+    pub open_time: i64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    /// Traded size in base units
+    pub volume: Decimal,
+    /// Traded notional in USDC
+    pub quote_volume: Decimal,
+    pub trades: u64,
+}
+
+impl Candle {
+    fn open_with(market_index: u8, resolution: CandleResolution, trade: &WsTrade) -> Self {
+        Self {
+            market_index,
+            resolution,
+            open_time: resolution.open_time(trade.timestamp),
+            open: trade.price,
+            high: trade.price,
+            low: trade.price,
+            close: trade.price,
+            volume: trade.size,
+            quote_volume: trade.price * trade.size,
+            trades: 1,
+        }
+    }
+
+    fn add(&mut self, trade: &WsTrade) {
+        self.high = self.high.max(trade.price);
+        self.low = self.low.min(trade.price);
+        self.close = trade.price;
+        self.volume += trade.size;
+        self.quote_volume += trade.price * trade.size;
+        self.trades += 1;
+    }
+}
+
+/// Change to the candles of a [`LighterClient::subscribe_candlesticks`] stream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CandleEvent {
+    /// The forming candle after new trades; replaces any earlier `Updated`
+    /// with the same `open_time`
+    Updated(Candle),
+    /// The candle is final: a trade of a later period arrived
+    Closed(Candle),
+}
+
+/// One candle of a `candlesticks` response
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+struct CandleRecord {
+    /// Start of the period, unix ms
+    timestamp: i64,
+    open: Decimal,
+    high: Decimal,
+    low: Decimal,
+    close: Decimal,
+    /// Base volume
+    volume0: Decimal,
+    /// Quote volume
+    volume1: Decimal,
+    /// Id of the period's last trade; only modelled so `strict-json` accepts it
+    #[serde(default)]
+    #[allow(dead_code)]
+    last_trade_id: i64,
+}
+
+impl CandleRecord {
+    fn into_candle(self, market_index: u8, resolution: CandleResolution) -> Candle {
+        Candle {
+            market_index,
+            resolution,
+            open_time: self.timestamp,
+            open: self.open,
+            high: self.high,
+            low: self.low,
+            close: self.close,
+            volume: self.volume0,
+            quote_volume: self.volume1,
+            trades: 0,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+struct WsTrade {
+    trade_id: i64,
+    price: Decimal,
+    size: Decimal,
+    /// unix ms
+    timestamp: i64,
+}
+
+/// Candle state built from a `trade/{market}` subscription
+struct CandleFeed {
+    subscription: WsSubscription,
+    market_index: u8,
+    resolution: CandleResolution,
+    forming: Option<Candle>,
+    /// Highest trade id counted; the snapshot after a reconnect repeats recent trades
+    last_trade_id: Option<i64>,
+    pending: VecDeque<CandleEvent>,
+}
+
+impl CandleFeed {
+    async fn next_event(&mut self) -> Option<Result<CandleEvent>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            let message = match self.subscription.next().await? {
+                Ok(WsFrame::Json(message)) => message,
+                // The new subscription's snapshot is deduplicated by trade id
+                Ok(WsFrame::Reconnected) => continue,
+                Err(e) => return Some(Err(e)),
+            };
+
+            match message["type"].as_str().unwrap_or_default() {
+                "subscribed/trade" | "update/trade" => {
+                    let trades: Vec<WsTrade> = match serde_json::from_value(message["trades"].clone()) {
+                        Ok(trades) => trades,
+                        Err(e) => return Some(Err(ApiError::Api(format!("unreadable trades: {}", e)))),
+                    };
+                    self.add_trades(trades);
+                }
+                "error" => {
+                    return Some(Err(ApiError::Api(format!("WebSocket: {}", message["message"]))));
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    /// Fold trades into the forming candle, queueing the candles they close and one `Updated`
+    fn add_trades(&mut self, mut trades: Vec<WsTrade>) {
+        trades.sort_by_key(|trade| trade.trade_id);
+        let mut changed = false;
+        for trade in &trades {
+            if self.last_trade_id.is_some_and(|last| trade.trade_id <= last) {
+                continue;
+            }
+            self.last_trade_id = Some(trade.trade_id);
+
+            let open_time = self.resolution.open_time(trade.timestamp);
+            match &mut self.forming {
+                Some(candle) if open_time == candle.open_time => candle.add(trade),
+                // Late trade of a closed period: that candle was already emitted as final
+                Some(candle) if open_time < candle.open_time => continue,
+                forming => {
+                    if let Some(closed) = forming.take() {
+                        self.pending.push_back(CandleEvent::Closed(closed));
+                    }
+                    *forming = Some(Candle::open_with(self.market_index, self.resolution, trade));
+                }
+            }
+            changed = true;
+        }
+        if let (true, Some(candle)) = (changed, &self.forming) {
+            self.pending.push_back(CandleEvent::Updated(candle.clone()));
+        }
+    }
+}
+
+impl LighterClient {
+    /// Fetch the candles of one market that open within `start..end` (unix ms)
+    ///
+    /// The endpoint serves at most [`MAX_CANDLES_PER_REQUEST`] candles and
+    /// would silently drop the oldest of a longer range, so a range holding
+    /// more candles than that fails with `ApiError::CandleRangeTooLarge`
+    /// (see [`max_range_for`]) before anything is requested. An empty range
+    /// fails with `ApiError::InvalidConfig`. Candles are oldest first; periods
+    /// without trades may be missing. The endpoint does not report trade
+    /// counts, so `trades` is 0.
+    pub async fn get_candles(
+        &self,
+        market_index: u8,
+        resolution: CandleResolution,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<Candle>> {
+        if end <= start {
+            return Err(ApiError::InvalidConfig(format!("candle range {}..{} is empty", start, end)));
+        }
+        let count = candle_count(resolution, start, end);
+        if count > MAX_CANDLES_PER_REQUEST {
+            return Err(ApiError::CandleRangeTooLarge {
+                resolution,
+                requested: count,
+                max: MAX_CANDLES_PER_REQUEST,
+                max_range: max_range_for(resolution),
+            });
+        }
+
+        let response = self
+            .get_json(
+                "/api/v1/candlesticks",
+                &[
+                    ("market_id", market_index.to_string()),
+                    ("resolution", resolution.as_str().to_string()),
+                    ("start_timestamp", start.to_string()),
+                    ("end_timestamp", end.to_string()),
+                    ("count_back", count.to_string()),
+                ],
+            )
+            .await?;
+        check_response_code(&response)?;
+        let mut candles: Vec<Candle> = decode_list::<CandleRecord>(&response, "candlesticks")?
+            .into_iter()
+            .map(|record| record.into_candle(market_index, resolution))
+            .filter(|candle| (start..end).contains(&candle.open_time))
+            .collect();
+        candles.sort_by_key(|candle| candle.open_time);
+        Ok(candles)
+    }
+
+    /// Stream live candles of one market, built from its trade feed
+    ///
+    /// The server has no candle channel, so this subscribes to `trade/{market}`
+    /// and aggregates trades into candles of `resolution`. Every message with
+    /// new trades yields [`CandleEvent::Updated`] with the forming candle as a
+    /// whole (replace, do not add, earlier updates of the same `open_time`).
+    /// The first trade of a later period yields [`CandleEvent::Closed`] for the
+    /// previous candle before the new candle's first `Updated`. Trades are
+    /// counted once by trade id, so the recent trades replayed on subscribing
+    /// again after a heartbeat reconnect add nothing; trades arriving after
+    /// their candle closed are dropped. Periods without trades produce no candle.
+    ///
+    /// The first candle starts with the trades the server sends on subscribing
+    /// and may miss earlier trades of its period. The stream ends when the
+    /// server closes the connection and after yielding the first error.
+    pub async fn subscribe_candlesticks(
+        &self,
+        market_index: u8,
+        resolution: CandleResolution,
+    ) -> Result<BoxStream<'static, Result<CandleEvent>>> {
+        let channel = format!("trade/{}", market_index);
+        let feed = CandleFeed {
+            subscription: WsSubscription::open(self.ws_config(), channel).await?,
+            market_index,
+            resolution,
+            forming: None,
+            last_trade_id: None,
+            pending: VecDeque::new(),
+        };
+
+        Ok(stream::unfold(Some(feed), |state| async move {
+            let mut feed = state?;
+            match feed.next_event().await? {
+                Ok(event) => Some((Ok(event), Some(feed))),
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+        .boxed())
+    }
+}
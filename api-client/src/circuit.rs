@@ -0,0 +1,156 @@
+use crate::{ApiError, LighterClient, Result};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// State of the client's circuit breaker, see [`LighterClient::circuit_state`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests go through (also the state of a client without a breaker)
+    Closed,
+    /// Requests fail with `ApiError::CircuitOpen` until the cooldown ends
+    Open {
+        /// Time left until a trial request is let through
+        retry_after: Duration,
+    },
+    /// The cooldown ended: one trial request decides whether to close or reopen
+    HalfOpen,
+}
+
+/// Thresholds of a circuit breaker
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CircuitConfig {
+    pub(crate) failures: u32,
+    pub(crate) window: Duration,
+    pub(crate) cooldown: Duration,
+}
+
+#[derive(Debug)]
+enum Phase {
+    Closed {
+        /// Consecutive failures, and when the first of them happened
+        failures: u32,
+        since: Instant,
+    },
+    Open { until: Instant },
+    HalfOpen { probing: bool },
+}
+
+/// Stops requests to a failing exchange for a while
+///
+/// Opens after `failures` consecutive failed requests within `window`; a
+/// success in between starts the count again. While open, requests fail
+/// without being sent. After `cooldown` a single trial request is let through:
+/// its success closes the breaker, its failure opens it for another cooldown.
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    config: Option<CircuitConfig>, // None = disabled
+    phase: Mutex<Phase>,
+}
+
+/// Admission of one request; reports its outcome to the breaker
+pub(crate) struct CircuitPermit<'a> {
+    breaker: &'a CircuitBreaker,
+    probe: bool,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(config: Option<CircuitConfig>) -> Self {
+        Self {
+            config,
+            phase: Mutex::new(Phase::Closed { failures: 0, since: Instant::now() }),
+        }
+    }
+
+    /// Let a request through, or fail with `ApiError::CircuitOpen`
+    pub(crate) fn admit(&self) -> Result<CircuitPermit<'_>> {
+        let permit = |probe| Ok(CircuitPermit { breaker: self, probe });
+        if self.config.is_none() {
+            return permit(false);
+        }
+        let mut phase = self.phase.lock().unwrap_or_else(|e| e.into_inner());
+        match *phase {
+            Phase::Closed { .. } => permit(false),
+            Phase::Open { until } => {
+                let now = Instant::now();
+                if now < until {
+                    return Err(ApiError::CircuitOpen { retry_after: until - now });
+                }
+                *phase = Phase::HalfOpen { probing: true };
+                permit(true)
+            }
+            Phase::HalfOpen { probing: true } => Err(ApiError::CircuitOpen { retry_after: Duration::ZERO }),
+            Phase::HalfOpen { probing: false } => {
+                *phase = Phase::HalfOpen { probing: true };
+                permit(true)
+            }
+        }
+    }
+
+    fn record(&self, probe: bool, healthy: bool) {
+        let Some(config) = self.config else {
+            return;
+        };
+        let now = Instant::now();
+        let mut phase = self.phase.lock().unwrap_or_else(|e| e.into_inner());
+        *phase = match (&*phase, healthy) {
+            (_, true) => Phase::Closed { failures: 0, since: now },
+            (Phase::HalfOpen { .. }, false) if probe => Phase::Open { until: now + config.cooldown },
+            (Phase::Closed { failures, since }, false) => {
+                let (failures, since) = if *failures == 0 || now.duration_since(*since) > config.window {
+                    (1, now)
+                } else {
+                    (failures + 1, *since)
+                };
+                if failures >= config.failures {
+                    Phase::Open { until: now + config.cooldown }
+                } else {
+                    Phase::Closed { failures, since }
+                }
+            }
+            // A request admitted before the breaker opened
+            (_, false) => return,
+        };
+    }
+
+    pub(crate) fn state(&self) -> CircuitState {
+        let phase = self.phase.lock().unwrap_or_else(|e| e.into_inner());
+        match *phase {
+            Phase::Closed { .. } => CircuitState::Closed,
+            Phase::Open { until } => match until.checked_duration_since(Instant::now()) {
+                Some(retry_after) if !retry_after.is_zero() => CircuitState::Open { retry_after },
+                _ => CircuitState::HalfOpen,
+            },
+            Phase::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+}
+
+impl CircuitPermit<'_> {
+    /// Report whether the exchange answered; failures count toward opening
+    pub(crate) fn record(mut self, healthy: bool) {
+        self.breaker.record(self.probe, healthy);
+        self.probe = false;
+    }
+}
+
+impl Drop for CircuitPermit<'_> {
+    fn drop(&mut self) {
+        // A trial request that ended without a verdict frees the trial slot
+        if self.probe {
+            let mut phase = self.breaker.phase.lock().unwrap_or_else(|e| e.into_inner());
+            if let Phase::HalfOpen { probing } = &mut *phase {
+                *probing = false;
+            }
+        }
+    }
+}
+
+impl LighterClient {
+    /// State of the circuit breaker, for monitoring
+    ///
+    /// Always `CircuitState::Closed` unless a breaker was configured with
+    /// `LighterClientBuilder::circuit_breaker`.
+    pub fn circuit_state(&self) -> CircuitState {
+        self.circuit.state()
+    }
+}
@@ -0,0 +1,25 @@
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Source of the current time for everything the client timestamps
+///
+/// Governs `ExpiredAt`, resolved `OrderExpiry` dates, auth token deadlines and
+/// rejection records. Supply a fixed implementation in tests to assert exact
+/// values; see `LighterClientBuilder::clock`.
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Current unix time in milliseconds
+    fn now_ms(&self) -> i64;
+}
+
+/// The operating system's wall clock (the default)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or_default()
+    }
+}
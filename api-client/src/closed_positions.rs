@@ -0,0 +1,181 @@
+use crate::account::TradeRecord;
+use crate::{LighterClient, Result, Side};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// A position that was opened and closed again, aggregated from the account's fills
+///
+/// Signs follow [`crate::Position`]: `size` is positive for a long and negative
+/// for a short. `realized_pnl` is the sum of the closing fills' PnL before fees.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosedPosition {
+    pub market_index: u8,
+    /// Time of the first opening fill (unix ms), `None` if the position was already open at `start`
+    pub opened_at: Option<i64>,
+    /// Time of the fill that brought the position back to flat (unix ms)
+    pub closed_at: i64,
+    /// Total size opened over the position's life, negative for a short
+    pub size: Decimal,
+    pub avg_entry_price: Decimal,
+    pub avg_exit_price: Decimal,
+    pub realized_pnl: Decimal,
+    /// Fees charged on the position's fills within the range
+    pub fees: Decimal,
+}
+
+impl ClosedPosition {
+    /// Realized PnL after fees
+    pub fn net_pnl(&self) -> Decimal {
+        self.realized_pnl - self.fees
+    }
+}
+
+/// A position being followed through its fills
+#[derive(Debug, Clone)]
+struct Lifecycle {
+    opened_at: Option<i64>,
+    is_long: bool,
+    /// Absolute size still open
+    open: Decimal,
+    opened: Decimal,
+    entry_quote: Decimal,
+    closed: Decimal,
+    exit_quote: Decimal,
+    realized_pnl: Decimal,
+    fees: Decimal,
+}
+
+impl Lifecycle {
+    fn new(opened_at: Option<i64>, is_long: bool, size: Decimal, entry_quote: Decimal, fees: Decimal) -> Self {
+        Self {
+            opened_at,
+            is_long,
+            open: size,
+            opened: size,
+            entry_quote,
+            closed: Decimal::ZERO,
+            exit_quote: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+            fees,
+        }
+    }
+
+    fn finish(self, market_index: u8, closed_at: i64) -> ClosedPosition {
+        ClosedPosition {
+            market_index,
+            opened_at: self.opened_at,
+            closed_at,
+            size: if self.is_long { self.opened } else { -self.opened },
+            avg_entry_price: self.entry_quote / self.opened,
+            avg_exit_price: self.exit_quote / self.closed,
+            realized_pnl: self.realized_pnl,
+            fees: self.fees,
+        }
+    }
+}
+
+/// Fold an account's fills (ascending by time) into the positions they closed
+///
+/// Each fill carries the account's position before it, so a position already
+/// open at the first fill is picked up with its entry price, and a fill that
+/// flips the position closes one and opens the next with its fee split pro rata.
+pub(crate) fn closed_positions(records: &[TradeRecord], account_index: i64) -> Vec<ClosedPosition> {
+    let mut open: HashMap<u8, Lifecycle> = HashMap::new();
+    let mut closed = Vec::new();
+
+    for record in records {
+        let Some((trade, position_before, entry_quote_before)) = record.with_position_before(account_index) else {
+            continue;
+        };
+        if trade.size.is_zero() {
+            continue;
+        }
+        let is_buy = trade.side == Side::Buy;
+
+        let lifecycle = match open.remove(&trade.market_index) {
+            Some(lifecycle) if !position_before.is_zero() => Some(lifecycle),
+            _ if !position_before.is_zero() => Some(Lifecycle::new(
+                None,
+                position_before > Decimal::ZERO,
+                position_before.abs(),
+                entry_quote_before,
+                Decimal::ZERO,
+            )),
+            _ => None,
+        };
+
+        let mut lifecycle = match lifecycle {
+            Some(lifecycle) if lifecycle.is_long != is_buy => lifecycle,
+            lifecycle => {
+                // Opens a new position or adds to the current one
+                let mut lifecycle = lifecycle.unwrap_or_else(|| {
+                    Lifecycle::new(Some(trade.timestamp), is_buy, Decimal::ZERO, Decimal::ZERO, Decimal::ZERO)
+                });
+                lifecycle.open += trade.size;
+                lifecycle.opened += trade.size;
+                lifecycle.entry_quote += trade.size * trade.price;
+                lifecycle.fees += trade.fee;
+                open.insert(trade.market_index, lifecycle);
+                continue;
+            }
+        };
+
+        let closing = trade.size.min(lifecycle.open);
+        let closing_fee = trade.fee * closing / trade.size;
+        lifecycle.open -= closing;
+        lifecycle.closed += closing;
+        lifecycle.exit_quote += closing * trade.price;
+        lifecycle.realized_pnl += trade.realized_pnl;
+        lifecycle.fees += closing_fee;
+
+        if !lifecycle.open.is_zero() {
+            open.insert(trade.market_index, lifecycle);
+            continue;
+        }
+        closed.push(lifecycle.finish(trade.market_index, trade.timestamp));
+
+        let remainder = trade.size - closing;
+        if !remainder.is_zero() {
+            open.insert(
+                trade.market_index,
+                Lifecycle::new(
+                    Some(trade.timestamp),
+                    is_buy,
+                    remainder,
+                    remainder * trade.price,
+                    trade.fee - closing_fee,
+                ),
+            );
+        }
+    }
+
+    closed
+}
+
+impl LighterClient {
+    /// Fetch the positions an account closed within a time range (authenticated)
+    ///
+    /// Positions are rebuilt from the account's fills, following trade pages until
+    /// the end of the range or the end of the data. A position counts once it is
+    /// back to flat; positions still open at `end` are left out, and one already
+    /// open at `start` reports `opened_at: None` with fees from `start` only.
+    ///
+    /// # Arguments
+    /// * `account_index` - Account whose positions to return
+    /// * `market_index` - Restrict to one market, or `None` for all markets
+    /// * `start` - Inclusive start timestamp (unix ms), `None` for no lower bound
+    /// * `end` - Inclusive end timestamp (unix ms), `None` for no upper bound
+    ///
+    /// # Returns
+    /// Closed positions in ascending `closed_at` order
+    pub async fn get_closed_positions(
+        &self,
+        account_index: i64,
+        market_index: Option<u8>,
+        start: Option<i64>,
+        end: Option<i64>,
+    ) -> Result<Vec<ClosedPosition>> {
+        let records = self.fetch_trade_records(account_index, market_index, start, end).await?;
+        Ok(closed_positions(&records, account_index))
+    }
+}
@@ -0,0 +1,58 @@
+use serde_json::Value;
+
+/// Exchange error codes the client reacts to, from the `code` field of a response
+///
+/// Codes not listed here are passed through untouched: submit methods return
+/// the response as is and queries fail with `ApiError::Api` carrying the code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    AccountNotFound = 21100,
+    /// The nonce is not the API key's next one, without saying which way
+    InvalidNonce = 21104,
+    /// The nonce was already consumed
+    NonceTooLow = 21105,
+    /// The nonce is past the API key's next one
+    NonceTooHigh = 21106,
+    ApiKeyNotFound = 21109,
+    /// Signature verification failed; the same signature may pass when resent
+    InvalidSignature = 21120,
+    MarketClosed = 21500,
+    InMaintenance = 21501,
+    /// The transaction's `ExpiredAt` passed before it was executed
+    TransactionExpired = 21711,
+    /// The order price is too far from the market price
+    PriceFlagged = 21733,
+    PostOnlyWouldCross = 21738,
+    InsufficientMargin = 21739,
+}
+
+impl ErrorCode {
+    /// The code for a numeric `code`, `None` if the client does not know it
+    pub fn from_code(code: i64) -> Option<Self> {
+        Some(match code {
+            21100 => ErrorCode::AccountNotFound,
+            21104 => ErrorCode::InvalidNonce,
+            21105 => ErrorCode::NonceTooLow,
+            21106 => ErrorCode::NonceTooHigh,
+            21109 => ErrorCode::ApiKeyNotFound,
+            21120 => ErrorCode::InvalidSignature,
+            21500 => ErrorCode::MarketClosed,
+            21501 => ErrorCode::InMaintenance,
+            21711 => ErrorCode::TransactionExpired,
+            21733 => ErrorCode::PriceFlagged,
+            21738 => ErrorCode::PostOnlyWouldCross,
+            21739 => ErrorCode::InsufficientMargin,
+            _ => return None,
+        })
+    }
+
+    /// The numeric code as the exchange sends it
+    pub fn code(self) -> i64 {
+        self as i64
+    }
+
+    /// The code of a response, `None` if it has none or one the client does not know
+    pub(crate) fn of(response: &Value) -> Option<Self> {
+        response["code"].as_i64().and_then(Self::from_code)
+    }
+}
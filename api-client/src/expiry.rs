@@ -0,0 +1,48 @@
+use crate::{ApiError, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Longest order lifetime the exchange accepts (30 days)
+pub const MAX_ORDER_EXPIRY: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Lifetime of a resting order, sent in the `OrderExpiry` field
+///
+/// Not to be confused with the transaction's `ExpiredAt`
+/// (`LighterClientBuilder::default_expiry`): `ExpiredAt` bounds how long the
+/// signed transaction may take to reach the exchange, `OrderExpiry` how long the
+/// accepted order stays on the book.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderExpiry {
+    /// No explicit expiry (`OrderExpiry` = 0); required for immediate-or-cancel orders
+    #[default]
+    None,
+    /// Good till date: unix timestamp in milliseconds
+    At(i64),
+    /// Good till date, relative to the moment the order is signed
+    After(Duration),
+}
+
+impl OrderExpiry {
+    /// Resolve to the `OrderExpiry` field value for an order signed at `now_ms`
+    ///
+    /// A date must lie in the future and no more than [`MAX_ORDER_EXPIRY`] ahead.
+    pub fn to_timestamp(self, now_ms: i64) -> Result<i64> {
+        let expiry = match self {
+            OrderExpiry::None => return Ok(0),
+            OrderExpiry::At(timestamp) => timestamp,
+            OrderExpiry::After(lifetime) => now_ms.saturating_add(lifetime.as_millis() as i64),
+        };
+
+        let latest = now_ms + MAX_ORDER_EXPIRY.as_millis() as i64;
+        if expiry <= now_ms || expiry > latest {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "OrderExpiry {} must be after now ({}) and at most {} days ahead",
+                expiry,
+                now_ms,
+                MAX_ORDER_EXPIRY.as_secs() / 86_400
+            )));
+        }
+        Ok(expiry)
+    }
+}
@@ -0,0 +1,85 @@
+use crate::LighterClient;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Consecutive 5xx responses from the active endpoint before failing over
+pub const DEFAULT_FAILOVER_THRESHOLD: u32 = 3;
+
+/// Base URLs a client can send to, and the one currently in use
+#[derive(Debug)]
+pub(crate) struct Endpoints {
+    urls: Vec<Arc<str>>,
+    active: AtomicUsize,
+    /// Consecutive server errors from the active endpoint
+    failures: AtomicU32,
+    threshold: u32,
+}
+
+impl Endpoints {
+    pub(crate) fn new(urls: Vec<Arc<str>>, threshold: u32) -> Self {
+        Self {
+            urls,
+            active: AtomicUsize::new(0),
+            failures: AtomicU32::new(0),
+            threshold,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.urls.len()
+    }
+
+    /// Position and base URL of the endpoint in use
+    pub(crate) fn active(&self) -> (usize, Arc<str>) {
+        let index = self.active.load(Ordering::SeqCst);
+        (index, self.urls[index].clone())
+    }
+
+    pub(crate) fn record_success(&self) {
+        self.failures.store(0, Ordering::SeqCst);
+    }
+
+    /// Count a 5xx from endpoint `index`; fails over once the threshold is reached
+    ///
+    /// Returns whether the client moved on to another endpoint.
+    pub(crate) fn record_server_error(&self, index: usize) -> bool {
+        if self.failures.fetch_add(1, Ordering::SeqCst) + 1 >= self.threshold {
+            return self.fail_over(index);
+        }
+        false
+    }
+
+    /// Move on from endpoint `index` to the next one in the list
+    ///
+    /// A no-op returning `true` if another request already moved on, so
+    /// concurrent failures skip only one endpoint.
+    pub(crate) fn fail_over(&self, index: usize) -> bool {
+        if self.urls.len() < 2 {
+            return false;
+        }
+        let next = (index + 1) % self.urls.len();
+        if self.active.compare_exchange(index, next, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+            self.failures.store(0, Ordering::SeqCst);
+        }
+        true
+    }
+
+    /// `url` (built on any of the base URLs) rerouted to `endpoint`
+    pub(crate) fn route(&self, url: &str, endpoint: &str) -> Option<String> {
+        self.urls
+            .iter()
+            .filter_map(|base| url.strip_prefix(&**base))
+            .min_by_key(|rest| rest.len())
+            .map(|rest| format!("{}{}", endpoint, rest))
+    }
+}
+
+impl LighterClient {
+    /// Base URL requests currently go to
+    ///
+    /// The first URL given to `LighterClientBuilder::base_urls` (or the
+    /// constructor's) until an endpoint fails and the client fails over.
+    pub fn active_base_url(&self) -> String {
+        self.endpoints.active().1.to_string()
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{ApiError, LighterClient, Result, SendTxResponse, SlippageGuard};
+use futures::future;
+use rust_decimal::Decimal;
+
+/// `CancelAllOrdersTx::time_in_force` canceling every open order right away
+const CANCEL_ALL_IMMEDIATE: u8 = 0;
+
+/// Outcome of closing one position in [`LighterClient::flatten_account`]
+#[derive(Debug)]
+pub struct PositionClose {
+    pub market_index: u8,
+    /// Signed size of the position before closing (positive for long)
+    pub size: Decimal,
+    /// Client order index of the closing order
+    pub client_order_index: u64,
+    /// The accepted order's response, or why the position was not closed
+    pub result: Result<SendTxResponse>,
+}
+
+/// What [`LighterClient::flatten_account`] did, step by step
+#[derive(Debug)]
+pub struct FlattenReport {
+    /// Response of the cancel-all transaction, or why it failed
+    pub cancel_all: Result<SendTxResponse>,
+    /// One entry per open position, or why positions could not be read
+    /// (in which case nothing was closed)
+    pub closes: Result<Vec<PositionClose>>,
+}
+
+impl FlattenReport {
+    /// Whether every order was canceled and every position closing order accepted
+    pub fn is_complete(&self) -> bool {
+        self.cancel_all.is_ok()
+            && self
+                .closes
+                .as_ref()
+                .is_ok_and(|closes| closes.iter().all(|close| close.result.is_ok()))
+    }
+
+    /// Markets whose position was not closed
+    pub fn failed_markets(&self) -> Vec<u8> {
+        match &self.closes {
+            Ok(closes) => closes
+                .iter()
+                .filter(|close| close.result.is_err())
+                .map(|close| close.market_index)
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+impl LighterClient {
+    /// Cancel every open order, then close every position: the account's kill switch
+    ///
+    /// First sends one cancel-all transaction (immediate), then reads the open
+    /// positions and closes each with a reduce-only market order for its full
+    /// size, priced by `guard` from the market's current book (see
+    /// [`Self::close_position`]). The closing orders go out together through
+    /// [`Self::send_tx_batch`]. Canceling comes first so resting orders cannot
+    /// reopen exposure while the positions close.
+    ///
+    /// No step aborts the others: a failed cancel-all still closes positions,
+    /// and a market whose book cannot be read or whose order is rejected does
+    /// not stop the rest. Everything is reported in the [`FlattenReport`]; check
+    /// [`FlattenReport::is_complete`] and retry what failed. Closing orders use
+    /// client order indices derived from the current time (unix ms * 100 plus
+    /// the position's number), returned in the report.
+    ///
+    /// Fails without doing anything only if `account_index` is not the client's
+    /// account, since orders are signed for the client's account only.
+    pub async fn flatten_account(&self, account_index: i64, guard: SlippageGuard) -> Result<FlattenReport> {
+        if account_index != self.account_index {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "cannot flatten account {} with a client for account {}",
+                account_index, self.account_index
+            )));
+        }
+
+        let cancel_all = self.cancel_all_orders(CANCEL_ALL_IMMEDIATE, 0).await;
+
+        let positions = match self.get_positions(account_index).await {
+            Ok(positions) => positions,
+            Err(e) => return Ok(FlattenReport { cancel_all, closes: Err(e) }),
+        };
+        let first_index = self.clock.now_ms().max(0) as u64 * 100;
+        let orders = future::join_all(positions.iter().enumerate().map(|(number, position)| {
+            self.closing_order(position, first_index + number as u64, guard)
+        }))
+        .await;
+
+        // Batch the orders that could be built; the others keep their error
+        let mut results = Vec::with_capacity(orders.len());
+        let (mut batched, mut batch) = (Vec::new(), Vec::new());
+        for (number, order) in orders.into_iter().enumerate() {
+            match order {
+                Ok(order) => {
+                    batched.push(number);
+                    batch.push(order);
+                    results.push(Err(ApiError::Api("not sent".to_string())));
+                }
+                Err(e) => results.push(Err(e)),
+            }
+        }
+        for (number, result) in batched.into_iter().zip(self.send_tx_batch(&batch).await.items) {
+            results[number] = result;
+        }
+
+        let closes = positions
+            .into_iter()
+            .zip(results)
+            .enumerate()
+            .map(|(number, (position, result))| PositionClose {
+                market_index: position.market_index,
+                size: position.size,
+                client_order_index: first_index + number as u64,
+                result,
+            })
+            .collect();
+        Ok(FlattenReport { cancel_all, closes: Ok(closes) })
+    }
+}
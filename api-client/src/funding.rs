@@ -0,0 +1,139 @@
+use crate::account::{check_response_code, decode};
+use crate::{LighterClient, Result};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+/// Maximum page size accepted by the position funding endpoint
+const FUNDING_PAGE_LIMIT: u32 = 100;
+
+/// Funding settled on one of the account's positions
+///
+/// Signs are from the account's point of view: a positive `amount` was
+/// received, a negative one paid. Longs pay when `rate` is positive and
+/// receive when it is negative; shorts the opposite.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundingPayment {
+    pub market_index: u8,
+    /// Settlement time, unix ms
+    pub timestamp: i64,
+    /// Funding rate applied at this settlement
+    pub rate: Decimal,
+    /// USDC received (positive) or paid (negative)
+    pub amount: Decimal,
+    /// Position size at settlement, negative for a short
+    pub position_size: Decimal,
+}
+
+/// Raw record from the position funding endpoint
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+struct FundingRecord {
+    market_id: u8,
+    timestamp: i64,
+    rate: Decimal,
+    /// Signed change to the account's collateral
+    change: Decimal,
+    /// Absolute size
+    position_size: Decimal,
+    /// "long" or "short"
+    position_side: String,
+}
+
+impl From<&FundingRecord> for FundingPayment {
+    fn from(record: &FundingRecord) -> Self {
+        let position_size = if record.position_side == "short" {
+            -record.position_size
+        } else {
+            record.position_size
+        };
+        Self {
+            market_index: record.market_id,
+            timestamp: record.timestamp,
+            rate: record.rate,
+            amount: record.change,
+            position_size,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+struct FundingPage {
+    position_fundings: Vec<FundingRecord>,
+    next_cursor: Option<String>,
+}
+
+impl LighterClient {
+    /// Fetch the funding an account paid and received within a time range (authenticated)
+    ///
+    /// Pages are followed until the end of the range or the end of the data.
+    /// See [`FundingPayment`] for the sign convention.
+    ///
+    /// # Arguments
+    /// * `account_index` - Account whose funding to return
+    /// * `market_index` - Restrict to one market, or `None` for all markets
+    /// * `start` - Inclusive start timestamp (unix ms), `None` for no lower bound
+    /// * `end` - Inclusive end timestamp (unix ms), `None` for no upper bound
+    ///
+    /// # Returns
+    /// Payments in ascending timestamp order
+    pub async fn get_funding_payments(
+        &self,
+        account_index: i64,
+        market_index: Option<u8>,
+        start: Option<i64>,
+        end: Option<i64>,
+    ) -> Result<Vec<FundingPayment>> {
+        let mut payments = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let mut query = vec![
+                ("account_index", account_index.to_string()),
+                ("sort_dir", "asc".to_string()),
+                ("limit", FUNDING_PAGE_LIMIT.to_string()),
+            ];
+            if let Some(market_index) = market_index {
+                query.push(("market_id", market_index.to_string()));
+            }
+            if let Some(start) = start {
+                query.push(("from", start.to_string()));
+            }
+            if let Some(cursor) = &cursor {
+                query.push(("cursor", cursor.clone()));
+            }
+
+            let response = self.get_json_auth("/api/v1/positionFunding", &query).await?;
+            check_response_code(&response)?;
+            let page: FundingPage = decode(response)?;
+
+            let mut past_end = false;
+            for record in &page.position_fundings {
+                if start.is_some_and(|start| record.timestamp < start) {
+                    continue;
+                }
+                if end.is_some_and(|end| record.timestamp > end) {
+                    past_end = true;
+                    break;
+                }
+                payments.push(FundingPayment::from(record));
+            }
+
+            cursor = match page.next_cursor {
+                Some(next)
+                    if !next.is_empty()
+                        && !past_end
+                        && !page.position_fundings.is_empty()
+                        && Some(&next) != cursor.as_ref() =>
+                {
+                    Some(next)
+                }
+                _ => break,
+            };
+        }
+
+        Ok(payments)
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{ApiError, LighterClient, Result};
+use std::error::Error as StdError;
+use std::fmt;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// Upper bound on a single ping before it is reported as a timeout
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Signature scheme version this client signs with
+pub const SIGNATURE_SCHEME_VERSION: u32 = signer::SCHEME_VERSION;
+
+/// Broad cause of a failed request, for diagnosing connectivity problems
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkErrorKind {
+    /// The host name could not be resolved
+    Dns,
+    /// The host resolved but nothing accepted the connection
+    ConnectionRefused,
+    /// No response arrived within the allowed time
+    Timeout,
+    /// Any other transport failure (TLS, reset, malformed response, ...)
+    Other,
+}
+
+impl fmt::Display for NetworkErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            NetworkErrorKind::Dns => "DNS resolution failed",
+            NetworkErrorKind::ConnectionRefused => "connection refused",
+            NetworkErrorKind::Timeout => "timed out",
+            NetworkErrorKind::Other => "transport error",
+        };
+        f.write_str(name)
+    }
+}
+
+impl NetworkErrorKind {
+    /// Classify a reqwest error by walking its source chain
+    pub(crate) fn classify(error: &reqwest::Error) -> Self {
+        if error.is_timeout() {
+            return NetworkErrorKind::Timeout;
+        }
+
+        let mut source: Option<&(dyn StdError + 'static)> = error.source();
+        while let Some(err) = source {
+            if let Some(io_err) = err.downcast_ref::<io::Error>() {
+                match io_err.kind() {
+                    io::ErrorKind::ConnectionRefused => return NetworkErrorKind::ConnectionRefused,
+                    io::ErrorKind::TimedOut => return NetworkErrorKind::Timeout,
+                    _ => {}
+                }
+            }
+            // hyper reports resolver failures as "dns error: ..." without a typed variant
+            if err.to_string().starts_with("dns error") {
+                return NetworkErrorKind::Dns;
+            }
+            source = err.source();
+        }
+
+        NetworkErrorKind::Other
+    }
+}
+
+impl LighterClient {
+    /// Measure the round-trip time to the API
+    ///
+    /// Hits the lightweight status endpoint on the shared HTTP client and returns
+    /// the time until the full response body arrived. Transport failures come back
+    /// as [`ApiError::Network`] with a [`NetworkErrorKind`] telling DNS failures,
+    /// refused connections and timeouts apart.
+    pub async fn ping(&self) -> Result<Duration> {
+        let url = format!("{}/", self.active_base_url().trim_end_matches('/'));
+        let network_error = |source: reqwest::Error| ApiError::Network {
+            kind: NetworkErrorKind::classify(&source),
+            source,
+        };
+
+        let started = Instant::now();
+        let response = self
+            .client
+            .get(&url)
+            .timeout(PING_TIMEOUT)
+            .send()
+            .await
+            .map_err(network_error)?;
+        let status = response.status();
+        response.bytes().await.map_err(network_error)?;
+        let latency = started.elapsed();
+
+        if !status.is_success() {
+            return Err(ApiError::Api(format!("status endpoint returned HTTP {}", status)));
+        }
+        Ok(latency)
+    }
+
+    /// Open a pooled connection to the API ahead of the first real request
+    ///
+    /// Resolves DNS and completes the TCP and TLS handshakes with one status
+    /// request, so the first order does not pay for them. Returns the time the
+    /// cold request took. Idle pooled connections are closed after about 90s, so
+    /// warm up shortly before trading starts. Optional: without it the first
+    /// request simply connects on demand.
+    pub async fn warm_up(&self) -> Result<Duration> {
+        self.ping().await
+    }
+
+    /// Signature scheme version the server expects, `None` if unknown
+    ///
+    /// The exchange does not document a field reporting the version, so it is
+    /// not known yet and this is always `None`; callers should treat `None` as
+    /// "not verified", never as a match.
+    pub async fn get_signature_scheme_version(&self) -> Result<Option<u32>> {
+        Ok(None)
+    }
+
+    /// Check that the server accepts [`SIGNATURE_SCHEME_VERSION`] signatures
+    ///
+    /// `Ok(true)` if the server's version matches; a different version fails
+    /// with `ApiError::SignatureSchemeMismatch`, so a protocol upgrade shows up
+    /// at startup instead of as every transaction being rejected for an invalid
+    /// signature. `Ok(false)` if the server's version is unknown (see
+    /// [`Self::get_signature_scheme_version`]) and nothing could be verified.
+    pub async fn check_signature_scheme(&self) -> Result<bool> {
+        check_scheme_version(self.get_signature_scheme_version().await?)
+    }
+}
+
+/// Compare the server's signature scheme version, if known, with this client's
+pub(crate) fn check_scheme_version(server: Option<u32>) -> Result<bool> {
+    match server {
+        Some(server) if server != SIGNATURE_SCHEME_VERSION => Err(ApiError::SignatureSchemeMismatch {
+            client: SIGNATURE_SCHEME_VERSION,
+            server,
+        }),
+        Some(_) => Ok(true),
+        None => Ok(false),
+    }
+}
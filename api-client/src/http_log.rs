@@ -0,0 +1,121 @@
+use crate::Result;
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Callback registered with `LighterClientBuilder::on_http_exchange`
+pub type HttpLogHandler = Arc<dyn Fn(&HttpExchange) + Send + Sync>;
+
+/// Query parameters and body fields whose values are replaced before logging
+///
+/// `auth` carries the signed auth token of authenticated reads, which grants
+/// access to the account until it expires. Transaction bodies hold only public
+/// data (the signature of a `tx_info` cannot be reused for another one), so
+/// they are logged as sent.
+const REDACTED_FIELDS: [&str; 1] = ["auth"];
+
+const REDACTED: &str = "<redacted>";
+
+/// One HTTP request and its outcome, as passed to the `on_http_exchange` callback
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpExchange {
+    pub method: String,
+    /// Full URL with the query string, secrets redacted
+    pub url: String,
+    /// Request body exactly as sent (e.g. the `sendTx` form), secrets redacted; `None` for GETs
+    pub body: Option<String>,
+    /// HTTP status, `None` when no response arrived
+    pub status: Option<u16>,
+    /// `code` field of a JSON response body
+    pub code: Option<i64>,
+    /// `message` field of a JSON response body
+    pub message: Option<String>,
+    /// Time from sending the request to reading the whole response (or failing)
+    pub elapsed: Duration,
+    /// Why the request failed without a response: connect error, timeout, ...
+    pub error: Option<String>,
+}
+
+impl HttpExchange {
+    pub(crate) fn new(request: &reqwest::Request) -> Self {
+        let body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| redact_body(&String::from_utf8_lossy(bytes)));
+        let url = match request.url().as_str().split_once('?') {
+            Some((path, query)) => format!("{}?{}", path, redact_form(query)),
+            None => request.url().to_string(),
+        };
+        Self {
+            method: request.method().to_string(),
+            url,
+            body,
+            status: None,
+            code: None,
+            message: None,
+            elapsed: Duration::ZERO,
+            error: None,
+        }
+    }
+
+    pub(crate) fn finish(&mut self, outcome: &Result<(reqwest::StatusCode, String)>, elapsed: Duration) {
+        self.elapsed = elapsed;
+        match outcome {
+            Ok((status, text)) => {
+                self.status = Some(status.as_u16());
+                if let Ok(response) = serde_json::from_str::<Value>(text) {
+                    self.code = response["code"].as_i64();
+                    self.message = response["message"].as_str().map(str::to_string);
+                }
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+}
+
+/// One line: method, URL, status, code and message, time taken
+impl fmt::Display for HttpExchange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.url)?;
+        match (self.status, &self.error) {
+            (Some(status), _) => write!(f, " -> {}", status)?,
+            (None, Some(error)) => write!(f, " -> failed: {}", error)?,
+            (None, None) => {}
+        }
+        if let Some(code) = self.code {
+            write!(f, " code {}", code)?;
+        }
+        if let Some(message) = &self.message {
+            write!(f, " {:?}", message)?;
+        }
+        write!(f, " ({:?})", self.elapsed)
+    }
+}
+
+/// Redact a form or JSON body, leaving everything else byte for byte
+fn redact_body(body: &str) -> String {
+    if let Ok(Value::Object(mut fields)) = serde_json::from_str::<Value>(body) {
+        if !REDACTED_FIELDS.iter().any(|name| fields.contains_key(*name)) {
+            return body.to_string();
+        }
+        for name in REDACTED_FIELDS {
+            if let Some(value) = fields.get_mut(name) {
+                *value = Value::from(REDACTED);
+            }
+        }
+        return Value::Object(fields).to_string();
+    }
+    redact_form(body)
+}
+
+/// Redact a query string or form body
+fn redact_form(form: &str) -> String {
+    form.split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, _)) if REDACTED_FIELDS.contains(&key) => format!("{}={}", key, REDACTED),
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
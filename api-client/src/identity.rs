@@ -0,0 +1,87 @@
+use crate::{ApiError, LighterClient, NonceCache, NonceGapTracker, Result};
+use signer::KeyManager;
+use std::sync::Arc;
+use tokio::sync::Mutex as AsyncMutex;
+
+/// An account and API key a [`MultiAccountClient`] signs for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Identity {
+    pub account_index: i64,
+    pub api_key_index: u8,
+}
+
+impl LighterClient {
+    /// The account and API key this client signs for
+    pub fn identity(&self) -> Identity {
+        Identity {
+            account_index: self.account_index,
+            api_key_index: self.api_key_index,
+        }
+    }
+
+    /// A client for another account and API key sharing this client's resources
+    ///
+    /// The HTTP connection pool, the `max_in_flight` limit, market metadata and
+    /// every builder setting are shared; the signing key, nonce cache, nonce
+    /// gap tracking and reported rate-limit budget belong to the new identity alone.
+    pub fn with_identity(&self, account_index: i64, api_key_index: u8, private_key_hex: &str) -> Result<Self> {
+        let key_manager = KeyManager::from_hex(private_key_hex)?;
+        Ok(Self {
+            signer: Arc::new(key_manager.prepare()),
+            key_manager: Arc::new(key_manager),
+            account_index,
+            api_key_index,
+            nonce_cache: Arc::new(AsyncMutex::new(NonceCache::new(self.nonce_ttl))),
+            nonce_gaps: Arc::new(NonceGapTracker::new(self.nonce_gaps.threshold)),
+            rate_limits: Arc::default(),
+            ..self.clone()
+        })
+    }
+}
+
+/// Several accounts behind one HTTP client
+///
+/// Each identity is a [`LighterClient`] created with
+/// [`LighterClient::with_identity`] from the first one, so all of them share
+/// the connection pool and `max_in_flight` limit while keeping their own key
+/// and nonces. Select an identity with [`Self::client`] and call any client
+/// method on it; transactions are signed with that identity's key and carry
+/// its account and API key index.
+#[derive(Debug, Clone)]
+pub struct MultiAccountClient {
+    clients: Vec<LighterClient>,
+}
+
+impl MultiAccountClient {
+    /// Start from a configured client, whose identity becomes the first one
+    pub fn new(client: LighterClient) -> Self {
+        Self { clients: vec![client] }
+    }
+
+    /// Add an identity, replacing an earlier one with the same account and API key
+    pub fn add_identity(&mut self, account_index: i64, api_key_index: u8, private_key_hex: &str) -> Result<Identity> {
+        let client = self.clients[0].with_identity(account_index, api_key_index, private_key_hex)?;
+        let identity = client.identity();
+        self.clients.retain(|existing| existing.identity() != identity);
+        self.clients.push(client);
+        Ok(identity)
+    }
+
+    /// The client signing for `identity`
+    pub fn client(&self, identity: Identity) -> Result<&LighterClient> {
+        self.clients
+            .iter()
+            .find(|client| client.identity() == identity)
+            .ok_or_else(|| {
+                ApiError::NotFound(format!(
+                    "identity for account {} and API key {}",
+                    identity.account_index, identity.api_key_index
+                ))
+            })
+    }
+
+    /// Every identity, in the order added
+    pub fn identities(&self) -> Vec<Identity> {
+        self.clients.iter().map(LighterClient::identity).collect()
+    }
+}
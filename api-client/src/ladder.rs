@@ -0,0 +1,153 @@
+use crate::{ApiError, CreateOrderRequest, LighterClient, MarketInfo, Result, Side, TickRounding};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+/// How [`LighterClient::place_ladder`] splits the total size across levels
+///
+/// Weights run from the level at `start_price` to the one at `end_price`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LadderDistribution {
+    /// The same size at every level
+    Uniform,
+    /// Sizes growing linearly: weights 1, 2, ..., levels
+    Linear,
+    /// Each level `ratio` times the previous one; below 1 the sizes shrink
+    Geometric(Decimal),
+}
+
+impl LadderDistribution {
+    fn weights(self, levels: usize) -> Result<Vec<Decimal>> {
+        match self {
+            LadderDistribution::Uniform => Ok(vec![Decimal::ONE; levels]),
+            LadderDistribution::Linear => Ok((1..=levels).map(Decimal::from).collect()),
+            LadderDistribution::Geometric(ratio) if ratio > Decimal::ZERO => {
+                let mut weight = Decimal::ONE;
+                let mut weights = Vec::with_capacity(levels);
+                for _ in 0..levels {
+                    weights.push(weight);
+                    weight = weight.checked_mul(ratio).ok_or_else(|| {
+                        ApiError::InvalidOrderParams(format!("ladder ratio {} overflows over {} levels", ratio, levels))
+                    })?;
+                }
+                Ok(weights)
+            }
+            LadderDistribution::Geometric(ratio) => Err(ApiError::InvalidOrderParams(format!(
+                "ladder ratio must be positive, got {}",
+                ratio
+            ))),
+        }
+    }
+}
+
+/// Integer prices of `levels` evenly spaced levels from `start` to `end`
+///
+/// Rounded away from the opposite side of the book, so no level crosses
+/// further than the price asked for; fails if two levels land on one tick.
+fn ladder_prices(market: &MarketInfo, side: Side, start: Decimal, end: Decimal, levels: usize) -> Result<Vec<i64>> {
+    let step = if levels > 1 { (end - start) / Decimal::from(levels - 1) } else { Decimal::ZERO };
+    let mut prices = Vec::with_capacity(levels);
+    for level in 0..levels {
+        let price = market.to_price_units(start + step * Decimal::from(level), TickRounding::TowardPassive(side))?;
+        if prices.last() == Some(&price) {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "ladder levels {} and {} both round to price {}; use fewer levels or a wider range",
+                level - 1,
+                level,
+                price
+            )));
+        }
+        prices.push(price);
+    }
+    Ok(prices)
+}
+
+/// Split `total_lots` by `weights`, rounding to whole lots so the sizes add up exactly
+///
+/// Each level gets the whole lots of its share; the lots left over go one each
+/// to the levels with the largest remainders.
+fn ladder_sizes(total_lots: i64, weights: &[Decimal]) -> Vec<i64> {
+    let total_weight: Decimal = weights.iter().sum();
+    let shares: Vec<Decimal> =
+        weights.iter().map(|weight| Decimal::from(total_lots) * weight / total_weight).collect();
+    let mut sizes: Vec<i64> = shares.iter().map(|share| share.floor().to_i64().unwrap_or(0)).collect();
+
+    let mut by_remainder: Vec<usize> = (0..shares.len()).collect();
+    by_remainder.sort_by(|a, b| shares[*b].fract().cmp(&shares[*a].fract()));
+    let leftover = total_lots - sizes.iter().sum::<i64>();
+    for level in by_remainder.into_iter().take(leftover.max(0) as usize) {
+        sizes[level] += 1;
+    }
+    sizes
+}
+
+impl LighterClient {
+    /// Place `levels` limit orders spread evenly from `start_price` to `end_price`
+    ///
+    /// `total_size` (in base units, e.g. ETH) is split across the levels by
+    /// `distribution`, in whole lots that add up to exactly `total_size`.
+    /// Prices are rounded onto the market's tick away from the opposite side
+    /// of the book (down for buys, up for sells). Everything is checked before
+    /// signing: `total_size` must be a whole number of lots, no two levels may
+    /// round to the same price, and every level must meet the market's minimum
+    /// size and order value; violations fail with `ApiError::InvalidOrderParams`.
+    ///
+    /// The orders rest good-till-time and are submitted in a single
+    /// `sendTxBatch` request, so `levels` may not exceed
+    /// [`Self::max_batch_size`]; the exchange accepts or rejects them together
+    /// and on failure nothing is placed. Client order indices are derived from
+    /// the current time (unix ms * 100 plus the level's number, starting at the
+    /// `start_price` level) and returned in level order.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn place_ladder(
+        &self,
+        market_index: u8,
+        side: Side,
+        start_price: Decimal,
+        end_price: Decimal,
+        levels: usize,
+        total_size: Decimal,
+        distribution: LadderDistribution,
+    ) -> Result<Vec<u64>> {
+        if levels == 0 || levels > self.max_batch_size {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "ladder needs 1..={} levels, got {}",
+                self.max_batch_size, levels
+            )));
+        }
+        let market = self.cached_market_info(market_index).await?;
+        let total_lots = total_size * Decimal::from(10u64.pow(market.size_decimals));
+        let total_lots = total_lots
+            .to_i64()
+            .filter(|lots| *lots > 0 && total_lots.fract().is_zero())
+            .ok_or_else(|| {
+                ApiError::InvalidOrderParams(format!(
+                    "ladder size {} is not a positive number of {} lots ({} size decimals)",
+                    total_size, market.symbol, market.size_decimals
+                ))
+            })?;
+
+        let prices = ladder_prices(&market, side, start_price, end_price, levels)?;
+        let sizes = ladder_sizes(total_lots, &distribution.weights(levels)?);
+        for (level, (price, size)) in prices.iter().zip(&sizes).enumerate() {
+            market.validate_order(*price, *size).map_err(|e| match e {
+                ApiError::InvalidOrderParams(reason) => {
+                    ApiError::InvalidOrderParams(format!("ladder level {}: {}", level, reason))
+                }
+                e => e,
+            })?;
+        }
+
+        let first_index = self.clock.now_ms().max(0) as u64 * 100;
+        let orders: Vec<_> = prices
+            .into_iter()
+            .zip(sizes)
+            .enumerate()
+            .map(|(level, (price, size))| CreateOrderRequest {
+                client_order_index: first_index + level as u64,
+                ..CreateOrderRequest::limit(market_index, side, size, price)
+            })
+            .collect();
+        self.place_together(&orders, |level| format!("ladder level {}", level)).await?;
+        Ok(orders.iter().map(|order| order.client_order_index).collect())
+    }
+}
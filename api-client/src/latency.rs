@@ -0,0 +1,123 @@
+use crate::LighterClient;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Samples kept per operation for percentiles unless configured otherwise
+pub const DEFAULT_LATENCY_WINDOW: usize = 1000;
+
+/// Weight of the newest sample in the moving average
+const EMA_ALPHA: f64 = 0.1;
+
+/// Latency of one kind of request, as seen by this client
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LatencySummary {
+    /// Successful requests measured since creation or the last reset
+    pub count: u64,
+    /// Exponential moving average (newest sample weighted 0.1)
+    pub ema: Option<Duration>,
+    /// Percentiles over the most recent window of samples
+    pub p50: Option<Duration>,
+    pub p95: Option<Duration>,
+    pub p99: Option<Duration>,
+    /// Slowest sample in the window
+    pub max: Option<Duration>,
+}
+
+/// Snapshot of the client's request latencies; see [`LighterClient::latency_stats`]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LatencyStats {
+    /// `nextNonce` requests
+    pub nonce_fetch: LatencySummary,
+    /// `sendTx` requests, from sending until the response body arrived
+    pub send_tx: LatencySummary,
+}
+
+#[derive(Debug, Default)]
+struct LatencySeries {
+    count: u64,
+    ema_secs: Option<f64>,
+    samples: VecDeque<Duration>,
+}
+
+impl LatencySeries {
+    fn record(&mut self, sample: Duration, window: usize) {
+        self.count += 1;
+        let secs = sample.as_secs_f64();
+        self.ema_secs = Some(match self.ema_secs {
+            Some(ema) => ema + EMA_ALPHA * (secs - ema),
+            None => secs,
+        });
+        if self.samples.len() == window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    fn summary(&self) -> LatencySummary {
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        LatencySummary {
+            count: self.count,
+            ema: self.ema_secs.map(Duration::from_secs_f64),
+            p50: percentile(&sorted, 50),
+            p95: percentile(&sorted, 95),
+            p99: percentile(&sorted, 99),
+            max: sorted.last().copied(),
+        }
+    }
+}
+
+/// Nearest-rank percentile of ascending `sorted`
+fn percentile(sorted: &[Duration], pct: usize) -> Option<Duration> {
+    let rank = (sorted.len() * pct).div_ceil(100).max(1);
+    sorted.get(rank - 1).copied()
+}
+
+/// Rolling latency of the requests on the order path
+#[derive(Debug)]
+pub(crate) struct LatencyTracker {
+    window: usize,
+    nonce_fetch: Mutex<LatencySeries>,
+    send_tx: Mutex<LatencySeries>,
+}
+
+impl LatencyTracker {
+    pub(crate) fn new(window: usize) -> Self {
+        Self {
+            window,
+            nonce_fetch: Mutex::default(),
+            send_tx: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn record_nonce_fetch(&self, sample: Duration) {
+        self.nonce_fetch.lock().expect("latency stats poisoned").record(sample, self.window);
+    }
+
+    pub(crate) fn record_send_tx(&self, sample: Duration) {
+        self.send_tx.lock().expect("latency stats poisoned").record(sample, self.window);
+    }
+}
+
+impl LighterClient {
+    /// Rolling latency statistics for nonce fetches and `sendTx` requests
+    ///
+    /// Only requests that returned a JSON body are measured; transport failures
+    /// and timeouts are not. Percentiles cover the last
+    /// `LighterClientBuilder::latency_window` samples of each kind, the average
+    /// and count everything since creation or [`Self::reset_latency_stats`].
+    /// Clones of a client share the statistics.
+    pub fn latency_stats(&self) -> LatencyStats {
+        LatencyStats {
+            nonce_fetch: self.latency.nonce_fetch.lock().expect("latency stats poisoned").summary(),
+            send_tx: self.latency.send_tx.lock().expect("latency stats poisoned").summary(),
+        }
+    }
+
+    /// Discard all latency samples, e.g. after a network change
+    pub fn reset_latency_stats(&self) {
+        *self.latency.nonce_fetch.lock().expect("latency stats poisoned") = LatencySeries::default();
+        *self.latency.send_tx.lock().expect("latency stats poisoned") = LatencySeries::default();
+    }
+}
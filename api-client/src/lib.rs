@@ -1,10 +1,115 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use signer::KeyManager;
-use std::time::{SystemTime, UNIX_EPOCH};
+use signer::{KeyManager, PreparedSigner};
 use thiserror::Error;
-use base64::Engine;
+
+#[cfg(not(any(feature = "native-tls", feature = "rustls-tls")))]
+compile_error!("api-client needs a TLS backend: enable the `native-tls` or `rustls-tls` feature");
+
+mod account;
+mod account_events;
+mod amend;
+mod backpressure;
+mod batch;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod bracket;
+mod builder;
+mod cancel;
+mod candles;
+mod circuit;
+mod closed_positions;
+mod clock;
+mod encoding;
+mod error_code;
+mod expiry;
+mod failover;
+mod flatten;
+mod funding;
+mod health;
+mod http_log;
+mod identity;
+mod ladder;
+mod latency;
+mod market;
+mod market_refresh;
+mod nonce_source;
+mod order_rules;
+mod pipeline;
+mod rate_limit;
+mod rejection;
+mod replay;
+mod response;
+mod sender;
+mod side;
+mod signature;
+mod signing_pool;
+mod sub_accounts;
+mod ticks;
+mod transaction;
+mod trigger;
+mod tx;
+mod withdrawal;
+mod ws;
+#[cfg(test)]
+mod tests;
+
+pub use account::{
+    AccountTrade, Leverage, MarginDirection, MarginMode, Order, OrderHistoryPage, OrderReconciliation, Position,
+    KEY_ACTIVE_POLL_INTERVAL,
+};
+pub use account_events::{AccountEvent, BalanceUpdate, LiquidationWarning};
+pub use amend::Amendment;
+pub use backpressure::{Backpressure, BufferedStream};
+pub use batch::{BatchResult, SignedOrder};
+pub use bracket::Bracket;
+pub use builder::{LighterClientBuilder, DEFAULT_CONNECT_TIMEOUT, DEFAULT_MAX_BATCH_SIZE, DEFAULT_NONCE_GAP_THRESHOLD, DEFAULT_PRICE_PROTECTION, DEFAULT_REQUEST_TIMEOUT,
+    DEFAULT_TX_EXPIRY, DEFAULT_USER_AGENT, DEFAULT_WS_PING_INTERVAL, DEFAULT_WS_PONG_TIMEOUT, MAX_TX_EXPIRY};
+pub use cancel::{
+    CancelGuard, CANCEL_ALL_TIF_ABORT, CANCEL_ALL_TIF_IMMEDIATE, CANCEL_ALL_TIF_SCHEDULED, DEFAULT_CANCEL_GUARD_TIMEOUT,
+};
+pub use candles::{max_range_for, Candle, CandleEvent, CandleResolution, MAX_CANDLES_PER_REQUEST};
+pub use circuit::CircuitState;
+pub use closed_positions::ClosedPosition;
+pub use clock::{Clock, SystemClock};
+pub use encoding::{SendTxEncoding, FIELD_PRICE_PROTECTION, FIELD_TX_INFO, FIELD_TX_INFOS, FIELD_TX_TYPE, FIELD_TX_TYPES};
+pub use error_code::ErrorCode;
+pub use expiry::{OrderExpiry, MAX_ORDER_EXPIRY};
+pub use failover::DEFAULT_FAILOVER_THRESHOLD;
+pub use flatten::{FlattenReport, PositionClose};
+pub use funding::FundingPayment;
+pub use health::{NetworkErrorKind, SIGNATURE_SCHEME_VERSION};
+pub use http_log::{HttpExchange, HttpLogHandler};
+pub use identity::{Identity, MultiAccountClient};
+pub use ladder::LadderDistribution;
+pub use latency::{LatencyStats, LatencySummary, DEFAULT_LATENCY_WINDOW};
+pub use market::{BookLevel, FillEstimate, MarketInfo, MarketStats, OrderBookSnapshot, SlippageGuard};
+pub use market_refresh::MarketRefresh;
+pub use nonce_source::NonceSource;
+pub use order_rules::{
+    ORDER_TIME_IN_FORCE_FOK, ORDER_TIME_IN_FORCE_GOOD_TILL_TIME, ORDER_TIME_IN_FORCE_IOC, ORDER_TIME_IN_FORCE_POST_ONLY,
+    ORDER_TYPE_LIMIT, ORDER_TYPE_MARKET, ORDER_TYPE_STOP_LOSS, ORDER_TYPE_STOP_LOSS_LIMIT, ORDER_TYPE_TAKE_PROFIT,
+    ORDER_TYPE_TAKE_PROFIT_LIMIT, ORDER_TYPE_TWAP,
+};
+pub use pipeline::{OrderPipeline, PipelineResult};
+pub use rate_limit::RateLimitStatus;
+pub use rejection::{RejectionHandler, RejectionRecord};
+pub use replay::{ReplayEvent, ReplaySender, SimulatedFill, SimulatedOrder};
+pub use response::{check_send_tx_response, parse_send_tx_response, SendTxResponse};
+pub use sender::TransactionSender;
+pub use side::Side;
+pub use signature::{Signature, SIGNATURE_LENGTH};
+pub use signing_pool::SigningPool;
+pub use sub_accounts::SubAccount;
+pub use ticks::{TickRounding, TickSize};
+pub use transaction::{
+    CancelAllOrdersTx, CancelOrderTx, ChangePubKeyTx, CreateOrderTx, ModifyOrderTx, Transaction, TxHeader,
+    UpdateLeverageTx, UpdateMarginTx,
+};
+pub use trigger::TriggerDirection;
+pub use withdrawal::{WithdrawalRef, WithdrawalState, WithdrawalStatus};
+pub use ws::{Bbo, BookEvent, BookUpdate, OrderBook, PriceLevel, WsFrameHandler};
 
 #[derive(Error, Debug)]
 pub enum ApiError {
@@ -12,16 +117,117 @@ pub enum ApiError {
     Signer(#[from] signer::SignerError),
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
+    #[error("WebSocket error: {0}")]
+    WebSocket(#[from] Box<tokio_tungstenite::tungstenite::Error>),
+    #[error("Network error ({kind}): {source}")]
+    Network {
+        kind: NetworkErrorKind,
+        #[source]
+        source: reqwest::Error,
+    },
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
     #[error("System time error: {0}")]
     SystemTime(#[from] std::time::SystemTimeError),
     #[error("API error: {0}")]
     Api(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+    #[error("Unsupported operation: {0}")]
+    Unsupported(String),
+    #[error("Invalid order parameters: {0}")]
+    InvalidOrderParams(String),
+    #[error("Transaction is not signed: {0}")]
+    Unsigned(String),
+    /// Signature bytes of the wrong length, or an undecodable encoding of them
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+    /// The server expects a different signature scheme than this client implements
+    #[error("Signature scheme version {client} does not match the server's version {server}; upgrade the client")]
+    SignatureSchemeMismatch { client: u32, server: u32 },
+    /// The market does not accept orders; `code` is `None` if the client refused before sending
+    #[error("Market closed: {message}")]
+    MarketClosed {
+        market_index: Option<u8>,
+        code: Option<i64>,
+        message: String,
+    },
+    /// The exchange is down for maintenance
+    #[error("Exchange in maintenance: {message}")]
+    InMaintenance { code: Option<i64>, message: String },
+    /// A post-only order would have crossed the book and taken liquidity
+    #[error("Post-only order would cross: {message}")]
+    PostOnlyWouldCross {
+        market_index: Option<u8>,
+        code: Option<i64>,
+        message: String,
+    },
+    /// The account lacks the margin or collateral the transaction needs
+    #[error("Insufficient margin: {message}")]
+    InsufficientMargin { code: Option<i64>, message: String },
+    /// The transaction's `ExpiredAt` passed before the exchange processed it
+    #[error("Transaction expired: {message}")]
+    TransactionExpired { code: Option<i64>, message: String },
+    /// The exchange rejected a transaction with a code no other variant covers
+    #[error("Rejected with code {code}: {message}")]
+    Rejected { code: i64, message: String },
+    /// A nonce the exchange had already consumed; the client skips ahead past it
+    #[error("Nonce {nonce} too low: {message}")]
+    NonceTooLow { nonce: i64, code: Option<i64>, message: String },
+    /// A nonce beyond the exchange's next one, leaving a gap that must be filled first
+    #[error("Nonce {nonce} too high: {message}")]
+    NonceTooHigh { nonce: i64, code: Option<i64>, message: String },
+    /// The circuit breaker is open after repeated failures; nothing was sent
+    #[error("Circuit breaker open, retry after {retry_after:?}")]
+    CircuitOpen { retry_after: Duration },
+    #[error("Connect timed out after {0:?}")]
+    ConnectTimeout(Duration),
+    #[error("Request timed out after {0:?}")]
+    RequestTimeout(Duration),
+    #[error("WebSocket received nothing for {0:?}")]
+    WebSocketIdle(Duration),
+    /// A candle request covering more candles than the server returns at once
+    ///
+    /// `max_range` is the longest range one request can cover at `resolution`.
+    #[error("{requested} {resolution} candles requested, at most {max} per request ({max_range:?}); split the range")]
+    CandleRangeTooLarge {
+        resolution: CandleResolution,
+        requested: i64,
+        max: i64,
+        max_range: Duration,
+    },
+    /// `wait_for_key_active` gave up before the exchange registered the key
+    #[error("API key {api_key_index} not active after {waited:?}")]
+    KeyActivationTimeout { api_key_index: u8, waited: Duration },
 }
 
 pub type Result<T> = std::result::Result<T, ApiError>;
 
+impl ApiError {
+    /// Exchange error code of a typed rejection, `None` for other errors
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            ApiError::MarketClosed { code, .. }
+            | ApiError::InMaintenance { code, .. }
+            | ApiError::PostOnlyWouldCross { code, .. }
+            | ApiError::InsufficientMargin { code, .. }
+            | ApiError::TransactionExpired { code, .. }
+            | ApiError::NonceTooLow { code, .. }
+            | ApiError::NonceTooHigh { code, .. } => *code,
+            ApiError::Rejected { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for ApiError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        ApiError::WebSocket(Box::new(e))
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct CreateOrderRequest {
     pub account_index: i64,
@@ -29,72 +235,457 @@ pub struct CreateOrderRequest {
     pub client_order_index: u64,
     pub base_amount: i64,
     pub price: i64,
-    pub is_ask: bool,
+    pub side: Side,
     pub order_type: u8,
     pub time_in_force: u8,
     pub reduce_only: bool,
     pub trigger_price: i64,
+    /// Good-till-date lifetime of the order on the book
+    #[serde(default)]
+    pub order_expiry: OrderExpiry,
+    /// Price protection for this order; `None` uses the client's default
+    /// (`LighterClientBuilder::price_protection`)
+    #[serde(default)]
+    pub price_protection: Option<bool>,
 }
 
-use std::sync::Arc;
-use std::time::{Duration, Instant};
+impl CreateOrderRequest {
+    /// A market order: immediate-or-cancel, filling at `worst_price` or better
+    ///
+    /// Market orders carry a price too: the exchange fills them no worse than
+    /// it, and the order is canceled beyond that (see
+    /// [`SlippageGuard::protected_price`] to derive one from the book).
+    /// `client_order_index` is 0 and every other option is off; set more
+    /// fields with struct update syntax, e.g.
+    /// `CreateOrderRequest { reduce_only: true, ..CreateOrderRequest::market(0, Side::Sell, 100, 290_000) }`.
+    /// `account_index` is 0 because the signing client's account is used.
+    pub fn market(market_index: u8, side: Side, base_amount: i64, worst_price: i64) -> Self {
+        Self {
+            account_index: 0,
+            order_book_index: market_index,
+            client_order_index: 0,
+            base_amount,
+            price: worst_price,
+            side,
+            order_type: ORDER_TYPE_MARKET,
+            time_in_force: ORDER_TIME_IN_FORCE_IOC,
+            reduce_only: false,
+            trigger_price: 0,
+            order_expiry: OrderExpiry::None,
+            price_protection: None,
+        }
+    }
+
+    /// A limit order resting at `price` until filled or canceled (good-till-time)
+    ///
+    /// Defaults as for [`Self::market`]: client order index 0, not
+    /// reduce-only, no trigger, no `OrderExpiry` and the client's default
+    /// price protection.
+    pub fn limit(market_index: u8, side: Side, base_amount: i64, price: i64) -> Self {
+        Self {
+            order_type: ORDER_TYPE_LIMIT,
+            time_in_force: ORDER_TIME_IN_FORCE_GOOD_TILL_TIME,
+            ..Self::market(market_index, side, base_amount, price)
+        }
+    }
+
+    /// Whether this order may be re-signed after an expiry rejection
+    ///
+    /// An expired transaction was never executed, so the resend cannot fill
+    /// twice; the risk is that it lands in a market that moved while the first
+    /// one waited. A reduce-only order can then at most shrink the position, and
+    /// a post-only order is rejected rather than crossing at a stale price. See
+    /// `LighterClientBuilder::resign_on_expiry`.
+    pub fn is_idempotent(&self) -> bool {
+        self.reduce_only || self.time_in_force == ORDER_TIME_IN_FORCE_POST_ONLY
+    }
+
+    /// CREATE_ORDER transaction for this order; the header is filled in on signing
+    ///
+    /// Resolves `order_expiry` against the current time. Fails with
+    /// `ApiError::InvalidOrderParams` if the expiry is out of range, or if the
+    /// order type, time in force and expiry are not a legal combination (a
+    /// market order, for one, is only immediate-or-cancel or fill-or-kill).
+    pub fn to_tx(&self) -> Result<CreateOrderTx> {
+        self.to_tx_at(SystemClock.now_ms())
+    }
+
+    /// [`Self::to_tx`] for an order signed at `now` (unix ms)
+    pub fn to_tx_at(&self, now: i64) -> Result<CreateOrderTx> {
+        order_rules::check_order_combination(self.order_type, self.time_in_force, self.order_expiry)?;
+
+        Ok(CreateOrderTx {
+            header: TxHeader::default(),
+            market_index: self.order_book_index,
+            client_order_index: self.client_order_index,
+            base_amount: self.base_amount,
+            price: self.price,
+            is_ask: self.side.as_is_ask(),
+            order_type: self.order_type,
+            time_in_force: self.time_in_force,
+            reduce_only: self.reduce_only as u8,
+            trigger_price: self.trigger_price,
+            order_expiry: self.order_expiry.to_timestamp(now)?,
+        })
+    }
+}
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use rand::RngCore;
+use std::time::Instant;
 use tokio::sync::Mutex as AsyncMutex;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Whether a sendTx rejection was caused by a stale or out-of-order nonce
+fn is_nonce_rejection(response: &Value) -> bool {
+    nonce_mismatch(response).is_some()
+}
+
+/// Which side of the exchange's next nonce a rejected nonce was on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NonceMismatch {
+    /// Already consumed: skip ahead to the exchange's next nonce
+    TooLow,
+    /// Past the exchange's next nonce: an earlier nonce was never consumed
+    TooHigh,
+    /// The rejection blames the nonce without saying which way
+    Unspecified,
+}
+
+/// Classify a nonce rejection by its code
+///
+/// `ErrorCode::InvalidNonce` does not say which way the nonce was off, so for
+/// it, and for a response without a known code, the message decides.
+fn nonce_mismatch(response: &Value) -> Option<NonceMismatch> {
+    let code = ErrorCode::of(response);
+    match code {
+        Some(ErrorCode::NonceTooLow) => return Some(NonceMismatch::TooLow),
+        Some(ErrorCode::NonceTooHigh) => return Some(NonceMismatch::TooHigh),
+        Some(ErrorCode::InvalidNonce) | None => {}
+        Some(_) => return None,
+    }
+    let lower = response["message"].as_str().unwrap_or_default().to_ascii_lowercase();
+    if !lower.contains("nonce") {
+        return (code == Some(ErrorCode::InvalidNonce)).then_some(NonceMismatch::Unspecified);
+    }
+    let low = ["too low", "too small", "lower than", "less than", "already used", "used nonce"];
+    let high = ["too high", "too large", "too big", "greater than", "higher than", "gap"];
+    if low.iter().any(|phrase| lower.contains(phrase)) {
+        Some(NonceMismatch::TooLow)
+    } else if high.iter().any(|phrase| lower.contains(phrase)) {
+        Some(NonceMismatch::TooHigh)
+    } else {
+        Some(NonceMismatch::Unspecified)
+    }
+}
+
+/// Typed error for a rejection of `nonce` that says which way the nonce was off
+fn nonce_error(nonce: i64, response: &Value) -> Option<ApiError> {
+    let code = response["code"].as_i64();
+    let message = response["message"].as_str().unwrap_or_default().to_string();
+    match nonce_mismatch(response)? {
+        NonceMismatch::TooLow => Some(ApiError::NonceTooLow { nonce, code, message }),
+        NonceMismatch::TooHigh => Some(ApiError::NonceTooHigh { nonce, code, message }),
+        NonceMismatch::Unspecified => None,
+    }
+}
+
+/// Error for a rejection of a transaction signed with `nonce` that was not recovered
+///
+/// Nonce rejections become [`unrecovered_nonce_error`]s, all others the error
+/// [`response::send_tx_error`] maps them to.
+fn unrecovered_error(nonce: i64, response: &Value) -> ApiError {
+    unrecovered_nonce_error(nonce, response).unwrap_or_else(|| response::send_tx_error(response, None))
+}
+
+/// [`nonce_error`] for a rejection handed back unrecovered; one that does not say
+/// which way counts toward a gap, so it becomes `ApiError::NonceTooHigh`
+fn unrecovered_nonce_error(nonce: i64, response: &Value) -> Option<ApiError> {
+    match nonce_mismatch(response)? {
+        NonceMismatch::Unspecified => Some(ApiError::NonceTooHigh {
+            nonce,
+            code: response["code"].as_i64(),
+            message: response["message"].as_str().unwrap_or_default().to_string(),
+        }),
+        _ => nonce_error(nonce, response),
+    }
+}
 
+/// Typed error for a `sendTx` rejection the caller must react to rather than retry
+///
+/// A closed market, maintenance, a post-only order that would cross,
+/// insufficient margin and an expired transaction are told apart by their
+/// exchange code (see [`ErrorCode`]).
+fn rejection_error(response: &Value, tx_info: &Value) -> Option<ApiError> {
+    let message = response["message"].as_str().unwrap_or_default().to_string();
+    let market_index = tx_info["MarketIndex"].as_u64().and_then(|index| u8::try_from(index).ok());
+    let code = response["code"].as_i64();
+    match ErrorCode::of(response)? {
+        ErrorCode::MarketClosed => Some(ApiError::MarketClosed { market_index, code, message }),
+        ErrorCode::InMaintenance => Some(ApiError::InMaintenance { code, message }),
+        ErrorCode::PostOnlyWouldCross => Some(ApiError::PostOnlyWouldCross { market_index, code, message }),
+        ErrorCode::InsufficientMargin => Some(ApiError::InsufficientMargin { code, message }),
+        ErrorCode::TransactionExpired => Some(ApiError::TransactionExpired { code, message }),
+        _ => None,
+    }
+}
+
+/// Lifetime of auth tokens generated for authenticated queries (10 minutes)
+const AUTH_TOKEN_EXPIRY_SECS: i64 = 600;
+
+/// Client for the Lighter REST API
+///
+/// Cloning is cheap: the HTTP connection pool, signing key and nonce cache are
+/// shared, so one client can be handed to many tokio tasks.
+///
+/// # Thread safety
+///
+/// The client is `Send + Sync`, checked at compile time, so `&LighterClient`,
+/// an `Arc` of it or a clone per worker can be used from any thread. Shared
+/// state is either immutable after `build` (signing key, configuration) or
+/// behind a lock or atomic: the nonce cache sits behind an async mutex held
+/// while a nonce is taken, so clones on different threads never receive the
+/// same nonce. Signing needs no lock. Callbacks and a custom [`Clock`] must be
+/// `Send + Sync` as well, since any thread may call them. The same holds for
+/// the `blocking` client, whose calls take turns on its one runtime thread.
+#[derive(Clone)]
 pub struct LighterClient {
     client: Client,
-    base_url: String,
-    key_manager: KeyManager,
+    // Primary base URL: the network signed for, and the prefix requests are built on
+    base_url: Arc<str>,
+    // Base URLs to fail over between, starting with the primary
+    endpoints: Arc<failover::Endpoints>,
+    // Stops requests for a while after repeated failures
+    circuit: Arc<circuit::CircuitBreaker>,
+    // Request budget reported by the exchange's rate-limit headers
+    rate_limits: Arc<rate_limit::RateLimitTracker>,
+    key_manager: Arc<KeyManager>,
+    // The same key with precomputed generator multiples, signs every transaction
+    signer: Arc<PreparedSigner>,
     account_index: i64,
     api_key_index: u8,
+    // Validity window added to the current time for each transaction's ExpiredAt
+    default_expiry: Duration,
     // Nonce cache for optimistic nonce management (like Python SDK)
     // Fetches once from API, then increments locally
     nonce_cache: Arc<AsyncMutex<NonceCache>>,
+    // Lifetime of a fetched nonce, for nonce caches of further identities
+    nonce_ttl: Option<Duration>,
+    // Endpoint the nonce is read from
+    nonce_source: NonceSource,
+    // Called with a structured record whenever an order is rejected
+    rejection_handler: Option<RejectionHandler>,
+    // Called with every HTTP request and its outcome, for debug logging
+    http_log_handler: Option<HttpLogHandler>,
+    // Dedicated signing threads; tokio's blocking pool if None
+    signing_pool: Option<Arc<SigningPool>>,
+    // Market metadata fetched so far, used to validate orders before signing
+    market_cache: Arc<RwLock<HashMap<u8, MarketInfo>>>,
+    // When the whole market cache was last replaced (unix ms), 0 if never
+    markets_refreshed_at: Arc<AtomicI64>,
+    // Whether orders are checked against market limits before signing
+    validate_orders: bool,
+    // Limits on establishing a connection (HTTP and WebSocket) and on a whole HTTP request
+    connect_timeout: Duration,
+    request_timeout: Duration,
+    // Longest a WebSocket subscription may go without a frame, if limited
+    ws_idle_timeout: Option<Duration>,
+    // WebSocket ping interval and pong deadline; None disables the heartbeat
+    ws_heartbeat: Option<(Duration, Duration)>,
+    // Called with every raw WebSocket text frame, for debugging
+    ws_frame_handler: Option<WsFrameHandler>,
+    // Whether WebSocket order books are checked for corruption after each change
+    check_book_consistency: bool,
+    // Consecutive nonce rejections and the transactions replayed after a gap
+    nonce_gaps: Arc<NonceGapTracker>,
+    // sendTx calls currently outstanding, and their optional cap
+    in_flight: Arc<InFlightLimit>,
+    // Body encoding of sendTx requests
+    send_tx_encoding: SendTxEncoding,
+    // price_protection sent with transactions that do not set their own
+    price_protection: bool,
+    // Time source for expiries, auth tokens and rejection records
+    clock: Arc<dyn Clock>,
+    // Times an expired reduce-only or post-only order is re-signed and resent
+    expiry_resigns: u32,
+    // Rolling latency of nonce fetches and sendTx requests
+    latency: Arc<latency::LatencyTracker>,
+    // Most transactions per sendTxBatch request
+    max_batch_size: usize,
 }
 
-struct NonceCache {
+// Clients are shared across tasks and threads; fail the build if a field stops being Send + Sync
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<LighterClient>();
+    assert_send_sync::<LighterClientBuilder>();
+    assert_send_sync::<MultiAccountClient>();
+    assert_send_sync::<SigningPool>();
+    assert_send_sync::<OrderPipeline<()>>();
+    assert_send_sync::<ReplaySender>();
+    #[cfg(feature = "blocking")]
+    assert_send_sync::<blocking::LighterClient>();
+};
+
+pub(crate) struct NonceCache {
     // Simple optimistic nonce management: fetch once, then increment locally
-    last_fetched_nonce: i64,  // Last nonce fetched from API (stored as nonce - 1, like Python)
-    nonce_offset: i64,        // How many nonces we've used since last fetch
+    last_fetched_nonce: Option<i64>,  // Last nonce fetched from API (stored as nonce - 1, like Python)
+    nonce_offset: i64,                // How many nonces we've used since last fetch
+    fetched_at: Option<Instant>,      // When last_fetched_nonce came from the API
+    ttl: Option<Duration>,            // Refetch once the fetched nonce is older than this
 }
 
 impl NonceCache {
-    fn new() -> Self {
+    pub(crate) fn new(ttl: Option<Duration>) -> Self {
         Self {
-            last_fetched_nonce: -1,  // -1 means not initialized
+            last_fetched_nonce: None,  // None means not initialized
             nonce_offset: 0,
-        }
-    }
-    
-    fn get_next_nonce(&mut self) -> Option<i64> {
-        if self.last_fetched_nonce == -1 {
-            None  // Not initialized, need to fetch from API
-        } else {
-            // Increment offset and return next nonce
-            // Formula: (last_fetched_nonce - 1) + offset + 1 = last_fetched_nonce + offset
-            self.nonce_offset += 1;
-            Some(self.last_fetched_nonce + self.nonce_offset)
+            fetched_at: None,
+            ttl,
         }
     }
     
     fn set_fetched_nonce(&mut self, nonce: i64) {
         // Store as nonce - 1, so first increment gives us the correct nonce
         // This matches Python's OptimisticNonceManager behavior
-        self.last_fetched_nonce = nonce - 1;
+        self.last_fetched_nonce = Some(nonce - 1);
         self.nonce_offset = 0;
+        self.fetched_at = Some(Instant::now());
+    }
+
+    fn next_nonce(&mut self) -> Option<i64> {
+        if self.is_expired() {
+            return None;
+        }
+        let base = self.last_fetched_nonce?;
+        self.nonce_offset += 1;
+        Some(base + self.nonce_offset)
+    }
+
+    /// Whether the next nonce has to come from the API
+    fn needs_fetch(&self) -> bool {
+        self.last_fetched_nonce.is_none() || self.is_expired()
+    }
+
+    fn is_expired(&self) -> bool {
+        match (self.ttl, self.fetched_at) {
+            (Some(ttl), Some(fetched_at)) => fetched_at.elapsed() >= ttl,
+            _ => false,
+        }
+    }
+
+    fn invalidate(&mut self) {
+        // Forget the local counter so the next nonce is fetched from the API
+        self.last_fetched_nonce = None;
+        self.nonce_offset = 0;
+        self.fetched_at = None;
     }
     
-    fn acknowledge_failure(&mut self) {
+    fn acknowledge_failure(&mut self, nonce: i64) {
         // Decrement offset on failure to allow retry with same nonce
-        // This matches Python's OptimisticNonceManager behavior
-        if self.nonce_offset > 0 {
+        // This matches Python's OptimisticNonceManager behavior. Only the most
+        // recently issued nonce is handed back, otherwise a concurrent task that
+        // already took a later nonce would see it issued twice.
+        if self.nonce_offset > 0 && self.last_fetched_nonce.map(|base| base + self.nonce_offset) == Some(nonce) {
             self.nonce_offset -= 1;
         }
     }
-    
-    fn clear(&mut self) {
-        self.last_fetched_nonce = -1;
-        self.nonce_offset = 0;
+}
+
+/// Caps the number of `sendTx` requests in flight at once
+#[derive(Debug)]
+pub(crate) struct InFlightLimit {
+    permits: Option<Semaphore>,      // None = unlimited
+    count: AtomicUsize,
+}
+
+impl InFlightLimit {
+    pub(crate) fn new(max_in_flight: Option<usize>) -> Self {
+        Self {
+            permits: max_in_flight.map(Semaphore::new),
+            count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Wait for a free slot; the slot is held until the guard is dropped
+    async fn acquire(&self) -> InFlightGuard<'_> {
+        let permit = match &self.permits {
+            Some(permits) => Some(permits.acquire().await.expect("in-flight semaphore is never closed")),
+            None => None,
+        };
+        self.count.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard { limit: self, _permit: permit }
+    }
+}
+
+struct InFlightGuard<'a> {
+    limit: &'a InFlightLimit,
+    _permit: Option<SemaphorePermit<'a>>,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.limit.count.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Detects a nonce gap from consecutive nonce rejections
+///
+/// A transaction dropped after taking a nonce leaves the local counter ahead of
+/// the server, so every later submission is rejected for its nonce. Once
+/// `threshold` such rejections arrive in a row the gap is assumed and rejected
+/// transactions are replayed with a refetched nonce.
+#[derive(Debug)]
+pub(crate) struct NonceGapTracker {
+    threshold: u32,                  // 0 disables recovery
+    consecutive_rejections: AtomicU32,
+    recovered: AtomicU64,            // Transactions accepted after a replay
+}
+
+impl NonceGapTracker {
+    pub(crate) fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            consecutive_rejections: AtomicU32::new(0),
+            recovered: AtomicU64::new(0),
+        }
+    }
+
+    /// Count a nonce rejection; true once the streak reaches the threshold
+    fn record_rejection(&self) -> bool {
+        let streak = self.consecutive_rejections.fetch_add(1, Ordering::SeqCst) + 1;
+        self.threshold > 0 && streak >= self.threshold
+    }
+
+    /// Whether nonce recovery is on (a non-zero threshold)
+    fn enabled(&self) -> bool {
+        self.threshold > 0
+    }
+
+    fn record_success(&self) {
+        self.consecutive_rejections.store(0, Ordering::SeqCst);
+    }
+
+    fn record_recovery(&self) {
+        self.recovered.fetch_add(1, Ordering::SeqCst);
+        self.record_success();
+    }
+}
+
+// Hand-written so the signing key and the HTTP client internals stay out of logs
+impl std::fmt::Debug for LighterClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LighterClient")
+            .field("base_url", &self.base_url)
+            .field("key_manager", &self.key_manager)
+            .field("account_index", &self.account_index)
+            .field("api_key_index", &self.api_key_index)
+            .field("default_expiry", &self.default_expiry)
+            .field("validate_orders", &self.validate_orders)
+            .finish_non_exhaustive()
     }
 }
 
@@ -105,20 +696,49 @@ impl LighterClient {
         account_index: i64,
         api_key_index: u8,
     ) -> Result<Self> {
-        let key_manager = KeyManager::from_hex(private_key_hex)?;
-        let client = Client::new();
-        
-        Ok(Self {
-            client,
-            base_url,
-            key_manager,
-            account_index,
-            api_key_index,
-            nonce_cache: Arc::new(AsyncMutex::new(NonceCache::new())),
-        })
+        Self::builder(base_url, private_key_hex, account_index, api_key_index).build()
+    }
+
+    /// Create a client and warm up its connection; see [`Self::warm_up`]
+    ///
+    /// Unlike [`Self::new`] this makes a request, so it fails if the API is unreachable.
+    pub async fn connect(
+        base_url: String,
+        private_key_hex: &str,
+        account_index: i64,
+        api_key_index: u8,
+    ) -> Result<Self> {
+        Self::builder(base_url, private_key_hex, account_index, api_key_index).connect().await
+    }
+
+    /// Create a builder for configuring optional client settings
+    pub fn builder(
+        base_url: String,
+        private_key_hex: &str,
+        account_index: i64,
+        api_key_index: u8,
+    ) -> LighterClientBuilder {
+        LighterClientBuilder::new(base_url, private_key_hex, account_index, api_key_index)
+    }
+
+    /// Validity window applied to `ExpiredAt` on every transaction
+    pub fn default_expiry(&self) -> Duration {
+        self.default_expiry
+    }
+
+    /// Price protection sent with orders that do not set their own
+    ///
+    /// See `LighterClientBuilder::price_protection`.
+    pub fn price_protection(&self) -> bool {
+        self.price_protection
+    }
+
+    /// Compute `ExpiredAt` (unix ms) for a transaction signed now
+    fn expired_at(&self) -> i64 {
+        self.clock.now_ms() + self.default_expiry.as_millis() as i64
     }
     
-    pub async fn create_order(&self, order: CreateOrderRequest) -> Result<Value> {
+    pub async fn create_order(&self, order: CreateOrderRequest) -> Result<SendTxResponse> {
         self.create_order_with_nonce(order, None).await
     }
     
@@ -126,10 +746,36 @@ impl LighterClient {
     /// If nonce is Some(n), uses that nonce (or -1 to fetch from API)
     /// If nonce is None, uses optimistic nonce management
     /// Automatically retries on invalid signature errors (21120) since same signature succeeds on retry
-    pub async fn create_order_with_nonce(&self, order: CreateOrderRequest, nonce: Option<i64>) -> Result<Value> {
+    /// Orders rejected as expired are rebuilt and re-signed if enabled with
+    /// `LighterClientBuilder::resign_on_expiry`
+    /// Rejections are reported to the handler set with `LighterClientBuilder::on_rejection`
+    pub async fn create_order_with_nonce(&self, order: CreateOrderRequest, nonce: Option<i64>) -> Result<SendTxResponse> {
+        let mut outcome = self.create_order_with_retries(&order, nonce).await;
+        // The rejected nonce was handed back, so the next attempt signs a new
+        // transaction with a fresh ExpiredAt and the next free nonce
+        let mut resigns = 0;
+        while resigns < self.expiry_resigns
+            && order.is_idempotent()
+            && matches!(outcome, Err(ApiError::TransactionExpired { .. }))
+        {
+            resigns += 1;
+            outcome = self.create_order_with_retries(&order, nonce).await;
+        }
+        self.report_rejection(&order, &outcome);
+        outcome
+    }
+
+    async fn create_order_with_retries(&self, order: &CreateOrderRequest, nonce: Option<i64>) -> Result<SendTxResponse> {
         const MAX_RETRIES: u32 = 5; // Increased from 3 to 5 for better success rate
         const RETRY_DELAY_MS: u64 = 500; // Start with 500ms delay
         
+        if self.validate_orders {
+            self.check_order_params(order.order_book_index, order.price, order.base_amount).await?;
+        }
+
+        let tx = order.to_tx_at(self.clock.now_ms())?;
+        let price_protection = order.price_protection.unwrap_or(self.price_protection);
+
         // Fetch nonce once before retry loop - we'll reuse the same nonce for retries
         let nonce = self.get_nonce_or_use(nonce).await?;
         
@@ -141,33 +787,35 @@ impl LighterClient {
                 tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
             }
             
-            match self.create_order_internal(&order, Some(nonce)).await {
+            match self.create_order_internal(tx.clone(), Some(nonce), price_protection).await {
                 Ok(response) => {
-                    let code = response["code"].as_i64().unwrap_or_default();
-                    if code == 200 {
-                        return Ok(response);
-                    } else if code == 21120 && attempt < MAX_RETRIES {
+                    if response::is_accepted(&response) {
+                        self.nonce_gaps.record_success();
+                        return response::send_tx_result(response, None);
+                    } else if response["code"].as_i64() == Some(21120) && attempt < MAX_RETRIES {
                         // Invalid signature - retry with same nonce
                         last_error = Some(ApiError::Api(format!("Invalid signature (code 21120) after {} attempts", attempt + 1)));
                         continue;
                     } else {
                         // Other error or max retries reached
-                        {
-                            let mut cache = self.nonce_cache.lock().await;
-                            cache.acknowledge_failure();
-                        }
-                        return Ok(response);
+                        return self.handle_rejection(nonce, tx, response, price_protection).await;
                     }
                 }
                 Err(e) => {
-                    if attempt < MAX_RETRIES {
+                    let rejected = matches!(
+                        e,
+                        ApiError::MarketClosed { .. }
+                            | ApiError::InMaintenance { .. }
+                            | ApiError::PostOnlyWouldCross { .. }
+                            | ApiError::InsufficientMargin { .. }
+                            | ApiError::TransactionExpired { .. }
+                            | ApiError::CircuitOpen { .. }
+                    );
+                    if attempt < MAX_RETRIES && !rejected {
                         last_error = Some(e);
                         continue;
                     } else {
-                        {
-                            let mut cache = self.nonce_cache.lock().await;
-                            cache.acknowledge_failure();
-                        }
+                        self.release_nonce(nonce, None).await;
                         return Err(e);
                     }
                 }
@@ -175,67 +823,18 @@ impl LighterClient {
         }
         
         // If we get here, all retries failed
-        {
-            let mut cache = self.nonce_cache.lock().await;
-            cache.acknowledge_failure();
-        }
+        self.release_nonce(nonce, None).await;
         Err(last_error.unwrap_or_else(|| ApiError::Api("Failed after all retries".to_string())))
     }
     
     /// Internal method to create order (without retry logic)
     /// This is called by create_order_with_nonce for each retry attempt
     /// Uses the provided nonce directly (no fetching)
-    async fn create_order_internal(&self, order: &CreateOrderRequest, nonce: Option<i64>) -> Result<Value> {
+    async fn create_order_internal(&self, mut tx: CreateOrderTx, nonce: Option<i64>, price_protection: bool) -> Result<Value> {
         let nonce = nonce.expect("Nonce should be provided to create_order_internal");
         
-        // Create transaction info with expiry time
-        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
-        let expired_at = now + 599_000; // 10 minutes - 1 second (in milliseconds)
-        
-        let tx_info = json!({
-            "AccountIndex": self.account_index,
-            "ApiKeyIndex": self.api_key_index,
-            "MarketIndex": order.order_book_index,
-            "ClientOrderIndex": order.client_order_index,
-            "BaseAmount": order.base_amount,
-            "Price": order.price,
-            "IsAsk": if order.is_ask { 1 } else { 0 },
-            "Type": order.order_type,
-            "TimeInForce": order.time_in_force,
-            "ReduceOnly": if order.reduce_only { 1 } else { 0 },
-            "TriggerPrice": order.trigger_price,
-            "OrderExpiry": 0,
-            "ExpiredAt": expired_at,
-            "Nonce": nonce,
-            "Sig": ""
-        });
-        
-        let tx_json = serde_json::to_string(&tx_info)?;
-        let signature = self.sign_transaction(&tx_json)?;
-        
-        let mut final_tx_info = tx_info;
-        let sig_base64 = base64::engine::general_purpose::STANDARD.encode(&signature);
-        final_tx_info["Sig"] = json!(sig_base64);
-        
-        let final_tx_json = serde_json::to_string(&final_tx_info)?;
-        
-        let form_data = [
-            ("tx_type", "14"), // CREATE_ORDER
-            ("tx_info", &final_tx_json),
-            ("price_protection", "true"),
-        ];
-        
-        let response = self
-            .client
-            .post(&format!("{}/api/v1/sendTx", self.base_url))
-            .form(&form_data)
-            .send()
-            .await?;
-        
-        let response_text = response.text().await?;
-        let response_json: Value = serde_json::from_str(&response_text)?;
-        
-        Ok(response_json)
+        let tx_info = self.build_and_sign(nonce, &mut tx)?;
+        self.send_tx_with(CreateOrderTx::TX_TYPE, &tx_info, price_protection).await
     }
 
     pub async fn create_market_order(
@@ -244,14 +843,14 @@ impl LighterClient {
         client_order_index: u64,
         base_amount: i64,
         avg_execution_price: i64,
-        is_ask: bool,
-    ) -> Result<Value> {
+        side: Side,
+    ) -> Result<SendTxResponse> {
         self.create_market_order_with_nonce(
             order_book_index,
             client_order_index,
             base_amount,
             avg_execution_price,
-            is_ask,
+            side,
             None,
         ).await
     }
@@ -263,154 +862,68 @@ impl LighterClient {
         client_order_index: u64,
         base_amount: i64,
         avg_execution_price: i64,
-        is_ask: bool,
+        side: Side,
         nonce: Option<i64>,
-    ) -> Result<Value> {
-        eprintln!("[DEBUG create_market_order] Starting order creation:");
-        eprintln!("  client_order_index: {}", client_order_index);
-        eprintln!("  base_amount: {}", base_amount);
-        eprintln!("  price: {}", avg_execution_price);
-        
+    ) -> Result<SendTxResponse> {
         let order = CreateOrderRequest {
             account_index: self.account_index,
-            order_book_index,
             client_order_index,
-            base_amount,
-            price: avg_execution_price,
-            is_ask,
-            order_type: 1, // MarketOrder
-            time_in_force: 0, // ImmediateOrCancel
-            reduce_only: false,
-            trigger_price: 0,
+            ..CreateOrderRequest::market(order_book_index, side, base_amount, avg_execution_price)
         };
         self.create_order_with_nonce(order, nonce).await
     }
 
-    pub async fn cancel_order(&self, order_book_index: u8, order_index: i64) -> Result<Value> {
+    pub async fn cancel_order(&self, order_book_index: u8, order_index: i64) -> Result<SendTxResponse> {
         let nonce = self.get_next_nonce_from_cache().await?;
-        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
-        let expired_at = now + 599_000;
-
-        let tx_info = json!({
-            "AccountIndex": self.account_index,
-            "ApiKeyIndex": self.api_key_index,
-            "MarketIndex": order_book_index,
-            "Index": order_index,
-            "ExpiredAt": expired_at,
-            "Nonce": nonce,
-            "Sig": ""
-        });
-
-        let tx_json = serde_json::to_string(&tx_info)?;
-        let signature = self.sign_transaction_with_type(&tx_json, 15)?; // TX_TYPE_CANCEL_ORDER
-
-        let mut final_tx_info = tx_info;
-        final_tx_info["Sig"] = json!(base64::engine::general_purpose::STANDARD.encode(&signature));
-
-        let form_data = [
-            ("tx_type", "15"), // CANCEL_ORDER
-            ("tx_info", &serde_json::to_string(&final_tx_info)?),
-            ("price_protection", "true"),
-        ];
-
-        let response = self
-            .client
-            .post(&format!("{}/api/v1/sendTx", self.base_url))
-            .form(&form_data)
-            .send()
-            .await?;
-
-        let response_text = response.text().await?;
-        let response_json: Value = serde_json::from_str(&response_text)?;
-
-        Ok(response_json)
+        self.submit_with_nonce(nonce, CancelOrderTx {
+            header: TxHeader::default(),
+            market_index: order_book_index,
+            index: order_index,
+        }).await
     }
 
-    pub async fn cancel_all_orders(&self, time_in_force: u8, time: i64) -> Result<Value> {
+    pub async fn cancel_all_orders(&self, time_in_force: u8, time: i64) -> Result<SendTxResponse> {
         let nonce = self.get_next_nonce_from_cache().await?;
-        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
-        let expired_at = now + 599_000;
-
-        let tx_info = json!({
-            "AccountIndex": self.account_index,
-            "ApiKeyIndex": self.api_key_index,
-            "TimeInForce": time_in_force,
-            "Time": time,
-            "ExpiredAt": expired_at,
-            "Nonce": nonce,
-            "Sig": ""
-        });
-
-        let tx_json = serde_json::to_string(&tx_info)?;
-        let signature = self.sign_transaction_with_type(&tx_json, 16)?; // TX_TYPE_CANCEL_ALL_ORDERS
-
-        let mut final_tx_info = tx_info;
-        final_tx_info["Sig"] = json!(base64::engine::general_purpose::STANDARD.encode(&signature));
-
-        let form_data = [
-            ("tx_type", "16"), // CANCEL_ALL_ORDERS
-            ("tx_info", &serde_json::to_string(&final_tx_info)?),
-            ("price_protection", "true"),
-        ];
-
-        let response = self
-            .client
-            .post(&format!("{}/api/v1/sendTx", self.base_url))
-            .form(&form_data)
-            .send()
-            .await?;
-
-        let response_text = response.text().await?;
-        let response_json: Value = serde_json::from_str(&response_text)?;
-
-        Ok(response_json)
+        self.submit_with_nonce(nonce, CancelAllOrdersTx {
+            header: TxHeader::default(),
+            time_in_force,
+            time,
+        }).await
     }
 
-    pub async fn change_api_key(&self, new_public_key: &[u8; 40]) -> Result<Value> {
+    pub async fn change_api_key(&self, new_public_key: &[u8; 40]) -> Result<SendTxResponse> {
         let nonce = self.get_next_nonce_from_cache().await?;
-        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
-        let expired_at = now + 599_000;
-
-        let tx_info = json!({
-            "AccountIndex": self.account_index,
-            "ApiKeyIndex": self.api_key_index,
-            "PubKey": hex::encode(new_public_key),
-            "ExpiredAt": expired_at,
-            "Nonce": nonce,
-            "Sig": ""
-        });
-
-        let tx_json = serde_json::to_string(&tx_info)?;
-        let signature = self.sign_transaction_with_type(&tx_json, 8)?; // TX_TYPE_CHANGE_PUB_KEY
-
-        let mut final_tx_info = tx_info;
-        final_tx_info["Sig"] = json!(base64::engine::general_purpose::STANDARD.encode(&signature));
-
-        let form_data = [
-            ("tx_type", "8"), // CHANGE_PUB_KEY
-            ("tx_info", &serde_json::to_string(&final_tx_info)?),
-            ("price_protection", "true"),
-        ];
-
-        let response = self
-            .client
-            .post(&format!("{}/api/v1/sendTx", self.base_url))
-            .form(&form_data)
-            .send()
-            .await?;
-
-        let response_text = response.text().await?;
-        let response_json: Value = serde_json::from_str(&response_text)?;
+        self.submit_with_nonce(nonce, ChangePubKeyTx {
+            header: TxHeader::default(),
+            pub_key: hex::encode(new_public_key),
+        }).await
+    }
 
-        Ok(response_json)
+    /// Deposit USDC collateral into the account
+    ///
+    /// Deposits originate on L1: there is no L2 deposit transaction an API key can
+    /// sign, so this always returns `ApiError::Unsupported` describing the L1 flow.
+    /// It exists so the deposit path fails loudly instead of silently doing nothing.
+    ///
+    /// To fund an account:
+    /// 1. From the account's L1 wallet, approve USDC for Lighter's L1 contract
+    /// 2. Call the contract's deposit function with the L1 address and amount
+    /// 3. Wait for the L1 transaction to be picked up by the sequencer
+    /// 4. Confirm the new collateral via the account query endpoint
+    pub async fn deposit(&self, usdc_amount: i64) -> Result<SendTxResponse> {
+        Err(ApiError::Unsupported(format!(
+            "deposit of {} USDC for account {}: deposits are made on L1 by the account's \
+             wallet through Lighter's L1 contract (approve USDC, then deposit); \
+             they cannot be signed with an API key",
+            usdc_amount, self.account_index
+        )))
     }
 
     pub fn create_auth_token(&self, expiry_seconds: i64) -> Result<String> {
-        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
-        let deadline = now + expiry_seconds;
+        let deadline = self.clock.now_ms() / 1000 + expiry_seconds;
         self.key_manager
             .create_auth_token(deadline, self.account_index, self.api_key_index)
-            .map_err(|e| ApiError::Signer(e))
+            .map_err(ApiError::Signer)
     }
 
     /// Update leverage for a market
@@ -418,76 +931,441 @@ impl LighterClient {
     /// # Arguments
     /// * `market_index` - Market index (0-based)
     /// * `leverage` - Leverage value (e.g., 3 for 3x leverage)
-    /// * `margin_mode` - Margin mode: 0 for CROSS_MARGIN, 1 for ISOLATED_MARGIN
+    /// * `margin_mode` - Cross or isolated margin
     /// 
     /// # Returns
-    /// JSON response from the API
+    /// The accepted `sendTx` response; rejections are errors
     pub async fn update_leverage(
         &self,
         market_index: u8,
         leverage: u16,
-        margin_mode: u8,
-    ) -> Result<Value> {
+        margin_mode: MarginMode,
+    ) -> Result<SendTxResponse> {
         let nonce = self.get_next_nonce_from_cache().await?;
-        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as i64;
-        let expired_at = now + 599_000;
 
         // Calculate InitialMarginFraction: IMF = 10,000 / leverage
         // Example: leverage 3x = 10,000 / 3 = 3333
         let initial_margin_fraction = (10_000u32 / leverage as u32) as u16;
 
-        let tx_info = json!({
-            "AccountIndex": self.account_index,
-            "ApiKeyIndex": self.api_key_index,
-            "MarketIndex": market_index,
-            "InitialMarginFraction": initial_margin_fraction,
-            "MarginMode": margin_mode,
-            "ExpiredAt": expired_at,
-            "Nonce": nonce,
-            "Sig": ""
-        });
-
-        let tx_json = serde_json::to_string(&tx_info)?;
-        let signature = self.sign_transaction_with_type(&tx_json, 20)?; // TX_TYPE_UPDATE_LEVERAGE
-
-        let mut final_tx_info = tx_info;
-        final_tx_info["Sig"] = json!(base64::engine::general_purpose::STANDARD.encode(&signature));
-
-        let form_data = [
-            ("tx_type", "20"), // UPDATE_LEVERAGE
-            ("tx_info", &serde_json::to_string(&final_tx_info)?),
-            ("price_protection", "true"),
-        ];
-
-        let response = self
-            .client
-            .post(&format!("{}/api/v1/sendTx", self.base_url))
-            .form(&form_data)
-            .send()
-            .await?;
-
-        let response_text = response.text().await?;
-        let response_json: Value = serde_json::from_str(&response_text)?;
-
-        Ok(response_json)
+        self.submit_with_nonce(nonce, UpdateLeverageTx {
+            header: TxHeader::default(),
+            market_index,
+            initial_margin_fraction,
+            margin_mode: margin_mode.as_u8(),
+        }).await
+    }
+
+    /// Add collateral to, or remove it from, an isolated-margin position
+    ///
+    /// This moves margin between the account's free collateral and one isolated
+    /// position; it is not a transfer between accounts. With `check_liquidation`,
+    /// a removal is first checked against the account's current position and the
+    /// market's maintenance margin, and refused with
+    /// `ApiError::InvalidOrderParams` if the position would be left at or below it.
+    ///
+    /// # Arguments
+    /// * `market_index` - Market of the isolated position
+    /// * `usdc_amount` - Amount of USDC to move (6 decimals, e.g. 10_000_000 = 10 USDC)
+    /// * `direction` - Add to or remove from the position's margin
+    /// * `check_liquidation` - Verify a removal leaves the position above maintenance margin
+    ///
+    /// # Returns
+    /// The accepted `sendTx` response; rejections are errors
+    pub async fn update_margin(
+        &self,
+        market_index: u8,
+        usdc_amount: i64,
+        direction: MarginDirection,
+        check_liquidation: bool,
+    ) -> Result<SendTxResponse> {
+        if usdc_amount <= 0 {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "margin amount must be positive, got {}",
+                usdc_amount
+            )));
+        }
+        if check_liquidation && direction == MarginDirection::Remove {
+            self.check_margin_removal(market_index, usdc_amount).await?;
+        }
+
+        let nonce = self.get_next_nonce_from_cache().await?;
+        self.submit_with_nonce(nonce, UpdateMarginTx {
+            header: TxHeader::default(),
+            market_index,
+            usdc_amount,
+            direction: direction.as_u8(),
+        }).await
+    }
+
+    /// Replace a resting order's price and size in place
+    ///
+    /// Uses the protocol's modify transaction (tx_type 17), so the change is
+    /// atomic: the order is never off the book between the cancel and the new
+    /// placement, and there is no window where both old and new could fill. The
+    /// order keeps its client order index (returned for convenience) but loses
+    /// its time priority; see [`Self::amend_order`] for size reductions that
+    /// keep it. Fails with `ApiError::Api` if the exchange rejects the
+    /// modification, e.g. because the order has already filled or been canceled.
+    ///
+    /// # Arguments
+    /// * `market_index` - Market the order rests on
+    /// * `client_order_index` - Client order index the order was placed with
+    /// * `new_price` - New limit price (integer, market price decimals)
+    /// * `new_size` - New base amount (integer, market size decimals)
+    pub async fn replace_order(
+        &self,
+        market_index: u8,
+        client_order_index: u64,
+        new_price: i64,
+        new_size: i64,
+    ) -> Result<u64> {
+        if self.validate_orders {
+            self.check_order_params(market_index, new_price, new_size).await?;
+        }
+
+        let nonce = self.get_next_nonce_from_cache().await?;
+        self.submit_with_nonce(nonce, ModifyOrderTx {
+            header: TxHeader::default(),
+            market_index,
+            index: client_order_index as i64,
+            base_amount: new_size,
+            price: new_price,
+            trigger_price: 0,
+        }).await?;
+        Ok(client_order_index)
+    }
+    
+    /// Sign and submit a transaction that took `nonce` from the cache, handing the
+    /// nonce back if the exchange did not accept it so the sequence stays gapless
+    ///
+    /// Every rejection is an error, mapped by [`response::send_tx_error`].
+    async fn submit_with_nonce<T: Transaction>(&self, nonce: i64, mut tx: T) -> Result<SendTxResponse> {
+        let result = match self.build_and_sign(nonce, &mut tx) {
+            Ok(tx_info) => self.send_tx(T::TX_TYPE, &tx_info).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(response) if response::is_accepted(&response) => {
+                self.nonce_gaps.record_success();
+                response::send_tx_result(response, None)
+            }
+            Ok(response) => self.handle_rejection(nonce, tx, response, self.price_protection).await,
+            Err(e) => {
+                self.release_nonce(nonce, None).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Release the nonce of a rejected transaction, replaying it if the nonce was off
+    ///
+    /// The two nonce rejections call for opposite fixes. A nonce that is too low
+    /// was already consumed (by another process on the same key, or by a
+    /// transaction thought lost), so the transaction is re-signed with the
+    /// exchange's next nonce right away. Orders are the exception: the lost
+    /// transaction may have been this very order, and re-signing it could place
+    /// it twice, so for them the cache only moves on. A nonce that is too high
+    /// means an earlier nonce was never consumed; since a lower nonce still in
+    /// flight may land yet, the gap is only assumed after `nonce_gap_threshold`
+    /// consecutive such rejections, and then filled by re-signing the
+    /// transaction with the exchange's next nonce. Rejections that blame the
+    /// nonce without saying which way are treated like a gap. The next nonce is
+    /// refetched.
+    ///
+    /// Only the transaction at hand is replayed; the client keeps no record of
+    /// the ones rejected before it. When a gap is assumed on the
+    /// `nonce_gap_threshold`-th rejection, the earlier ones have already failed
+    /// with `ApiError::NonceTooHigh`, and their callers must resubmit them.
+    ///
+    /// The replay's response is returned in place of the rejection. A nonce
+    /// rejection that is not recovered fails with `ApiError::NonceTooLow` or
+    /// `ApiError::NonceTooHigh`; other rejections fail with the error
+    /// [`response::send_tx_error`] maps them to.
+    async fn handle_rejection<T: Transaction>(
+        &self,
+        nonce: i64,
+        mut tx: T,
+        response: Value,
+        price_protection: bool,
+    ) -> Result<SendTxResponse> {
+        self.release_nonce(nonce, Some(&response)).await;
+        let replay = match nonce_mismatch(&response) {
+            None => false,
+            Some(NonceMismatch::TooLow) => self.nonce_gaps.enabled() && T::TX_TYPE != CreateOrderTx::TX_TYPE,
+            Some(NonceMismatch::TooHigh | NonceMismatch::Unspecified) => self.nonce_gaps.record_rejection(),
+        };
+        if !replay {
+            return Err(unrecovered_error(nonce, &response));
+        }
+
+        let nonce = self.get_next_nonce_from_cache().await?;
+        let result = match self.build_and_sign(nonce, &mut tx) {
+            Ok(tx_info) => self.send_tx_with(T::TX_TYPE, &tx_info, price_protection).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(response) if response::is_accepted(&response) => {
+                self.nonce_gaps.record_recovery();
+                response::send_tx_result(response, None)
+            }
+            Ok(response) => {
+                self.release_nonce(nonce, Some(&response)).await;
+                Err(unrecovered_error(nonce, &response))
+            }
+            Err(e) => {
+                self.release_nonce(nonce, None).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Number of transactions accepted after being replayed to close a nonce gap
+    pub fn recovered_transactions(&self) -> u64 {
+        self.nonce_gaps.recovered.load(Ordering::SeqCst)
+    }
+
+    /// Number of `sendTx` requests currently awaiting a response
+    ///
+    /// Submissions still waiting for a slot under
+    /// `LighterClientBuilder::max_in_flight` are not counted.
+    pub fn in_flight_requests(&self) -> usize {
+        self.in_flight.count.load(Ordering::SeqCst)
+    }
+
+    /// Return a nonce that was not consumed by the exchange
+    ///
+    /// A rejection that blames the nonce means the local counter has drifted: the
+    /// cache is dropped and the next transaction refetches from the API.
+    async fn release_nonce(&self, nonce: i64, response: Option<&Value>) {
+        let mut cache = self.nonce_cache.lock().await;
+        if response.is_some_and(is_nonce_rejection) {
+            cache.invalidate();
+        } else {
+            cache.acknowledge_failure(nonce);
+        }
+    }
+
+    /// Header for a transaction signed now by this client with `nonce`
+    pub fn tx_header(&self, nonce: i64) -> Result<TxHeader> {
+        Ok(TxHeader {
+            account_index: self.account_index,
+            api_key_index: self.api_key_index,
+            expired_at: self.expired_at(),
+            nonce,
+        })
+    }
+
+    /// Complete and sign a transaction's `tx_info`
+    ///
+    /// The account, API key, nonce and expiry are filled into the header here so
+    /// every transaction type shares one header. The signing message is built
+    /// from `tx::body_fields`, which fixes the hashed field order per type, and
+    /// the base64 signature is stored in `Sig`.
+    fn build_and_sign<T: Transaction>(&self, nonce: i64, tx: &mut T) -> Result<Value> {
+        self.build_and_sign_with_signature(nonce, tx).map(|(tx_info, _)| tx_info)
+    }
+
+    /// [`Self::build_and_sign`], also returning the signature stored in `Sig`
+    fn build_and_sign_with_signature<T: Transaction>(&self, nonce: i64, tx: &mut T) -> Result<(Value, Signature)> {
+        *tx.header_mut() = self.tx_header(nonce)?;
+        let mut tx_info = serde_json::to_value(&*tx)?;
+
+        let hash = tx::message_hash(tx::chain_id(&self.base_url), T::TX_TYPE, &tx_info)?;
+        let signature = Signature::from(self.signer.sign(&hash)?);
+        tx_info["Sig"] = json!(signature.to_base64());
+        Ok((tx_info, signature))
+    }
+
+    /// Submit a signed `tx_info` to `sendTx`
+    ///
+    /// Rejections because the market is closed, the exchange is in maintenance,
+    /// a post-only order would cross, margin is short or the transaction expired
+    /// become `ApiError::MarketClosed`, `ApiError::InMaintenance`,
+    /// `ApiError::PostOnlyWouldCross`, `ApiError::InsufficientMargin` or
+    /// `ApiError::TransactionExpired`; every other response is returned as is,
+    /// for the caller to retry or replay before turning it into an error. Sent
+    /// with the client's default price protection.
+    async fn send_tx(&self, tx_type: u32, tx_info: &Value) -> Result<Value> {
+        self.send_tx_with(tx_type, tx_info, self.price_protection).await
+    }
+
+    /// [`Self::send_tx`] with an explicit `price_protection` flag
+    async fn send_tx_with(&self, tx_type: u32, tx_info: &Value, price_protection: bool) -> Result<Value> {
+        signature::check_signed(tx_type, tx_info)?;
+        let request = self.client.post(format!("{}/api/v1/sendTx", self.base_url));
+        let request = encoding::send_tx_body(request, self.send_tx_encoding, tx_type, tx_info, price_protection)?;
+        let _slot = self.in_flight.acquire().await;
+        let started = Instant::now();
+        let response = self.send_json(request).await?;
+        self.latency.record_send_tx(started.elapsed());
+        if !response::is_accepted(&response) {
+            if let Some(error) = rejection_error(&response, tx_info) {
+                if let (ApiError::MarketClosed { market_index: Some(index), .. }, Ok(mut cache)) =
+                    (&error, self.market_cache.write())
+                {
+                    // Refetch the status before the next order to this market
+                    cache.remove(index);
+                }
+                return Err(error);
+            }
+        }
+        Ok(response)
     }
     
     /// Fetch a single nonce from API
     async fn fetch_nonce_from_api(&self) -> Result<i64> {
-        let url = format!(
-            "{}/api/v1/nextNonce?account_index={}&api_key_index={}",
-            self.base_url, self.account_index, self.api_key_index
-        );
-        
-        let response = self.client.get(&url).send().await?;
-        let response_text = response.text().await?;
-        let response_json: Value = serde_json::from_str(&response_text)?;
-        
-        let nonce = response_json["nonce"]
-            .as_i64()
-            .ok_or_else(|| ApiError::Api("Invalid nonce response format".to_string()))?;
-        
-        Ok(nonce)
+        let started = Instant::now();
+        let nonce = match self.nonce_source {
+            NonceSource::NextNonce => {
+                let url = format!(
+                    "{}/api/v1/nextNonce?account_index={}&api_key_index={}",
+                    self.base_url, self.account_index, self.api_key_index
+                );
+                let response_json = self.send_json(self.client.get(&url)).await?;
+                response_json["nonce"].as_i64()
+            }
+            NonceSource::Account => {
+                let account = self.fetch_account(self.account_index).await?;
+                nonce_source::account_nonce(&account, self.api_key_index)
+            }
+        };
+        self.latency.record_nonce_fetch(started.elapsed());
+
+        nonce.ok_or_else(|| ApiError::Api("Invalid nonce response format".to_string()))
+    }
+
+    /// Reseed an empty or expired nonce counter from an account response fetched for another purpose
+    ///
+    /// Only with [`NonceSource::Account`] and for this client's own account.
+    /// Skipped while another task holds the counter, since that task is
+    /// already taking or fetching a nonce.
+    pub(crate) fn piggyback_nonce(&self, account_index: i64, account: &Value) {
+        if self.nonce_source != NonceSource::Account || account_index != self.account_index {
+            return;
+        }
+        let Some(nonce) = nonce_source::account_nonce(account, self.api_key_index) else {
+            return;
+        };
+        if let Ok(mut cache) = self.nonce_cache.try_lock() {
+            if cache.needs_fetch() {
+                cache.set_fetched_nonce(nonce);
+            }
+        }
+    }
+    
+    /// Send a GET request to an API endpoint and parse the JSON body
+    async fn get_json(&self, path: &str, query: &[(&str, String)]) -> Result<Value> {
+        let request = self.client.get(format!("{}{}", self.base_url, path)).query(query);
+        self.send_json(request).await
+    }
+
+    /// Send a request and parse the JSON body
+    ///
+    /// Timeouts are reported by phase: `ConnectTimeout` if no connection could be
+    /// established, `RequestTimeout` if the response did not arrive in time.
+    /// Transport errors and 5xx responses count as failures for the circuit
+    /// breaker, which fails the request with `CircuitOpen` while it is open.
+    /// While the exchange reports an exhausted rate-limit budget the request
+    /// waits for the reported reset (at most the request timeout).
+    async fn send_json(&self, request: reqwest::RequestBuilder) -> Result<Value> {
+        if let Some(wait) = self.rate_limits.wait() {
+            tokio::time::sleep(wait.min(self.request_timeout)).await;
+        }
+        let permit = self.circuit.admit()?;
+        let response = self.send_text(request).await;
+        match &response {
+            Ok((server_error, _)) => permit.record(!server_error),
+            Err(ApiError::Http(_) | ApiError::ConnectTimeout(_) | ApiError::RequestTimeout(_)) => permit.record(false),
+            Err(_) => {}
+        }
+        Ok(serde_json::from_str(&response?.1)?)
+    }
+
+    /// Send a request; the body, and whether the status was a 5xx
+    ///
+    /// The request and its outcome go to the `on_http_exchange` callback, if set.
+    async fn send_text(&self, request: reqwest::RequestBuilder) -> Result<(bool, String)> {
+        let Some(handler) = &self.http_log_handler else {
+            return self.send_http(request).await.map(|(status, text)| (status.is_server_error(), text));
+        };
+        let (client, request) = request.build_split();
+        let request = request?;
+        let mut exchange = HttpExchange::new(&request);
+        let started = Instant::now();
+        let outcome = self.send_http(reqwest::RequestBuilder::from_parts(client, request)).await;
+        exchange.finish(&outcome, started.elapsed());
+        handler(&exchange);
+        outcome.map(|(status, text)| (status.is_server_error(), text))
+    }
+
+    /// Send a request, failing over between endpoints; the status and body
+    ///
+    /// A request is resent on the next endpoint only if the connection could not
+    /// be established. A 5xx is returned as is: a query counts toward
+    /// `failover_threshold`, and a transaction (a POST) moves the next request
+    /// to another endpoint at once.
+    async fn send_http(&self, request: reqwest::RequestBuilder) -> Result<(reqwest::StatusCode, String)> {
+        let timeout_error = |e: reqwest::Error| match (e.is_timeout(), e.is_connect()) {
+            (true, true) => ApiError::ConnectTimeout(self.connect_timeout),
+            (true, false) => ApiError::RequestTimeout(self.request_timeout),
+            _ => ApiError::Http(e),
+        };
+        if self.endpoints.len() == 1 {
+            let response = request.send().await.map_err(timeout_error)?;
+            self.rate_limits.observe(response.status(), response.headers());
+            let status = response.status();
+            let response_text = response.text().await.map_err(timeout_error)?;
+            return Ok((status, response_text));
+        }
+
+        // Resend on the next endpoint only when the active one is unreachable: nothing reached
+        // the server then, while a request answered with a 5xx may still have been executed
+        let (client, request) = request.build_split();
+        let request = request?;
+        let url = request.url().to_string();
+        for attempts_left in (0..self.endpoints.len()).rev() {
+            let (index, endpoint) = self.endpoints.active();
+            let mut attempt = request
+                .try_clone()
+                .ok_or_else(|| ApiError::Api("request body cannot be resent".to_string()))?;
+            if let Some(routed) = self.endpoints.route(&url, &endpoint) {
+                *attempt.url_mut() = routed
+                    .parse()
+                    .map_err(|e| ApiError::InvalidConfig(format!("base URL {}: {}", endpoint, e)))?;
+            }
+
+            let response = match client.execute(attempt).await {
+                Err(e) if e.is_connect() => {
+                    self.endpoints.fail_over(index);
+                    if attempts_left > 0 {
+                        continue;
+                    }
+                    return Err(timeout_error(e));
+                }
+                Err(e) => return Err(timeout_error(e)),
+                Ok(response) => response,
+            };
+            self.rate_limits.observe(response.status(), response.headers());
+            let status = response.status();
+            if !status.is_server_error() {
+                self.endpoints.record_success();
+            } else if request.method().is_idempotent() {
+                self.endpoints.record_server_error(index);
+            } else {
+                // A transaction the endpoint failed on goes back to the caller; the next one goes elsewhere
+                self.endpoints.fail_over(index);
+            }
+            let response_text = response.text().await.map_err(timeout_error)?;
+            return Ok((status, response_text));
+        }
+        unreachable!("at least one endpoint is configured")
+    }
+
+    /// Send an authenticated GET request (adds an `auth` token query parameter)
+    async fn get_json_auth(&self, path: &str, query: &[(&str, String)]) -> Result<Value> {
+        let mut query = query.to_vec();
+        query.push(("auth", self.create_auth_token(AUTH_TOKEN_EXPIRY_SECS)?));
+        self.get_json(path, &query).await
     }
     
     /// Generate a 12-byte random nonce converted to i64
@@ -504,23 +1382,32 @@ impl LighterClient {
         i64::from_le_bytes(nonce_bytes)
     }
     
-    /// Get next nonce - fetches from API each time
-    /// This ensures we're always in sync with the API
-    async fn get_next_nonce_from_cache(&self) -> Result<i64> {
-        let nonce = self.fetch_nonce_from_api().await?;
-        
+    /// Take the next nonce for a transaction
+    ///
+    /// The first call fetches from the API (see `LighterClientBuilder::nonce_source`
+    /// for which endpoint); later calls increment locally until
+    /// the fetched value is older than the nonce TTL (see
+    /// `LighterClientBuilder::nonce_ttl`) or a nonce rejection invalidates the
+    /// cache. `force` skips the cache and refetches immediately. The cache lock is
+    /// held across the fetch so concurrent callers on cloned clients never receive
+    /// the same nonce.
+    ///
+    /// The returned nonce is reserved: sign and submit a transaction with it.
+    pub async fn get_nonce(&self, force: bool) -> Result<i64> {
         let mut cache = self.nonce_cache.lock().await;
-        cache.set_fetched_nonce(nonce);
-        
-        Ok(nonce)
-    }
-    
-    /// Refresh nonce from API (useful when errors occur)
-    async fn refresh_nonce_cache(&self) -> Result<()> {
+        if !force {
+            if let Some(nonce) = cache.next_nonce() {
+                return Ok(nonce);
+            }
+        }
+
         let nonce = self.fetch_nonce_from_api().await?;
-        let mut cache = self.nonce_cache.lock().await;
         cache.set_fetched_nonce(nonce);
-        Ok(())
+        Ok(cache.next_nonce().unwrap_or(nonce))
+    }
+
+    async fn get_next_nonce_from_cache(&self) -> Result<i64> {
+        self.get_nonce(false).await
     }
     
     /// Get next nonce using optimistic nonce management
@@ -546,18 +1433,81 @@ impl LighterClient {
         Ok(nonce)
     }
     
-            /// Signs a transaction JSON string and returns the signature.
+    /// Signs a typed transaction and returns the signature.
+    /// 
+    /// The transaction is signed as-is, header included; use [`Self::tx_header`]
+    /// to fill in the account, API key, expiry and nonce.
     /// 
-    /// This method is a convenience wrapper for CREATE_ORDER transactions (type 14).
+    /// # Returns
+    /// The 80-byte signature; `to_base64()` gives the `Sig` field value
+    pub fn sign_transaction<T: Transaction>(&self, tx: &T) -> Result<Signature> {
+        let tx_info = serde_json::to_value(tx)?;
+        let hash = tx::message_hash(tx::chain_id(&self.base_url), T::TX_TYPE, &tx_info)?;
+        Ok(Signature::from(self.signer.sign(&hash)?))
+    }
+
+    /// [`Self::sign_transaction`] off the async runtime
+    ///
+    /// Runs on the signing pool set with `LighterClientBuilder::signing_threads`
+    /// or `signing_pool`, or on tokio's blocking pool by default, so signing
+    /// never stalls the tasks driving I/O.
+    pub async fn sign_transaction_async<T: Transaction + Send + 'static>(&self, tx: T) -> Result<Signature> {
+        let client = self.clone();
+        self.spawn_signing(move || client.sign_transaction(&tx)).await
+    }
+
+    /// Signs a CREATE_ORDER transaction given as a JSON string.
+    /// 
+    /// Escape hatch for JSON built by hand; prefer [`Self::sign_transaction`].
     /// For other transaction types, use `sign_transaction_with_type`.
     /// 
     /// # Arguments
     /// * `tx_json` - JSON string representation of the transaction
     /// 
     /// # Returns
-    /// An 80-byte signature array
-    pub fn sign_transaction(&self, tx_json: &str) -> Result<[u8; 80]> {
-        self.sign_transaction_internal(tx_json, 14) // CREATE_ORDER
+    /// The 80-byte signature
+    pub fn sign_raw(&self, tx_json: &str) -> Result<Signature> {
+        self.sign_transaction_internal(tx_json, CreateOrderTx::TX_TYPE)
+    }
+
+    /// Signs a transaction and returns the signature base64-encoded.
+    /// 
+    /// This is the format the `sendTx` endpoint expects in the `Sig` field of `tx_info`;
+    /// the client's own submit path uses it, so callers never need to encode by hand.
+    /// 
+    /// # Arguments
+    /// * `tx_json` - JSON string representation of the transaction
+    /// * `tx_type` - Transaction type code
+    pub fn sign_transaction_base64(&self, tx_json: &str, tx_type: u32) -> Result<String> {
+        Ok(self.sign_transaction_internal(tx_json, tx_type)?.to_base64())
+    }
+
+    /// Signs a transaction and returns the signature hex-encoded (no `0x` prefix).
+    /// 
+    /// Useful for logging and comparing against other SDKs; the API itself expects base64.
+    /// 
+    /// # Arguments
+    /// * `tx_json` - JSON string representation of the transaction
+    /// * `tx_type` - Transaction type code
+    pub fn sign_transaction_hex(&self, tx_json: &str, tx_type: u32) -> Result<String> {
+        Ok(self.sign_transaction_internal(tx_json, tx_type)?.to_hex())
+    }
+
+    /// Signs a fully specified transaction deterministically, for test vectors
+    ///
+    /// `tx_json` is hashed exactly as given: nothing is filled in, so the nonce,
+    /// expiry, account and API key must all be set. The Schnorr nonce scalar
+    /// (`signing_nonce`, 40 bytes little-endian) is fixed too, so equal inputs
+    /// always give equal signatures that can be diffed against another SDK.
+    /// Never use this to sign live transactions; see
+    /// [`KeyManager::sign_with_fixed_nonce`].
+    ///
+    /// # Returns
+    /// The 80-byte signature
+    pub fn sign_transaction_deterministic(&self, tx_json: &str, tx_type: u32, signing_nonce: &[u8; 40]) -> Result<Signature> {
+        let tx_value: Value = serde_json::from_str(tx_json)?;
+        let hash = tx::message_hash(tx::chain_id(&self.base_url), tx_type, &tx_value)?;
+        Ok(Signature::from(self.signer.sign_with_fixed_nonce(&hash, signing_nonce)?))
     }
 
     /// Signs a transaction with a specific transaction type.
@@ -567,15 +1517,16 @@ impl LighterClient {
     /// * `tx_type` - Transaction type code (e.g., 14 for CREATE_ORDER, 15 for CANCEL_ORDER, 20 for UPDATE_LEVERAGE)
     /// 
     /// # Returns
-    /// An 80-byte signature array
-    pub fn sign_transaction_with_type(&self, tx_json: &str, tx_type: u32) -> Result<[u8; 80]> {
+    /// The 80-byte signature
+    pub fn sign_transaction_with_type(&self, tx_json: &str, tx_type: u32) -> Result<Signature> {
         self.sign_transaction_internal(tx_json, tx_type)
     }
 
     /// Internal method to sign a transaction.
     /// 
     /// This method extracts fields from the transaction JSON, converts them to Goldilocks
-    /// field elements in the correct order, hashes them using Poseidon2, and signs the hash.
+    /// field elements in the canonical order defined in `tx`, hashes them using Poseidon2,
+    /// and signs the hash.
     /// 
     /// The transaction hash includes:
     /// - Chain ID (304 for mainnet, 300 for testnet)
@@ -588,186 +1539,10 @@ impl LighterClient {
     /// * `tx_type` - Transaction type code
     /// 
     /// # Returns
-    /// An 80-byte signature array (s || e format)
-    fn sign_transaction_internal(&self, tx_json: &str, tx_type: u32) -> Result<[u8; 80]> {
+    /// The 80-byte signature (s || e format)
+    fn sign_transaction_internal(&self, tx_json: &str, tx_type: u32) -> Result<Signature> {
         let tx_value: Value = serde_json::from_str(tx_json)?;
-
-        // Determine chain ID based on base URL
-        // Mainnet: 304, Testnet: 300
-        let lighter_chain_id = if self.base_url.contains("mainnet") {
-            304u32
-        } else {
-            300u32
-        };
-        let nonce = tx_value["Nonce"].as_i64().unwrap_or(0);
-        let expired_at = tx_value["ExpiredAt"].as_i64().unwrap_or(0);
-        let account_index = tx_value["AccountIndex"].as_i64().unwrap_or(0);
-        let api_key_index = tx_value["ApiKeyIndex"].as_u64().unwrap_or(0) as u32;
-
-        use poseidon_hash::Goldilocks;
-
-        // Helper function to convert signed i64 to Goldilocks field element
-        // Handles sign extension properly for negative values
-        let to_goldi_i64 = |val: i64| Goldilocks::from_i64(val);
-
-        let elements = match tx_type {
-            14 => {
-                // CREATE_ORDER: 16 elements
-        let market_index = tx_value["MarketIndex"].as_u64().unwrap_or(0) as u32;
-        let client_order_index = tx_value["ClientOrderIndex"].as_i64().unwrap_or(0);
-        let base_amount = tx_value["BaseAmount"].as_i64().unwrap_or(0);
-        let price = tx_value["Price"]
-            .as_u64()
-            .or_else(|| tx_value["Price"].as_i64().map(|v| v as u64))
-            .unwrap_or(0) as u32;
-        let is_ask = tx_value["IsAsk"]
-            .as_u64()
-            .or_else(|| tx_value["IsAsk"].as_i64().map(|v| v as u64))
-            .unwrap_or(0) as u32;
-        let order_type = tx_value["Type"]
-            .as_u64()
-            .or_else(|| tx_value["Type"].as_i64().map(|v| v as u64))
-            .unwrap_or(0) as u32;
-        let time_in_force = tx_value["TimeInForce"]
-            .as_u64()
-            .or_else(|| tx_value["TimeInForce"].as_i64().map(|v| v as u64))
-            .unwrap_or(0) as u32;
-        let reduce_only = tx_value["ReduceOnly"]
-            .as_u64()
-            .or_else(|| tx_value["ReduceOnly"].as_i64().map(|v| v as u64))
-            .unwrap_or(0) as u32;
-        let trigger_price = tx_value["TriggerPrice"]
-            .as_u64()
-            .or_else(|| tx_value["TriggerPrice"].as_i64().map(|v| v as u64))
-            .unwrap_or(0) as u32;
-        let order_expiry = tx_value["OrderExpiry"].as_i64().unwrap_or(0);
-        
-        vec![
-                    Goldilocks::from_canonical_u64(lighter_chain_id as u64),
-                    Goldilocks::from_canonical_u64(tx_type as u64),
-                    to_goldi_i64(nonce),
-                    to_goldi_i64(expired_at),
-                    to_goldi_i64(account_index),
-                    Goldilocks::from_canonical_u64(api_key_index as u64),
-                    Goldilocks::from_canonical_u64(market_index as u64),
-                    to_goldi_i64(client_order_index),
-                    to_goldi_i64(base_amount),
-                    Goldilocks::from_canonical_u64(price as u64),
-                    Goldilocks::from_canonical_u64(is_ask as u64),
-                    Goldilocks::from_canonical_u64(order_type as u64),
-                    Goldilocks::from_canonical_u64(time_in_force as u64),
-                    Goldilocks::from_canonical_u64(reduce_only as u64),
-                    Goldilocks::from_canonical_u64(trigger_price as u64),
-                    to_goldi_i64(order_expiry),
-                ]
-            }
-            15 => {
-                // CANCEL_ORDER: 8 elements
-                let market_index = tx_value["MarketIndex"].as_u64().unwrap_or(0) as u32;
-                let order_index = tx_value["Index"].as_i64().unwrap_or(0);
-
-                vec![
-                    Goldilocks::from_canonical_u64(lighter_chain_id as u64),
-                    Goldilocks::from_canonical_u64(tx_type as u64),
-                    to_goldi_i64(nonce),
-                    to_goldi_i64(expired_at),
-                    to_goldi_i64(account_index),
-                    Goldilocks::from_canonical_u64(api_key_index as u64),
-                    Goldilocks::from_canonical_u64(market_index as u64),
-                    to_goldi_i64(order_index),
-                ]
-            }
-            16 => {
-                // CANCEL_ALL_ORDERS: 8 elements
-                let time_in_force = tx_value["TimeInForce"]
-                    .as_u64()
-                    .or_else(|| tx_value["TimeInForce"].as_i64().map(|v| v as u64))
-                    .unwrap_or(0) as u32;
-                let time = tx_value["Time"].as_i64().unwrap_or(0);
-
-                vec![
-                    Goldilocks::from_canonical_u64(lighter_chain_id as u64),
-                    Goldilocks::from_canonical_u64(tx_type as u64),
-                    to_goldi_i64(nonce),
-                    to_goldi_i64(expired_at),
-                    to_goldi_i64(account_index),
-                    Goldilocks::from_canonical_u64(api_key_index as u64),
-                    Goldilocks::from_canonical_u64(time_in_force as u64),
-                    to_goldi_i64(time),
-                ]
-            }
-            8 => {
-                // CHANGE_PUB_KEY: needs pubkey parsing (ArrayFromCanonicalLittleEndianBytes)
-                let pubkey_hex = tx_value["PubKey"].as_str().unwrap_or("");
-                let pubkey_bytes = hex::decode(pubkey_hex)
-                    .map_err(|e| ApiError::Api(format!("Invalid PubKey hex: {}", e)))?;
-                if pubkey_bytes.len() != 40 {
-                    return Err(ApiError::Api("PubKey must be 40 bytes".to_string()));
-                }
-                // Convert 40-byte public key to 5 Goldilocks elements (8 bytes per element)
-                let mut pubkey_elems = Vec::new();
-                for i in 0..5 {
-                    let chunk = &pubkey_bytes[i*8..(i+1)*8];
-                    let val = u64::from_le_bytes(chunk.try_into().unwrap());
-                    pubkey_elems.push(Goldilocks::from_canonical_u64(val));
-                }
-
-                let mut elems = vec![
-                    Goldilocks::from_canonical_u64(lighter_chain_id as u64),
-                    Goldilocks::from_canonical_u64(tx_type as u64),
-                    to_goldi_i64(nonce),
-                    to_goldi_i64(expired_at),
-                    to_goldi_i64(account_index),
-                    Goldilocks::from_canonical_u64(api_key_index as u64),
-                ];
-                elems.extend(pubkey_elems);
-                elems
-            }
-            20 => {
-                // UPDATE_LEVERAGE: 9 elements
-                // Order: lighterChainId, txType, nonce, expiredAt, accountIndex, apiKeyIndex, marketIndex, initialMarginFraction, marginMode
-                let market_index = tx_value["MarketIndex"]
-                    .as_u64()
-                    .or_else(|| tx_value["MarketIndex"].as_i64().map(|v| v as u64))
-                    .unwrap_or(0) as u32;
-                let initial_margin_fraction = tx_value["InitialMarginFraction"]
-                    .as_u64()
-                    .or_else(|| tx_value["InitialMarginFraction"].as_i64().map(|v| v as u64))
-                    .unwrap_or(0) as u32;
-                let margin_mode = tx_value["MarginMode"]
-                    .as_u64()
-                    .or_else(|| tx_value["MarginMode"].as_i64().map(|v| v as u64))
-                    .unwrap_or(0) as u32;
-
-                vec![
-                    Goldilocks::from_canonical_u64(lighter_chain_id as u64),
-                    Goldilocks::from_canonical_u64(tx_type as u64),
-                    to_goldi_i64(nonce),
-                    to_goldi_i64(expired_at),
-                    to_goldi_i64(account_index),
-                    Goldilocks::from_canonical_u64(api_key_index as u64),
-                    Goldilocks::from_canonical_u64(market_index as u64),
-                    Goldilocks::from_canonical_u64(initial_margin_fraction as u64),
-                    Goldilocks::from_canonical_u64(margin_mode as u64),
-                ]
-            }
-            _ => {
-                return Err(ApiError::Api(format!("Unsupported transaction type: {}", tx_type)));
-            }
-        };
-        
-        // Hash the Goldilocks field elements using Poseidon2 to produce a 40-byte hash
-        use poseidon_hash::hash_to_quintic_extension;
-        let hash_result = hash_to_quintic_extension(&elements);
-        let message_array = hash_result.to_bytes_le();
-        
-        let mut hash_bytes = [0u8; 40];
-        hash_bytes.copy_from_slice(&message_array[..40]);
-
-        // Sign the transaction hash using Schnorr signature
-        let signature = self.key_manager.sign(&hash_bytes)
-            .map_err(|e| ApiError::Signer(e))?;
-        
-        Ok(signature)
+        let hash = tx::message_hash(tx::chain_id(&self.base_url), tx_type, &tx_value)?;
+        Ok(Signature::from(self.signer.sign(&hash)?))
     }
 }
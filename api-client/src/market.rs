@@ -0,0 +1,490 @@
+use crate::account::{check_response_code, decode, decode_list};
+use crate::{ApiError, CreateOrderRequest, LighterClient, Position, Result, SendTxResponse, Side};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+
+/// `MarketInfo::status` of a market that accepts orders
+const MARKET_STATUS_ACTIVE: &str = "active";
+
+/// Number of levels per side requested for slippage checks
+const SLIPPAGE_BOOK_DEPTH: u32 = 1;
+
+/// Number of levels per side requested for fill estimates
+const FILL_ESTIMATE_BOOK_DEPTH: u32 = 100;
+
+/// Static trading parameters of a market
+///
+/// A view over the exchange's order book details, which carry many more
+/// fields, so the `strict-json` feature does not apply to it.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MarketInfo {
+    #[serde(rename = "market_id")]
+    pub market_index: u8,
+    pub symbol: String,
+    /// e.g. "active", "frozen"
+    pub status: String,
+    /// Decimals of the integer `Price` field (price 3024.66 with 2 decimals is 302466)
+    #[serde(rename = "supported_price_decimals")]
+    pub price_decimals: u32,
+    /// Decimals of the integer `BaseAmount` field
+    #[serde(rename = "supported_size_decimals")]
+    pub size_decimals: u32,
+    pub min_base_amount: Decimal,
+    pub min_quote_amount: Decimal,
+    /// Decimals of USDC amounts
+    #[serde(rename = "supported_quote_decimals")]
+    pub quote_decimals: u32,
+    pub taker_fee: Decimal,
+    pub maker_fee: Decimal,
+}
+
+/// Trading activity of a market over the last 24 hours
+///
+/// Fields the exchange reports as null (e.g. a market without recent trades)
+/// are zero. A view over the exchange's market details, which carry many more
+/// fields, so the `strict-json` feature does not apply to it.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct MarketStats {
+    #[serde(rename = "market_id")]
+    pub market_index: u8,
+    pub symbol: String,
+    #[serde(rename = "last_trade_price", deserialize_with = "zero_if_null")]
+    pub last_price: Decimal,
+    /// Traded size in base units
+    #[serde(rename = "daily_base_token_volume", deserialize_with = "zero_if_null")]
+    pub volume_24h: Decimal,
+    /// Traded notional in USDC
+    #[serde(rename = "daily_quote_token_volume", deserialize_with = "zero_if_null")]
+    pub quote_volume_24h: Decimal,
+    /// Price change in percent
+    #[serde(rename = "daily_price_change", deserialize_with = "zero_if_null")]
+    pub price_change_24h: Decimal,
+    #[serde(rename = "daily_price_high", deserialize_with = "zero_if_null")]
+    pub high_24h: Decimal,
+    #[serde(rename = "daily_price_low", deserialize_with = "zero_if_null")]
+    pub low_24h: Decimal,
+    #[serde(rename = "daily_trades_count", deserialize_with = "zero_if_null")]
+    pub trades_24h: u64,
+    /// Open interest in base units
+    #[serde(deserialize_with = "zero_if_null")]
+    pub open_interest: Decimal,
+}
+
+fn zero_if_null<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+impl MarketInfo {
+    /// Check an order's integer price and base amount against this market's limits
+    ///
+    /// `Price` and `BaseAmount` are integers counted in ticks (10^-price_decimals)
+    /// and lots (10^-size_decimals), so every value lies on the grid; what can be
+    /// violated is the range of the 32-bit price field and the market minimums.
+    /// The error names the first violated limit.
+    pub fn validate_order(&self, price: i64, base_amount: i64) -> Result<()> {
+        if price <= 0 || price > u32::MAX as i64 {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "Price {} outside 1..={} for market {}",
+                price,
+                u32::MAX,
+                self.market_index
+            )));
+        }
+        if base_amount <= 0 {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "BaseAmount {} must be positive",
+                base_amount
+            )));
+        }
+
+        let size = Decimal::new(base_amount, self.size_decimals);
+        if size < self.min_base_amount {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "BaseAmount {} ({} {}) below minimum size {}",
+                base_amount, size, self.symbol, self.min_base_amount
+            )));
+        }
+
+        let quote = size * Decimal::new(price, self.price_decimals);
+        if quote < self.min_quote_amount {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "order value {} below minimum quote amount {} for {}",
+                quote, self.min_quote_amount, self.symbol
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// One resting order in an order book snapshot
+///
+/// A view over the exchange's order objects, which carry the owner, order
+/// index and more, so the `strict-json` feature does not apply to it.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BookLevel {
+    pub price: Decimal,
+    pub remaining_base_amount: Decimal,
+}
+
+/// Resting orders of one market, best price first on each side
+///
+/// Exempt from the `strict-json` feature along with its [`BookLevel`]s.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct OrderBookSnapshot {
+    pub bids: Vec<BookLevel>,
+    pub asks: Vec<BookLevel>,
+}
+
+impl OrderBookSnapshot {
+    pub fn best_bid(&self) -> Option<Decimal> {
+        self.bids.first().map(|level| level.price)
+    }
+
+    pub fn best_ask(&self) -> Option<Decimal> {
+        self.asks.first().map(|level| level.price)
+    }
+
+    /// Expected execution of a market order of `size` (base units) against this book
+    ///
+    /// Walks the side the order takes (asks for a buy, bids for a sell) from the
+    /// best price until `size` is filled or the levels run out.
+    pub fn estimate_fill(&self, side: Side, size: Decimal) -> FillEstimate {
+        let levels = match side {
+            Side::Buy => &self.asks,
+            Side::Sell => &self.bids,
+        };
+        let mut filled = Decimal::ZERO;
+        let mut notional = Decimal::ZERO;
+        let mut worst_price = None;
+        for level in levels {
+            if filled >= size {
+                break;
+            }
+            let take = level.remaining_base_amount.min(size - filled);
+            filled += take;
+            notional += take * level.price;
+            worst_price = Some(level.price);
+        }
+
+        let average_price = (!filled.is_zero()).then(|| notional / filled);
+        let best_price = levels.first().map(|level| level.price);
+        let slippage_pct = match (average_price, best_price) {
+            (Some(average), Some(best)) if !best.is_zero() => {
+                Some((average - best).abs() / best * Decimal::ONE_HUNDRED)
+            }
+            _ => None,
+        };
+        FillEstimate {
+            size,
+            filled,
+            average_price,
+            worst_price,
+            slippage_pct,
+            sufficient_liquidity: filled >= size,
+        }
+    }
+}
+
+/// Expected execution of a market order, see [`LighterClient::estimate_market_fill`]
+///
+/// Prices and sizes are in human units (e.g. USDC and ETH), not integer lots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FillEstimate {
+    /// Size the estimate was made for
+    pub size: Decimal,
+    /// Size the visible book can fill, at most `size`
+    pub filled: Decimal,
+    /// Volume-weighted average price of the filled part, `None` if nothing fills
+    pub average_price: Option<Decimal>,
+    /// Price of the last level reached, `None` if nothing fills
+    pub worst_price: Option<Decimal>,
+    /// Distance of `average_price` from the best price, in percent
+    pub slippage_pct: Option<Decimal>,
+    /// `false` if the visible book is too thin to fill all of `size`
+    pub sufficient_liquidity: bool,
+}
+
+/// Worst acceptable execution price for a market order, as a percentage off the best price
+///
+/// The resulting bound is sent in the market order's `Price` field, which the
+/// exchange treats as the protection limit: a buy never fills above it and a
+/// sell never fills below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlippageGuard {
+    max_slippage_pct: Decimal,
+}
+
+impl SlippageGuard {
+    /// `max_slippage_pct` is in percent (`0.5` allows 0.5% from the best price)
+    ///
+    /// Must be greater than 0 and less than 100.
+    pub fn new(max_slippage_pct: Decimal) -> Result<Self> {
+        if max_slippage_pct <= Decimal::ZERO || max_slippage_pct >= Decimal::ONE_HUNDRED {
+            return Err(ApiError::InvalidConfig(format!(
+                "slippage must be between 0 and 100 percent, got {}",
+                max_slippage_pct
+            )));
+        }
+        Ok(Self { max_slippage_pct })
+    }
+
+    pub fn max_slippage_pct(&self) -> Decimal {
+        self.max_slippage_pct
+    }
+
+    /// Compute the integer `Price` bound for a market order
+    ///
+    /// Buys are bounded above the best ask, sells below the best bid. The bound
+    /// is rounded towards the best price so rounding never loosens the guard. Fails if the book is missing or has no liquidity on
+    /// the side the order would take.
+    pub fn protected_price(
+        &self,
+        book: Option<&OrderBookSnapshot>,
+        side: Side,
+        price_decimals: u32,
+    ) -> Result<i64> {
+        let taken = match side {
+            Side::Buy => "asks",
+            Side::Sell => "bids",
+        };
+        let best = book
+            .and_then(|book| match side {
+                Side::Buy => book.best_ask(),
+                Side::Sell => book.best_bid(),
+            })
+            .ok_or_else(|| {
+                ApiError::NotFound(format!("no order book {} to apply the slippage guard to", taken))
+            })?;
+
+        let fraction = self.max_slippage_pct / Decimal::ONE_HUNDRED;
+        let scale = Decimal::from(10u64.pow(price_decimals));
+        let bound = match side {
+            Side::Buy => (best * (Decimal::ONE + fraction) * scale).floor(),
+            Side::Sell => (best * (Decimal::ONE - fraction) * scale).ceil(),
+        };
+
+        bound
+            .to_i64()
+            .filter(|price| *price > 0)
+            .ok_or_else(|| ApiError::Api(format!("slippage bound {} out of range", bound)))
+    }
+}
+
+impl LighterClient {
+    /// Fetch the trading parameters of one market
+    pub async fn get_market_info(&self, market_index: u8) -> Result<MarketInfo> {
+        let response = self
+            .get_json("/api/v1/orderBooks", &[("market_id", market_index.to_string())])
+            .await?;
+        check_response_code(&response)?;
+
+        let books: Vec<MarketInfo> = decode_list(&response, "order_books")?;
+        let market = books
+            .into_iter()
+            .find(|book| book.market_index == market_index)
+            .ok_or_else(|| ApiError::NotFound(format!("market {}", market_index)))?;
+
+        if let Ok(mut cache) = self.market_cache.write() {
+            cache.insert(market_index, market.clone());
+        }
+        Ok(market)
+    }
+
+    /// Fetch the trading parameters of every market in one request
+    ///
+    /// Replaces the whole metadata cache in one step, so orders validated
+    /// afterwards need no further metadata requests and see either the old or
+    /// the new metadata of every market, never a mix; markets no longer listed
+    /// are dropped. Ordered by market index.
+    pub async fn get_all_markets(&self) -> Result<Vec<MarketInfo>> {
+        let response = self.get_json("/api/v1/orderBooks", &[]).await?;
+        check_response_code(&response)?;
+        let mut markets: Vec<MarketInfo> = decode_list(&response, "order_books")?;
+        markets.sort_by_key(|market| market.market_index);
+
+        let refreshed: HashMap<u8, MarketInfo> =
+            markets.iter().map(|market| (market.market_index, market.clone())).collect();
+        if let Ok(mut cache) = self.market_cache.write() {
+            *cache = refreshed;
+            self.markets_refreshed_at.store(self.clock.now_ms(), Ordering::SeqCst);
+        }
+        Ok(markets)
+    }
+
+    /// Fetch 24h volume, open interest, last price and 24h change of one market
+    pub async fn get_market_stats(&self, market_index: u8) -> Result<MarketStats> {
+        self.fetch_market_stats(Some(market_index))
+            .await?
+            .into_iter()
+            .find(|stats| stats.market_index == market_index)
+            .ok_or_else(|| ApiError::NotFound(format!("market {}", market_index)))
+    }
+
+    /// Fetch the statistics of every market in one request, ordered by market index
+    pub async fn get_all_market_stats(&self) -> Result<Vec<MarketStats>> {
+        let mut stats = self.fetch_market_stats(None).await?;
+        stats.sort_by_key(|stats| stats.market_index);
+        Ok(stats)
+    }
+
+    async fn fetch_market_stats(&self, market_index: Option<u8>) -> Result<Vec<MarketStats>> {
+        let query: Vec<_> = market_index.map(|index| ("market_id", index.to_string())).into_iter().collect();
+        let response = self.get_json("/api/v1/orderBookDetails", &query).await?;
+        check_response_code(&response)?;
+        decode_list(&response, "order_book_details")
+    }
+
+    /// Market metadata from the cache, fetching it on first use
+    pub(crate) async fn cached_market_info(&self, market_index: u8) -> Result<MarketInfo> {
+        let cached = self
+            .market_cache
+            .read()
+            .ok()
+            .and_then(|cache| cache.get(&market_index).cloned());
+        match cached {
+            Some(market) => Ok(market),
+            None => self.get_market_info(market_index).await,
+        }
+    }
+
+    /// Validate an order against its market before signing
+    ///
+    /// If the metadata cannot be loaded the order is let through unchecked; the
+    /// exchange applies the same limits anyway. A market whose status is not
+    /// active fails with `ApiError::MarketClosed` without a round trip to `sendTx`.
+    pub(crate) async fn check_order_params(&self, market_index: u8, price: i64, base_amount: i64) -> Result<()> {
+        match self.cached_market_info(market_index).await {
+            Ok(market) if market.status != MARKET_STATUS_ACTIVE => {
+                // Dropped from the cache so the status is fetched again until the market reopens
+                if let Ok(mut cache) = self.market_cache.write() {
+                    cache.remove(&market_index);
+                }
+                Err(ApiError::MarketClosed {
+                    market_index: Some(market_index),
+                    code: None,
+                    message: format!("market {} is {}", market_index, market.status),
+                })
+            }
+            Ok(market) => market.validate_order(price, base_amount),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Fetch the top `limit` resting orders on each side of a market
+    pub async fn get_order_book(&self, market_index: u8, limit: u32) -> Result<OrderBookSnapshot> {
+        let response = self
+            .get_json(
+                "/api/v1/orderBookOrders",
+                &[("market_id", market_index.to_string()), ("limit", limit.to_string())],
+            )
+            .await?;
+        check_response_code(&response)?;
+        decode(response)
+    }
+
+    /// Estimate average and worst fill price of a market order from the current book
+    ///
+    /// `base_amount` is in integer lots, as in an order. Reads the top 100 levels
+    /// of the side the order takes; `FillEstimate::sufficient_liquidity` is
+    /// `false` when they cannot fill it all. Only a snapshot: the book can move
+    /// before an order arrives, so use it to size the order and pick a
+    /// [`SlippageGuard`], not as a promise.
+    pub async fn estimate_market_fill(&self, market_index: u8, side: Side, base_amount: i64) -> Result<FillEstimate> {
+        let market = self.cached_market_info(market_index).await?;
+        let size = Decimal::new(base_amount, market.size_decimals);
+        let book = self.get_order_book(market_index, FILL_ESTIMATE_BOOK_DEPTH).await?;
+        Ok(book.estimate_fill(side, size))
+    }
+
+    /// Submit a market order whose price bound comes from a [`SlippageGuard`]
+    ///
+    /// Reads the market's price decimals and the current top of book, derives the
+    /// worst acceptable price and sends it as the order's `Price`. Fails before
+    /// signing anything if the book has no liquidity on the side being taken.
+    pub async fn create_market_order_with_slippage(
+        &self,
+        order_book_index: u8,
+        client_order_index: u64,
+        base_amount: i64,
+        side: Side,
+        guard: SlippageGuard,
+    ) -> Result<SendTxResponse> {
+        let market = self.get_market_info(order_book_index).await?;
+        let book = self.get_order_book(order_book_index, SLIPPAGE_BOOK_DEPTH).await?;
+        let price = guard.protected_price(Some(&book), side, market.price_decimals)?;
+
+        self.create_market_order(order_book_index, client_order_index, base_amount, price, side)
+            .await
+    }
+
+    /// Close the whole position in one market with a reduce-only market order
+    ///
+    /// Reads the current position and sends the opposite side (a sell for a
+    /// long, a buy for a short) for its full size, bounded by `guard` like
+    /// [`Self::create_market_order_with_slippage`]. Being reduce-only, the order
+    /// can never flip the position. Fails with `ApiError::InvalidOrderParams` if
+    /// the position is already flat, or if `account_index` is not the client's
+    /// account (orders are signed for the client's account only).
+    pub async fn close_position(
+        &self,
+        account_index: i64,
+        market_index: u8,
+        client_order_index: u64,
+        guard: SlippageGuard,
+    ) -> Result<SendTxResponse> {
+        if account_index != self.account_index {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "cannot close a position of account {} with a client for account {}",
+                account_index, self.account_index
+            )));
+        }
+        let position = self
+            .get_positions(account_index)
+            .await?
+            .into_iter()
+            .find(|position| position.market_index == market_index)
+            .ok_or_else(|| {
+                ApiError::InvalidOrderParams(format!("no open position in market {}", market_index))
+            })?;
+
+        let order = self.closing_order(&position, client_order_index, guard).await?;
+        self.create_order(order).await
+    }
+
+    /// Reduce-only market order closing all of `position`, priced by `guard`
+    pub(crate) async fn closing_order(
+        &self,
+        position: &Position,
+        client_order_index: u64,
+        guard: SlippageGuard,
+    ) -> Result<CreateOrderRequest> {
+        let market_index = position.market_index;
+        let side = if position.size.is_sign_negative() { Side::Buy } else { Side::Sell };
+        let market = self.get_market_info(market_index).await?;
+        let lots = position.size.abs() * Decimal::from(10u64.pow(market.size_decimals));
+        let base_amount = lots
+            .round()
+            .to_i64()
+            .ok_or_else(|| ApiError::Api(format!("position size {} out of range", position.size)))?;
+        let book = self.get_order_book(market_index, SLIPPAGE_BOOK_DEPTH).await?;
+        let price = guard.protected_price(Some(&book), side, market.price_decimals)?;
+
+        Ok(CreateOrderRequest {
+            account_index: self.account_index,
+            client_order_index,
+            reduce_only: true,
+            ..CreateOrderRequest::market(market_index, side, base_amount, price)
+        })
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{ApiError, LighterClient, Result};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+/// Background task refreshing a client's market metadata, see [`LighterClient::spawn_market_refresh`]
+///
+/// Dropping the handle stops the refreshes.
+#[derive(Debug)]
+pub struct MarketRefresh {
+    task: JoinHandle<()>,
+}
+
+impl MarketRefresh {
+    /// Stop refreshing; same as dropping the handle
+    pub fn stop(self) {}
+}
+
+impl Drop for MarketRefresh {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl LighterClient {
+    /// Refetch the metadata of every market and replace the cache with it
+    ///
+    /// Same as [`Self::get_all_markets`] without the result; call it after a
+    /// listing or parameter change is announced.
+    pub async fn refresh_markets(&self) -> Result<()> {
+        self.get_all_markets().await.map(drop)
+    }
+
+    /// When the market cache was last replaced by [`Self::refresh_markets`] or
+    /// [`Self::get_all_markets`] (unix ms), `None` if never
+    ///
+    /// Metadata of single markets fetched on demand does not count.
+    pub fn last_market_refresh(&self) -> Option<i64> {
+        Some(self.markets_refreshed_at.load(Ordering::SeqCst)).filter(|at| *at > 0)
+    }
+
+    /// Refresh the market metadata every `interval` in the background
+    ///
+    /// Tick sizes, minimum sizes and market status change while a bot runs;
+    /// this keeps order validation current. The first refresh happens at once.
+    /// A failed refresh keeps the cached metadata and is tried again at the
+    /// next interval. Runs until the returned handle is dropped. Must be called
+    /// within a Tokio runtime; fails with `ApiError::InvalidConfig` for a zero
+    /// interval.
+    pub fn spawn_market_refresh(&self, interval: Duration) -> Result<MarketRefresh> {
+        if interval.is_zero() {
+            return Err(ApiError::InvalidConfig("market refresh interval must be non-zero".to_string()));
+        }
+        let client = self.clone();
+        let task = tokio::spawn(async move {
+            let mut timer = tokio::time::interval(interval);
+            timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                timer.tick().await;
+                let _ = client.refresh_markets().await;
+            }
+        });
+        Ok(MarketRefresh { task })
+    }
+}
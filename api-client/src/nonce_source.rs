@@ -0,0 +1,29 @@
+use serde_json::Value;
+
+/// Where the client reads the next nonce of its API key from
+///
+/// [`NonceSource::NextNonce`] costs a dedicated request whenever the local
+/// counter needs a fresh value. [`NonceSource::Account`] reads the nonce from
+/// the account query instead, for deployments that report it there: the
+/// account reads the client makes anyway (e.g. `get_positions`) then reseed an
+/// empty or expired counter for free, so a latency-sensitive caller that polls
+/// its account rarely pays for a separate nonce fetch. The account response is
+/// larger and slower than `nextNonce`, so a fetch that cannot piggyback costs
+/// more; prefer the default unless the account is queried regularly anyway.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NonceSource {
+    /// `GET /api/v1/nextNonce` (what the exchange documents)
+    #[default]
+    NextNonce,
+    /// `GET /api/v1/account`, the `nonce` of this client's entry in the account's `api_keys`
+    Account,
+}
+
+/// Nonce of `api_key_index` in an account object, `None` if the account does not report it
+pub(crate) fn account_nonce(account: &Value, api_key_index: u8) -> Option<i64> {
+    account["api_keys"]
+        .as_array()?
+        .iter()
+        .find(|key| key["api_key_index"].as_u64() == Some(api_key_index as u64))?["nonce"]
+        .as_i64()
+}
@@ -0,0 +1,77 @@
+use crate::{ApiError, OrderExpiry, Result};
+
+/// `order_type` values of `CreateOrderRequest`
+pub const ORDER_TYPE_LIMIT: u8 = 0;
+pub const ORDER_TYPE_MARKET: u8 = 1;
+pub const ORDER_TYPE_STOP_LOSS: u8 = 2;
+pub const ORDER_TYPE_STOP_LOSS_LIMIT: u8 = 3;
+pub const ORDER_TYPE_TAKE_PROFIT: u8 = 4;
+pub const ORDER_TYPE_TAKE_PROFIT_LIMIT: u8 = 5;
+pub const ORDER_TYPE_TWAP: u8 = 6;
+
+/// `time_in_force` values of `CreateOrderRequest`
+pub const ORDER_TIME_IN_FORCE_IOC: u8 = 0;
+pub const ORDER_TIME_IN_FORCE_GOOD_TILL_TIME: u8 = 1;
+pub const ORDER_TIME_IN_FORCE_FOK: u8 = 2;
+pub const ORDER_TIME_IN_FORCE_POST_ONLY: u8 = 3;
+
+const IOC: u8 = ORDER_TIME_IN_FORCE_IOC;
+const GTT: u8 = ORDER_TIME_IN_FORCE_GOOD_TILL_TIME;
+const FOK: u8 = ORDER_TIME_IN_FORCE_FOK;
+const POST_ONLY: u8 = ORDER_TIME_IN_FORCE_POST_ONLY;
+
+/// Time-in-force values each order type accepts
+///
+/// Orders that execute at market when placed or triggered can only be
+/// immediate; limit orders can rest, and only a plain limit order can be
+/// post-only (a triggered limit may cross the moment it fires). A TWAP runs
+/// over time, so it is good-till-time.
+const RULES: [(u8, &str, &[u8]); 7] = [
+    (ORDER_TYPE_LIMIT, "limit", &[IOC, GTT, FOK, POST_ONLY]),
+    (ORDER_TYPE_MARKET, "market", &[IOC, FOK]),
+    (ORDER_TYPE_STOP_LOSS, "stop-loss", &[IOC, FOK]),
+    (ORDER_TYPE_STOP_LOSS_LIMIT, "stop-loss limit", &[IOC, GTT, FOK]),
+    (ORDER_TYPE_TAKE_PROFIT, "take-profit", &[IOC, FOK]),
+    (ORDER_TYPE_TAKE_PROFIT_LIMIT, "take-profit limit", &[IOC, GTT, FOK]),
+    (ORDER_TYPE_TWAP, "TWAP", &[GTT]),
+];
+
+fn time_in_force_name(time_in_force: u8) -> &'static str {
+    match time_in_force {
+        IOC => "immediate-or-cancel",
+        GTT => "good-till-time",
+        FOK => "fill-or-kill",
+        POST_ONLY => "post-only",
+        _ => "unknown",
+    }
+}
+
+/// Reject order type, time in force and expiry combinations the exchange does not accept
+///
+/// See [`RULES`]; an `order_expiry` only applies to orders that can rest on
+/// the book, i.e. good-till-time and post-only ones.
+pub(crate) fn check_order_combination(order_type: u8, time_in_force: u8, order_expiry: OrderExpiry) -> Result<()> {
+    let invalid = |reason: String| Err(ApiError::InvalidOrderParams(reason));
+    let Some((_, type_name, allowed)) = RULES.iter().find(|(value, ..)| *value == order_type) else {
+        return invalid(format!("unknown order type {}", order_type));
+    };
+    if time_in_force > POST_ONLY {
+        return invalid(format!("unknown time in force {}", time_in_force));
+    }
+    if !allowed.contains(&time_in_force) {
+        return invalid(format!(
+            "{} orders cannot be {} (time in force {}); allowed: {}",
+            type_name,
+            time_in_force_name(time_in_force),
+            time_in_force,
+            allowed.iter().map(|&tif| time_in_force_name(tif)).collect::<Vec<_>>().join(", ")
+        ));
+    }
+    if order_expiry != OrderExpiry::None && !matches!(time_in_force, GTT | POST_ONLY) {
+        return invalid(format!(
+            "{} orders cannot carry an OrderExpiry",
+            time_in_force_name(time_in_force)
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use crate::{CreateOrderRequest, LighterClient, Result, SendTxResponse};
+use futures::stream::{self, BoxStream};
+use futures::StreamExt;
+use tokio::sync::mpsc;
+
+/// Outcome of one [`OrderPipeline::submit`], carrying the caller's tag back
+#[derive(Debug)]
+pub struct PipelineResult<T> {
+    /// The tag passed to `submit`, unchanged
+    pub tag: T,
+    pub client_order_index: u64,
+    /// What [`LighterClient::create_order`] returned for the order
+    pub result: Result<SendTxResponse>,
+}
+
+/// Submits orders concurrently and reports each outcome on one channel
+///
+/// Every order is submitted on its own task with an opaque tag of the caller's
+/// choosing (a strategy intent, a correlation id), and its result arrives on
+/// the receiver returned by [`Self::new`] together with that tag, in completion
+/// order. The tag stays on this side: it is never signed or sent. Clones share
+/// the client and the result channel.
+#[derive(Debug)]
+pub struct OrderPipeline<T> {
+    client: LighterClient,
+    results: mpsc::UnboundedSender<PipelineResult<T>>,
+}
+
+impl<T> Clone for OrderPipeline<T> {
+    fn clone(&self) -> Self {
+        Self {
+            client: self.client.clone(),
+            results: self.results.clone(),
+        }
+    }
+}
+
+impl<T: Send + 'static> OrderPipeline<T> {
+    /// A pipeline submitting with `client` and the receiver of its results
+    ///
+    /// The receiver yields `None` once every pipeline clone is dropped and all
+    /// submitted orders have completed.
+    pub fn new(client: LighterClient) -> (Self, mpsc::UnboundedReceiver<PipelineResult<T>>) {
+        let (results, receiver) = mpsc::unbounded_channel();
+        (Self { client, results }, receiver)
+    }
+
+    /// Submit `order` in the background; its result is sent with `tag`
+    ///
+    /// Must be called within a Tokio runtime. Results of orders still in flight
+    /// when the receiver is dropped are discarded.
+    pub fn submit(&self, order: CreateOrderRequest, tag: T) {
+        let client = self.client.clone();
+        let results = self.results.clone();
+        tokio::spawn(async move {
+            let client_order_index = order.client_order_index;
+            let result = client.create_order(order).await;
+            let _ = results.send(PipelineResult { tag, client_order_index, result });
+        });
+    }
+}
+
+impl LighterClient {
+    /// Place orders with at most `concurrency` in flight, yielding each outcome as it completes
+    ///
+    /// Each order goes through [`Self::create_order`]; the next one starts as
+    /// soon as a running one finishes, so a failure shows up without waiting
+    /// for the rest. Results arrive in completion order, tagged with the
+    /// order's position in `orders`. Orders are taken from the iterator only as
+    /// slots free up, and dropping the stream stops submitting: orders already
+    /// sent are not canceled. A `concurrency` of zero is treated as one.
+    ///
+    /// Nonces are reserved as orders start, in input order, but concurrent
+    /// orders can reach the exchange out of order; see
+    /// `LighterClientBuilder::nonce_gap_threshold` for how such gaps are recovered.
+    pub fn place_orders_stream<I>(&self, orders: I, concurrency: usize) -> BoxStream<'static, PipelineResult<usize>>
+    where
+        I: IntoIterator<Item = CreateOrderRequest>,
+        I::IntoIter: Send + 'static,
+    {
+        let client = self.clone();
+        stream::iter(orders.into_iter().enumerate())
+            .map(move |(position, order)| {
+                let client = client.clone();
+                async move {
+                    let client_order_index = order.client_order_index;
+                    let result = client.create_order(order).await;
+                    PipelineResult { tag: position, client_order_index, result }
+                }
+            })
+            .buffer_unordered(concurrency.max(1))
+            .boxed()
+    }
+}
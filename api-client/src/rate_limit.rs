@@ -0,0 +1,105 @@
+use crate::LighterClient;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Headers with the request budget of the current window
+const LIMIT_HEADER: &str = "x-ratelimit-limit";
+const REMAINING_HEADER: &str = "x-ratelimit-remaining";
+/// Seconds until the window resets, or the reset time in unix seconds
+const RESET_HEADER: &str = "x-ratelimit-reset";
+
+/// Reset values above this are unix timestamps rather than delays (2001-09-09)
+const EPOCH_THRESHOLD_SECS: u64 = 1_000_000_000;
+
+/// Request budget last reported by the exchange, see [`LighterClient::rate_limit_status`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// Requests allowed per window, if reported
+    pub limit: Option<u64>,
+    /// Requests left in the current window, if reported
+    pub remaining: Option<u64>,
+    /// Time until the window resets, if reported; zero once it has passed
+    pub reset_after: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Observed {
+    limit: Option<u64>,
+    remaining: Option<u64>,
+    resets_at: Option<Instant>,
+}
+
+/// Rate-limit headers of the latest response that carried any
+#[derive(Debug, Default)]
+pub(crate) struct RateLimitTracker {
+    observed: Mutex<Option<Observed>>,
+}
+
+fn header_u64(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// When a reset header value (delay or unix seconds) comes due
+fn reset_instant(value: u64, now: Instant) -> Instant {
+    if value < EPOCH_THRESHOLD_SECS {
+        return now + Duration::from_secs(value);
+    }
+    let unix_now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    now + Duration::from_secs(value.saturating_sub(unix_now))
+}
+
+impl RateLimitTracker {
+    /// Record the budget reported by a response; responses without rate-limit headers change nothing
+    ///
+    /// A 429 empties the budget until its `Retry-After`, if it gives one.
+    pub(crate) fn observe(&self, status: StatusCode, headers: &HeaderMap) {
+        let now = Instant::now();
+        let limit = header_u64(headers, LIMIT_HEADER);
+        let mut remaining = header_u64(headers, REMAINING_HEADER);
+        let mut resets_at = header_u64(headers, RESET_HEADER).map(|value| reset_instant(value, now));
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            remaining = Some(0);
+            if let Some(retry_after) = header_u64(headers, RETRY_AFTER.as_str()) {
+                resets_at = Some(now + Duration::from_secs(retry_after));
+            }
+        }
+        if limit.is_none() && remaining.is_none() && resets_at.is_none() {
+            return;
+        }
+        let mut observed = self.observed.lock().unwrap_or_else(|e| e.into_inner());
+        let previous_limit = observed.and_then(|observed| observed.limit);
+        *observed = Some(Observed { limit: limit.or(previous_limit), remaining, resets_at });
+    }
+
+    pub(crate) fn status(&self) -> Option<RateLimitStatus> {
+        let observed = (*self.observed.lock().unwrap_or_else(|e| e.into_inner()))?;
+        Some(RateLimitStatus {
+            limit: observed.limit,
+            remaining: observed.remaining,
+            reset_after: observed.resets_at.map(|at| at.saturating_duration_since(Instant::now())),
+        })
+    }
+
+    /// How long to hold a request back: until the reset of an exhausted budget
+    pub(crate) fn wait(&self) -> Option<Duration> {
+        let status = self.status()?;
+        match (status.remaining, status.reset_after) {
+            (Some(0), Some(reset_after)) if !reset_after.is_zero() => Some(reset_after),
+            _ => None,
+        }
+    }
+}
+
+impl LighterClient {
+    /// Request budget last reported by the exchange
+    ///
+    /// Read from the `X-RateLimit-Limit`, `X-RateLimit-Remaining` and
+    /// `X-RateLimit-Reset` headers of the latest response that carried any (a
+    /// 429 counts as an empty budget until its `Retry-After`). `None` until
+    /// such a response arrives; fields the exchange did not send are `None`.
+    pub fn rate_limit_status(&self) -> Option<RateLimitStatus> {
+        self.rate_limits.status()
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{Clock, CreateOrderRequest, LighterClient, Result, SendTxResponse, Side, SystemClock};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Callback invoked with every order rejection, see [`LighterClientBuilder::on_rejection`](crate::LighterClientBuilder::on_rejection)
+pub type RejectionHandler = Arc<dyn Fn(&RejectionRecord) + Send + Sync>;
+
+/// Structured description of an order the exchange did not accept
+///
+/// Serializable so it can be persisted as-is for post-mortems.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RejectionRecord {
+    /// Local time of the rejection in milliseconds since the Unix epoch
+    pub timestamp: i64,
+    pub client_order_index: u64,
+    pub market_index: u8,
+    pub price: i64,
+    pub base_amount: i64,
+    pub side: Side,
+    /// Exchange error code of the rejection (see `ApiError::code`);
+    /// `None` when the request never got an answer
+    pub code: Option<i64>,
+    pub message: String,
+}
+
+impl RejectionRecord {
+    /// Describe the outcome of an order submission, or `None` if it was accepted
+    pub fn from_outcome(order: &CreateOrderRequest, outcome: &Result<SendTxResponse>) -> Option<Self> {
+        Self::from_outcome_at(order, outcome, SystemClock.now_ms())
+    }
+
+    /// [`Self::from_outcome`] for a rejection observed at `timestamp` (unix ms)
+    pub fn from_outcome_at(order: &CreateOrderRequest, outcome: &Result<SendTxResponse>, timestamp: i64) -> Option<Self> {
+        let e = outcome.as_ref().err()?;
+
+        Some(Self {
+            timestamp,
+            client_order_index: order.client_order_index,
+            market_index: order.order_book_index,
+            price: order.price,
+            base_amount: order.base_amount,
+            side: order.side,
+            code: e.code(),
+            message: e.to_string(),
+        })
+    }
+}
+
+impl LighterClient {
+    /// Pass a rejected order to the configured handler, if any
+    pub(crate) fn report_rejection(&self, order: &CreateOrderRequest, outcome: &Result<SendTxResponse>) {
+        if let Some(handler) = &self.rejection_handler {
+            if let Some(record) = RejectionRecord::from_outcome_at(order, outcome, self.clock.now_ms()) {
+                handler(&record);
+            }
+        }
+    }
+}
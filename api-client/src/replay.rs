@@ -0,0 +1,324 @@
+use crate::response::send_tx_error;
+use crate::{ApiError, CreateOrderRequest, ErrorCode, Result, SendTxResponse, Side, TransactionSender};
+use serde_json::json;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// Response code of fill-or-kill orders the simulated venue cannot fill
+const REJECTED: i64 = 400;
+
+/// `(price, base_amount)` levels of one side, best first
+type Levels = Vec<(i64, i64)>;
+
+/// Historical market data fed to a [`ReplaySender`]
+///
+/// Prices and amounts are integers in the market's units, like
+/// [`CreateOrderRequest`]'s `price` and `base_amount`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayEvent {
+    /// Book levels as `(price, base_amount)`, best first, replacing the market's previous levels
+    Book {
+        market_index: u8,
+        timestamp: i64,
+        bids: Vec<(i64, i64)>,
+        asks: Vec<(i64, i64)>,
+    },
+    /// A trade printed by the exchange; `taker_side` is the aggressor
+    Trade {
+        market_index: u8,
+        timestamp: i64,
+        price: i64,
+        base_amount: i64,
+        taker_side: Side,
+    },
+}
+
+/// A fill produced by the simulation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulatedFill {
+    pub order_index: i64,
+    pub client_order_index: u64,
+    pub market_index: u8,
+    /// Timestamp of the feed event the fill happened at
+    pub timestamp: i64,
+    pub side: Side,
+    pub price: i64,
+    pub base_amount: i64,
+    /// `true` if a resting order was hit by a replayed trade
+    pub is_maker: bool,
+}
+
+/// An order resting in the simulation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulatedOrder {
+    pub order_index: i64,
+    pub client_order_index: u64,
+    pub market_index: u8,
+    pub side: Side,
+    pub price: i64,
+    pub remaining_base_amount: i64,
+}
+
+#[derive(Debug, Default)]
+struct ReplayState {
+    /// Timestamp of the latest event
+    timestamp: i64,
+    last_order_index: i64,
+    /// (bids, asks) per market, best first
+    books: HashMap<u8, (Levels, Levels)>,
+    /// Resting orders in arrival order
+    resting: Vec<SimulatedOrder>,
+    fills: Vec<SimulatedFill>,
+}
+
+/// Simulated venue for backtesting against recorded market data
+///
+/// Implements [`TransactionSender`], so strategy code written against the trait
+/// runs unchanged. Matching is deliberately simple and fully deterministic:
+///
+/// * An incoming order takes liquidity from the latest [`ReplayEvent::Book`]
+///   levels of its market up to its limit price (a market order's price is its
+///   worst acceptable price). Consumed size stays consumed until the next book
+///   event for that market.
+/// * The rest of a good-till-time or post-only limit order rests;
+///   immediate-or-cancel and market remainders are dropped. Fill-or-kill
+///   orders that cannot fill completely and post-only orders that would cross
+///   are rejected, failing with `ApiError::Rejected` and
+///   `ApiError::PostOnlyWouldCross` like the exchange's rejections.
+/// * A replayed [`ReplayEvent::Trade`] fills resting orders on the passive side
+///   whose price it reaches or trades through, best price first and then in
+///   arrival order, up to the traded amount, at the resting order's price.
+///
+/// Accepted transactions answer with code 200 and an empty `tx_hash`; what an
+/// order did is in [`Self::fills`] and [`Self::open_orders`]. Trigger and TWAP
+/// order types are not simulated. Cloning shares the state.
+#[derive(Debug, Clone, Default)]
+pub struct ReplaySender {
+    state: Arc<Mutex<ReplayState>>,
+}
+
+impl ReplaySender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advance the simulation by one event, returning the fills it caused
+    pub fn apply(&self, event: ReplayEvent) -> Vec<SimulatedFill> {
+        let mut state = self.state.lock().expect("replay state poisoned");
+        match event {
+            ReplayEvent::Book { market_index, timestamp, bids, asks } => {
+                state.timestamp = timestamp;
+                state.books.insert(market_index, (bids, asks));
+                Vec::new()
+            }
+            ReplayEvent::Trade { market_index, timestamp, price, base_amount, taker_side } => {
+                state.timestamp = timestamp;
+                state.match_trade(market_index, price, base_amount, taker_side.opposite())
+            }
+        }
+    }
+
+    /// Apply every event in order, returning all fills they caused
+    pub fn replay(&self, events: impl IntoIterator<Item = ReplayEvent>) -> Vec<SimulatedFill> {
+        events.into_iter().flat_map(|event| self.apply(event)).collect()
+    }
+
+    /// Every fill so far, in the order they happened
+    pub fn fills(&self) -> Vec<SimulatedFill> {
+        self.state.lock().expect("replay state poisoned").fills.clone()
+    }
+
+    /// Orders currently resting, in arrival order
+    pub fn open_orders(&self) -> Vec<SimulatedOrder> {
+        self.state.lock().expect("replay state poisoned").resting.clone()
+    }
+
+    fn submit(&self, order: &CreateOrderRequest) -> Result<SendTxResponse> {
+        if !matches!(order.order_type, 0 | 1) {
+            return Err(ApiError::Unsupported(format!(
+                "ReplaySender simulates limit and market orders only, got order type {}",
+                order.order_type
+            )));
+        }
+        if order.base_amount <= 0 || order.price <= 0 {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "price and base amount must be positive, got {} @ {}",
+                order.base_amount, order.price
+            )));
+        }
+
+        let mut state = self.state.lock().expect("replay state poisoned");
+        let available = state.available(order);
+        match order.time_in_force {
+            // Fill or kill
+            2 if available < order.base_amount => {
+                return Err(rejected(order, REJECTED, "fill-or-kill order cannot be filled completely"));
+            }
+            // Post only
+            3 if available > 0 => {
+                let code = ErrorCode::PostOnlyWouldCross.code();
+                return Err(rejected(order, code, "post-only order would cross the book"));
+            }
+            _ => {}
+        }
+
+        state.last_order_index += 1;
+        let mut resting = SimulatedOrder {
+            order_index: state.last_order_index,
+            client_order_index: order.client_order_index,
+            market_index: order.order_book_index,
+            side: order.side,
+            price: order.price,
+            remaining_base_amount: order.base_amount,
+        };
+        state.take_liquidity(&mut resting);
+
+        // Only good-till-time and post-only limit orders rest
+        let rests = order.order_type == 0 && matches!(order.time_in_force, 1 | 3);
+        if rests && resting.remaining_base_amount > 0 {
+            state.resting.push(resting);
+        }
+
+        Ok(accepted())
+    }
+
+    fn cancel(&self, market_index: u8, order_index: i64) -> Result<SendTxResponse> {
+        let mut state = self.state.lock().expect("replay state poisoned");
+        let position = state
+            .resting
+            .iter()
+            .position(|order| order.market_index == market_index && order.order_index == order_index)
+            .ok_or_else(|| ApiError::NotFound(format!("order {} on market {}", order_index, market_index)))?;
+        state.resting.remove(position);
+        Ok(accepted())
+    }
+
+    fn cancel_all(&self, time_in_force: u8) -> Result<SendTxResponse> {
+        if time_in_force != 0 {
+            return Err(ApiError::Unsupported(
+                "ReplaySender only simulates immediate cancel-all".to_string(),
+            ));
+        }
+        self.state.lock().expect("replay state poisoned").resting.clear();
+        Ok(accepted())
+    }
+}
+
+impl ReplayState {
+    /// Book levels on the side `side` trades against, best first
+    fn opposite_levels(&mut self, market_index: u8, side: Side) -> &mut Levels {
+        let (bids, asks) = self.books.entry(market_index).or_default();
+        match side {
+            Side::Buy => asks,
+            Side::Sell => bids,
+        }
+    }
+
+    /// Size the book offers within the order's limit price
+    fn available(&mut self, order: &CreateOrderRequest) -> i64 {
+        let (side, limit) = (order.side, order.price);
+        self.opposite_levels(order.order_book_index, side)
+            .iter()
+            .take_while(|(price, _)| crosses(side, limit, *price))
+            .map(|(_, size)| size)
+            .sum()
+    }
+
+    /// Fill `order` against the book as taker, consuming the levels it takes
+    fn take_liquidity(&mut self, order: &mut SimulatedOrder) -> Vec<SimulatedFill> {
+        let timestamp = self.timestamp;
+        let levels = self.opposite_levels(order.market_index, order.side);
+        let mut fills = Vec::new();
+        for (price, size) in levels.iter_mut() {
+            if order.remaining_base_amount == 0 || !crosses(order.side, order.price, *price) {
+                break;
+            }
+            let amount = order.remaining_base_amount.min(*size);
+            *size -= amount;
+            order.remaining_base_amount -= amount;
+            fills.push(fill(order, timestamp, *price, amount, false));
+        }
+        levels.retain(|(_, size)| *size > 0);
+        self.fills.extend_from_slice(&fills);
+        fills
+    }
+
+    /// Fill resting `side` orders reached by a trade at `price`
+    fn match_trade(&mut self, market_index: u8, price: i64, mut amount: i64, side: Side) -> Vec<SimulatedFill> {
+        // Price priority, then arrival order (the sort is stable)
+        let mut queue: Vec<usize> = (0..self.resting.len())
+            .filter(|&i| {
+                let order = &self.resting[i];
+                order.market_index == market_index && order.side == side && crosses(side, order.price, price)
+            })
+            .collect();
+        queue.sort_by_key(|&i| match side {
+            Side::Buy => -self.resting[i].price,
+            Side::Sell => self.resting[i].price,
+        });
+
+        let mut fills = Vec::new();
+        for i in queue {
+            if amount == 0 {
+                break;
+            }
+            let order = &mut self.resting[i];
+            let filled = amount.min(order.remaining_base_amount);
+            order.remaining_base_amount -= filled;
+            amount -= filled;
+            fills.push(fill(order, self.timestamp, order.price, filled, true));
+        }
+        self.resting.retain(|order| order.remaining_base_amount > 0);
+        self.fills.extend_from_slice(&fills);
+        fills
+    }
+}
+
+/// Whether an order on `side` limited at `limit` trades at `price`
+fn crosses(side: Side, limit: i64, price: i64) -> bool {
+    match side {
+        Side::Buy => price <= limit,
+        Side::Sell => price >= limit,
+    }
+}
+
+fn fill(order: &SimulatedOrder, timestamp: i64, price: i64, base_amount: i64, is_maker: bool) -> SimulatedFill {
+    SimulatedFill {
+        order_index: order.order_index,
+        client_order_index: order.client_order_index,
+        market_index: order.market_index,
+        timestamp,
+        side: order.side,
+        price,
+        base_amount,
+        is_maker,
+    }
+}
+
+fn accepted() -> SendTxResponse {
+    SendTxResponse { code: 200, ..SendTxResponse::default() }
+}
+
+/// Error for `order` rejected with `code`, mapped like the exchange's `sendTx` rejections
+fn rejected(order: &CreateOrderRequest, code: i64, message: &str) -> ApiError {
+    let tx_info = json!({"MarketIndex": order.order_book_index});
+    send_tx_error(&json!({"code": code, "message": message}), Some(&tx_info))
+}
+
+impl TransactionSender for ReplaySender {
+    fn create_order(&self, order: CreateOrderRequest) -> impl Future<Output = Result<SendTxResponse>> + Send {
+        let result = self.submit(&order);
+        async move { result }
+    }
+
+    fn cancel_order(&self, market_index: u8, order_index: i64) -> impl Future<Output = Result<SendTxResponse>> + Send {
+        let result = self.cancel(market_index, order_index);
+        async move { result }
+    }
+
+    fn cancel_all_orders(&self, time_in_force: u8, _time: i64) -> impl Future<Output = Result<SendTxResponse>> + Send {
+        let result = self.cancel_all(time_in_force);
+        async move { result }
+    }
+}
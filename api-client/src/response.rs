@@ -0,0 +1,72 @@
+use crate::{nonce_error, rejection_error, ApiError, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// A `sendTx` response the exchange accepted
+///
+/// A view over the response, which carries more fields (e.g.
+/// `predicted_execution_time_ms`), so the `strict-json` feature does not apply to it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct SendTxResponse {
+    /// Hash of the accepted transaction
+    pub tx_hash: String,
+    /// Always 200, as only accepted responses become a `SendTxResponse`
+    pub code: i64,
+    /// Free-form notes from the exchange, e.g. on the rate-limit quota used
+    pub message: String,
+}
+
+/// Parse the body of a `sendTx` response
+///
+/// Returns the response if the exchange accepted the transaction (code 200)
+/// and an error otherwise; see [`check_send_tx_response`] for how rejections
+/// map to errors. A body that is not JSON fails with `ApiError::Json`.
+pub fn parse_send_tx_response(text: &str) -> Result<SendTxResponse> {
+    check_send_tx_response(serde_json::from_str(text)?)
+}
+
+/// Turn a `sendTx` response into a [`SendTxResponse`], or an error unless the exchange accepted it
+///
+/// This is the mapping the submit methods apply to their responses, for
+/// responses obtained elsewhere (e.g. a `sendTx` posted by hand). Rejections
+/// because the market is closed, the exchange is in maintenance, a post-only
+/// order would cross, margin is short or the transaction expired become their
+/// typed `ApiError`s, any other code `ApiError::Rejected` with the code and
+/// message, and a response without a code `ApiError::Api`.
+pub fn check_send_tx_response(response: Value) -> Result<SendTxResponse> {
+    send_tx_result(response, None)
+}
+
+/// Whether the exchange accepted a submitted transaction
+pub(crate) fn is_accepted(response: &Value) -> bool {
+    response["code"].as_i64() == Some(200)
+}
+
+/// [`check_send_tx_response`] for a response to `tx_info`
+///
+/// With the transaction at hand the error also names its market, and nonce
+/// rejections that say which way the nonce was off become
+/// `ApiError::NonceTooLow` or `ApiError::NonceTooHigh` for its `Nonce`.
+pub(crate) fn send_tx_result(response: Value, tx_info: Option<&Value>) -> Result<SendTxResponse> {
+    if is_accepted(&response) {
+        return Ok(serde_json::from_value(response)?);
+    }
+    Err(send_tx_error(&response, tx_info))
+}
+
+/// Error for a `sendTx` response the exchange did not accept
+pub(crate) fn send_tx_error(response: &Value, tx_info: Option<&Value>) -> ApiError {
+    let tx_info = tx_info.unwrap_or(&Value::Null);
+    if let Some(error) = rejection_error(response, tx_info) {
+        return error;
+    }
+    if let Some(error) = tx_info["Nonce"].as_i64().and_then(|nonce| nonce_error(nonce, response)) {
+        return error;
+    }
+    let message = response["message"].as_str().unwrap_or_default();
+    match response["code"].as_i64() {
+        Some(code) => ApiError::Rejected { code, message: message.to_string() },
+        None => ApiError::Api(format!("response without a code: {}", response)),
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{CreateOrderRequest, LighterClient, Result, SendTxResponse};
+use std::future::Future;
+
+/// Order submission shared by the live client and simulated venues
+///
+/// Write strategy code against this trait to run the same code path live
+/// ([`LighterClient`]) and in backtests ([`crate::ReplaySender`]).
+pub trait TransactionSender {
+    /// Submit an order; see [`LighterClient::create_order`]
+    fn create_order(&self, order: CreateOrderRequest) -> impl Future<Output = Result<SendTxResponse>> + Send;
+
+    /// Cancel a resting order by its exchange order index; see [`LighterClient::cancel_order`]
+    fn cancel_order(&self, market_index: u8, order_index: i64) -> impl Future<Output = Result<SendTxResponse>> + Send;
+
+    /// Cancel every resting order; see [`LighterClient::cancel_all_orders`]
+    fn cancel_all_orders(&self, time_in_force: u8, time: i64) -> impl Future<Output = Result<SendTxResponse>> + Send;
+}
+
+impl TransactionSender for LighterClient {
+    fn create_order(&self, order: CreateOrderRequest) -> impl Future<Output = Result<SendTxResponse>> + Send {
+        LighterClient::create_order(self, order)
+    }
+
+    fn cancel_order(&self, market_index: u8, order_index: i64) -> impl Future<Output = Result<SendTxResponse>> + Send {
+        LighterClient::cancel_order(self, market_index, order_index)
+    }
+
+    fn cancel_all_orders(&self, time_in_force: u8, time: i64) -> impl Future<Output = Result<SendTxResponse>> + Send {
+        LighterClient::cancel_all_orders(self, time_in_force, time)
+    }
+}
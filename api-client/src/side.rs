@@ -0,0 +1,68 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+
+/// Order side
+///
+/// The wire format encodes the side as `IsAsk`: `Sell` is `1`, `Buy` is `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    /// Whether this side rests on the ask side of the book
+    pub fn is_ask(self) -> bool {
+        matches!(self, Side::Sell)
+    }
+
+    /// The `IsAsk` value sent in transactions
+    pub fn as_is_ask(self) -> u8 {
+        self.is_ask() as u8
+    }
+
+    pub fn from_is_ask(is_ask: bool) -> Self {
+        if is_ask {
+            Side::Sell
+        } else {
+            Side::Buy
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        })
+    }
+}
+
+/// Deserialize an `is_ask` response field (boolean or 0/1) into a [`Side`]
+pub(crate) fn side_from_is_ask<'de, D>(deserializer: D) -> std::result::Result<Side, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum IsAsk {
+        Bool(bool),
+        Int(u8),
+    }
+
+    match IsAsk::deserialize(deserializer)? {
+        IsAsk::Bool(is_ask) => Ok(Side::from_is_ask(is_ask)),
+        IsAsk::Int(0) => Ok(Side::Buy),
+        IsAsk::Int(1) => Ok(Side::Sell),
+        IsAsk::Int(other) => Err(serde::de::Error::custom(format!("invalid is_ask value {}", other))),
+    }
+}
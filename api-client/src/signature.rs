@@ -0,0 +1,95 @@
+use crate::{ApiError, Result};
+use base64::Engine;
+use serde_json::Value;
+use std::fmt;
+
+/// Length of a Schnorr signature over Goldilocks: `s || e`, 40 bytes each
+pub const SIGNATURE_LENGTH: usize = 80;
+
+/// A transaction signature, as raw bytes
+///
+/// Always exactly [`SIGNATURE_LENGTH`] bytes. Encode it with
+/// [`Self::to_base64`] for the `Sig` field of `tx_info` (what `sendTx`
+/// expects), or [`Self::to_hex`] for logs and comparing against other SDKs.
+/// Keeping the bytes behind this type means a signature cannot be encoded
+/// twice or sent raw by mistake.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Signature([u8; SIGNATURE_LENGTH]);
+
+impl Signature {
+    /// Wrap raw signature bytes; fails unless there are exactly [`SIGNATURE_LENGTH`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let bytes = bytes.try_into().map_err(|_| {
+            ApiError::InvalidSignature(format!("expected {} bytes, got {}", SIGNATURE_LENGTH, bytes.len()))
+        })?;
+        Ok(Self(bytes))
+    }
+
+    /// Read a base64 signature, e.g. the `Sig` field of a signed `tx_info`
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| ApiError::InvalidSignature(format!("not base64: {}", e)))?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Read a hex signature, with or without a `0x` prefix
+    pub fn from_hex(encoded: &str) -> Result<Self> {
+        let encoded = encoded.strip_prefix("0x").unwrap_or(encoded);
+        let bytes = hex::decode(encoded).map_err(|e| ApiError::InvalidSignature(format!("not hex: {}", e)))?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; SIGNATURE_LENGTH] {
+        &self.0
+    }
+
+    /// Standard base64 with padding, the encoding of the `Sig` field
+    pub fn to_base64(&self) -> String {
+        base64::engine::general_purpose::STANDARD.encode(self.0)
+    }
+
+    /// Lowercase hex without a `0x` prefix
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+}
+
+/// Refuse a `tx_info` whose `Sig` is missing, empty or not a [`SIGNATURE_LENGTH`]-byte base64 signature
+///
+/// Checked before any `sendTx` / `sendTxBatch` request, so a signing bug fails
+/// here with `ApiError::Unsigned` instead of as a confusing exchange rejection.
+pub(crate) fn check_signed(tx_type: u32, tx_info: &Value) -> Result<()> {
+    let sig = match tx_info.get("Sig").and_then(Value::as_str) {
+        Some(sig) if !sig.is_empty() => sig,
+        _ => return Err(ApiError::Unsigned(format!("tx_info of transaction type {} has no Sig", tx_type))),
+    };
+    Signature::from_base64(sig)
+        .map(|_| ())
+        .map_err(|e| ApiError::Unsigned(format!("tx_info of transaction type {} has a malformed Sig: {}", tx_type, e)))
+}
+
+impl From<[u8; SIGNATURE_LENGTH]> for Signature {
+    fn from(bytes: [u8; SIGNATURE_LENGTH]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl AsRef<[u8]> for Signature {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Signature({})", self.to_hex())
+    }
+}
+
+/// Formats as base64, the form the API takes
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_base64())
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{ApiError, LighterClient, Result};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use tokio::sync::oneshot;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Dedicated threads for signing transactions, separate from tokio's blocking pool
+///
+/// Signing is pure CPU work (a Poseidon2 hash and a Schnorr signature, tens of
+/// microseconds each). Running it on threads of its own keeps it from queueing
+/// behind blocking I/O on tokio's blocking pool, and the I/O from queueing
+/// behind it, which makes tail latency predictable. Size it to the cores set
+/// aside for signing. The threads are named `lighter-signer-<n>`; they are not
+/// bound to cores, so pin them with the OS (e.g. `taskset`) if needed.
+///
+/// Set one up with `LighterClientBuilder::signing_threads`, or create one here
+/// and share it between clients with `LighterClientBuilder::signing_pool`. The
+/// threads exit once the pool is dropped and their queued work is done.
+pub struct SigningPool {
+    sender: Mutex<mpsc::Sender<Job>>,
+    threads: usize,
+}
+
+impl SigningPool {
+    /// Start `threads` signing threads; fails with `ApiError::InvalidConfig` for 0
+    pub fn new(threads: usize) -> Result<Self> {
+        if threads == 0 {
+            return Err(ApiError::InvalidConfig("a signing pool needs at least one thread".to_string()));
+        }
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for n in 0..threads {
+            let receiver = receiver.clone();
+            thread::Builder::new()
+                .name(format!("lighter-signer-{}", n))
+                .spawn(move || loop {
+                    // The lock is released before the job runs, so the other threads keep taking jobs
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+                    match job {
+                        // A panicking job drops its result sender, failing only its own caller
+                        Ok(job) => drop(panic::catch_unwind(AssertUnwindSafe(job))),
+                        Err(_) => return,
+                    }
+                })
+                .map_err(|e| ApiError::InvalidConfig(format!("cannot start signing thread: {}", e)))?;
+        }
+        Ok(Self { sender: Mutex::new(sender), threads })
+    }
+
+    /// Number of signing threads
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Queue `job` now; the returned future resolves to its result
+    pub(crate) fn spawn<T, F>(&self, job: F) -> BoxFuture<'static, Result<T>>
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let (result_sender, result) = oneshot::channel();
+        let queued = self.sender.lock().map_err(|_| ()).and_then(|sender| {
+            sender
+                .send(Box::new(move || {
+                    let _ = result_sender.send(job());
+                }))
+                .map_err(|_| ())
+        });
+        async move {
+            queued.map_err(|_| ApiError::Api("signing pool is shut down".to_string()))?;
+            result
+                .await
+                .unwrap_or_else(|_| Err(ApiError::Api("signing task failed: the job panicked".to_string())))
+        }
+        .boxed()
+    }
+}
+
+impl fmt::Debug for SigningPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningPool").field("threads", &self.threads).finish()
+    }
+}
+
+impl LighterClient {
+    /// Threads of the dedicated signing pool, `None` when signing runs on tokio's blocking pool
+    pub fn signing_threads(&self) -> Option<usize> {
+        self.signing_pool.as_ref().map(|pool| pool.threads())
+    }
+
+    /// Start `job` on the signing pool, or tokio's blocking pool if none is set
+    ///
+    /// The job is queued right away; the returned future only waits for it.
+    pub(crate) fn spawn_signing<T, F>(&self, job: F) -> BoxFuture<'static, Result<T>>
+    where
+        F: FnOnce() -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        match &self.signing_pool {
+            Some(pool) => pool.spawn(job),
+            None => {
+                let handle = tokio::task::spawn_blocking(job);
+                async move {
+                    handle
+                        .await
+                        .unwrap_or_else(|e| Err(ApiError::Api(format!("signing task failed: {}", e))))
+                }
+                .boxed()
+            }
+        }
+    }
+}
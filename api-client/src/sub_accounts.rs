@@ -0,0 +1,85 @@
+use crate::account::{check_response_code, decode_list};
+use crate::{ApiError, LighterClient, Result};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+/// `account_type` of a sub-account
+const ACCOUNT_TYPE_SUB: u8 = 1;
+
+/// A sub-account with its balance summary, see [`LighterClient::get_sub_accounts`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubAccount {
+    pub account_index: i64,
+    /// Name given to the sub-account, empty if none
+    pub label: String,
+    /// USDC deposited, before unrealized PnL
+    pub collateral: Decimal,
+    /// USDC free for new orders and withdrawals
+    pub available_balance: Decimal,
+    /// Collateral plus the value of open positions, USDC
+    pub total_asset_value: Decimal,
+}
+
+/// Raw account record from the account endpoints
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+struct AccountRecord {
+    index: i64,
+    /// 0 = main account, 1 = sub-account
+    account_type: u8,
+    l1_address: String,
+    name: String,
+    collateral: Decimal,
+    available_balance: Decimal,
+    total_asset_value: Decimal,
+}
+
+impl From<AccountRecord> for SubAccount {
+    fn from(record: AccountRecord) -> Self {
+        Self {
+            account_index: record.index,
+            label: record.name,
+            collateral: record.collateral,
+            available_balance: record.available_balance,
+            total_asset_value: record.total_asset_value,
+        }
+    }
+}
+
+impl LighterClient {
+    /// List the sub-accounts of an account with their balances
+    ///
+    /// Sub-accounts belong to the L1 address of their main account, so this
+    /// looks up `parent_account_index`'s address and lists the other accounts
+    /// registered to it. An account that is itself a sub-account has no
+    /// children and yields an empty list. Fails with `ApiError::NotFound` if
+    /// `parent_account_index` does not exist.
+    pub async fn get_sub_accounts(&self, parent_account_index: i64) -> Result<Vec<SubAccount>> {
+        let response = self
+            .get_json(
+                "/api/v1/account",
+                &[("by", "index".to_string()), ("value", parent_account_index.to_string())],
+            )
+            .await?;
+        check_response_code(&response)?;
+        let parent = decode_list::<AccountRecord>(&response, "accounts")?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ApiError::NotFound(format!("account {}", parent_account_index)))?;
+        if parent.account_type == ACCOUNT_TYPE_SUB {
+            return Ok(Vec::new());
+        }
+
+        let response = self
+            .get_json("/api/v1/accountsByL1Address", &[("l1_address", parent.l1_address)])
+            .await?;
+        check_response_code(&response)?;
+        let accounts: Vec<AccountRecord> = decode_list(&response, "sub_accounts")?;
+        Ok(accounts
+            .into_iter()
+            .filter(|account| account.index != parent_account_index && account.account_type == ACCOUNT_TYPE_SUB)
+            .map(SubAccount::from)
+            .collect())
+    }
+}
@@ -0,0 +1,4520 @@
+use crate::{
+    ApiError, LighterClient, NetworkErrorKind, OrderExpiry, Side, DEFAULT_TX_EXPIRY, MAX_TX_EXPIRY,
+    SIGNATURE_SCHEME_VERSION,
+};
+use futures::StreamExt;
+use signer::KeyManager;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const TESTNET_URL: &str = "https://testnet.zklighter.elliot.ai";
+
+fn test_key_hex() -> String {
+    hex::encode(KeyManager::generate().private_key_bytes())
+}
+
+/// Start a local HTTP server answering every request with the JSON body produced
+/// by `handler` (which receives the raw request text). Returns the base URL.
+async fn mock_server<F>(handler: F) -> String
+where
+    F: Fn(&str) -> String + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handler = Arc::new(handler);
+
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let request = read_request(&mut socket).await;
+                let body = handler(&request);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+
+    format!("http://{}", addr)
+}
+
+/// Wait for the next text message on `ws` and append it to `log`
+async fn record_text(
+    ws: &mut tokio_tungstenite::WebSocketStream<TcpStream>,
+    log: &std::sync::Mutex<Vec<String>>,
+) {
+    use tokio_tungstenite::tungstenite::Message;
+
+    while let Some(Ok(message)) = ws.next().await {
+        if let Message::Text(text) = message {
+            log.lock().unwrap().push(text);
+            return;
+        }
+    }
+}
+
+/// Frame in a [`mock_ws_server`] script that waits for the next client message instead of sending
+const AWAIT_CLIENT: &str = "";
+
+/// Frame in a [`mock_ws_server`] script that holds the connection without reading or answering pings
+const STALL: &str = "stall";
+
+/// Start a local WebSocket server that waits for the client's subscribe message,
+/// then plays `script` (text frames to send, [`AWAIT_CLIENT`] or [`STALL`]) and
+/// closes. Returns the base URL and every client message received.
+async fn mock_ws_server(script: Vec<String>) -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+    mock_ws_sessions(vec![script]).await
+}
+
+/// Like [`mock_ws_server`], playing one script per accepted connection in turn
+async fn mock_ws_sessions(scripts: Vec<Vec<String>>) -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+    use futures::SinkExt;
+    use tokio_tungstenite::tungstenite::Message;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let log = received.clone();
+    tokio::spawn(async move {
+        for script in scripts {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            let log = log.clone();
+            tokio::spawn(async move {
+                record_text(&mut ws, &log).await;
+                for frame in script {
+                    if frame == AWAIT_CLIENT {
+                        record_text(&mut ws, &log).await;
+                    } else if frame == STALL {
+                        tokio::time::sleep(Duration::from_secs(5)).await;
+                    } else {
+                        ws.send(Message::Text(frame)).await.unwrap();
+                    }
+                }
+                let _ = ws.close(None).await;
+            });
+        }
+    });
+
+    (format!("http://{}", addr), received)
+}
+
+async fn read_request(socket: &mut TcpStream) -> String {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let n = socket.read(&mut buf).await.unwrap_or(0);
+        if n == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..n]);
+        let text = String::from_utf8_lossy(&data);
+        if let Some(header_end) = text.find("\r\n\r\n") {
+            let content_length = text[..header_end]
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if data.len() >= header_end + 4 + content_length {
+                break;
+            }
+        }
+    }
+    String::from_utf8_lossy(&data).into_owned()
+}
+
+#[test]
+fn test_default_expiry() {
+    let client = LighterClient::new(TESTNET_URL.to_string(), &test_key_hex(), 1, 0).unwrap();
+    assert_eq!(client.default_expiry(), DEFAULT_TX_EXPIRY);
+}
+
+#[test]
+fn test_custom_expiry() {
+    let client = LighterClient::builder(TESTNET_URL.to_string(), &test_key_hex(), 1, 0)
+        .default_expiry(Duration::from_secs(60))
+        .build()
+        .unwrap();
+    assert_eq!(client.default_expiry(), Duration::from_secs(60));
+}
+
+#[test]
+fn test_expiry_beyond_server_limit_is_rejected() {
+    let result = LighterClient::builder(TESTNET_URL.to_string(), &test_key_hex(), 1, 0)
+        .default_expiry(MAX_TX_EXPIRY + Duration::from_millis(1))
+        .build();
+    assert!(matches!(result, Err(ApiError::InvalidConfig(_))));
+
+    let result = LighterClient::builder(TESTNET_URL.to_string(), &test_key_hex(), 1, 0)
+        .default_expiry(Duration::ZERO)
+        .build();
+    assert!(matches!(result, Err(ApiError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_debug_output_redacts_private_key() {
+    let key = test_key_hex();
+    let builder = LighterClient::builder(TESTNET_URL.to_string(), &key, 1, 0);
+    assert!(!format!("{:?}", builder).contains(&key));
+
+    let client = builder.build().unwrap();
+    let debug = format!("{:?}", client);
+    assert!(!debug.contains(&key));
+    assert!(debug.contains("<redacted>"));
+}
+
+#[test]
+fn test_bad_key_hex_error_does_not_echo_key() {
+    let bad_key = format!("{}zz", &test_key_hex()[2..]);
+    let err = LighterClient::new(TESTNET_URL.to_string(), &bad_key, 1, 0).unwrap_err();
+    let message = err.to_string();
+    assert!(!message.contains('z'), "error leaked key characters: {}", message);
+}
+
+#[tokio::test]
+async fn test_order_history_stream_follows_cursor_and_ends() {
+    let base_url = mock_server(|request| {
+        if request.contains("cursor=page2") {
+            r#"{"code":200,"orders":[{"order_index":3,"status":"canceled"}],"next_cursor":""}"#
+        } else {
+            r#"{"code":200,"orders":[{"order_index":1,"price":"3500.25","status":"filled"},{"order_index":2,"status":"filled"}],"next_cursor":"page2"}"#
+        }
+        .to_string()
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let orders: Vec<_> = client.order_history_stream(1, None).collect().await;
+    let indices: Vec<i64> = orders.into_iter().map(|o| o.unwrap().order_index).collect();
+    assert_eq!(indices, vec![1, 2, 3]);
+
+    let page = client.get_order_history(1, Some(0), None).await.unwrap();
+    assert_eq!(page.next_cursor.as_deref(), Some("page2"));
+    assert_eq!(page.orders[0].price.to_string(), "3500.25");
+}
+
+#[test]
+fn test_realized_pnl_long_and_short() {
+    use crate::account::realized_pnl;
+    use rust_decimal::Decimal;
+
+    let d = |s: &str| s.parse::<Decimal>().unwrap();
+
+    // Long 2 @ 100, sell 1 @ 110 -> +10
+    assert_eq!(realized_pnl(Side::Sell, d("110"), d("1"), d("2"), d("200")), d("10"));
+    // Short 2 @ 100, buy 1 @ 110 -> -10
+    assert_eq!(realized_pnl(Side::Buy, d("110"), d("1"), d("-2"), d("200")), d("-10"));
+    // Short 1 @ 100, buy 3 @ 90 -> only 1 closes: +10
+    assert_eq!(realized_pnl(Side::Buy, d("90"), d("3"), d("-1"), d("100")), d("10"));
+    // Adding to a long realizes nothing
+    assert_eq!(realized_pnl(Side::Buy, d("90"), d("1"), d("2"), d("200")), Decimal::ZERO);
+}
+
+#[tokio::test]
+async fn test_get_my_trades_filters_range_and_takes_account_side() {
+    let base_url = mock_server(|_| {
+        r#"{"code":200,"trades":[
+            {"trade_id":1,"timestamp":1000,"price":"100","size":"1","ask_account_id":7,"bid_account_id":9,"is_maker_ask":true,"maker_fee":"0.01","taker_fee":"0.02"},
+            {"trade_id":2,"timestamp":2000,"price":"101","size":"2","ask_account_id":9,"bid_account_id":7,"is_maker_ask":true,"maker_fee":"0.01","taker_fee":"0.02"},
+            {"trade_id":3,"timestamp":3000,"price":"102","size":"1","ask_account_id":7,"bid_account_id":9,"is_maker_ask":false,"maker_fee":"0.01","taker_fee":"0.02"}
+        ],"next_cursor":"more"}"#
+            .to_string()
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 7, 0).unwrap();
+
+    let trades = client.get_my_trades(7, Some(0), Some(1000), Some(2500)).await.unwrap();
+    assert_eq!(trades.len(), 2);
+    assert!(trades[0].side == Side::Sell && trades[0].is_maker);
+    assert_eq!(trades[0].fee.to_string(), "0.01");
+    assert!(trades[1].side == Side::Buy && !trades[1].is_maker);
+    assert_eq!(trades[1].fee.to_string(), "0.02");
+}
+
+#[tokio::test]
+async fn test_get_closed_positions_aggregates_fills_into_lifecycles() {
+    let base_url = mock_server(|request| {
+        if request.contains("cursor=p2") {
+            r#"{"code":200,"trades":[
+                {"trade_id":3,"timestamp":3000,"price":"115","size":"1","ask_account_id":9,"bid_account_id":7,"is_maker_ask":true,"taker_fee":"0.05","taker_position_size_before":"-1","taker_entry_quote_before":"120"},
+                {"trade_id":4,"timestamp":4000,"price":"116","size":"1","ask_account_id":9,"bid_account_id":7,"is_maker_ask":true,"taker_fee":"0.05"}
+            ]}"#
+        } else {
+            r#"{"code":200,"trades":[
+                {"trade_id":1,"timestamp":1000,"price":"110","size":"1","ask_account_id":9,"bid_account_id":7,"is_maker_ask":true,"taker_fee":"0.1","taker_position_size_before":"1","taker_entry_quote_before":"100"},
+                {"trade_id":2,"timestamp":2000,"price":"120","size":"3","ask_account_id":7,"bid_account_id":9,"is_maker_ask":true,"maker_fee":"0.3","maker_position_size_before":"2","maker_entry_quote_before":"210"}
+            ],"next_cursor":"p2"}"#
+        }
+        .to_string()
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 7, 0).unwrap();
+
+    let positions = client.get_closed_positions(7, Some(0), None, None).await.unwrap();
+    assert_eq!(positions.len(), 2);
+
+    // Long 1 @ 100 held before the range, added 1 @ 110, closed by a flipping sell of 3 @ 120
+    let long = &positions[0];
+    assert_eq!((long.opened_at, long.closed_at), (None, 2000));
+    assert_eq!(long.size.to_string(), "2");
+    assert_eq!((long.avg_entry_price.to_string(), long.avg_exit_price.to_string()), ("105".into(), "120".into()));
+    assert_eq!(long.realized_pnl.to_string(), "30");
+    assert_eq!(long.fees.to_string(), "0.3");
+
+    // The flip's remaining 1 short @ 120, bought back @ 115; the last buy is still open
+    let short = &positions[1];
+    assert_eq!((short.opened_at, short.closed_at), (Some(2000), 3000));
+    assert_eq!(short.size.to_string(), "-1");
+    assert_eq!((short.avg_entry_price.to_string(), short.avg_exit_price.to_string()), ("120".into(), "115".into()));
+    assert_eq!(short.realized_pnl.to_string(), "5");
+    assert_eq!(short.fees.to_string(), "0.15");
+    assert_eq!(short.net_pnl().to_string(), "4.85");
+}
+
+#[tokio::test]
+async fn test_verify_credentials_reports_misconfiguration() {
+    let key = KeyManager::generate();
+    let key_hex = hex::encode(key.private_key_bytes());
+    let registered = format!("0x{}", hex::encode(key.public_key_bytes()));
+
+    let base_url = mock_server(move |request| {
+        if request.contains("/api/v1/account?") {
+            return if request.contains("value=1") {
+                r#"{"code":200,"accounts":[{"index":1}]}"#.to_string()
+            } else {
+                r#"{"code":200,"accounts":[]}"#.to_string()
+            };
+        }
+        let public_key = if request.contains("api_key_index=0") {
+            registered.clone()
+        } else if request.contains("api_key_index=2") {
+            format!("0x{}", "ab".repeat(40))
+        } else {
+            return r#"{"code":200,"api_keys":[]}"#.to_string();
+        };
+        let index = if request.contains("api_key_index=0") { 0 } else { 2 };
+        format!(r#"{{"code":200,"api_keys":[{{"account_index":1,"api_key_index":{},"public_key":"{}"}}]}}"#, index, public_key)
+    })
+    .await;
+    let client = |account_index, api_key_index| {
+        LighterClient::new(base_url.clone(), &key_hex, account_index, api_key_index).unwrap()
+    };
+
+    client(1, 0).verify_credentials().await.unwrap();
+    assert!(matches!(client(9, 0).verify_credentials().await, Err(ApiError::NotFound(_))));
+    let mismatch = client(1, 2).verify_credentials().await.unwrap_err();
+    assert!(matches!(&mismatch, ApiError::InvalidConfig(m) if m.contains("registered with public key")), "{}", mismatch);
+    let unregistered = client(1, 3).verify_credentials().await.unwrap_err();
+    assert!(matches!(&unregistered, ApiError::InvalidConfig(m) if m.contains("no API key")), "{}", unregistered);
+}
+
+#[tokio::test]
+async fn test_wait_for_key_active_polls_until_registered_or_times_out() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let key = KeyManager::generate();
+    let key_hex = hex::encode(key.private_key_bytes());
+    let new_key = hex::encode(key.public_key_bytes());
+
+    // The old key, then a lookup rejected mid-propagation, then the new key
+    let lookups = Arc::new(AtomicUsize::new(0));
+    let base_url = {
+        let lookups = lookups.clone();
+        mock_server(move |request| {
+            assert!(request.contains("/api/v1/apikeys") && request.contains("api_key_index=3"));
+            let public_key = match lookups.fetch_add(1, Ordering::SeqCst) {
+                0 => "ab".repeat(40),
+                1 => return r#"{"code":21109,"message":"api key not found"}"#.to_string(),
+                _ => new_key.clone(),
+            };
+            format!(r#"{{"code":200,"api_keys":[{{"account_index":1,"api_key_index":3,"public_key":"0x{}"}}]}}"#, public_key)
+        })
+        .await
+    };
+    let client = LighterClient::new(base_url, &key_hex, 1, 3).unwrap();
+    client.wait_for_key_active(3, Duration::from_secs(5)).await.unwrap();
+    assert_eq!(lookups.load(Ordering::SeqCst), 3);
+
+    // A key that never shows up times out after the deadline, not a poll later
+    let base_url = mock_server(|_| r#"{"code":200,"api_keys":[]}"#.to_string()).await;
+    let client = LighterClient::new(base_url, &key_hex, 1, 3).unwrap();
+    let started = std::time::Instant::now();
+    match client.wait_for_key_active(3, Duration::from_millis(700)).await {
+        Err(ApiError::KeyActivationTimeout { api_key_index: 3, waited }) => assert!(waited >= Duration::from_millis(700)),
+        other => panic!("expected KeyActivationTimeout, got {:?}", other),
+    }
+    assert!(started.elapsed() < Duration::from_millis(700) + crate::KEY_ACTIVE_POLL_INTERVAL);
+}
+
+#[tokio::test]
+async fn test_get_funding_payments_follows_pages_within_range() {
+    let base_url = mock_server(|request| {
+        assert!(request.contains("/api/v1/positionFunding"));
+        if request.contains("cursor=p2") {
+            r#"{"code":200,"position_fundings":[
+                {"timestamp":3000,"market_id":1,"rate":"-0.0002","change":"0.30","position_size":"2","position_side":"short"},
+                {"timestamp":4000,"market_id":1,"rate":"0.0001","change":"0.10","position_size":"2","position_side":"short"}
+            ],"next_cursor":"p3"}"#
+        } else {
+            r#"{"code":200,"position_fundings":[
+                {"timestamp":1000,"market_id":0,"rate":"0.0001","change":"-0.50","position_size":"1.5","position_side":"long"},
+                {"timestamp":2000,"market_id":0,"rate":"0.0001","change":"-0.50","position_size":"1.5","position_side":"long"}
+            ],"next_cursor":"p2"}"#
+        }
+        .to_string()
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let payments = client.get_funding_payments(1, None, Some(2000), Some(3500)).await.unwrap();
+    assert_eq!(payments.len(), 2);
+    assert_eq!(payments[0].amount.to_string(), "-0.50");
+    assert_eq!(payments[0].position_size.to_string(), "1.5");
+    assert_eq!((payments[1].market_index, payments[1].timestamp), (1, 3000));
+    assert_eq!(payments[1].amount.to_string(), "0.30");
+    assert_eq!(payments[1].position_size.to_string(), "-2");
+}
+
+#[tokio::test]
+async fn test_get_order_by_client_order_index() {
+    let base_url = mock_server(|request| {
+        if request.contains("/api/v1/accountActiveOrders") {
+            r#"{"code":200,"orders":[{"order_index":10,"client_order_index":1,"status":"open",
+                "filled_base_amount":"0.5","filled_quote_amount":"1500.5"}]}"#
+        } else if request.contains("/api/v1/accountInactiveOrders") {
+            r#"{"code":200,"orders":[{"order_index":9,"client_order_index":2,"status":"filled"}],"next_cursor":"x"}"#
+        } else {
+            r#"{"code":200,"accounts":[{"positions":[{"market_id":0,"open_order_count":1}]}]}"#
+        }
+        .to_string()
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let open = client.get_order(1, 1).await.unwrap().unwrap();
+    assert_eq!(open.status, "open");
+    assert_eq!(open.average_fill_price().unwrap().to_string(), "3001");
+    let filled = client.get_order(1, 2).await.unwrap().unwrap();
+    assert_eq!((filled.order_index, filled.average_fill_price()), (9, None));
+    assert!(client.get_order(1, 3).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_reconcile_orders_diffs_local_and_active_orders() {
+    let base_url = mock_server(|request| {
+        if request.contains("/api/v1/accountActiveOrders") && request.contains("market_id=2") {
+            r#"{"code":200,"orders":[{"order_index":20,"client_order_index":5,"market_index":2}]}"#
+        } else if request.contains("/api/v1/accountActiveOrders") {
+            r#"{"code":200,"orders":[{"order_index":10,"client_order_index":1},{"order_index":11,"client_order_index":2}]}"#
+        } else {
+            r#"{"code":200,"accounts":[{"positions":[
+                {"market_id":0,"open_order_count":2},
+                {"market_id":1,"open_order_count":0},
+                {"market_id":2,"open_order_count":1}
+            ]}]}"#
+        }
+        .to_string()
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let diff = client.reconcile_orders(&[1, 3, 5]).await.unwrap();
+    let matched: Vec<i64> = diff.matched.iter().map(|o| o.order_index).collect();
+    let unknown: Vec<i64> = diff.unknown.iter().map(|o| o.order_index).collect();
+    assert_eq!(matched, vec![10, 20]);
+    assert_eq!(unknown, vec![11]);
+    assert_eq!(diff.missing, vec![3]);
+    assert!(!diff.is_consistent());
+}
+
+#[tokio::test]
+async fn test_expired_orders_are_judged_by_the_client_clock() {
+    use crate::Clock;
+
+    #[derive(Debug)]
+    struct FixedClock(i64);
+    impl Clock for FixedClock {
+        fn now_ms(&self) -> i64 {
+            self.0
+        }
+    }
+
+    const NOW: i64 = 1_700_000_000_000;
+    let base_url = mock_server(|request| {
+        if request.contains("/api/v1/accountActiveOrders") {
+            format!(
+                r#"{{"code":200,"orders":[
+                    {{"order_index":10,"client_order_index":1,"order_expiry":0}},
+                    {{"order_index":11,"client_order_index":2,"order_expiry":{}}},
+                    {{"order_index":12,"client_order_index":3,"order_expiry":{}}}
+                ]}}"#,
+                NOW,
+                NOW + 60_000
+            )
+        } else {
+            r#"{"code":200,"accounts":[{"positions":[{"market_id":0,"open_order_count":3}]}]}"#.to_string()
+        }
+    })
+    .await;
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .clock(FixedClock(NOW))
+        .build()
+        .unwrap();
+
+    // Expiring exactly now counts as expired; no expiry never does
+    let expired = client.get_expired_orders(1).await.unwrap();
+    assert_eq!(expired.iter().map(|o| o.order_index).collect::<Vec<_>>(), vec![11]);
+
+    // A tracked order that expired is dead: listed as expired and missing, not matched
+    let diff = client.reconcile_orders(&[1, 2]).await.unwrap();
+    assert_eq!(diff.matched.iter().map(|o| o.order_index).collect::<Vec<_>>(), vec![10]);
+    assert_eq!(diff.unknown.iter().map(|o| o.order_index).collect::<Vec<_>>(), vec![12]);
+    assert_eq!(diff.expired.iter().map(|o| o.order_index).collect::<Vec<_>>(), vec![11]);
+    assert_eq!(diff.missing, vec![2]);
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_blocking_client_without_async_runtime() {
+    // The mock server runs on its own runtime's worker threads
+    let server_runtime = tokio::runtime::Runtime::new().unwrap();
+    let base_url = server_runtime.block_on(async {
+        mock_server(|request| {
+            if request.contains("/api/v1/nextNonce") {
+                r#"{"code":200,"nonce":41}"#.to_string()
+            } else {
+                r#"{"code":200,"accounts":[{"positions":[{"market_id":0,"symbol":"ETH","sign":-1,"position":"2","avg_entry_price":"3000","position_value":"6000"}]}]}"#.to_string()
+            }
+        })
+        .await
+    });
+    let client = crate::blocking::LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    assert_eq!(client.get_nonce(false).unwrap(), 41);
+    assert_eq!(client.get_nonce(false).unwrap(), 42);
+    let positions = client.get_positions(1).unwrap();
+    assert_eq!(positions[0].size.to_string(), "-2");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn test_max_in_flight_bounds_concurrent_send_tx() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let active = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let base_url = {
+        let (active, peak) = (active.clone(), peak.clone());
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":1}"#.to_string();
+            }
+            let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(100));
+            active.fetch_sub(1, Ordering::SeqCst);
+            r#"{"code":200,"tx_hash":"0x1"}"#.to_string()
+        })
+        .await
+    };
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .max_in_flight(2)
+        .build()
+        .unwrap();
+
+    let tasks: Vec<_> = (0..6)
+        .map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move { client.cancel_order(0, i).await })
+        })
+        .collect();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(client.in_flight_requests() <= 2);
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+    assert_eq!(client.in_flight_requests(), 0);
+
+    let result = LighterClient::builder(TESTNET_URL.to_string(), &test_key_hex(), 1, 0)
+        .max_in_flight(0)
+        .build();
+    assert!(matches!(result, Err(ApiError::InvalidConfig(_))));
+}
+
+#[tokio::test]
+async fn test_buffered_stream_strategies() {
+    use crate::{Backpressure, BufferedStream};
+    use futures::stream::{self, BoxStream};
+
+    fn source() -> BoxStream<'static, crate::Result<u32>> {
+        let items = (0..100).map(Ok).chain(std::iter::once(Err(ApiError::Api("closed".to_string()))));
+        stream::iter(items).boxed()
+    }
+    // Let the reading task run ahead of a consumer that has not started yet
+    async fn drain(mut stream: BufferedStream<u32>) -> (Vec<u32>, u64) {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let mut items = Vec::new();
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok(item) => items.push(item),
+                Err(e) => assert!(matches!(e, ApiError::Api(_))),
+            }
+        }
+        (items, stream.dropped())
+    }
+
+    let (items, dropped) = drain(BufferedStream::new(source(), Backpressure::Block)).await;
+    assert_eq!(items, (0..100).collect::<Vec<_>>());
+    assert_eq!(dropped, 0);
+
+    let (items, dropped) = drain(BufferedStream::new(source(), Backpressure::DropOldest(10))).await;
+    assert_eq!(items, (90..100).collect::<Vec<_>>());
+    assert_eq!(dropped, 90);
+
+    let (items, dropped) = drain(BufferedStream::new(source(), Backpressure::LatestOnly)).await;
+    assert_eq!(items, vec![99]);
+    assert_eq!(dropped, 99);
+}
+
+#[tokio::test]
+async fn test_ping_returns_latency() {
+    let base_url = mock_server(|request| {
+        assert!(request.starts_with("GET / "));
+        r#"{"status":200,"network_id":1,"timestamp":1700000000}"#.to_string()
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let latency = client.ping().await.unwrap();
+    assert!(latency < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_connect_warms_up_and_fails_when_unreachable() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let requests = Arc::new(AtomicUsize::new(0));
+    let base_url = {
+        let requests = requests.clone();
+        mock_server(move |request| {
+            assert!(request.starts_with("GET / "));
+            requests.fetch_add(1, Ordering::SeqCst);
+            r#"{"status":200}"#.to_string()
+        })
+        .await
+    };
+    LighterClient::connect(base_url, &test_key_hex(), 1, 0).await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let result = LighterClient::connect(format!("http://{}", addr), &test_key_hex(), 1, 0).await;
+    assert!(matches!(result, Err(ApiError::Network { .. })));
+}
+
+#[tokio::test]
+async fn test_ping_reports_connection_refused() {
+    // Grab a free port, then close it so nothing is listening
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let client = LighterClient::new(format!("http://{}", addr), &test_key_hex(), 1, 0).unwrap();
+    match client.ping().await {
+        Err(ApiError::Network { kind, .. }) => assert_eq!(kind, NetworkErrorKind::ConnectionRefused),
+        other => panic!("expected connection refused, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_check_signature_scheme() {
+    use crate::health::check_scheme_version;
+
+    match check_scheme_version(Some(SIGNATURE_SCHEME_VERSION + 1)) {
+        Err(ApiError::SignatureSchemeMismatch { client, server }) => {
+            assert_eq!(client, SIGNATURE_SCHEME_VERSION);
+            assert_eq!(server, SIGNATURE_SCHEME_VERSION + 1);
+        }
+        other => panic!("expected scheme mismatch, got {:?}", other),
+    }
+    assert!(check_scheme_version(Some(SIGNATURE_SCHEME_VERSION)).unwrap());
+
+    // The server's version is unknown, so the check reports that it verified nothing
+    let client = LighterClient::new(TESTNET_URL.to_string(), &test_key_hex(), 1, 0).unwrap();
+    assert_eq!(client.get_signature_scheme_version().await.unwrap(), None);
+    assert!(!client.check_signature_scheme().await.unwrap());
+}
+
+#[test]
+fn test_client_is_shared_across_os_threads() {
+    use crate::CreateOrderRequest;
+    use std::collections::HashSet;
+
+    // One client borrowed by a pool of threads, none of them reconstructing it
+    let client = LighterClient::new(TESTNET_URL.to_string(), &test_key_hex(), 1, 0).unwrap();
+    let signatures: Vec<_> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..4i64)
+            .map(|worker| {
+                let client = &client;
+                scope.spawn(move || {
+                    (0..8)
+                        .map(|n| {
+                            let mut tx = CreateOrderRequest::limit(0, Side::Buy, 100, 300_000).to_tx().unwrap();
+                            tx.header = client.tx_header(worker * 8 + n).unwrap();
+                            client.sign_transaction(&tx).unwrap()
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+    });
+    let distinct: HashSet<_> = signatures.iter().map(|signature| signature.to_hex()).collect();
+    assert_eq!(distinct.len(), 32);
+}
+
+#[tokio::test]
+async fn test_cloned_client_shares_nonces_across_tasks() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    fn assert_send_sync<T: Send + Sync + Clone>() {}
+    assert_send_sync::<LighterClient>();
+
+    let nonce_fetches = Arc::new(AtomicUsize::new(0));
+    let submitted = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let nonce_fetches = nonce_fetches.clone();
+        let submitted = submitted.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                nonce_fetches.fetch_add(1, Ordering::SeqCst);
+                return r#"{"code":200,"nonce":40}"#.to_string();
+            }
+            // Form bodies are urlencoded: ..."Nonce":41,... becomes %22Nonce%22%3A41%2C
+            let nonce = request
+                .split("%22Nonce%22%3A")
+                .nth(1)
+                .and_then(|rest| rest.split('%').next())
+                .and_then(|digits| digits.parse::<i64>().ok())
+                .expect("sendTx body carries a nonce");
+            submitted.lock().unwrap().push(nonce);
+            r#"{"code":200}"#.to_string()
+        })
+        .await
+    };
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let tasks: Vec<_> = (0..16)
+        .map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move { client.cancel_order(0, i).await })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+
+    let mut nonces = submitted.lock().unwrap().clone();
+    nonces.sort_unstable();
+    assert_eq!(nonces, (40..56).collect::<Vec<i64>>());
+    assert_eq!(nonce_fetches.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_rejected_order_reports_structured_record() {
+    use crate::{CreateOrderRequest, RejectionRecord};
+    use std::sync::Mutex;
+
+    let base_url = mock_server(|request| {
+        if request.contains("/api/v1/nextNonce") {
+            r#"{"code":200,"nonce":5}"#.to_string()
+        } else {
+            r#"{"code":21706,"message":"invalid order base or quote amount"}"#.to_string()
+        }
+    })
+    .await;
+
+    let records: Arc<Mutex<Vec<RejectionRecord>>> = Arc::new(Mutex::new(Vec::new()));
+    let sink = records.clone();
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .on_rejection(move |record| sink.lock().unwrap().push(record.clone()))
+        .build()
+        .unwrap();
+
+    let result = client
+        .create_order(CreateOrderRequest {
+            account_index: 1,
+            order_book_index: 3,
+            client_order_index: 77,
+            base_amount: 0,
+            price: 250_000,
+            side: Side::Sell,
+            order_type: 0,
+            time_in_force: 1,
+            reduce_only: false,
+            trigger_price: 0,
+            order_expiry: OrderExpiry::None,
+            price_protection: None,
+        })
+        .await;
+    assert!(matches!(result, Err(ApiError::Rejected { code: 21706, .. })), "{:?}", result);
+
+    let records = records.lock().unwrap();
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.client_order_index, 77);
+    assert_eq!(record.market_index, 3);
+    assert_eq!(record.price, 250_000);
+    assert_eq!(record.base_amount, 0);
+    assert_eq!(record.side, Side::Sell);
+    assert_eq!(record.code, Some(21706));
+    assert_eq!(record.message, "Rejected with code 21706: invalid order base or quote amount");
+    assert!(record.timestamp > 0);
+}
+
+#[test]
+fn test_slippage_guard_bounds_by_side() {
+    use crate::{BookLevel, OrderBookSnapshot, SlippageGuard};
+    use rust_decimal::Decimal;
+
+    let level = |price: &str| BookLevel {
+        price: price.parse().unwrap(),
+        remaining_base_amount: Decimal::ONE,
+    };
+    let book = OrderBookSnapshot {
+        bids: vec![level("3000.00"), level("2999.50")],
+        asks: vec![level("3001.00"), level("3002.00")],
+    };
+    let guard = SlippageGuard::new("0.5".parse().unwrap()).unwrap();
+
+    // Buy: 3001.00 * 1.005 = 3016.005 -> floored to 301600 at 2 decimals
+    assert_eq!(guard.protected_price(Some(&book), Side::Buy, 2).unwrap(), 301_600);
+    // Sell: 3000.00 * 0.995 = 2985.00
+    assert_eq!(guard.protected_price(Some(&book), Side::Sell, 2).unwrap(), 298_500);
+
+    assert!(matches!(guard.protected_price(None, Side::Buy, 2), Err(ApiError::NotFound(_))));
+    let no_asks = OrderBookSnapshot { bids: book.bids.clone(), asks: vec![] };
+    assert!(matches!(guard.protected_price(Some(&no_asks), Side::Buy, 2), Err(ApiError::NotFound(_))));
+
+    assert!(SlippageGuard::new(Decimal::ZERO).is_err());
+    assert!(SlippageGuard::new(Decimal::ONE_HUNDRED).is_err());
+}
+
+#[test]
+fn test_side_maps_to_is_ask() {
+    // Pinned wire mapping: a sell is an ask
+    assert_eq!(Side::Sell.as_is_ask(), 1);
+    assert_eq!(Side::Buy.as_is_ask(), 0);
+    assert_eq!(Side::from_is_ask(true), Side::Sell);
+    assert_eq!(Side::from_is_ask(false), Side::Buy);
+
+    let order: crate::Order = serde_json::from_value(serde_json::json!({"is_ask": true})).unwrap();
+    assert_eq!(order.side, Side::Sell);
+    let order: crate::Order = serde_json::from_value(serde_json::json!({"is_ask": 0})).unwrap();
+    assert_eq!(order.side, Side::Buy);
+}
+
+/// Pinned defaults of the one-line order constructors
+#[test]
+fn test_market_and_limit_constructor_defaults() {
+    use crate::CreateOrderRequest;
+    use serde_json::json;
+
+    let market = CreateOrderRequest::market(2, Side::Sell, 100, 290_000);
+    assert_eq!(
+        serde_json::to_value(&market).unwrap(),
+        json!({
+            "account_index": 0, "order_book_index": 2, "client_order_index": 0, "base_amount": 100,
+            "price": 290_000, "side": serde_json::to_value(Side::Sell).unwrap(), "order_type": 1,
+            "time_in_force": 0, "reduce_only": false, "trigger_price": 0,
+            "order_expiry": serde_json::to_value(OrderExpiry::None).unwrap(), "price_protection": null,
+        })
+    );
+
+    let limit = CreateOrderRequest::limit(2, Side::Buy, 100, 300_000);
+    assert_eq!(
+        serde_json::to_value(&limit).unwrap(),
+        json!({
+            "account_index": 0, "order_book_index": 2, "client_order_index": 0, "base_amount": 100,
+            "price": 300_000, "side": serde_json::to_value(Side::Buy).unwrap(), "order_type": 0,
+            "time_in_force": 1, "reduce_only": false, "trigger_price": 0,
+            "order_expiry": serde_json::to_value(OrderExpiry::None).unwrap(), "price_protection": null,
+        })
+    );
+
+    // Both are legal combinations as constructed
+    assert!(market.to_tx_at(1_700_000_000_000).is_ok());
+    assert!(limit.to_tx_at(1_700_000_000_000).is_ok());
+}
+
+/// Every order type and time in force pairing outside the documented matrix is refused before signing
+#[test]
+fn test_order_type_time_in_force_matrix() {
+    use crate::CreateOrderRequest;
+
+    let order = |order_type, time_in_force, order_expiry| CreateOrderRequest {
+        account_index: 1,
+        order_book_index: 0,
+        client_order_index: 7,
+        base_amount: 100,
+        price: 300_000,
+        side: Side::Buy,
+        order_type,
+        time_in_force,
+        reduce_only: false,
+        trigger_price: 0,
+        order_expiry,
+        price_protection: None,
+    };
+    let now = 1_700_000_000_000;
+
+    // Rows: limit, market, stop-loss, stop-loss limit, take-profit, take-profit limit, TWAP
+    // Columns: immediate-or-cancel, good-till-time, fill-or-kill, post-only
+    let legal = [
+        [true, true, true, true],
+        [true, false, true, false],
+        [true, false, true, false],
+        [true, true, true, false],
+        [true, false, true, false],
+        [true, true, true, false],
+        [false, true, false, false],
+    ];
+    for (order_type, row) in legal.iter().enumerate() {
+        for (time_in_force, &allowed) in row.iter().enumerate() {
+            let result = order(order_type as u8, time_in_force as u8, OrderExpiry::None).to_tx_at(now);
+            if allowed {
+                assert!(result.is_ok(), "type {} / tif {}: {:?}", order_type, time_in_force, result.err());
+            } else {
+                let Err(ApiError::InvalidOrderParams(message)) = result else {
+                    panic!("type {} / tif {} accepted", order_type, time_in_force);
+                };
+                assert!(message.contains("allowed:"), "{}", message);
+            }
+        }
+    }
+
+    // Out-of-range values, and an expiry on an order that cannot rest
+    let day = Duration::from_secs(24 * 60 * 60);
+    for invalid in [
+        order(7, 1, OrderExpiry::None),
+        order(0, 4, OrderExpiry::None),
+        order(0, 0, OrderExpiry::After(day)),
+        order(0, 2, OrderExpiry::After(day)),
+    ] {
+        assert!(matches!(invalid.to_tx_at(now), Err(ApiError::InvalidOrderParams(_))));
+    }
+    assert!(order(0, 1, OrderExpiry::After(day)).to_tx_at(now).is_ok());
+    assert!(order(0, 3, OrderExpiry::After(day)).to_tx_at(now).is_ok());
+}
+
+#[test]
+fn test_trigger_direction_from_order_type_and_side() {
+    use crate::{CreateOrderRequest, TriggerDirection};
+
+    let client = LighterClient::new(TESTNET_URL.to_string(), &test_key_hex(), 1, 0).unwrap();
+    let entry = 300_000;
+    let conditional = |order_type, side, trigger_price| CreateOrderRequest {
+        order_type,
+        trigger_price,
+        ..CreateOrderRequest::market(0, side, 100, entry)
+    };
+
+    // (order type, side, direction, a legal trigger on that side of the entry)
+    let cases = [
+        (2, Side::Sell, TriggerDirection::Below, 290_000),
+        (3, Side::Sell, TriggerDirection::Below, 290_000),
+        (4, Side::Sell, TriggerDirection::Above, 310_000),
+        (5, Side::Sell, TriggerDirection::Above, 310_000),
+        (2, Side::Buy, TriggerDirection::Above, 310_000),
+        (3, Side::Buy, TriggerDirection::Above, 310_000),
+        (4, Side::Buy, TriggerDirection::Below, 290_000),
+        (5, Side::Buy, TriggerDirection::Below, 290_000),
+    ];
+    for (order_type, side, direction, trigger_price) in cases {
+        let order = conditional(order_type, side, trigger_price);
+        assert_eq!(order.trigger_direction(), Some(direction), "type {} {:?}", order_type, side);
+        order.validate_trigger(entry).unwrap();
+
+        // Mirrored around the entry, or at it, the trigger is on the wrong side
+        for wrong in [2 * entry - trigger_price, entry] {
+            let result = conditional(order_type, side, wrong).validate_trigger(entry);
+            assert!(matches!(result, Err(ApiError::InvalidOrderParams(_))), "type {} {:?} at {}", order_type, side, wrong);
+        }
+
+        // The direction is carried by Type and IsAsk alone; tx_info has no field for it
+        let mut tx = order.to_tx_at(1_700_000_000_000).unwrap();
+        let tx_info = client.build_and_sign(i64::MAX, &mut tx).unwrap();
+        assert_eq!(tx_info["Type"], order_type);
+        assert_eq!(tx_info["IsAsk"], u8::from(side == Side::Sell));
+        assert_eq!(tx_info["TriggerPrice"], trigger_price);
+        let body = crate::tx::body_fields(<crate::CreateOrderTx as crate::Transaction>::TX_TYPE).unwrap();
+        assert_eq!(tx_info.as_object().unwrap().len(), body.len() + 5);
+    }
+
+    // Non-conditional orders have no direction, and a conditional one needs a positive trigger
+    for order_type in [0, 1, 6] {
+        let order = conditional(order_type, Side::Sell, 290_000);
+        assert_eq!(order.trigger_direction(), None);
+        assert!(matches!(order.validate_trigger(entry), Err(ApiError::InvalidOrderParams(_))));
+    }
+    assert!(matches!(conditional(4, Side::Buy, 0).validate_trigger(entry), Err(ApiError::InvalidOrderParams(_))));
+}
+
+#[tokio::test]
+async fn test_get_nonce_caches_until_ttl_or_force() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let base_url = {
+        let fetches = fetches.clone();
+        mock_server(move |_| {
+            // Each fetch reports a nonce 100 higher, as if another client had been busy
+            let n = fetches.fetch_add(1, Ordering::SeqCst) as i64;
+            format!(r#"{{"code":200,"nonce":{}}}"#, 100 * (n + 1))
+        })
+        .await
+    };
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .nonce_ttl(Duration::from_millis(200))
+        .build()
+        .unwrap();
+
+    assert_eq!(client.get_nonce(false).await.unwrap(), 100);
+    assert_eq!(client.get_nonce(false).await.unwrap(), 101);
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+    assert_eq!(client.get_nonce(true).await.unwrap(), 200);
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(client.get_nonce(false).await.unwrap(), 300);
+    assert_eq!(fetches.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_account_nonce_source_reads_and_piggybacks_the_account_query() {
+    use crate::NonceSource;
+
+    let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let base_url = {
+        let requests = requests.clone();
+        mock_server(move |request| {
+            let path = request.split_whitespace().nth(1).unwrap().to_string();
+            requests.lock().unwrap().push(path);
+            assert!(!request.contains("/api/v1/nextNonce"), "nonce must come from the account query");
+            r#"{"code":200,"accounts":[{"index":1,"positions":[],
+                "api_keys":[{"api_key_index":0,"nonce":7},{"api_key_index":2,"nonce":42}]}]}"#
+                .to_string()
+        })
+        .await
+    };
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 2)
+        .nonce_source(NonceSource::Account)
+        .build()
+        .unwrap();
+
+    // An account read made anyway seeds the counter: no nonce request follows
+    client.get_positions(1).await.unwrap();
+    assert_eq!(client.get_nonce(false).await.unwrap(), 42);
+    assert_eq!(client.get_nonce(false).await.unwrap(), 43);
+    assert_eq!(requests.lock().unwrap().len(), 1);
+
+    // A live counter is not reset by later account reads
+    client.get_positions(1).await.unwrap();
+    assert_eq!(client.get_nonce(false).await.unwrap(), 44);
+
+    // A forced fetch queries the account too
+    assert_eq!(client.get_nonce(true).await.unwrap(), 42);
+    let requests = requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 3);
+    assert!(requests.iter().all(|path| path.starts_with("/api/v1/account?")), "{:?}", requests);
+}
+
+#[tokio::test]
+async fn test_nonce_rejection_invalidates_cache() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let base_url = {
+        let fetches = fetches.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                fetches.fetch_add(1, Ordering::SeqCst);
+                r#"{"code":200,"nonce":10}"#.to_string()
+            } else {
+                r#"{"code":21104,"message":"invalid nonce"}"#.to_string()
+            }
+        })
+        .await
+    };
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let result = client.cancel_order(0, 1).await;
+    assert!(matches!(result, Err(ApiError::NonceTooHigh { nonce: 10, code: Some(21104), .. })), "{:?}", result);
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+    // The rejected nonce is not reused from the local counter; it is refetched
+    client.get_nonce(false).await.unwrap();
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_replace_order_sends_modify_transaction() {
+    use std::sync::Mutex;
+
+    let sent = Arc::new(Mutex::new(String::new()));
+    let base_url = {
+        let sent = sent.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":3}"#.to_string();
+            }
+            *sent.lock().unwrap() = request.to_string();
+            r#"{"code":200,"tx_hash":"abc"}"#.to_string()
+        })
+        .await
+    };
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    assert_eq!(client.replace_order(2, 42, 310_000, 500).await.unwrap(), 42);
+
+    let request = sent.lock().unwrap().clone();
+    assert!(request.contains("tx_type=17"));
+    for field in ["%22MarketIndex%22%3A2%2C", "%22Index%22%3A42%2C", "%22Price%22%3A310000%2C", "%22BaseAmount%22%3A500%2C"] {
+        assert!(request.contains(field), "missing {} in {}", field, request);
+    }
+}
+
+#[tokio::test]
+async fn test_replace_order_surfaces_rejection() {
+    let base_url = mock_server(|request| {
+        if request.contains("/api/v1/nextNonce") {
+            r#"{"code":200,"nonce":3}"#.to_string()
+        } else {
+            r#"{"code":21702,"message":"order not found"}"#.to_string()
+        }
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    assert!(matches!(client.replace_order(2, 42, 310_000, 500).await, Err(ApiError::Rejected { code: 21702, .. })));
+}
+
+#[tokio::test]
+async fn test_amend_order_keeps_priority_only_for_size_down_at_same_price() {
+    use std::sync::Mutex;
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let sent = sent.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/orderBooks") {
+                ETH_MARKET_JSON.to_string()
+            } else if request.contains("/api/v1/accountActiveOrders") {
+                r#"{"code":200,"orders":[{"order_index":10,"client_order_index":42,"market_index":0,"status":"open",
+                    "price":"3100.50","trigger_price":"0","remaining_base_amount":"0.5000"}]}"#
+                    .to_string()
+            } else if request.contains("/api/v1/accountInactiveOrders") {
+                r#"{"code":200,"orders":[]}"#.to_string()
+            } else if request.contains("/api/v1/nextNonce") {
+                r#"{"code":200,"nonce":3}"#.to_string()
+            } else if request.contains("/api/v1/sendTx") {
+                sent.lock().unwrap().push(request.to_string());
+                r#"{"code":200,"tx_hash":"abc"}"#.to_string()
+            } else {
+                r#"{"code":200,"accounts":[{"positions":[{"market_id":0,"open_order_count":1}]}]}"#.to_string()
+            }
+        })
+        .await
+    };
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    // Size down, price kept: a modify at the current price
+    let amended = client.amend_order(42, 2_000, None).await.unwrap();
+    assert_eq!((amended.client_order_index, amended.priority_preserved), (42, true));
+    let request = sent.lock().unwrap().pop().unwrap();
+    assert!(request.contains("tx_type=17"));
+    for field in ["%22Index%22%3A42%2C", "%22Price%22%3A310050%2C", "%22BaseAmount%22%3A2000%2C"] {
+        assert!(request.contains(field), "missing {} in {}", field, request);
+    }
+
+    // A larger size or a new price loses priority
+    assert!(!client.amend_order(42, 6_000, None).await.unwrap().priority_preserved);
+    let amended = client.amend_order(42, 2_000, Some(310_100)).await.unwrap();
+    assert!(!amended.priority_preserved);
+    assert!(sent.lock().unwrap().pop().unwrap().contains("%22Price%22%3A310100%2C"));
+
+    assert!(matches!(client.amend_order(43, 2_000, None).await, Err(ApiError::NotFound(_))));
+
+    // The current size at the current price is no amendment, and spends no nonce
+    let sends = sent.lock().unwrap().len();
+    for unchanged in [None, Some(310_050)] {
+        let result = client.amend_order(42, 5_000, unchanged).await;
+        assert!(matches!(result, Err(ApiError::InvalidOrderParams(_))), "{:?}", result);
+    }
+    assert_eq!(sent.lock().unwrap().len(), sends);
+}
+
+#[tokio::test]
+async fn test_modify_order_keeps_unset_fields_and_returns_new_state() {
+    use rust_decimal::Decimal;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    let reject = Arc::new(AtomicBool::new(false));
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let (reject, sent) = (reject.clone(), sent.clone());
+        mock_server(move |request| {
+            if request.contains("/api/v1/orderBooks") {
+                ETH_MARKET_JSON.to_string()
+            } else if request.contains("/api/v1/accountActiveOrders") {
+                r#"{"code":200,"orders":[{"order_index":10,"client_order_index":42,"market_index":0,"status":"open",
+                    "price":"3100.50","trigger_price":"3000","initial_base_amount":"0.8000","remaining_base_amount":"0.5000"}]}"#
+                    .to_string()
+            } else if request.contains("/api/v1/accountInactiveOrders") {
+                r#"{"code":200,"orders":[]}"#.to_string()
+            } else if request.contains("/api/v1/nextNonce") {
+                r#"{"code":200,"nonce":3}"#.to_string()
+            } else if request.contains("/api/v1/sendTx") {
+                sent.lock().unwrap().push(request.to_string());
+                if reject.load(Ordering::SeqCst) {
+                    r#"{"code":21700,"message":"order not found"}"#.to_string()
+                } else {
+                    r#"{"code":200,"tx_hash":"abc"}"#.to_string()
+                }
+            } else {
+                r#"{"code":200,"accounts":[{"positions":[{"market_id":0,"open_order_count":1}]}]}"#.to_string()
+            }
+        })
+        .await
+    };
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    // Only the price changes: size and trigger price are carried over
+    let order = client.modify_order(42, Some(310_100), None).await.unwrap();
+    let request = sent.lock().unwrap().pop().unwrap();
+    assert!(request.contains("tx_type=17"));
+    for field in ["%22Index%22%3A42%2C", "%22Price%22%3A310100%2C", "%22BaseAmount%22%3A5000%2C", "%22TriggerPrice%22%3A300000"] {
+        assert!(request.contains(field), "missing {} in {}", field, request);
+    }
+    assert_eq!(order.price, Decimal::new(310_100, 2));
+    assert_eq!(order.remaining_base_amount, Decimal::new(5_000, 4));
+
+    // Only the size changes: the price is carried over, the initial size follows
+    let order = client.modify_order(42, None, Some(3_000)).await.unwrap();
+    let request = sent.lock().unwrap().pop().unwrap();
+    assert!(request.contains("%22Price%22%3A310050%2C") && request.contains("%22BaseAmount%22%3A3000%2C"));
+    assert_eq!((order.price, order.remaining_base_amount), (Decimal::new(310_050, 2), Decimal::new(3_000, 4)));
+    assert_eq!(order.initial_base_amount, Decimal::new(6_000, 4));
+
+    // Nothing to change, nothing that exists, or a rejection: no order state comes back
+    for (new_price, new_size) in [(None, None), (Some(310_050), None), (Some(310_050), Some(5_000)), (None, Some(0))] {
+        let result = client.modify_order(42, new_price, new_size).await;
+        assert!(matches!(result, Err(ApiError::InvalidOrderParams(_))), "{:?}", result);
+    }
+    assert!(matches!(client.modify_order(43, Some(310_100), None).await, Err(ApiError::NotFound(_))));
+    assert!(sent.lock().unwrap().is_empty());
+    reject.store(true, Ordering::SeqCst);
+    assert!(client.modify_order(42, Some(310_100), None).await.is_err());
+}
+
+const ETH_MARKET_JSON: &str = r#"{"code":200,"order_books":[{"symbol":"ETH","market_id":0,"status":"active",
+    "taker_fee":"0.0000","maker_fee":"0.0000","min_base_amount":"0.0050","min_quote_amount":"10.000000",
+    "supported_size_decimals":4,"supported_price_decimals":2,"supported_quote_decimals":6}]}"#;
+
+#[test]
+fn test_tick_size_conversions_across_markets() {
+    use crate::{MarketInfo, TickRounding, TickSize};
+    use rust_decimal::Decimal;
+
+    let market = |symbol: &str, price_decimals: u32| MarketInfo {
+        symbol: symbol.to_string(),
+        price_decimals,
+        ..MarketInfo::default()
+    };
+
+    // ETH, 2 decimals: a 0.05 grid is 5 units; 3496.59 lies between 3496.55 and 3496.60
+    let eth = market("ETH", 2).tick_size(Decimal::new(5, 2)).unwrap();
+    assert_eq!(eth.raw(), 5);
+    assert_eq!(eth.price_to_ticks(349_659, TickRounding::Down), 69_931);
+    assert_eq!(eth.price_to_ticks(349_659, TickRounding::Up), 69_932);
+    assert_eq!(eth.price_to_ticks(349_659, TickRounding::Nearest), 69_932);
+    assert_eq!(eth.price_to_ticks(349_656, TickRounding::Nearest), 69_931);
+    assert_eq!(eth.ticks_to_price(69_932), 349_660);
+    assert_eq!(eth.round_price(349_655, TickRounding::Up), 349_655);
+
+    // BTC, 1 decimal: a whole-dollar grid is 10 units; halfway rounds up
+    let btc = market("BTC", 1).tick_size(Decimal::ONE).unwrap();
+    assert_eq!(btc.round_price(650_005, TickRounding::Nearest), 650_010);
+    assert_eq!(btc.round_price(650_004, TickRounding::Nearest), 650_000);
+    assert_eq!(btc.round_price(650_001, TickRounding::Down), 650_000);
+
+    // 6 decimals: 0.0001 is 100 units; the minimum tick is a single unit
+    let small = market("DOGE", 6);
+    let grid = small.tick_size(Decimal::new(1, 4)).unwrap();
+    assert_eq!(grid.price_to_ticks(123_456, TickRounding::Down), 1_234);
+    assert_eq!(grid.price_to_ticks(123_456, TickRounding::Up), 1_235);
+    assert_eq!(small.min_tick().price_to_ticks(123_456, TickRounding::Up), 123_456);
+
+    // Finer than the market's decimals, or not positive
+    assert!(matches!(market("ETH", 2).tick_size(Decimal::new(5, 3)), Err(ApiError::InvalidConfig(_))));
+    assert!(matches!(TickSize::new(0), Err(ApiError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_price_units_rounding_modes() {
+    use crate::{MarketInfo, Side, TickRounding, TickSize};
+    use rust_decimal::Decimal;
+
+    let d = |value: &str| value.parse::<Decimal>().unwrap();
+    let eth = MarketInfo { symbol: "ETH".to_string(), price_decimals: 2, ..MarketInfo::default() };
+    let units = |price: &str, rounding| eth.to_price_units(d(price), rounding).unwrap();
+
+    assert_eq!(TickRounding::default(), TickRounding::Nearest);
+    // 3496.594 lies between 349659 and 349660
+    assert_eq!(units("3496.594", TickRounding::Down), 349_659);
+    assert_eq!(units("3496.594", TickRounding::Up), 349_660);
+    assert_eq!(units("3496.594", TickRounding::Nearest), 349_659);
+    assert_eq!(units("3496.595", TickRounding::Nearest), 349_660);
+    // a buy rounds down and a sell up, so neither crosses further into the book
+    assert_eq!(units("3496.596", TickRounding::TowardPassive(Side::Buy)), 349_659);
+    assert_eq!(units("3496.591", TickRounding::TowardPassive(Side::Sell)), 349_660);
+    // prices on the grid are kept in every mode
+    for rounding in [TickRounding::Down, TickRounding::Up, TickRounding::Nearest, TickRounding::TowardPassive(Side::Sell)] {
+        assert_eq!(units("3496.59", rounding), 349_659);
+    }
+
+    // the same modes on a coarser grid
+    let grid = TickSize::new(5).unwrap();
+    assert_eq!(grid.round_price(349_659, TickRounding::TowardPassive(Side::Buy)), 349_655);
+    assert_eq!(grid.round_price(349_656, TickRounding::TowardPassive(Side::Sell)), 349_660);
+
+    // not a usable price
+    assert!(matches!(eth.to_price_units(Decimal::ZERO, TickRounding::Nearest), Err(ApiError::InvalidOrderParams(_))));
+    assert!(matches!(eth.to_price_units(d("0.001"), TickRounding::Down), Err(ApiError::InvalidOrderParams(_))));
+    assert_eq!(units("0.001", TickRounding::Up), 1);
+}
+
+#[test]
+fn test_market_info_validates_order_limits() {
+    use crate::MarketInfo;
+
+    let json: serde_json::Value = serde_json::from_str(ETH_MARKET_JSON).unwrap();
+    let market: MarketInfo = serde_json::from_value(json["order_books"][0].clone()).unwrap();
+
+    // 0.0100 ETH at 3000.00
+    assert!(market.validate_order(300_000, 100).is_ok());
+
+    let violation = |price, base| match market.validate_order(price, base) {
+        Err(ApiError::InvalidOrderParams(message)) => message,
+        other => panic!("expected InvalidOrderParams, got {:?}", other),
+    };
+    assert!(violation(0, 100).contains("Price"));
+    assert!(violation(u32::MAX as i64 + 1, 100).contains("Price"));
+    assert!(violation(300_000, 0).contains("BaseAmount"));
+    assert!(violation(300_000, 49).contains("minimum size"));
+    // 0.0050 ETH at 1000.00 = 5 USDC < 10 USDC
+    assert!(violation(100_000, 50).contains("minimum quote"));
+}
+
+#[tokio::test]
+async fn test_invalid_order_rejected_before_signing_unless_opted_out() {
+    use crate::CreateOrderRequest;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let submissions = Arc::new(AtomicUsize::new(0));
+    let base_url = {
+        let submissions = submissions.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/orderBooks") {
+                ETH_MARKET_JSON.to_string()
+            } else if request.contains("/api/v1/nextNonce") {
+                r#"{"code":200,"nonce":1}"#.to_string()
+            } else {
+                submissions.fetch_add(1, Ordering::SeqCst);
+                r#"{"code":200}"#.to_string()
+            }
+        })
+        .await
+    };
+    let too_small = || CreateOrderRequest {
+        account_index: 1,
+        order_book_index: 0,
+        client_order_index: 1,
+        base_amount: 10,
+        price: 300_000,
+        side: Side::Buy,
+        order_type: 0,
+        time_in_force: 1,
+        reduce_only: false,
+        trigger_price: 0,
+        order_expiry: OrderExpiry::None,
+        price_protection: None,
+    };
+
+    let client = LighterClient::new(base_url.clone(), &test_key_hex(), 1, 0).unwrap();
+    let result = client.create_order(too_small()).await;
+    assert!(matches!(result, Err(ApiError::InvalidOrderParams(_))));
+    assert_eq!(submissions.load(Ordering::SeqCst), 0);
+
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .validate_orders(false)
+        .build()
+        .unwrap();
+    client.create_order(too_small()).await.unwrap();
+    assert_eq!(submissions.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_get_positions_long_short_and_flat() {
+    use rust_decimal::Decimal;
+
+    let base_url = mock_server(|request| {
+        if request.contains("value=2") {
+            return r#"{"code":200,"accounts":[{"index":2,"positions":[
+                {"market_id":0,"symbol":"ETH","sign":1,"position":"0.0000","avg_entry_price":"0.00","position_value":"0"}
+            ]}]}"#
+                .to_string();
+        }
+        r#"{"code":200,"accounts":[{"index":1,"positions":[
+            {"market_id":0,"symbol":"ETH","sign":1,"position":"2.0000","avg_entry_price":"3000.00","position_value":"6100.00","realized_pnl":"5","margin_mode":0},
+            {"market_id":1,"symbol":"BTC","sign":-1,"position":"0.50000","avg_entry_price":"60000.0","position_value":"30500.0","margin_mode":1},
+            {"market_id":2,"symbol":"SOL","sign":1,"position":"0.000","avg_entry_price":"0","position_value":"0"}
+        ]}]}"#
+            .to_string()
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+    let d = |s: &str| s.parse::<Decimal>().unwrap();
+
+    let positions = client.get_positions(1).await.unwrap();
+    assert_eq!(positions.len(), 2);
+
+    // Long 2 ETH from 3000, marked at 3050: +100
+    let long = &positions[0];
+    assert_eq!(long.size, d("2"));
+    assert_eq!(long.mark_price, d("3050"));
+    assert_eq!(long.unrealized_pnl, d("100"));
+
+    // Short 0.5 BTC from 60000, marked at 61000: -500
+    let mut short = positions[1].clone();
+    assert_eq!(short.size, d("-0.5"));
+    assert_eq!(short.unrealized_pnl, d("-500"));
+    short.mark_to(d("59000"));
+    assert_eq!(short.unrealized_pnl, d("500"));
+
+    assert!(client.get_positions(2).await.unwrap().is_empty());
+}
+
+/// Signing message for each tx type, pinned as raw field elements
+#[test]
+fn test_canonical_signing_message_per_tx_type() {
+    use crate::tx::{message_elements, message_hash};
+    use poseidon_hash::{hash_to_quintic_extension, Goldilocks};
+    use serde_json::json;
+
+    const P: u64 = 0xFFFF_FFFF_0000_0001;
+    let header = |tx_type: u64| vec![300, tx_type, 7, 1_700_000_000_000, 42, 3];
+    let common = json!({"Nonce": 7, "ExpiredAt": 1_700_000_000_000i64, "AccountIndex": 42, "ApiKeyIndex": 3, "Sig": ""});
+    let with = |body: serde_json::Value| {
+        let mut tx = common.clone();
+        for (k, v) in body.as_object().unwrap() {
+            tx[k] = v.clone();
+        }
+        tx
+    };
+
+    let pubkey: Vec<u8> = (1..=40).collect();
+    let limbs: Vec<u64> = pubkey.chunks(8).map(|c| u64::from_le_bytes(c.try_into().unwrap())).collect();
+
+    let cases: Vec<(u32, serde_json::Value, Vec<u64>)> = vec![
+        (8, with(json!({"PubKey": hex::encode(&pubkey)})), limbs),
+        (
+            14,
+            with(json!({"MarketIndex": 1, "ClientOrderIndex": 99, "BaseAmount": 1000, "Price": 305000,
+                "IsAsk": 1, "Type": 0, "TimeInForce": 1, "ReduceOnly": 0, "TriggerPrice": 0, "OrderExpiry": -1})),
+            // Negative ints hash as their two's complement reduced mod p (as in the Go signer)
+            vec![1, 99, 1000, 305000, 1, 0, 1, 0, 0, u64::MAX - P],
+        ),
+        (15, with(json!({"MarketIndex": 2, "Index": 123456})), vec![2, 123456]),
+        (16, with(json!({"TimeInForce": 1, "Time": 1_700_000_100_000i64})), vec![1, 1_700_000_100_000]),
+        (
+            17,
+            with(json!({"MarketIndex": 2, "Index": 55, "BaseAmount": 500, "Price": 310000, "TriggerPrice": 0})),
+            vec![2, 55, 500, 310000, 0],
+        ),
+        (20, with(json!({"MarketIndex": 0, "InitialMarginFraction": 3333, "MarginMode": 1})), vec![0, 3333, 1]),
+        (29, with(json!({"MarketIndex": 3, "USDCAmount": 25_000_000, "Direction": 1})), vec![3, 25_000_000, 1]),
+    ];
+
+    for (tx_type, tx, body) in cases {
+        let expected: Vec<u64> = header(tx_type as u64).into_iter().chain(body).collect();
+        let elements = message_elements(300, tx_type, &tx).unwrap();
+        let actual: Vec<u64> = elements.iter().map(|e| e.to_canonical_u64()).collect();
+        assert_eq!(actual, expected, "tx type {}", tx_type);
+
+        let expected_elements: Vec<Goldilocks> = expected.iter().map(|&v| Goldilocks::from_canonical_u64(v)).collect();
+        let expected_hash = hash_to_quintic_extension(&expected_elements).to_bytes_le();
+        assert_eq!(message_hash(300, tx_type, &tx).unwrap()[..], expected_hash[..40], "tx type {}", tx_type);
+    }
+
+    // A missing field is an error, not a silently hashed zero
+    let incomplete = with(json!({"MarketIndex": 2}));
+    assert!(message_elements(300, 15, &incomplete).is_err());
+    assert!(message_elements(300, 99, &common).is_err());
+}
+
+/// Pinned signature of a fully specified order with a fixed key and signing nonce
+#[test]
+fn test_deterministic_signature_vector() {
+    let private_key: Vec<u8> = (1..=32).chain([0; 8]).collect();
+    let signing_nonce: [u8; 40] = std::array::from_fn(|i| if i < 32 { 0x42 } else { 0 });
+    let client = LighterClient::new(TESTNET_URL.to_string(), &hex::encode(&private_key), 42, 3).unwrap();
+    let tx_json = r#"{"AccountIndex":42,"ApiKeyIndex":3,"ExpiredAt":1700000600000,"Nonce":7,"MarketIndex":1,
+        "ClientOrderIndex":99,"BaseAmount":1000,"Price":305000,"IsAsk":1,"Type":0,"TimeInForce":1,
+        "ReduceOnly":0,"TriggerPrice":0,"OrderExpiry":1702419200000}"#;
+
+    let signature = client.sign_transaction_deterministic(tx_json, 14, &signing_nonce).unwrap();
+    assert_eq!(signature, client.sign_transaction_deterministic(tx_json, 14, &signing_nonce).unwrap());
+    assert_eq!(
+        hex::encode(signature),
+        concat!(
+            "4c68658a2db3fc2ecfe65560edc0b1c1b892e34f5267fa9313f21bfdcc14b8422e5dd7307f874e2a",
+            "3794373ea1ed752a6b2ff299abef732e35a8ed18d34f5c9cf80ad864a52035a9441758dc24660d69",
+        )
+    );
+}
+
+/// Signatures round-trip through both encodings and refuse any other length
+#[test]
+fn test_signature_encodings_and_length() {
+    use crate::{Signature, SIGNATURE_LENGTH};
+
+    let client = LighterClient::new(TESTNET_URL.to_string(), &test_key_hex(), 42, 3).unwrap();
+    let tx_json = r#"{"AccountIndex":42,"ApiKeyIndex":3,"ExpiredAt":1700000600000,"Nonce":7,"MarketIndex":1,
+        "ClientOrderIndex":99,"BaseAmount":1000,"Price":305000,"IsAsk":1,"Type":0,"TimeInForce":1,
+        "ReduceOnly":0,"TriggerPrice":0,"OrderExpiry":1702419200000}"#;
+    let signature = client.sign_transaction_deterministic(tx_json, 14, &[7; 40]).unwrap();
+
+    assert_eq!(signature.as_bytes().len(), SIGNATURE_LENGTH);
+    assert_eq!(Signature::from_bytes(signature.as_bytes()).unwrap(), signature);
+    assert_eq!(Signature::from_base64(&signature.to_base64()).unwrap(), signature);
+    assert_eq!(Signature::from_hex(&signature.to_hex()).unwrap(), signature);
+    assert_eq!(Signature::from_hex(&format!("0x{}", signature.to_hex())).unwrap(), signature);
+    assert_eq!(signature.to_string(), signature.to_base64());
+    assert_eq!(format!("{:?}", signature), format!("Signature({})", signature.to_hex()));
+
+    // Raw bytes are not a signature's encoding, and an encoding is not its bytes
+    for invalid in [
+        Signature::from_bytes(&signature.as_bytes()[..79]),
+        Signature::from_bytes(signature.to_base64().as_bytes()),
+        Signature::from_base64(&signature.to_hex()),
+        Signature::from_base64("not base64!"),
+        Signature::from_hex("abcd"),
+    ] {
+        assert!(matches!(invalid, Err(ApiError::InvalidSignature(_))), "{:?}", invalid);
+    }
+}
+
+/// The base64 and hex signing helpers give signatures that decode and re-encode unchanged
+#[test]
+fn test_sign_transaction_base64_and_hex() {
+    use crate::{Signature, SIGNATURE_LENGTH};
+
+    let client = LighterClient::new(TESTNET_URL.to_string(), &test_key_hex(), 42, 3).unwrap();
+    let tx_json = r#"{"AccountIndex":42,"ApiKeyIndex":3,"ExpiredAt":1700000600000,"Nonce":7,"MarketIndex":1,
+        "ClientOrderIndex":99,"BaseAmount":1000,"Price":305000,"IsAsk":1,"Type":0,"TimeInForce":1,
+        "ReduceOnly":0,"TriggerPrice":0,"OrderExpiry":1702419200000}"#;
+
+    // Each call draws a fresh signing nonce, so the two encode different signatures
+    let base64 = client.sign_transaction_base64(tx_json, 14).unwrap();
+    let hex = client.sign_transaction_hex(tx_json, 14).unwrap();
+    assert_eq!(hex.len(), 2 * SIGNATURE_LENGTH);
+    assert!(!hex.starts_with("0x") && hex == hex.to_lowercase(), "{}", hex);
+    assert_eq!(Signature::from_base64(&base64).unwrap().to_base64(), base64);
+    assert_eq!(Signature::from_hex(&hex).unwrap().to_hex(), hex);
+
+    // Bad input fails before anything is signed
+    for invalid in [
+        client.sign_transaction_base64("not json", 14),
+        client.sign_transaction_hex("not json", 14),
+    ] {
+        assert!(matches!(invalid, Err(ApiError::Json(_))), "{:?}", invalid);
+    }
+    assert!(client.sign_transaction_base64(r#"{"MarketIndex":1}"#, 14).is_err());
+    assert!(client.sign_transaction_hex(tx_json, 99).is_err());
+}
+
+#[tokio::test]
+async fn test_withdrawal_status_follows_pages_and_reports_unindexed_as_pending() {
+    use crate::{WithdrawalRef, WithdrawalState};
+    use rust_decimal::Decimal;
+
+    let base_url = mock_server(|request| {
+        assert!(request.contains("/api/v1/withdraw_history?"));
+        assert!(request.contains("auth="));
+        if request.contains("cursor=page2") {
+            r#"{"code":200,"withdraws":[{"id":"w-1","nonce":11,"amount":"250.5","timestamp":1700000000000,
+                "status":"claimable","type":"secure","l1_tx_hash":"0xabc","estimated_completion_time":1700003600000}]}"#
+                .to_string()
+        } else {
+            r#"{"code":200,"cursor":"page2","withdraws":[{"id":"w-2","nonce":12,"amount":"5","timestamp":1700000100000,
+                "status":"pending","type":"fast","l1_tx_hash":""}]}"#
+                .to_string()
+        }
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let status = client.get_withdrawal_status(1, WithdrawalRef::Nonce(11)).await.unwrap();
+    assert_eq!(status.state, WithdrawalState::Claimable);
+    assert!(status.indexed && !status.is_final());
+    assert_eq!(status.id.as_deref(), Some("w-1"));
+    assert_eq!(status.amount, Some(Decimal::new(2505, 1)));
+    assert_eq!(status.l1_tx_hash.as_deref(), Some("0xabc"));
+    assert_eq!(status.estimated_completion, Some(1_700_003_600_000));
+
+    let status = client.get_withdrawal_status(1, WithdrawalRef::Id("w-2".to_string())).await.unwrap();
+    assert_eq!(status.state, WithdrawalState::Pending);
+    assert_eq!(status.l1_tx_hash, None);
+
+    let status = client.get_withdrawal_status(1, WithdrawalRef::Id("w-3".to_string())).await.unwrap();
+    assert_eq!(status.state, WithdrawalState::Pending);
+    assert!(!status.indexed);
+}
+
+#[tokio::test]
+async fn test_get_leverage_configured_and_default() {
+    use crate::MarginMode;
+    use rust_decimal::Decimal;
+
+    let base_url = mock_server(|request| {
+        if request.contains("/api/v1/orderBookDetails") {
+            return r#"{"code":200,"order_book_details":[{"market_id":5,"default_initial_margin_fraction":1000}]}"#
+                .to_string();
+        }
+        r#"{"code":200,"accounts":[{"index":1,"positions":[
+            {"market_id":0,"symbol":"ETH","sign":1,"position":"0","initial_margin_fraction":"20.00","margin_mode":1}
+        ]}]}"#
+            .to_string()
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let configured = client.get_leverage(1, 0).await.unwrap();
+    assert_eq!(configured.leverage, Decimal::from(5));
+    assert_eq!(configured.margin_mode, MarginMode::Isolated);
+    assert!(!configured.is_default);
+
+    // Never traded: market default of 10% initial margin = 10x cross
+    let default = client.get_leverage(1, 5).await.unwrap();
+    assert_eq!(default.leverage, Decimal::from(10));
+    assert_eq!(default.margin_mode, MarginMode::Cross);
+    assert!(default.is_default);
+}
+
+#[tokio::test]
+async fn test_update_margin_liquidation_check() {
+    use crate::MarginDirection;
+
+    let base_url = mock_server(|request| {
+        if request.contains("/api/v1/orderBookDetails") {
+            return r#"{"code":200,"order_book_details":[{"market_id":0,"maintenance_margin_fraction":500}]}"#
+                .to_string();
+        }
+        if request.contains("/api/v1/nextNonce") {
+            return r#"{"code":200,"nonce":1}"#.to_string();
+        }
+        if request.contains("/api/v1/sendTx") {
+            return r#"{"code":200,"tx_hash":"0xabc"}"#.to_string();
+        }
+        // Long 1 ETH @ 3000 marked at 3000 (no uPnL), 200 USDC allocated; maintenance is 150
+        r#"{"code":200,"accounts":[{"index":1,"positions":[
+            {"market_id":0,"symbol":"ETH","sign":1,"position":"1","avg_entry_price":"3000","position_value":"3000",
+             "margin_mode":1,"allocated_margin":"200"}
+        ]}]}"#
+            .to_string()
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let err = client.update_margin(0, 60_000_000, MarginDirection::Remove, true).await.unwrap_err();
+    assert!(matches!(err, ApiError::InvalidOrderParams(_)), "{:?}", err);
+    client.update_margin(0, 40_000_000, MarginDirection::Remove, true).await.unwrap();
+    // Unchecked removals and additions go straight to the exchange
+    client.update_margin(0, 60_000_000, MarginDirection::Remove, false).await.unwrap();
+    client.update_margin(0, 60_000_000, MarginDirection::Add, true).await.unwrap();
+    assert!(matches!(
+        client.update_margin(0, 0, MarginDirection::Add, false).await,
+        Err(ApiError::InvalidOrderParams(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_user_agent_header() {
+    use crate::DEFAULT_USER_AGENT;
+    use std::sync::Mutex;
+
+    let agents = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let agents = agents.clone();
+        mock_server(move |request| {
+            let agent = request
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("user-agent").then(|| value.trim().to_string())
+                })
+                .unwrap_or_default();
+            agents.lock().unwrap().push(agent);
+            r#"{"status":200}"#.to_string()
+        })
+        .await
+    };
+
+    let client = LighterClient::new(base_url.clone(), &test_key_hex(), 1, 0).unwrap();
+    client.ping().await.unwrap();
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .user_agent("my-bot/2.1")
+        .build()
+        .unwrap();
+    client.ping().await.unwrap();
+
+    let agents = agents.lock().unwrap();
+    assert_eq!(agents[0], DEFAULT_USER_AGENT);
+    assert_eq!(agents[1], format!("my-bot/2.1 lighter-rust/{}", env!("CARGO_PKG_VERSION")));
+}
+
+#[tokio::test]
+async fn test_http_exchange_logging_captures_send_tx_form_and_redacts_auth() {
+    use crate::{CreateOrderRequest, HttpExchange};
+    use std::sync::Mutex;
+
+    let base_url = mock_server(|request| {
+        if request.contains("/api/v1/nextNonce") {
+            r#"{"code":200,"nonce":7}"#.to_string()
+        } else if request.contains("/api/v1/sendTx") {
+            r#"{"code":21738,"message":"post only order would cross"}"#.to_string()
+        } else {
+            r#"{"code":200,"orders":[]}"#.to_string()
+        }
+    })
+    .await;
+    let exchanges: Arc<Mutex<Vec<HttpExchange>>> = Arc::new(Mutex::new(Vec::new()));
+    let client = {
+        let exchanges = exchanges.clone();
+        LighterClient::builder(base_url.clone(), &test_key_hex(), 1, 0)
+            .validate_orders(false)
+            .on_http_exchange(move |exchange| exchanges.lock().unwrap().push(exchange.clone()))
+            .build()
+            .unwrap()
+    };
+
+    let order = CreateOrderRequest { account_index: 1, ..CreateOrderRequest::limit(0, Side::Buy, 100, 300_000) };
+    assert!(client.create_order(order).await.is_err());
+    client.get_active_orders(1, 0).await.unwrap();
+
+    let exchanges = exchanges.lock().unwrap();
+    assert_eq!(exchanges.len(), 3);
+    let (nonce, send_tx, orders) = (&exchanges[0], &exchanges[1], &exchanges[2]);
+    assert_eq!((nonce.method.as_str(), nonce.body.as_deref(), nonce.code), ("GET", None, Some(200)));
+
+    // The form body exactly as sent, with the response code and message
+    assert_eq!(send_tx.method, "POST");
+    assert_eq!(send_tx.url, format!("{}/api/v1/sendTx", base_url));
+    let form = url_decoded_form(send_tx.body.as_deref().unwrap());
+    assert_eq!(form[0], ("tx_type".to_string(), "14".to_string()));
+    let tx_info: serde_json::Value = serde_json::from_str(&form[1].1).unwrap();
+    assert_eq!((tx_info["Nonce"].as_i64(), tx_info["Price"].as_i64()), (Some(7), Some(300_000)));
+    assert_eq!((send_tx.status, send_tx.code), (Some(200), Some(21738)));
+    assert_eq!(send_tx.message.as_deref(), Some("post only order would cross"));
+    assert!(send_tx.to_string().starts_with(&format!("POST {}/api/v1/sendTx -> 200 code 21738", base_url)));
+
+    // The auth token of an authenticated read never reaches the log
+    assert!(orders.url.contains("auth=<redacted>"), "{}", orders.url);
+    assert!(orders.url.contains("account_index=1"));
+}
+
+#[test]
+fn test_book_update_snapshot_then_removing_diff() {
+    use crate::{BookUpdate, OrderBook};
+    use rust_decimal::Decimal;
+
+    let d = |s: &str| s.parse::<Decimal>().unwrap();
+    let message = |json: &str| BookUpdate::from_message(&serde_json::from_str(json).unwrap()).unwrap().unwrap();
+    let snapshot = message(
+        r#"{"type":"subscribed/order_book","channel":"order_book:0","order_book":{"offset":41,
+            "bids":[{"price":"3000.00","size":"1.0"},{"price":"2999.50","size":"2.0"}],
+            "asks":[{"price":"3001.00","size":"0.5"}]}}"#,
+    );
+    assert!(snapshot.is_snapshot);
+    assert_eq!(snapshot.sequence, 41);
+    assert_eq!(snapshot.bids, vec![(d("3000.00"), d("1.0")), (d("2999.50"), d("2.0"))]);
+
+    let diff = message(
+        r#"{"type":"update/order_book","channel":"order_book:0","order_book":{"offset":42,
+            "bids":[{"price":"3000.00","size":"0"}],"asks":[{"price":"3001.50","size":"4"}]}}"#,
+    );
+    assert!(!diff.is_snapshot);
+    assert_eq!(diff.sequence, 42);
+
+    let mut book = OrderBook::new(0);
+    book.apply_update(&snapshot);
+    book.apply_update(&diff);
+    assert_eq!(book.bids().map(|level| (level.price, level.size)).collect::<Vec<_>>(), vec![(d("2999.50"), d("2.0"))]);
+    assert_eq!(book.asks().count(), 2);
+
+    // A later snapshot replaces everything
+    book.apply_update(&snapshot);
+    assert_eq!(book.best_bid().unwrap().price, d("3000.00"));
+    assert_eq!(book.asks().count(), 1);
+
+    let other = serde_json::json!({"type": "update/trade", "trades": []});
+    assert!(BookUpdate::from_message(&other).unwrap().is_none());
+}
+
+#[test]
+fn test_order_book_imbalance_and_microprice() {
+    use crate::{BookUpdate, OrderBook};
+    use rust_decimal::Decimal;
+
+    let d = |value: &str| value.parse::<Decimal>().unwrap();
+    let mut book = OrderBook::new(0);
+    assert_eq!(book.imbalance(5), None);
+    assert_eq!(book.microprice(), None);
+
+    book.apply_update(&BookUpdate {
+        bids: vec![(d("100"), d("3")), (d("99"), d("5"))],
+        asks: vec![(d("101"), d("1")), (d("102"), d("1"))],
+        sequence: 1,
+        is_snapshot: true,
+    });
+    // top level: (3 - 1) / 4; two levels: (8 - 2) / 10
+    assert_eq!(book.imbalance(1), Some(d("0.5")));
+    assert_eq!(book.imbalance(2), Some(d("0.6")));
+    assert_eq!(book.imbalance(10), Some(d("0.6")));
+    assert_eq!(book.imbalance(0), None);
+    // (100 * 1 + 101 * 3) / 4: pulled toward the thin ask side
+    assert_eq!(book.microprice(), Some(d("100.75")));
+
+    // one-sided book
+    book.apply_update(&BookUpdate {
+        asks: vec![(d("101"), d("0")), (d("102"), d("0"))],
+        ..BookUpdate::default()
+    });
+    assert_eq!(book.imbalance(1), Some(d("1")));
+    assert_eq!(book.microprice(), None);
+}
+
+#[tokio::test]
+async fn test_subscribe_bbo_emits_only_top_of_book_changes() {
+    use crate::PriceLevel;
+    use rust_decimal::Decimal;
+
+    let level = |price: &str, size: &str| PriceLevel { price: price.parse().unwrap(), size: size.parse().unwrap() };
+    let frames = vec![
+        r#"{"type":"connected"}"#,
+        r#"{"type":"subscribed/order_book","channel":"order_book:0","order_book":{
+            "bids":[{"price":"3000.00","size":"1.0"},{"price":"2999.50","size":"2.0"}],
+            "asks":[{"price":"3001.00","size":"0.5"},{"price":"3002.00","size":"3.0"}]}}"#,
+        // deeper levels only: no item
+        r#"{"type":"update/order_book","channel":"order_book:0","order_book":{
+            "bids":[{"price":"2999.50","size":"0"}],"asks":[{"price":"3005.00","size":"1.0"}]}}"#,
+        r#"{"type":"ping"}"#,
+        // best ask consumed
+        r#"{"type":"update/order_book","channel":"order_book:0","order_book":{
+            "bids":[],"asks":[{"price":"3001.00","size":"0"}]}}"#,
+        // size change at the best bid
+        r#"{"type":"update/order_book","channel":"order_book:0","order_book":{
+            "bids":[{"price":"3000.00","size":"1.5"}],"asks":[]}}"#,
+    ];
+    let (base_url, _) = mock_ws_server(frames.into_iter().map(String::from).collect()).await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let updates: Vec<_> = client
+        .subscribe_bbo(0)
+        .await
+        .unwrap()
+        .map(|bbo| bbo.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(updates.len(), 3);
+    assert!(updates.iter().all(|bbo| bbo.market_index == 0));
+    assert_eq!(updates[0].bid, Some(level("3000.00", "1.0")));
+    assert_eq!(updates[0].ask, Some(level("3001.00", "0.5")));
+    assert_eq!(updates[1].bid, Some(level("3000.00", "1.0")));
+    assert_eq!(updates[1].ask, Some(level("3002.00", "3.0")));
+    assert_eq!(updates[2].bid.map(|l| l.size), Some(Decimal::new(15, 1)));
+    assert_eq!(updates[2].ask, Some(level("3002.00", "3.0")));
+}
+
+#[tokio::test]
+async fn test_sign_orders_preserves_order_and_isolates_failures() {
+    use crate::CreateOrderRequest;
+
+    let base_url = mock_server(|request| {
+        if request.contains("/api/v1/orderBooks") {
+            ETH_MARKET_JSON.to_string()
+        } else {
+            r#"{"code":200,"nonce":100}"#.to_string()
+        }
+    })
+    .await;
+    let order = |client_order_index, base_amount| CreateOrderRequest {
+        account_index: 1,
+        order_book_index: 0,
+        client_order_index,
+        base_amount,
+        price: 300_000,
+        side: Side::Buy,
+        order_type: 0,
+        time_in_force: 1,
+        reduce_only: false,
+        trigger_price: 0,
+        order_expiry: OrderExpiry::None,
+        price_protection: None,
+    };
+    let orders: Vec<_> = (1..=6).map(|i| order(i, if i == 2 { 10 } else { 100 })).collect();
+
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+    let signed = client.sign_orders(&orders).await;
+
+    assert_eq!(signed.len(), orders.len());
+    assert!(matches!(signed[1], Err(ApiError::InvalidOrderParams(_))));
+    let ok: Vec<_> = signed.iter().filter_map(|result| result.as_ref().ok()).collect();
+    assert_eq!(ok.iter().map(|s| s.client_order_index).collect::<Vec<_>>(), vec![1, 3, 4, 5, 6]);
+    assert_eq!(ok.iter().map(|s| s.nonce).collect::<Vec<_>>(), vec![100, 101, 102, 103, 104]);
+    for signed in &ok {
+        assert_eq!(signed.tx_type, 14);
+        assert_eq!(signed.tx_info["Nonce"], signed.nonce);
+        assert_eq!(signed.tx_info["ClientOrderIndex"], signed.client_order_index);
+        assert!(signed.tx_info["Sig"].as_str().is_some_and(|sig| !sig.is_empty()));
+        assert_eq!(signed.tx_info["Sig"], signed.signature.to_base64());
+    }
+
+    // the next transaction continues after the batch
+    assert_eq!(client.get_nonce(false).await.unwrap(), 105);
+}
+
+#[tokio::test]
+async fn test_sign_orders_stops_at_a_signing_failure_and_hands_back_nonces() {
+    use crate::CreateOrderRequest;
+
+    let base_url = mock_server(|_| r#"{"code":200,"nonce":100}"#.to_string()).await;
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .validate_orders(false)
+        .build()
+        .unwrap();
+    // A client order index beyond i64 passes validation but cannot be hashed
+    let orders: Vec<_> = [1, 2, u64::MAX, 4]
+        .into_iter()
+        .map(|client_order_index| CreateOrderRequest {
+            account_index: 1,
+            client_order_index,
+            ..CreateOrderRequest::limit(0, Side::Buy, 100, 300_000)
+        })
+        .collect();
+
+    let signed = client.sign_orders(&orders).await;
+    assert_eq!(signed.iter().filter_map(|s| s.as_ref().ok()).map(|s| s.nonce).collect::<Vec<_>>(), vec![100, 101]);
+    assert!(matches!(&signed[2], Err(ApiError::Api(message)) if message.contains("ClientOrderIndex")), "{:?}", signed[2]);
+    assert!(matches!(&signed[3], Err(ApiError::Api(message)) if message.contains("order 2")), "{:?}", signed[3]);
+
+    // the nonces of the failed order and the one after it are free again
+    assert_eq!(client.get_nonce(false).await.unwrap(), 102);
+}
+
+#[tokio::test]
+async fn test_signing_pool_runs_signing_on_its_own_threads() {
+    use crate::{CreateOrderRequest, SigningPool, TxHeader};
+
+    let base_url = mock_server(|_| r#"{"code":200,"nonce":100}"#.to_string()).await;
+    let default = LighterClient::new(base_url.clone(), &test_key_hex(), 1, 0).unwrap();
+    assert_eq!(default.signing_threads(), None);
+    let thread_name = || std::thread::current().name().map(str::to_string);
+    let name = default.spawn_signing(move || Ok(thread_name())).await.unwrap();
+    assert!(!name.is_some_and(|name| name.starts_with("lighter-signer")));
+
+    let client = LighterClient::builder(base_url.clone(), &test_key_hex(), 1, 0)
+        .signing_threads(2)
+        .validate_orders(false)
+        .build()
+        .unwrap();
+    assert_eq!(client.signing_threads(), Some(2));
+    let names: Vec<_> = futures::future::join_all((0..8).map(|_| client.spawn_signing(move || Ok(thread_name())))).await;
+    for name in names {
+        let name = name.unwrap().unwrap();
+        assert!(name == "lighter-signer-0" || name == "lighter-signer-1", "{}", name);
+    }
+
+    // Single transactions and batches are signed there
+    let mut tx = CreateOrderRequest::limit(0, Side::Buy, 100, 300_000).to_tx_at(1_700_000_000_000).unwrap();
+    tx.header = TxHeader { account_index: 1, api_key_index: 0, expired_at: 1_700_000_600_000, nonce: 5 };
+    let signature = client.sign_transaction_async(tx).await.unwrap();
+    assert_ne!(signature.as_bytes(), &[0u8; 80]);
+    let orders: Vec<_> = (1..=3)
+        .map(|i| CreateOrderRequest { account_index: 1, client_order_index: i, ..CreateOrderRequest::limit(0, Side::Buy, 100, 300_000) })
+        .collect();
+    let signed = client.sign_orders(&orders).await;
+    assert_eq!(signed.iter().map(|s| s.as_ref().unwrap().nonce).collect::<Vec<_>>(), vec![100, 101, 102]);
+
+    // A panicking job fails alone and the pool keeps serving
+    let pool = std::sync::Arc::new(SigningPool::new(1).unwrap());
+    let shared = LighterClient::builder(base_url, &test_key_hex(), 1, 0).signing_pool(pool.clone()).build().unwrap();
+    assert_eq!(shared.signing_threads(), Some(1));
+    let panicked = shared.spawn_signing(|| -> crate::Result<()> { panic!("boom") }).await;
+    assert!(matches!(panicked, Err(ApiError::Api(_))));
+    assert_eq!(shared.spawn_signing(|| Ok(7)).await.unwrap(), 7);
+
+    assert!(matches!(SigningPool::new(0), Err(ApiError::InvalidConfig(_))));
+    assert!(LighterClient::builder(TESTNET_URL.to_string(), &test_key_hex(), 1, 0).signing_threads(0).build().is_err());
+}
+
+#[tokio::test]
+async fn test_nonce_gap_replays_with_refetched_nonce() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // The server expects nonce 8, but its first two nonce answers are stale (5)
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let base_url = {
+        let fetches = fetches.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                let nonce = if fetches.fetch_add(1, Ordering::SeqCst) < 2 { 5 } else { 8 };
+                format!(r#"{{"code":200,"nonce":{}}}"#, nonce)
+            } else if request.contains("%22Nonce%22%3A5%2C") {
+                r#"{"code":21104,"message":"invalid nonce"}"#.to_string()
+            } else {
+                r#"{"code":200}"#.to_string()
+            }
+        })
+        .await
+    };
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .nonce_gap_threshold(2)
+        .build()
+        .unwrap();
+
+    // below the threshold the rejection goes back to the caller as a gap
+    let result = client.cancel_order(0, 1).await;
+    assert!(matches!(result, Err(ApiError::NonceTooHigh { nonce: 5, .. })), "{:?}", result);
+    assert_eq!(client.recovered_transactions(), 0);
+
+    // the second consecutive rejection is replayed with the refetched nonce; the first is not
+    assert_eq!(client.cancel_order(0, 2).await.unwrap().code, 200);
+    assert_eq!(client.recovered_transactions(), 1);
+    assert_eq!(fetches.load(Ordering::SeqCst), 3);
+
+    // the corrected counter carries on locally
+    assert_eq!(client.cancel_order(0, 3).await.unwrap().code, 200);
+    assert_eq!(client.get_nonce(false).await.unwrap(), 10);
+    assert_eq!(fetches.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_nonce_too_low_skips_ahead_at_once() {
+    use crate::CreateOrderRequest;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Another process on the key already used nonces 5 to 8; the first nonce answer is stale
+    let (fetches, orders) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let base_url = {
+        let (fetches, orders) = (fetches.clone(), orders.clone());
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                let nonce = if fetches.fetch_add(1, Ordering::SeqCst) == 0 { 5 } else { 9 };
+                return format!(r#"{{"code":200,"nonce":{}}}"#, nonce);
+            }
+            if request.contains("tx_type=14") {
+                orders.fetch_add(1, Ordering::SeqCst);
+            }
+            if request.contains("%22Nonce%22%3A5%2C") {
+                r#"{"code":21105,"message":"invalid nonce"}"#.to_string()
+            } else {
+                r#"{"code":200}"#.to_string()
+            }
+        })
+        .await
+    };
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .validate_orders(false)
+        .build()
+        .unwrap();
+
+    // replayed on the first rejection, with the refetched nonce
+    assert_eq!(client.cancel_order(0, 1).await.unwrap().code, 200);
+    assert_eq!(client.recovered_transactions(), 1);
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+    // an order is not re-signed, since the consumed nonce may have been this very order
+    let order = CreateOrderRequest { account_index: 1, ..CreateOrderRequest::limit(0, Side::Buy, 100, 300_000) };
+    match client.create_order_with_nonce(order, Some(5)).await {
+        Err(ApiError::NonceTooLow { nonce: 5, code: Some(21105), .. }) => {}
+        other => panic!("expected NonceTooLow, got {:?}", other),
+    }
+    assert_eq!(orders.load(Ordering::SeqCst), 1);
+    assert_eq!(client.recovered_transactions(), 1);
+    // the cache moves past the consumed nonces
+    assert_eq!(client.get_nonce(false).await.unwrap(), 9);
+    assert_eq!(fetches.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_nonce_too_high_fills_gap_after_threshold() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // The server expects nonce 8, but its first two nonce answers run ahead (12)
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let base_url = {
+        let fetches = fetches.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                let nonce = if fetches.fetch_add(1, Ordering::SeqCst) < 2 { 12 } else { 8 };
+                format!(r#"{{"code":200,"nonce":{}}}"#, nonce)
+            } else if request.contains("%22Nonce%22%3A12%2C") {
+                r#"{"code":21106,"message":"invalid nonce"}"#.to_string()
+            } else {
+                r#"{"code":200}"#.to_string()
+            }
+        })
+        .await
+    };
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .nonce_gap_threshold(2)
+        .build()
+        .unwrap();
+
+    // a lower nonce may still be in flight, so the first one is only reported
+    match client.cancel_order(0, 1).await {
+        Err(ApiError::NonceTooHigh { nonce: 12, code: Some(21106), .. }) => {}
+        other => panic!("expected NonceTooHigh, got {:?}", other),
+    }
+    assert_eq!(client.recovered_transactions(), 0);
+
+    // the second in a row fills the gap with the refetched nonce
+    assert_eq!(client.cancel_order(0, 2).await.unwrap().code, 200);
+    assert_eq!(client.recovered_transactions(), 1);
+    assert_eq!(fetches.load(Ordering::SeqCst), 3);
+    assert_eq!(client.get_nonce(false).await.unwrap(), 9);
+}
+
+/// Pinned `tx_info` shape: integers are JSON numbers, only PubKey and Sig are strings
+#[test]
+fn test_tx_info_integers_are_json_numbers() {
+    use crate::{
+        CancelAllOrdersTx, CancelOrderTx, ChangePubKeyTx, CreateOrderTx, ModifyOrderTx, Transaction, TxHeader,
+        UpdateLeverageTx, UpdateMarginTx,
+    };
+    use serde_json::Value;
+
+    fn signed<T: Transaction>(client: &LighterClient, mut tx: T) -> (u32, Value) {
+        (T::TX_TYPE, client.build_and_sign(i64::MAX, &mut tx).unwrap())
+    }
+
+    let client = LighterClient::new(TESTNET_URL.to_string(), &test_key_hex(), 281_474_976_710_655, 254).unwrap();
+    let header = TxHeader::default();
+    // Beyond 2^53, where a float-based JSON reader would lose precision
+    let large = 9_007_199_254_740_993;
+    let signed_txs = [
+        signed(&client, ChangePubKeyTx { header, pub_key: hex::encode([7u8; 40]) }),
+        signed(&client, CreateOrderTx {
+            header,
+            market_index: 1,
+            client_order_index: large as u64,
+            base_amount: large,
+            price: 305_000,
+            is_ask: 1,
+            order_type: 0,
+            time_in_force: 1,
+            reduce_only: 0,
+            trigger_price: 0,
+            order_expiry: 1_702_419_200_000,
+        }),
+        signed(&client, CancelOrderTx { header, market_index: 1, index: large }),
+        signed(&client, CancelAllOrdersTx { header, time_in_force: 1, time: 1_702_419_200_000 }),
+        signed(&client, ModifyOrderTx { header, market_index: 1, index: large, base_amount: 10, price: 305_000, trigger_price: 0 }),
+        signed(&client, UpdateLeverageTx { header, market_index: 1, initial_margin_fraction: 500, margin_mode: 1 }),
+        signed(&client, UpdateMarginTx { header, market_index: 1, usdc_amount: large, direction: 1 }),
+    ];
+
+    for (tx_type, tx_info) in &signed_txs {
+        let fields = tx_info.as_object().unwrap();
+        let mut expected: Vec<&str> = ["Nonce", "ExpiredAt", "AccountIndex", "ApiKeyIndex", "Sig"].to_vec();
+        expected.extend(crate::tx::body_fields(*tx_type).unwrap().iter().map(|(name, _)| *name));
+        let mut keys: Vec<&str> = fields.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(keys, expected, "tx type {}", tx_type);
+
+        for (name, value) in fields {
+            match name.as_str() {
+                "PubKey" | "Sig" => assert!(value.is_string(), "tx type {}: {} = {}", tx_type, name, value),
+                _ => assert!(value.is_i64() || value.is_u64(), "tx type {}: {} = {}", tx_type, name, value),
+            }
+        }
+        let text = serde_json::to_string(tx_info).unwrap();
+        assert!(text.contains(&format!(r#""Nonce":{}"#, i64::MAX)), "{}", text);
+        assert!(text.contains(r#""AccountIndex":281474976710655"#), "{}", text);
+    }
+    let create_order = serde_json::to_string(&signed_txs[1].1).unwrap();
+    assert!(create_order.contains(r#""ClientOrderIndex":9007199254740993"#), "{}", create_order);
+    assert!(create_order.contains(r#""BaseAmount":9007199254740993"#), "{}", create_order);
+
+    // A quoted integer is refused before signing instead of hashing differently than the exchange
+    let tx_json = r#"{"AccountIndex":42,"ApiKeyIndex":3,"ExpiredAt":1700000600000,"Nonce":"7","MarketIndex":1,"Index":99}"#;
+    let Err(ApiError::Api(message)) = client.sign_transaction_with_type(tx_json, 15) else {
+        panic!("quoted Nonce was signed");
+    };
+    assert!(message.contains("Nonce must be a JSON number"), "{}", message);
+}
+
+#[test]
+fn test_typed_transaction_json_and_signature() {
+    use crate::{CancelOrderTx, CreateOrderRequest, Transaction, TxHeader};
+    use serde_json::json;
+
+    let header = TxHeader { account_index: 42, api_key_index: 3, expired_at: 1_700_000_000_000, nonce: 7 };
+    let cancel = CancelOrderTx { header, market_index: 1, index: 99 };
+    assert_eq!(CancelOrderTx::TX_TYPE, 15);
+    assert_eq!(
+        serde_json::to_value(&cancel).unwrap(),
+        json!({"AccountIndex": 42, "ApiKeyIndex": 3, "ExpiredAt": 1_700_000_000_000i64, "Nonce": 7, "MarketIndex": 1, "Index": 99})
+    );
+
+    let order = CreateOrderRequest {
+        account_index: 42,
+        order_book_index: 0,
+        client_order_index: 12,
+        base_amount: 100,
+        price: 300_000,
+        side: Side::Sell,
+        order_type: 0,
+        time_in_force: 1,
+        reduce_only: true,
+        trigger_price: 0,
+        order_expiry: OrderExpiry::None,
+        price_protection: None,
+    };
+    let mut tx = order.to_tx().unwrap();
+    tx.header = header;
+    let tx_json = serde_json::to_string(&tx).unwrap();
+    for field in [r#""IsAsk":1"#, r#""Type":0"#, r#""ReduceOnly":1"#, r#""OrderExpiry":0"#] {
+        assert!(tx_json.contains(field), "missing {} in {}", field, tx_json);
+    }
+
+    // Signatures are randomized, so compare through verification of the same
+    // message: the typed and raw paths must hash identically
+    let client = LighterClient::new(TESTNET_URL.to_string(), &test_key_hex(), 42, 3).unwrap();
+    let typed = crate::tx::message_hash(300, 14, &serde_json::to_value(&tx).unwrap()).unwrap();
+    let raw = crate::tx::message_hash(300, 14, &serde_json::from_str(&tx_json).unwrap()).unwrap();
+    assert_eq!(typed, raw);
+    client.sign_transaction(&tx).unwrap();
+    client.sign_raw(&tx_json).unwrap();
+}
+
+#[tokio::test]
+async fn test_order_book_desync_triggers_resubscribe() {
+    use crate::BookEvent;
+
+    let script = vec![
+        r#"{"type":"subscribed/order_book","channel":"order_book:0","order_book":{
+            "bids":[{"price":"3000.00","size":"1.0"}],"asks":[{"price":"3001.00","size":"0.5"}]}}"#,
+        // a bid through the best ask: a missed update left the book crossed
+        r#"{"type":"update/order_book","channel":"order_book:0","order_book":{
+            "bids":[{"price":"3002.00","size":"1.0"}],"asks":[]}}"#,
+        AWAIT_CLIENT,
+        AWAIT_CLIENT,
+        // in flight before the resubscription: ignored
+        r#"{"type":"update/order_book","channel":"order_book:0","order_book":{
+            "bids":[{"price":"2990.00","size":"9.0"}],"asks":[]}}"#,
+        r#"{"type":"subscribed/order_book","channel":"order_book:0","order_book":{
+            "bids":[{"price":"3001.50","size":"2.0"}],"asks":[{"price":"3002.50","size":"1.0"}]}}"#,
+    ];
+    let (base_url, received) = mock_ws_server(script.into_iter().map(String::from).collect()).await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let events: Vec<_> = client
+        .subscribe_order_book(0, None)
+        .await
+        .unwrap()
+        .map(|event| event.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 3);
+    assert!(matches!(&events[0], BookEvent::Updated(book) if book.consistency_error().is_none()));
+    match &events[1] {
+        BookEvent::DesyncDetected { market_index, reason } => {
+            assert_eq!(*market_index, 0);
+            assert!(reason.contains("crossed"), "{}", reason);
+        }
+        other => panic!("expected DesyncDetected, got {:?}", other),
+    }
+    let BookEvent::Updated(book) = &events[2] else { panic!("expected Updated, got {:?}", events[2]) };
+    assert_eq!(book.bids().count(), 1);
+    assert_eq!(book.best_bid().unwrap().price, "3001.50".parse().unwrap());
+
+    let received = received.lock().unwrap().clone();
+    let channels: Vec<_> = received
+        .iter()
+        .map(|text| serde_json::from_str::<serde_json::Value>(text).unwrap())
+        .map(|message| format!("{} {}", message["type"].as_str().unwrap(), message["channel"].as_str().unwrap()))
+        .collect();
+    assert_eq!(channels, ["subscribe order_book/0", "unsubscribe order_book/0", "subscribe order_book/0"]);
+}
+
+#[tokio::test]
+async fn test_order_book_depth_limits_levels_and_skips_deep_updates() {
+    use crate::BookEvent;
+
+    let script = vec![
+        r#"{"type":"subscribed/order_book","channel":"order_book:0","order_book":{
+            "bids":[{"price":"3000","size":"1"},{"price":"2999","size":"1"},{"price":"2998","size":"1"}],
+            "asks":[{"price":"3001","size":"1"},{"price":"3002","size":"1"},{"price":"3003","size":"1"}]}}"#,
+        // Third level only: outside the requested depth
+        r#"{"type":"update/order_book","channel":"order_book:0","order_book":{
+            "bids":[{"price":"2998","size":"5"}],"asks":[]}}"#,
+        r#"{"type":"update/order_book","channel":"order_book:0","order_book":{
+            "bids":[],"asks":[{"price":"3001","size":"0"}]}}"#,
+    ];
+    let (base_url, _) = mock_ws_server(script.into_iter().map(String::from).collect()).await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let books: Vec<_> = client
+        .subscribe_order_book(0, Some(2))
+        .await
+        .unwrap()
+        .map(|event| match event.unwrap() {
+            BookEvent::Updated(book) => book,
+            other => panic!("expected Updated, got {:?}", other),
+        })
+        .collect()
+        .await;
+
+    assert_eq!(books.len(), 2);
+    assert!(books.iter().all(|book| book.bids().count() == 2 && book.asks().count() == 2));
+    let asks: Vec<String> = books[1].asks().map(|level| level.price.to_string()).collect();
+    assert_eq!(asks, ["3002", "3003"]);
+}
+
+#[tokio::test]
+async fn test_request_and_ws_idle_timeouts_are_distinct_errors() {
+    // Accepts connections, reads the request and never answers
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut sockets = Vec::new();
+        while let Ok((mut socket, _)) = listener.accept().await {
+            read_request(&mut socket).await;
+            sockets.push(socket);
+        }
+    });
+    let client = LighterClient::builder(format!("http://{}", addr), &test_key_hex(), 1, 0)
+        .request_timeout(Duration::from_millis(200))
+        .build()
+        .unwrap();
+    match client.get_nonce(false).await {
+        Err(ApiError::RequestTimeout(timeout)) => assert_eq!(timeout, Duration::from_millis(200)),
+        other => panic!("expected RequestTimeout, got {:?}", other),
+    }
+
+    let snapshot = r#"{"type":"subscribed/order_book","channel":"order_book:0","order_book":{"bids":[],"asks":[]}}"#;
+    let (base_url, _) = mock_ws_server(vec![snapshot.to_string(), AWAIT_CLIENT.to_string()]).await;
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .ws_idle_timeout(Duration::from_millis(200))
+        .build()
+        .unwrap();
+    let mut book = client.subscribe_order_book(0, None).await.unwrap();
+    assert!(book.next().await.unwrap().is_ok());
+    assert!(matches!(book.next().await, Some(Err(ApiError::WebSocketIdle(_)))));
+    assert!(book.next().await.is_none());
+
+    let zero = LighterClient::builder(TESTNET_URL.to_string(), &test_key_hex(), 1, 0)
+        .connect_timeout(Duration::ZERO)
+        .build();
+    assert!(matches!(zero, Err(ApiError::InvalidConfig(_))));
+}
+
+#[tokio::test]
+async fn test_missed_heartbeat_pong_reconnects_subscription() {
+    use crate::BookEvent;
+
+    let snapshot = |bid: &str| {
+        format!(
+            r#"{{"type":"subscribed/order_book","channel":"order_book:0","order_book":{{"bids":[{{"price":"{}","size":"1.0"}}],"asks":[]}}}}"#,
+            bid
+        )
+    };
+    let (base_url, received) = mock_ws_sessions(vec![
+        vec![snapshot("3000.00"), STALL.to_string()],
+        vec![snapshot("3100.00")],
+    ])
+    .await;
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .ws_heartbeat(Duration::from_millis(100), Duration::from_millis(200))
+        .build()
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    let events: Vec<_> = client
+        .subscribe_order_book(0, None)
+        .await
+        .unwrap()
+        .map(|event| event.unwrap())
+        .collect()
+        .await;
+
+    assert!(started.elapsed() < Duration::from_secs(4), "stall was not detected");
+    assert_eq!(events.len(), 3);
+    assert!(matches!(events[1], BookEvent::Reconnected { market_index: 0 }));
+    let BookEvent::Updated(book) = &events[2] else { panic!("expected Updated, got {:?}", events[2]) };
+    assert_eq!(book.best_bid().unwrap().price, "3100.00".parse().unwrap());
+    // subscribed once per connection
+    assert_eq!(received.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_good_till_date_limit_order_tx_info() {
+    use crate::{CreateOrderRequest, MAX_ORDER_EXPIRY};
+    use std::sync::Mutex;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let sent = Arc::new(Mutex::new(String::new()));
+    let base_url = {
+        let sent = sent.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":1}"#.to_string();
+            }
+            *sent.lock().unwrap() = request.to_string();
+            r#"{"code":200}"#.to_string()
+        })
+        .await
+    };
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .validate_orders(false)
+        .build()
+        .unwrap();
+    let order = |time_in_force, order_expiry| CreateOrderRequest {
+        account_index: 1,
+        order_book_index: 0,
+        client_order_index: 7,
+        base_amount: 100,
+        price: 300_000,
+        side: Side::Buy,
+        order_type: 0,
+        time_in_force,
+        reduce_only: false,
+        trigger_price: 0,
+        order_expiry,
+        price_protection: None,
+    };
+
+    let day = Duration::from_secs(24 * 60 * 60);
+    let now = || SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as i64;
+    let before = now();
+    client.create_order(order(1, OrderExpiry::After(day))).await.unwrap();
+    let after = now();
+
+    let request = sent.lock().unwrap().clone();
+    let body = request.split("\r\n\r\n").nth(1).unwrap();
+    let (_, tx_info) = url_decoded_form(body).into_iter().find(|(key, _)| key == "tx_info").unwrap();
+    let tx_info: serde_json::Value = serde_json::from_str(&tx_info).unwrap();
+
+    // OrderExpiry is the order's lifetime on the book, ExpiredAt the transaction's validity
+    let order_expiry = tx_info["OrderExpiry"].as_i64().unwrap();
+    assert!((before + day.as_millis() as i64..=after + day.as_millis() as i64).contains(&order_expiry));
+    let expired_at = tx_info["ExpiredAt"].as_i64().unwrap();
+    assert!((before + DEFAULT_TX_EXPIRY.as_millis() as i64..=after + DEFAULT_TX_EXPIRY.as_millis() as i64).contains(&expired_at));
+
+    let invalid = [
+        order(1, OrderExpiry::At(now() - 1)),
+        order(1, OrderExpiry::After(MAX_ORDER_EXPIRY + day)),
+        order(0, OrderExpiry::After(day)),
+    ];
+    for order in invalid {
+        assert!(matches!(client.create_order(order).await, Err(ApiError::InvalidOrderParams(_))));
+    }
+}
+
+/// Decode an `application/x-www-form-urlencoded` body
+fn url_decoded_form(body: &str) -> Vec<(String, String)> {
+    let decode = |text: &str| {
+        let bytes = text.replace('+', " ").into_bytes();
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'%' && i + 2 < bytes.len() {
+                out.push(u8::from_str_radix(std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap(), 16).unwrap());
+                i += 3;
+            } else {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+        String::from_utf8(out).unwrap()
+    };
+    body.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (decode(key), decode(value)))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_replay_sender_matches_with_price_time_priority() {
+    use crate::{CreateOrderRequest, ReplayEvent, ReplaySender, SendTxResponse, TransactionSender};
+
+    // Strategy code written once against the trait
+    async fn buy<S: TransactionSender>(sender: &S, index: u64, price: i64, size: i64, tif: u8) -> crate::Result<SendTxResponse> {
+        let order = CreateOrderRequest {
+            account_index: 1,
+            order_book_index: 0,
+            client_order_index: index,
+            base_amount: size,
+            price,
+            side: Side::Buy,
+            order_type: 0,
+            time_in_force: tif,
+            reduce_only: false,
+            trigger_price: 0,
+            order_expiry: OrderExpiry::None,
+            price_protection: None,
+        };
+        sender.create_order(order).await
+    }
+
+    let sim = ReplaySender::new();
+    sim.apply(ReplayEvent::Book {
+        market_index: 0,
+        timestamp: 1000,
+        bids: vec![(99, 5)],
+        asks: vec![(101, 5), (102, 5)],
+    });
+
+    // Takes 5 @ 101 and 3 @ 102, remainder 0
+    assert_eq!(buy(&sim, 1, 102, 8, 1).await.unwrap().code, 200);
+    assert_eq!(sim.fills().iter().map(|f| (f.price, f.base_amount)).collect::<Vec<_>>(), [(101, 5), (102, 3)]);
+    assert!(sim.open_orders().is_empty());
+
+    // 101 no longer crosses (only 2 @ 102 left): both rest
+    assert_eq!(buy(&sim, 2, 101, 3, 3).await.unwrap().code, 200);
+    assert_eq!(buy(&sim, 3, 101, 2, 1).await.unwrap().code, 200);
+    assert_eq!(buy(&sim, 4, 100, 4, 1).await.unwrap().code, 200);
+    let result = buy(&sim, 5, 102, 1, 3).await;
+    assert!(matches!(result, Err(ApiError::PostOnlyWouldCross { market_index: Some(0), .. })), "{:?}", result);
+    let result = buy(&sim, 6, 103, 5, 2).await;
+    assert!(matches!(result, Err(ApiError::Rejected { code: 400, .. })), "fill-or-kill lacks liquidity: {:?}", result);
+    assert_eq!(sim.open_orders().len(), 3);
+
+    let fills = sim.apply(ReplayEvent::Trade {
+        market_index: 0,
+        timestamp: 2000,
+        price: 100,
+        base_amount: 6,
+        taker_side: Side::Sell,
+    });
+    let matched: Vec<(u64, i64, i64)> = fills.iter().map(|f| (f.client_order_index, f.price, f.base_amount)).collect();
+    assert_eq!(matched, [(2, 101, 3), (3, 101, 2), (4, 100, 1)]);
+    assert!(fills.iter().all(|f| f.is_maker && f.timestamp == 2000));
+
+    let rest = sim.open_orders();
+    assert_eq!((rest.len(), rest[0].remaining_base_amount), (1, 3));
+    sim.cancel_order(0, rest[0].order_index).await.unwrap();
+    assert!(matches!(sim.cancel_order(0, rest[0].order_index).await, Err(ApiError::NotFound(_))));
+    assert_eq!(sim.fills().len(), 5);
+}
+
+#[tokio::test]
+async fn test_send_tx_form_and_json_encodings() {
+    use crate::{SendTxEncoding, FIELD_PRICE_PROTECTION, FIELD_TX_INFO, FIELD_TX_TYPE};
+    use std::sync::Mutex;
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let sent = sent.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":1}"#.to_string();
+            }
+            sent.lock().unwrap().push(request.to_string());
+            r#"{"code":200}"#.to_string()
+        })
+        .await
+    };
+
+    for encoding in [SendTxEncoding::Form, SendTxEncoding::Json] {
+        let client = LighterClient::builder(base_url.clone(), &test_key_hex(), 1, 0)
+            .send_tx_encoding(encoding)
+            .build()
+            .unwrap();
+        client.cancel_order(2, 77).await.unwrap();
+    }
+
+    let sent = sent.lock().unwrap().clone();
+    let body = |request: &str| request.split_once("\r\n\r\n").unwrap().1.to_string();
+
+    let form = url_decoded_form(&body(&sent[0]));
+    let field = |name: &str| form.iter().find(|(key, _)| key == name).unwrap().1.clone();
+    assert_eq!(field(FIELD_TX_TYPE), "15");
+    assert_eq!(field(FIELD_PRICE_PROTECTION), "true");
+    let tx_info: serde_json::Value = serde_json::from_str(&field(FIELD_TX_INFO)).unwrap();
+    assert_eq!(tx_info["Index"], 77);
+
+    assert!(sent[1].to_ascii_lowercase().contains("content-type: application/json"));
+    let json: serde_json::Value = serde_json::from_str(&body(&sent[1])).unwrap();
+    assert_eq!(json[FIELD_TX_TYPE], 15);
+    assert_eq!(json[FIELD_PRICE_PROTECTION], true);
+    let tx_info: serde_json::Value = serde_json::from_str(json[FIELD_TX_INFO].as_str().unwrap()).unwrap();
+    assert_eq!(tx_info["MarketIndex"], 2);
+}
+
+#[tokio::test]
+async fn test_ws_frame_handler_sees_unrecognized_frames_and_stream_continues() {
+    use crate::BookEvent;
+    use std::sync::Mutex;
+
+    let snapshot = r#"{"type":"subscribed/order_book","channel":"order_book:0","order_book":{"bids":[{"price":"3000","size":"1"}],"asks":[]}}"#;
+    let script = vec![
+        snapshot,
+        "not json",
+        r#"{"type":"brand_new_message","payload":1}"#,
+        r#"{"type":"update/order_book","channel":"order_book:0","order_book":{"bids":"unexpected"}}"#,
+        AWAIT_CLIENT,
+        AWAIT_CLIENT,
+        snapshot,
+    ];
+    let (base_url, _) = mock_ws_server(script.into_iter().map(String::from).collect()).await;
+    let frames = Arc::new(Mutex::new(Vec::new()));
+    let sink = frames.clone();
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .on_ws_frame(move |frame| sink.lock().unwrap().push(frame.to_string()))
+        .build()
+        .unwrap();
+
+    let events: Vec<_> = client
+        .subscribe_order_book(0, None)
+        .await
+        .unwrap()
+        .map(|event| event.unwrap())
+        .collect()
+        .await;
+
+    assert_eq!(events.len(), 3);
+    assert!(matches!(&events[1], BookEvent::DesyncDetected { reason, .. } if reason.contains("unreadable update")));
+    assert!(matches!(&events[2], BookEvent::Updated(book) if book.best_bid().is_some()));
+    let frames = frames.lock().unwrap().clone();
+    assert_eq!(frames.len(), 5);
+    assert_eq!(frames[1], "not json");
+    assert!(frames[2].contains("brand_new_message"));
+}
+
+#[tokio::test]
+async fn test_fixed_clock_sets_exact_expiries() {
+    use crate::{Clock, CreateOrderRequest};
+    use std::sync::Mutex;
+
+    #[derive(Debug)]
+    struct FixedClock(i64);
+    impl Clock for FixedClock {
+        fn now_ms(&self) -> i64 {
+            self.0
+        }
+    }
+
+    const NOW: i64 = 1_700_000_000_000;
+    let sent = Arc::new(Mutex::new(String::new()));
+    let base_url = {
+        let sent = sent.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":1}"#.to_string();
+            }
+            *sent.lock().unwrap() = request.to_string();
+            r#"{"code":200}"#.to_string()
+        })
+        .await
+    };
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .clock(FixedClock(NOW))
+        .default_expiry(Duration::from_secs(300))
+        .validate_orders(false)
+        .build()
+        .unwrap();
+
+    let order = CreateOrderRequest {
+        account_index: 1,
+        order_book_index: 0,
+        client_order_index: 7,
+        base_amount: 100,
+        price: 300_000,
+        side: Side::Buy,
+        order_type: 0,
+        time_in_force: 1,
+        reduce_only: false,
+        trigger_price: 0,
+        order_expiry: OrderExpiry::After(Duration::from_secs(3600)),
+        price_protection: None,
+    };
+    client.create_order(order).await.unwrap();
+
+    let request = sent.lock().unwrap().clone();
+    let body = request.split_once("\r\n\r\n").unwrap().1;
+    let (_, tx_info) = url_decoded_form(body).into_iter().find(|(key, _)| key == "tx_info").unwrap();
+    let tx_info: serde_json::Value = serde_json::from_str(&tx_info).unwrap();
+    assert_eq!(tx_info["ExpiredAt"], NOW + 300_000);
+    assert_eq!(tx_info["OrderExpiry"], NOW + 3_600_000);
+}
+
+#[tokio::test]
+async fn test_order_pipeline_returns_tags_with_results() {
+    use crate::{CreateOrderRequest, OrderPipeline};
+
+    let base_url = mock_server(|request| {
+        if request.contains("/api/v1/nextNonce") {
+            return r#"{"code":200,"nonce":1}"#.to_string();
+        }
+        // The tag must never reach the wire
+        assert!(!request.contains("intent-"));
+        r#"{"code":200,"tx_hash":"0xabc"}"#.to_string()
+    })
+    .await;
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .validate_orders(false)
+        .build()
+        .unwrap();
+
+    let (pipeline, mut results) = OrderPipeline::new(client);
+    for client_order_index in 1..=3 {
+        let order = CreateOrderRequest {
+            account_index: 1,
+            order_book_index: 0,
+            client_order_index,
+            base_amount: 100,
+            price: 300_000,
+            side: Side::Buy,
+            order_type: 0,
+            time_in_force: 1,
+            reduce_only: false,
+            trigger_price: 0,
+            order_expiry: OrderExpiry::default(),
+            price_protection: None,
+        };
+        pipeline.submit(order, format!("intent-{}", client_order_index));
+    }
+    drop(pipeline);
+
+    let mut received = Vec::new();
+    while let Some(result) = results.recv().await {
+        assert_eq!(result.result.unwrap().code, 200);
+        received.push((result.client_order_index, result.tag));
+    }
+    received.sort();
+    assert_eq!(
+        received,
+        vec![(1, "intent-1".to_string()), (2, "intent-2".to_string()), (3, "intent-3".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn test_place_orders_stream_bounds_concurrency_and_yields_as_completed() {
+    use crate::CreateOrderRequest;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // sendTx is answered after a delay so concurrent orders overlap on the server
+    let (active, peak) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    {
+        let (active, peak) = (active.clone(), peak.clone());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let (active, peak) = (active.clone(), peak.clone());
+                tokio::spawn(async move {
+                    let request = read_request(&mut socket).await;
+                    let body = if request.contains("/api/v1/orderBooks") {
+                        ETH_MARKET_JSON.to_string()
+                    } else if request.contains("/api/v1/nextNonce") {
+                        r#"{"code":200,"nonce":100}"#.to_string()
+                    } else {
+                        let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        active.fetch_sub(1, Ordering::SeqCst);
+                        r#"{"code":200,"tx_hash":"0xabc"}"#.to_string()
+                    };
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = socket.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+    }
+    let order = |client_order_index, base_amount| CreateOrderRequest {
+        account_index: 1,
+        order_book_index: 0,
+        client_order_index,
+        base_amount,
+        price: 300_000,
+        side: Side::Buy,
+        order_type: 0,
+        time_in_force: 1,
+        reduce_only: false,
+        trigger_price: 0,
+        order_expiry: OrderExpiry::None,
+        price_protection: None,
+    };
+    // The fifth order is below the market minimum and fails without being sent
+    let orders: Vec<_> = (1..=6).map(|i| order(i, if i == 5 { 10 } else { 100 })).collect();
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let mut results = client.place_orders_stream(orders, 2);
+    let mut received = Vec::new();
+    while let Some(result) = results.next().await {
+        // The validation failure does not wait behind the orders on the wire
+        if result.tag == 4 {
+            assert!(matches!(result.result, Err(ApiError::InvalidOrderParams(_))));
+            assert!(received.len() < 5);
+        } else {
+            assert_eq!(result.result.unwrap().code, 200);
+        }
+        assert_eq!(result.client_order_index, result.tag as u64 + 1);
+        received.push(result.tag);
+    }
+    received.sort();
+    assert_eq!(received, vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(peak.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_estimate_market_fill_walks_the_book() {
+    use rust_decimal::Decimal;
+
+    let base_url = mock_server(|request| {
+        if request.contains("/api/v1/orderBooks") {
+            return ETH_MARKET_JSON.to_string();
+        }
+        assert!(request.contains("/api/v1/orderBookOrders"));
+        r#"{"code":200,"bids":[{"price":"2999.00","remaining_base_amount":"1"}],
+            "asks":[{"price":"3000.00","remaining_base_amount":"1"},{"price":"3010.00","remaining_base_amount":"2"}]}"#
+            .to_string()
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    // 2 ETH in lots of 0.0001: 1 @ 3000 and 1 @ 3010
+    let estimate = client.estimate_market_fill(0, Side::Buy, 20_000).await.unwrap();
+    assert!(estimate.sufficient_liquidity);
+    assert_eq!(estimate.filled, Decimal::new(2, 0));
+    assert_eq!(estimate.average_price, Some(Decimal::new(3005, 0)));
+    assert_eq!(estimate.worst_price, Some(Decimal::new(3010, 0)));
+    assert_eq!(estimate.slippage_pct.unwrap().round_dp(4), Decimal::new(1667, 4));
+
+    // Only 1 ETH of bids
+    let estimate = client.estimate_market_fill(0, Side::Sell, 15_000).await.unwrap();
+    assert!(!estimate.sufficient_liquidity);
+    assert_eq!(estimate.filled, Decimal::ONE);
+    assert_eq!(estimate.average_price, Some(Decimal::new(2999, 0)));
+}
+
+#[tokio::test]
+async fn test_close_position_sends_opposite_reduce_only_order() {
+    use crate::SlippageGuard;
+    use rust_decimal::Decimal;
+    use std::sync::Mutex;
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let sent = sent.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/account?") {
+                return r#"{"code":200,"accounts":[{"positions":[
+                    {"market_id":0,"symbol":"ETH","sign":-1,"position":"1.5","avg_entry_price":"3000","position_value":"4500"},
+                    {"market_id":1,"symbol":"BTC","sign":1,"position":"0"}
+                ]}]}"#
+                    .to_string();
+            }
+            if request.contains("/api/v1/orderBooks") {
+                return ETH_MARKET_JSON.to_string();
+            }
+            if request.contains("/api/v1/orderBookOrders") {
+                return r#"{"code":200,"bids":[{"price":"2999.00","remaining_base_amount":"1"}],
+                    "asks":[{"price":"3000.00","remaining_base_amount":"1"}]}"#
+                    .to_string();
+            }
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":1}"#.to_string();
+            }
+            sent.lock().unwrap().push(request.to_string());
+            r#"{"code":200}"#.to_string()
+        })
+        .await
+    };
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+    let guard = SlippageGuard::new(Decimal::ONE).unwrap();
+
+    client.close_position(1, 0, 9, guard).await.unwrap();
+    let request = sent.lock().unwrap()[0].clone();
+    let body = request.split_once("\r\n\r\n").unwrap().1;
+    let (_, tx_info) = url_decoded_form(body).into_iter().find(|(key, _)| key == "tx_info").unwrap();
+    let tx_info: serde_json::Value = serde_json::from_str(&tx_info).unwrap();
+    // Short 1.5 ETH -> buy 15000 lots (4 size decimals), at most 1% above the best ask
+    assert_eq!(tx_info["IsAsk"], 0);
+    assert_eq!(tx_info["ReduceOnly"], 1);
+    assert_eq!(tx_info["BaseAmount"], 15_000);
+    assert_eq!(tx_info["Price"], 303_000);
+
+    for (account, market) in [(1, 1), (2, 0)] {
+        let result = client.close_position(account, market, 10, guard).await;
+        assert!(matches!(result, Err(ApiError::InvalidOrderParams(_))), "{:?}", result);
+    }
+    assert_eq!(sent.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_flatten_account_cancels_then_closes_and_reports_partial_failures() {
+    use crate::SlippageGuard;
+    use rust_decimal::Decimal;
+    use std::sync::Mutex;
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let sent = sent.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/account?") {
+                // No market metadata for BTC, so its closing order cannot be built
+                return r#"{"code":200,"accounts":[{"positions":[
+                    {"market_id":0,"symbol":"ETH","sign":-1,"position":"1.5","avg_entry_price":"3000","position_value":"4500"},
+                    {"market_id":1,"symbol":"BTC","sign":1,"position":"2","avg_entry_price":"60000","position_value":"120000"}
+                ]}]}"#
+                    .to_string();
+            }
+            if request.contains("/api/v1/orderBooks") {
+                return ETH_MARKET_JSON.to_string();
+            }
+            if request.contains("/api/v1/orderBookOrders") {
+                return r#"{"code":200,"bids":[{"price":"2999.00","remaining_base_amount":"1"}],
+                    "asks":[{"price":"3000.00","remaining_base_amount":"1"}]}"#
+                    .to_string();
+            }
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":1}"#.to_string();
+            }
+            sent.lock().unwrap().push(request.to_string());
+            if request.contains("/api/v1/sendTxBatch") {
+                r#"{"code":200,"tx_hash":["h0"]}"#.to_string()
+            } else {
+                r#"{"code":29500,"message":"internal error"}"#.to_string()
+            }
+        })
+        .await
+    };
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+    let guard = SlippageGuard::new(Decimal::ONE).unwrap();
+
+    let report = client.flatten_account(1, guard).await.unwrap();
+    // The failed cancel-all did not stop the closes, nor did the BTC failure stop ETH
+    assert!(matches!(report.cancel_all, Err(ApiError::Rejected { code: 29500, .. })));
+    let closes = report.closes.as_ref().unwrap();
+    assert_eq!(closes.iter().map(|close| (close.market_index, close.size)).collect::<Vec<_>>(),
+        vec![(0, Decimal::new(-15, 1)), (1, Decimal::new(2, 0))]);
+    assert_eq!(closes[0].result.as_ref().unwrap().tx_hash, "h0");
+    assert!(closes[1].result.is_err());
+    assert_ne!(closes[0].client_order_index, closes[1].client_order_index);
+    assert!(!report.is_complete());
+    assert_eq!(report.failed_markets(), vec![1]);
+
+    // Cancel first, then one batch with the reduce-only close
+    let sent = sent.lock().unwrap().clone();
+    assert_eq!(sent.len(), 2);
+    assert!(sent[0].contains("/api/v1/sendTx ") && sent[0].contains("tx_type=16"));
+    assert!(sent[1].contains("/api/v1/sendTxBatch"));
+    let form = url_decoded_form(sent[1].split_once("\r\n\r\n").unwrap().1);
+    let (_, tx_infos) = form.into_iter().find(|(key, _)| key == "tx_infos").unwrap();
+    let tx_infos: Vec<String> = serde_json::from_str(&tx_infos).unwrap();
+    let tx: serde_json::Value = serde_json::from_str(&tx_infos[0]).unwrap();
+    assert_eq!((tx["MarketIndex"].as_u64(), tx["IsAsk"].as_u64(), tx["ReduceOnly"].as_u64()), (Some(0), Some(0), Some(1)));
+    assert_eq!(tx["BaseAmount"], 15_000);
+
+    assert!(matches!(client.flatten_account(2, guard).await, Err(ApiError::InvalidOrderParams(_))));
+}
+
+#[tokio::test]
+async fn test_latency_stats_track_nonce_and_send_tx() {
+    let base_url = mock_server(|request| {
+        if request.contains("/api/v1/nextNonce") {
+            return r#"{"code":200,"nonce":1}"#.to_string();
+        }
+        r#"{"code":200}"#.to_string()
+    })
+    .await;
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .latency_window(2)
+        .build()
+        .unwrap();
+    assert_eq!(client.latency_stats().send_tx.count, 0);
+    assert_eq!(client.latency_stats().send_tx.p50, None);
+
+    for order_index in 0..3 {
+        client.cancel_order(0, order_index).await.unwrap();
+    }
+    let stats = client.latency_stats();
+    assert_eq!(stats.nonce_fetch.count, 1);
+    assert_eq!(stats.send_tx.count, 3);
+    let send_tx = stats.send_tx;
+    assert!(send_tx.ema.is_some());
+    assert!(send_tx.p50 <= send_tx.p95 && send_tx.p95 <= send_tx.p99 && send_tx.p99 == send_tx.max);
+
+    client.clone().reset_latency_stats();
+    assert_eq!(client.latency_stats(), Default::default());
+
+    let result = LighterClient::builder(TESTNET_URL.to_string(), &test_key_hex(), 1, 0)
+        .latency_window(0)
+        .build();
+    assert!(matches!(result, Err(ApiError::InvalidConfig(_))));
+}
+
+#[tokio::test]
+async fn test_submit_signed_sends_payload_unchanged() {
+    use crate::CreateOrderRequest;
+    use std::sync::Mutex;
+
+    let signer_url = mock_server(|request| {
+        if request.contains("/api/v1/orderBooks") {
+            ETH_MARKET_JSON.to_string()
+        } else {
+            r#"{"code":200,"nonce":7}"#.to_string()
+        }
+    })
+    .await;
+    let signer = LighterClient::new(signer_url, &test_key_hex(), 1, 0).unwrap();
+    let order = CreateOrderRequest {
+        account_index: 1,
+        order_book_index: 0,
+        client_order_index: 5,
+        base_amount: 100,
+        price: 300_000,
+        side: Side::Sell,
+        order_type: 0,
+        time_in_force: 1,
+        reduce_only: false,
+        trigger_price: 0,
+        order_expiry: OrderExpiry::None,
+        price_protection: None,
+    };
+    let signed = signer.sign_orders(&[order]).await.remove(0).unwrap();
+
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let submitter_url = {
+        let requests = requests.clone();
+        mock_server(move |request| {
+            requests.lock().unwrap().push(request.to_string());
+            r#"{"code":200}"#.to_string()
+        })
+        .await
+    };
+    // Different key: the submitting process does not need the signing key
+    let other_key = hex::encode(signer::KeyManager::generate().private_key_bytes());
+    let submitter = LighterClient::new(submitter_url, &other_key, 2, 3).unwrap();
+
+    let response = submitter.submit_signed(signed.tx_type, &signed.tx_info).await.unwrap();
+    assert_eq!(response.code, 200);
+    let sent = requests.lock().unwrap().clone();
+    assert_eq!(sent.len(), 1, "no nonce fetch expected: {:?}", sent);
+    let body = sent[0].split_once("\r\n\r\n").unwrap().1;
+    let form = url_decoded_form(body);
+    let field = |name: &str| form.iter().find(|(key, _)| key == name).unwrap().1.clone();
+    assert_eq!(field("tx_type"), "14");
+    let tx_info: serde_json::Value = serde_json::from_str(&field("tx_info")).unwrap();
+    assert_eq!(tx_info, signed.tx_info);
+
+    // Missing, empty, undecodable and wrongly sized signatures never reach the network
+    let short_sig = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, [7u8; 40]);
+    let bad_sigs = [serde_json::Value::Null, serde_json::json!(""), serde_json::json!("not base64!"), serde_json::json!(short_sig)];
+    for sig in bad_sigs {
+        let mut unsigned = signed.tx_info.clone();
+        unsigned["Sig"] = sig;
+        let result = submitter.submit_signed(signed.tx_type, &unsigned).await;
+        assert!(matches!(result, Err(ApiError::Unsigned(_))), "{:?}", result);
+        let result = submitter.post_tx_batch(&[(signed.tx_type, &signed.tx_info), (signed.tx_type, &unsigned)]).await;
+        assert!(matches!(result, Err(ApiError::Unsigned(_))), "{:?}", result);
+    }
+    assert_eq!(requests.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_market_stats_parse_and_default_missing_values_to_zero() {
+    use rust_decimal::Decimal;
+
+    let base_url = mock_server(|request| {
+        let eth = r#"{"market_id":0,"symbol":"ETH","last_trade_price":3024.66,"daily_trades_count":1200,
+            "daily_base_token_volume":"850.5","daily_quote_token_volume":2571000.25,"daily_price_low":2950,
+            "daily_price_high":3050.5,"daily_price_change":-1.25,"open_interest":412.3}"#;
+        // A market nobody traded today
+        let quiet = r#"{"market_id":7,"symbol":"NEW","last_trade_price":null,"daily_trades_count":null,
+            "daily_base_token_volume":0,"daily_price_change":null,"open_interest":null}"#;
+        if request.contains("market_id=0") {
+            format!(r#"{{"code":200,"order_book_details":[{}]}}"#, eth)
+        } else if request.contains("market_id=") {
+            r#"{"code":200,"order_book_details":[]}"#.to_string()
+        } else {
+            format!(r#"{{"code":200,"order_book_details":[{},{}]}}"#, quiet, eth)
+        }
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let eth = client.get_market_stats(0).await.unwrap();
+    assert_eq!(eth.symbol, "ETH");
+    assert_eq!(eth.last_price, Decimal::new(302466, 2));
+    assert_eq!(eth.volume_24h, Decimal::new(8505, 1));
+    assert_eq!(eth.quote_volume_24h, Decimal::new(257100025, 2));
+    assert_eq!(eth.price_change_24h, Decimal::new(-125, 2));
+    assert_eq!(eth.trades_24h, 1200);
+    assert_eq!(eth.open_interest, Decimal::new(4123, 1));
+
+    let all = client.get_all_market_stats().await.unwrap();
+    assert_eq!(all.iter().map(|stats| stats.market_index).collect::<Vec<_>>(), vec![0, 7]);
+    let quiet = &all[1];
+    assert_eq!(quiet.last_price, Decimal::ZERO);
+    assert_eq!(quiet.quote_volume_24h, Decimal::ZERO);
+    assert_eq!(quiet.trades_24h, 0);
+    assert_eq!(quiet.open_interest, Decimal::ZERO);
+
+    assert!(matches!(client.get_market_stats(3).await, Err(ApiError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn test_send_tx_batch_maps_failures_to_input_positions() {
+    use crate::CreateOrderRequest;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    let (reject, short) = (Arc::new(AtomicBool::new(false)), Arc::new(AtomicBool::new(false)));
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let (reject, short, batches) = (reject.clone(), short.clone(), batches.clone());
+        mock_server(move |request| {
+            if request.contains("/api/v1/orderBooks") {
+                return ETH_MARKET_JSON.to_string();
+            }
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":100}"#.to_string();
+            }
+            batches.lock().unwrap().push(request.to_string());
+            if reject.load(Ordering::SeqCst) {
+                r#"{"code":21120,"message":"invalid signature"}"#.to_string()
+            } else if short.load(Ordering::SeqCst) {
+                r#"{"code":200,"tx_hash":["h4"]}"#.to_string()
+            } else {
+                r#"{"code":200,"tx_hash":["h0","h2"]}"#.to_string()
+            }
+        })
+        .await
+    };
+    let order = |client_order_index, base_amount| CreateOrderRequest {
+        account_index: 1,
+        order_book_index: 0,
+        client_order_index,
+        base_amount,
+        price: 300_000,
+        side: Side::Buy,
+        order_type: 0,
+        time_in_force: 1,
+        reduce_only: false,
+        trigger_price: 0,
+        order_expiry: OrderExpiry::None,
+        price_protection: None,
+    };
+    // The second order is below the market minimum
+    let orders = [order(1, 100), order(2, 10), order(3, 100)];
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let result = client.send_tx_batch(&orders).await;
+    assert!(!result.is_success());
+    let failed = result.failed_items();
+    assert_eq!(failed.len(), 1);
+    assert!(matches!(failed[0], (1, ApiError::InvalidOrderParams(_))));
+    let succeeded = result.succeeded_items();
+    assert_eq!(succeeded.iter().map(|(index, _)| *index).collect::<Vec<_>>(), vec![0, 2]);
+    assert_eq!(succeeded[1].1.tx_hash, "h2");
+
+    let request = batches.lock().unwrap()[0].clone();
+    let form = url_decoded_form(request.split_once("\r\n\r\n").unwrap().1);
+    let field = |name: &str| form.iter().find(|(key, _)| key == name).unwrap().1.clone();
+    assert_eq!(field("tx_types"), "[14,14]");
+    let tx_infos: Vec<String> = serde_json::from_str(&field("tx_infos")).unwrap();
+    let sent: Vec<serde_json::Value> = tx_infos.iter().map(|info| serde_json::from_str(info).unwrap()).collect();
+    assert_eq!(sent.iter().map(|tx| tx["ClientOrderIndex"].as_u64().unwrap()).collect::<Vec<_>>(), vec![1, 3]);
+    assert_eq!(sent.iter().map(|tx| tx["Nonce"].as_i64().unwrap()).collect::<Vec<_>>(), vec![100, 101]);
+
+    // A rejected batch fails every submitted order and hands their nonces back
+    reject.store(true, Ordering::SeqCst);
+    let result = client.send_tx_batch(&orders[..1]).await;
+    assert!(matches!(result.failed_items()[..], [(0, ApiError::Api(_))]));
+    assert_eq!(client.get_nonce(false).await.unwrap(), 102);
+
+    // An accepted request without a hash for an order fails that order, but its nonce stays used
+    reject.store(false, Ordering::SeqCst);
+    short.store(true, Ordering::SeqCst);
+    let result = client.send_tx_batch(&[order(4, 100), order(5, 100)]).await;
+    let succeeded = result.succeeded_items();
+    assert_eq!(succeeded.iter().map(|(index, response)| (*index, response.tx_hash.as_str())).collect::<Vec<_>>(), [(0, "h4")]);
+    assert!(matches!(result.failed_items()[..], [(1, ApiError::Api(_))]));
+    assert_eq!(client.get_nonce(false).await.unwrap(), 105);
+}
+
+#[tokio::test]
+async fn test_send_tx_batch_splits_into_chunks_and_stops_after_a_failure() {
+    use crate::{CreateOrderRequest, DEFAULT_MAX_BATCH_SIZE};
+    use std::sync::Mutex;
+
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let batches = batches.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":100}"#.to_string();
+            }
+            let form = url_decoded_form(request.split_once("\r\n\r\n").unwrap().1);
+            let tx_infos = &form.iter().find(|(key, _)| key == "tx_infos").unwrap().1;
+            let tx_infos: Vec<String> = serde_json::from_str(tx_infos).unwrap();
+            let indices: Vec<u64> = tx_infos
+                .iter()
+                .map(|info| serde_json::from_str::<serde_json::Value>(info).unwrap()["ClientOrderIndex"].as_u64().unwrap())
+                .collect();
+            batches.lock().unwrap().push(indices.clone());
+            if indices.contains(&13) {
+                return r#"{"code":23000,"message":"too many requests"}"#.to_string();
+            }
+            let hashes: Vec<String> = indices.iter().map(|index| format!("h{}", index)).collect();
+            serde_json::json!({"code": 200, "tx_hash": hashes}).to_string()
+        })
+        .await
+    };
+    let order = |client_order_index| CreateOrderRequest {
+        account_index: 1,
+        order_book_index: 0,
+        client_order_index,
+        base_amount: 100,
+        price: 300_000,
+        side: Side::Buy,
+        order_type: 0,
+        time_in_force: 1,
+        reduce_only: false,
+        trigger_price: 0,
+        order_expiry: OrderExpiry::None,
+        price_protection: None,
+    };
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .validate_orders(false)
+        .max_batch_size(2)
+        .build()
+        .unwrap();
+    assert_eq!(client.max_batch_size(), 2);
+    let default_client = LighterClient::new(TESTNET_URL.to_string(), &test_key_hex(), 1, 0).unwrap();
+    assert_eq!(default_client.max_batch_size(), DEFAULT_MAX_BATCH_SIZE);
+
+    let orders: Vec<_> = (1..=5).map(order).collect();
+    let result = client.send_tx_batch(&orders).await;
+    assert!(result.is_success());
+    assert_eq!(*batches.lock().unwrap(), vec![vec![1, 2], vec![3, 4], vec![5]]);
+    let hashes: Vec<_> = result.items.iter().map(|item| item.as_ref().unwrap().tx_hash.as_str()).collect();
+    assert_eq!(hashes, vec!["h1", "h2", "h3", "h4", "h5"]);
+
+    // The second request is rejected: the third is never sent and all their nonces come back
+    batches.lock().unwrap().clear();
+    let orders: Vec<_> = (11..=15).map(order).collect();
+    let result = client.send_tx_batch(&orders).await;
+    assert_eq!(*batches.lock().unwrap(), vec![vec![11, 12], vec![13, 14]]);
+    assert_eq!(result.failed_items().iter().map(|(index, _)| *index).collect::<Vec<_>>(), vec![2, 3, 4]);
+    assert_eq!(client.get_nonce(false).await.unwrap(), 107);
+
+    let result = LighterClient::builder(TESTNET_URL.to_string(), &test_key_hex(), 1, 0)
+        .max_batch_size(0)
+        .build();
+    assert!(matches!(result, Err(ApiError::InvalidConfig(_))));
+}
+
+#[tokio::test]
+async fn test_place_bracket_validates_legs_and_submits_one_batch() {
+    use crate::{Bracket, CreateOrderRequest};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    let reject = Arc::new(AtomicBool::new(false));
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let (reject, batches) = (reject.clone(), batches.clone());
+        mock_server(move |request| {
+            if request.contains("/api/v1/orderBooks") {
+                return ETH_MARKET_JSON.to_string();
+            }
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":100}"#.to_string();
+            }
+            batches.lock().unwrap().push(request.to_string());
+            if reject.load(Ordering::SeqCst) {
+                r#"{"code":21120,"message":"invalid signature"}"#.to_string()
+            } else {
+                r#"{"code":200,"tx_hash":["h0","h1","h2"]}"#.to_string()
+            }
+        })
+        .await
+    };
+    let order = |client_order_index, side, order_type, price, trigger_price| CreateOrderRequest {
+        account_index: 1,
+        order_book_index: 0,
+        client_order_index,
+        base_amount: 100,
+        price,
+        side,
+        order_type,
+        time_in_force: 1,
+        reduce_only: order_type != 0,
+        trigger_price,
+        order_expiry: OrderExpiry::None,
+        price_protection: None,
+    };
+    let entry = |index| order(index, Side::Buy, 0, 300_000, 0);
+    let stop_loss = |index| order(index, Side::Sell, 3, 290_000, 290_000);
+    let take_profit = |index| order(index, Side::Sell, 5, 310_000, 310_000);
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    // Inconsistent legs are refused before anything is signed or sent
+    let invalid = [
+        (entry(1), order(2, Side::Buy, 3, 290_000, 290_000), take_profit(3)),
+        (entry(1), stop_loss(2), order(3, Side::Sell, 3, 310_000, 310_000)),
+        (entry(1), take_profit(2), stop_loss(3)),
+        (entry(1), stop_loss(2), take_profit(1)),
+        (order(1, Side::Sell, 0, 300_000, 0), order(2, Side::Buy, 3, 290_000, 290_000), order(3, Side::Buy, 5, 310_000, 310_000)),
+    ];
+    for (entry, stop_loss, take_profit) in invalid {
+        let result = client.place_bracket(entry, stop_loss, take_profit).await;
+        assert!(matches!(result, Err(ApiError::InvalidOrderParams(ref message)) if message.starts_with("bracket")), "{:?}", result);
+    }
+    assert!(batches.lock().unwrap().is_empty());
+
+    let bracket = client.place_bracket(entry(1), stop_loss(2), take_profit(3)).await.unwrap();
+    assert_eq!(bracket, Bracket { market_index: 0, entry: 1, stop_loss: 2, take_profit: 3 });
+    let request = batches.lock().unwrap()[0].clone();
+    let form = url_decoded_form(request.split_once("\r\n\r\n").unwrap().1);
+    let field = |name: &str| form.iter().find(|(key, _)| key == name).unwrap().1.clone();
+    assert_eq!(field("tx_types"), "[14,14,14]");
+    let tx_infos: Vec<String> = serde_json::from_str(&field("tx_infos")).unwrap();
+    let sent: Vec<serde_json::Value> = tx_infos.iter().map(|info| serde_json::from_str(info).unwrap()).collect();
+    assert_eq!(sent.iter().map(|tx| tx["ClientOrderIndex"].as_u64().unwrap()).collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(sent.iter().map(|tx| tx["TriggerPrice"].as_i64().unwrap()).collect::<Vec<_>>(), vec![0, 290_000, 310_000]);
+
+    // A rejected batch places none of the legs and hands every nonce back
+    reject.store(true, Ordering::SeqCst);
+    let result = client.place_bracket(entry(4), stop_loss(5), take_profit(6)).await;
+    assert!(matches!(result, Err(ApiError::Rejected { code: 21120, .. })));
+    assert_eq!(client.get_nonce(false).await.unwrap(), 103);
+}
+
+#[tokio::test]
+async fn test_place_ladder_spreads_prices_and_sizes_in_one_batch() {
+    use crate::LadderDistribution;
+    use rust_decimal::Decimal;
+    use std::sync::Mutex;
+
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let batches = batches.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/orderBooks") {
+                return ETH_MARKET_JSON.to_string();
+            }
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":100}"#.to_string();
+            }
+            batches.lock().unwrap().push(request.to_string());
+            r#"{"code":200,"tx_hash":["h0","h1","h2"]}"#.to_string()
+        })
+        .await
+    };
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+    let d = |s: &str| s.parse::<Decimal>().unwrap();
+
+    // Sizes finer than a lot, a non-positive ratio, colliding levels and a level under the minimum size
+    let invalid = [
+        (d("3000"), d("3010"), 3, d("0.10005"), LadderDistribution::Uniform),
+        (d("3000"), d("3010"), 3, d("0.1"), LadderDistribution::Geometric(Decimal::ZERO)),
+        (d("3000"), d("3000.05"), 10, d("1"), LadderDistribution::Uniform),
+        (d("3000"), d("3010"), 3, d("0.01"), LadderDistribution::Uniform),
+        (d("3000"), d("3010"), 0, d("0.1"), LadderDistribution::Uniform),
+    ];
+    for (start, end, levels, size, distribution) in invalid {
+        let result = client.place_ladder(0, Side::Sell, start, end, levels, size, distribution).await;
+        assert!(matches!(result, Err(ApiError::InvalidOrderParams(_))), "{:?}", result);
+    }
+    assert!(batches.lock().unwrap().is_empty());
+
+    let indices = client
+        .place_ladder(0, Side::Sell, d("3000.001"), d("3010"), 3, d("0.1001"), LadderDistribution::Linear)
+        .await
+        .unwrap();
+    assert_eq!(indices.len(), 3);
+    assert_eq!((indices[1] - indices[0], indices[2] - indices[0]), (1, 2));
+
+    let request = batches.lock().unwrap()[0].clone();
+    let form = url_decoded_form(request.split_once("\r\n\r\n").unwrap().1);
+    let field = |name: &str| form.iter().find(|(key, _)| key == name).unwrap().1.clone();
+    let tx_infos: Vec<String> = serde_json::from_str(&field("tx_infos")).unwrap();
+    let sent: Vec<serde_json::Value> = tx_infos.iter().map(|info| serde_json::from_str(info).unwrap()).collect();
+    // A sell rounds its price up, and the 1001 lots split 1:2:3 with the leftover to the largest remainders
+    assert_eq!(sent.iter().map(|tx| tx["Price"].as_i64().unwrap()).collect::<Vec<_>>(), vec![300_001, 300_501, 301_000]);
+    assert_eq!(sent.iter().map(|tx| tx["BaseAmount"].as_i64().unwrap()).collect::<Vec<_>>(), vec![167, 334, 500]);
+    assert_eq!(sent.iter().map(|tx| tx["ClientOrderIndex"].as_u64().unwrap()).collect::<Vec<_>>(), indices);
+    assert!(sent.iter().all(|tx| tx["IsAsk"] == 1 && tx["Type"] == 0));
+}
+
+#[tokio::test]
+async fn test_expired_idempotent_order_is_resigned_with_fresh_expiry() {
+    use crate::{Clock, CreateOrderRequest};
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Mutex;
+
+    #[derive(Debug)]
+    struct SteppingClock(AtomicI64);
+    impl Clock for SteppingClock {
+        fn now_ms(&self) -> i64 {
+            self.0.fetch_add(1_000, Ordering::SeqCst)
+        }
+    }
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let sent = sent.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/orderBooks") {
+                return ETH_MARKET_JSON.to_string();
+            }
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":50}"#.to_string();
+            }
+            let body = request.split_once("\r\n\r\n").unwrap().1;
+            let (_, tx_info) = url_decoded_form(body).into_iter().find(|(key, _)| key == "tx_info").unwrap();
+            let mut sent = sent.lock().unwrap();
+            sent.push(serde_json::from_str::<serde_json::Value>(&tx_info).unwrap());
+            if sent.len() % 2 == 1 {
+                r#"{"code":21711,"message":"transaction expired"}"#.to_string()
+            } else {
+                r#"{"code":200}"#.to_string()
+            }
+        })
+        .await
+    };
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .clock(SteppingClock(AtomicI64::new(1_700_000_000_000)))
+        .resign_on_expiry(1)
+        .build()
+        .unwrap();
+    let order = |time_in_force, reduce_only| CreateOrderRequest {
+        account_index: 1,
+        order_book_index: 0,
+        client_order_index: 4,
+        base_amount: 100,
+        price: 300_000,
+        side: Side::Sell,
+        order_type: 0,
+        time_in_force,
+        reduce_only,
+        trigger_price: 0,
+        order_expiry: OrderExpiry::None,
+        price_protection: None,
+    };
+
+    let response = client.create_order(order(3, false)).await.unwrap();
+    assert_eq!(response.code, 200);
+    {
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0]["Nonce"], sent[1]["Nonce"], "the expired nonce was not consumed");
+        assert!(sent[1]["ExpiredAt"].as_i64() > sent[0]["ExpiredAt"].as_i64());
+        assert!(sent[0]["Sig"] != sent[1]["Sig"]);
+    }
+
+    // A plain good-till-time order could cross at a stale price, so the rejection is returned
+    let result = client.create_order(order(1, false)).await;
+    assert!(matches!(result, Err(ApiError::TransactionExpired { code: Some(21711), .. })), "{:?}", result);
+    assert_eq!(sent.lock().unwrap().len(), 3);
+}
+
+#[test]
+fn test_error_codes_round_trip_and_key_expiry_rejections() {
+    use crate::ErrorCode;
+
+    for code in [ErrorCode::InvalidNonce, ErrorCode::TransactionExpired, ErrorCode::InsufficientMargin] {
+        assert_eq!(ErrorCode::from_code(code.code()), Some(code));
+    }
+    assert_eq!(ErrorCode::from_code(200), None);
+
+    let error = |response| crate::response::send_tx_error(&response, None);
+    assert!(matches!(
+        error(serde_json::json!({"code": 21711, "message": "bad expiry"})),
+        ApiError::TransactionExpired { code: Some(21711), .. }
+    ));
+    // Only the code counts; a message mentioning expiry under another code is not re-signed
+    assert!(matches!(
+        error(serde_json::json!({"code": 21706, "message": "order expired"})),
+        ApiError::Rejected { code: 21706, .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_post_only_cross_is_typed_error() {
+    use crate::CreateOrderRequest;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let submissions = Arc::new(AtomicUsize::new(0));
+    let base_url = {
+        let submissions = submissions.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":1}"#.to_string();
+            }
+            submissions.fetch_add(1, Ordering::SeqCst);
+            r#"{"code":21738,"message":"order would cross the book"}"#.to_string()
+        })
+        .await
+    };
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .validate_orders(false)
+        .build()
+        .unwrap();
+
+    let order = CreateOrderRequest {
+        account_index: 1,
+        order_book_index: 0,
+        client_order_index: 1,
+        base_amount: 100,
+        price: 300_200,
+        side: Side::Buy,
+        order_type: 0,
+        time_in_force: 3,
+        reduce_only: false,
+        trigger_price: 0,
+        order_expiry: OrderExpiry::default(),
+        price_protection: None,
+    };
+    match client.create_order(order).await {
+        Err(ApiError::PostOnlyWouldCross { market_index, code, .. }) => assert_eq!((market_index, code), (Some(0), Some(21738))),
+        other => panic!("expected PostOnlyWouldCross, got {:?}", other),
+    }
+    assert_eq!(submissions.load(Ordering::SeqCst), 1, "post-only rejections are not retried");
+}
+
+#[tokio::test]
+async fn test_closed_market_and_maintenance_are_typed_errors() {
+    use crate::{CreateOrderRequest, RejectionRecord};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    let reply = Arc::new(Mutex::new(String::new()));
+    let (metadata_fetches, submissions) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    let base_url = {
+        let (reply, metadata_fetches, submissions) = (reply.clone(), metadata_fetches.clone(), submissions.clone());
+        mock_server(move |request| {
+            if request.contains("/api/v1/orderBooks") {
+                metadata_fetches.fetch_add(1, Ordering::SeqCst);
+                return if request.contains("market_id=1") {
+                    ETH_MARKET_JSON.replace(r#""market_id":0,"status":"active""#, r#""market_id":1,"status":"frozen""#)
+                } else {
+                    ETH_MARKET_JSON.to_string()
+                };
+            }
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":1}"#.to_string();
+            }
+            submissions.fetch_add(1, Ordering::SeqCst);
+            reply.lock().unwrap().clone()
+        })
+        .await
+    };
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    *reply.lock().unwrap() = r#"{"code":21500,"message":"market is not open for trading"}"#.to_string();
+    let order = || CreateOrderRequest::market(0, Side::Buy, 100, 300_000);
+    let result = client.create_order(order()).await;
+    match &result {
+        Err(ApiError::MarketClosed { market_index, code, .. }) => assert_eq!((*market_index, *code), (Some(0), Some(21500))),
+        other => panic!("expected MarketClosed, got {:?}", other),
+    }
+    assert_eq!(submissions.load(Ordering::SeqCst), 1, "closures are not retried");
+    assert_eq!(RejectionRecord::from_outcome(&order(), &result).unwrap().code, Some(21500));
+
+    *reply.lock().unwrap() = r#"{"code":21501,"message":"service unavailable"}"#.to_string();
+    let result = client.create_market_order(0, 2, 100, 300_000, Side::Buy).await;
+    assert!(matches!(result, Err(ApiError::InMaintenance { code: Some(21501), .. })), "{:?}", result);
+
+    // Only the code counts, not the wording
+    *reply.lock().unwrap() = r#"{"code":21706,"message":"market closed for this order size"}"#.to_string();
+    let result = client.create_market_order(0, 3, 100, 300_000, Side::Buy).await;
+    assert!(matches!(result, Err(ApiError::Rejected { code: 21706, .. })), "{:?}", result);
+
+    // A market cached as not active fails before signing, and its status is refetched each time
+    let fetches = metadata_fetches.load(Ordering::SeqCst);
+    for _ in 0..2 {
+        let result = client.create_market_order(1, 3, 100, 300_000, Side::Buy).await;
+        assert!(matches!(result, Err(ApiError::MarketClosed { market_index: Some(1), .. })), "{:?}", result);
+    }
+    assert_eq!(metadata_fetches.load(Ordering::SeqCst), fetches + 2);
+    assert_eq!(submissions.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_multi_account_client_keeps_nonces_per_identity() {
+    use crate::{Identity, MultiAccountClient};
+    use std::sync::Mutex;
+
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let requests = requests.clone();
+        mock_server(move |request| {
+            requests.lock().unwrap().push(request.to_string());
+            if request.contains("account_index=1&") {
+                r#"{"code":200,"nonce":10}"#.to_string()
+            } else if request.contains("account_index=2&") {
+                r#"{"code":200,"nonce":500}"#.to_string()
+            } else {
+                r#"{"code":200}"#.to_string()
+            }
+        })
+        .await
+    };
+    let first = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .validate_orders(false)
+        .build()
+        .unwrap();
+    let mut accounts = MultiAccountClient::new(first);
+    let other_key = hex::encode(signer::KeyManager::generate().private_key_bytes());
+    let second = accounts.add_identity(2, 4, &other_key).unwrap();
+    let first = Identity { account_index: 1, api_key_index: 0 };
+    assert_eq!(accounts.identities(), vec![first, second]);
+
+    for identity in [first, second, first, second] {
+        accounts.client(identity).unwrap().cancel_order(0, 7).await.unwrap();
+    }
+
+    let sent: Vec<serde_json::Value> = requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|request| request.contains("/api/v1/sendTx"))
+        .map(|request| {
+            let form = url_decoded_form(request.split_once("\r\n\r\n").unwrap().1);
+            let (_, tx_info) = form.into_iter().find(|(key, _)| key == "tx_info").unwrap();
+            serde_json::from_str(&tx_info).unwrap()
+        })
+        .collect();
+    let summary: Vec<_> = sent
+        .iter()
+        .map(|tx| (tx["AccountIndex"].as_i64().unwrap(), tx["ApiKeyIndex"].as_u64().unwrap(), tx["Nonce"].as_i64().unwrap()))
+        .collect();
+    assert_eq!(summary, vec![(1, 0, 10), (2, 4, 500), (1, 0, 11), (2, 4, 501)]);
+    let nonce_fetches = requests.lock().unwrap().iter().filter(|r| r.contains("/api/v1/nextNonce")).count();
+    assert_eq!(nonce_fetches, 2);
+
+    let unknown = Identity { account_index: 3, api_key_index: 0 };
+    assert!(matches!(accounts.client(unknown), Err(ApiError::NotFound(_))));
+    assert!(accounts.add_identity(3, 0, "zz").is_err());
+}
+
+#[tokio::test]
+async fn test_candlestick_stream_separates_updates_from_new_candles() {
+    use crate::{CandleEvent, CandleResolution};
+    use rust_decimal::Decimal;
+
+    // 1m candles; 1_700_000_040_000 opens a minute
+    let trade = |id: i64, price: &str, size: &str, timestamp: i64| {
+        format!(r#"{{"trade_id":{},"price":"{}","size":"{}","timestamp":{}}}"#, id, price, size, timestamp)
+    };
+    let message = |kind: &str, trades: Vec<String>| {
+        format!(r#"{{"type":"{}/trade","channel":"trade:0","trades":[{}]}}"#, kind, trades.join(","))
+    };
+    let script = vec![
+        message("subscribed", vec![trade(2, "3001", "2", 1_700_000_041_000), trade(1, "3000", "1", 1_700_000_040_500)]),
+        message("update", vec![trade(3, "2999", "1", 1_700_000_099_999)]),
+        // Replayed trade 3 is not counted again; trade 4 starts the next minute
+        message("update", vec![trade(3, "2999", "1", 1_700_000_099_999), trade(4, "3005", "0.5", 1_700_000_100_000)]),
+        // Late trade of the closed minute
+        message("update", vec![trade(5, "2000", "9", 1_700_000_090_000)]),
+    ];
+    let (base_url, received) = mock_ws_server(script).await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let events: Vec<CandleEvent> = client
+        .subscribe_candlesticks(0, CandleResolution::OneMinute)
+        .await
+        .unwrap()
+        .map(|event| event.unwrap())
+        .collect()
+        .await;
+
+    assert!(received.lock().unwrap()[0].contains(r#""channel":"trade/0""#));
+    let summary: Vec<_> = events
+        .iter()
+        .map(|event| match event {
+            CandleEvent::Updated(candle) => ("updated", candle.open_time, candle.volume, candle.trades),
+            CandleEvent::Closed(candle) => ("closed", candle.open_time, candle.volume, candle.trades),
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("updated", 1_700_000_040_000, Decimal::from(3), 2),
+            ("updated", 1_700_000_040_000, Decimal::from(4), 3),
+            ("closed", 1_700_000_040_000, Decimal::from(4), 3),
+            ("updated", 1_700_000_100_000, Decimal::new(5, 1), 1),
+        ]
+    );
+    let CandleEvent::Closed(closed) = &events[2] else { unreachable!() };
+    assert_eq!(
+        (closed.open, closed.high, closed.low, closed.close),
+        (Decimal::from(3000), Decimal::from(3001), Decimal::from(2999), Decimal::from(2999))
+    );
+    assert_eq!(closed.quote_volume, Decimal::from(3000 + 6002 + 2999));
+}
+
+#[tokio::test]
+async fn test_get_candles_rejects_ranges_beyond_the_server_cap() {
+    use crate::{max_range_for, CandleResolution, MAX_CANDLES_PER_REQUEST};
+    use rust_decimal::Decimal;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    assert_eq!("4h".parse::<CandleResolution>().unwrap(), CandleResolution::FourHours);
+    assert!(matches!("2h".parse::<CandleResolution>(), Err(ApiError::Unsupported(_))));
+    assert_eq!(max_range_for(CandleResolution::OneMinute), Duration::from_secs(500 * 60));
+    assert_eq!(max_range_for(CandleResolution::OneDay), Duration::from_secs(500 * 86_400));
+
+    let requests = Arc::new(AtomicUsize::new(0));
+    let base_url = {
+        let requests = requests.clone();
+        mock_server(move |request| {
+            requests.fetch_add(1, Ordering::SeqCst);
+            assert!(request.contains("/api/v1/candlesticks?market_id=0&resolution=1h"));
+            assert!(request.contains("count_back=3"), "{}", request);
+            // Newest first, with one candle outside the requested range
+            r#"{"code":200,"resolution":"1h","candlesticks":[
+                {"timestamp":1700010000000,"open":"3","high":"3","low":"3","close":"3","volume0":"1","volume1":"3"},
+                {"timestamp":1700006400000,"open":"2","high":"2.5","low":"1.5","close":"2","volume0":"2","volume1":"4"},
+                {"timestamp":1700002800000,"open":"1","high":"1","low":"1","close":"1","volume0":"1","volume1":"1"},
+                {"timestamp":1699999200000,"open":"0","high":"0","low":"0","close":"0","volume0":"1","volume1":"0"}
+            ]}"#
+            .to_string()
+        })
+        .await
+    };
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    // 1_700_002_800_000 is on an hour boundary, so the range holds three candles
+    let hour = 3_600_000;
+    let start = 1_700_002_800_000;
+    let candles = client.get_candles(0, CandleResolution::OneHour, start, start + 3 * hour).await.unwrap();
+    assert_eq!(candles.iter().map(|candle| candle.open_time).collect::<Vec<_>>(), vec![start, start + hour, start + 2 * hour]);
+    assert_eq!(candles[1].high, Decimal::new(25, 1));
+    assert_eq!((candles[1].volume, candles[1].quote_volume), (Decimal::from(2), Decimal::from(4)));
+
+    // One candle over the cap is refused before any request is made
+    let end = start + (MAX_CANDLES_PER_REQUEST + 1) * hour;
+    match client.get_candles(0, CandleResolution::OneHour, start, end).await {
+        Err(ApiError::CandleRangeTooLarge { requested: 501, max: 500, max_range, .. }) => {
+            assert_eq!(max_range, max_range_for(CandleResolution::OneHour));
+        }
+        other => panic!("expected CandleRangeTooLarge, got {:?}", other),
+    }
+    assert!(matches!(
+        client.get_candles(0, CandleResolution::OneHour, start, start).await,
+        Err(ApiError::InvalidConfig(_))
+    ));
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_unknown_response_fields_are_ignored_unless_strict() {
+    use crate::CandleResolution;
+
+    // Every field of today's candlesticks response, plus one the API might add later
+    const CANDLES: &str = r#"{"code":200,"resolution":"1h","candlesticks":[{"timestamp":1700002800000,
+        "open":"3000.10","high":"3010.00","low":"2995.50","close":"3005.25","volume0":"12.5","volume1":"37550.75",
+        "last_trade_id":918273}]}"#;
+    let base_url = mock_server(|request| {
+        if request.contains("market_id=1") {
+            CANDLES.replace(r#""last_trade_id":918273"#, r#""last_trade_id":918273,"added_later":1"#)
+        } else {
+            CANDLES.to_string()
+        }
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+    let (start, end) = (1_700_002_800_000, 1_700_006_400_000);
+
+    let candles = client.get_candles(0, CandleResolution::OneHour, start, end).await.unwrap();
+    assert_eq!(candles.len(), 1);
+    let drifted = client.get_candles(1, CandleResolution::OneHour, start, end).await;
+    if cfg!(feature = "strict-json") {
+        assert!(matches!(drifted, Err(ApiError::Json(ref e)) if e.to_string().contains("added_later")), "{:?}", drifted);
+    } else {
+        assert_eq!(drifted.unwrap().len(), 1);
+    }
+}
+
+#[tokio::test]
+async fn test_market_and_order_views_accept_extra_fields_even_when_strict() {
+    let base_url = mock_server(|request| {
+        if request.contains("/api/v1/orderBooks") {
+            ETH_MARKET_JSON.replace(r#""status":"active","#, r#""status":"active","order_quote_limit":"1000000","#)
+        } else if request.contains("/api/v1/orderBookOrders") {
+            r#"{"code":200,"total_bids":1,"total_asks":0,"bids":[{"order_index":"281474976710657","order_id":"1",
+                "owner_account_index":7,"initial_base_amount":"1","remaining_base_amount":"1","price":"3000.00",
+                "order_expiry":1702419200000}],"asks":[]}"#
+                .to_string()
+        } else {
+            r#"{"code":200,"orders":[{"order_index":10,"client_order_index":1,"trigger_status":"na"}]}"#.to_string()
+        }
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    assert_eq!(client.get_market_info(0).await.unwrap().symbol, "ETH");
+    assert_eq!(client.get_active_orders(1, 0).await.unwrap()[0].order_index, 10);
+    assert_eq!(client.get_order_book(0, 1).await.unwrap().best_bid().unwrap().to_string(), "3000.00");
+}
+
+#[tokio::test]
+async fn test_get_all_markets_fills_the_metadata_cache() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let metadata_requests = Arc::new(AtomicUsize::new(0));
+    let base_url = {
+        let metadata_requests = metadata_requests.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/orderBooks") {
+                metadata_requests.fetch_add(1, Ordering::SeqCst);
+                assert!(!request.contains("market_id="), "bulk request expected: {}", request);
+                let btc = ETH_MARKET_JSON
+                    .replace(r#""symbol":"ETH","market_id":0"#, r#""symbol":"BTC","market_id":1"#);
+                let btc = btc.split_once(r#""order_books":["#).unwrap().1.trim_end_matches("]}");
+                return ETH_MARKET_JSON.replacen(r#""order_books":["#, &format!(r#""order_books":[{},"#, btc), 1);
+            }
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":1}"#.to_string();
+            }
+            r#"{"code":200}"#.to_string()
+        })
+        .await
+    };
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let markets = client.get_all_markets().await.unwrap();
+    assert_eq!(markets.iter().map(|m| (m.market_index, m.symbol.as_str())).collect::<Vec<_>>(), vec![(0, "ETH"), (1, "BTC")]);
+
+    // Validation for either market is served from the cache
+    client.create_market_order(1, 1, 100, 300_000, Side::Buy).await.unwrap();
+    let result = client.create_market_order(0, 2, 10, 300_000, Side::Buy).await;
+    assert!(matches!(result, Err(ApiError::InvalidOrderParams(_))));
+    assert_eq!(metadata_requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_cancel_guard_cancels_on_drop_unless_disarmed() {
+    use crate::{CancelGuard, Clock};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[derive(Debug)]
+    struct FixedClock;
+    impl Clock for FixedClock {
+        fn now_ms(&self) -> i64 {
+            1_700_000_000_000
+        }
+    }
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let sent = sent.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":1}"#.to_string();
+            }
+            let form = url_decoded_form(request.split_once("\r\n\r\n").unwrap().1);
+            let (_, tx_info) = form.into_iter().find(|(key, _)| key == "tx_info").unwrap();
+            sent.lock().unwrap().push(serde_json::from_str::<serde_json::Value>(&tx_info).unwrap());
+            r#"{"code":200}"#.to_string()
+        })
+        .await
+    };
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .clock(FixedClock)
+        .build()
+        .unwrap();
+
+    drop(CancelGuard::new(client.clone()).timeout(Duration::from_secs(2)));
+    // The drop waited for the cancellation
+    assert_eq!(sent.lock().unwrap().len(), 1);
+    assert_eq!(sent.lock().unwrap()[0]["TimeInForce"], 0);
+
+    let mut guard = CancelGuard::new(client.clone());
+    guard.disarm();
+    drop(guard);
+    assert_eq!(sent.lock().unwrap().len(), 1);
+
+    client.schedule_cancel_all(Duration::from_secs(60)).await.unwrap();
+    client.abort_scheduled_cancel_all().await.unwrap();
+    let sent = sent.lock().unwrap();
+    assert_eq!((sent[1]["TimeInForce"].as_u64(), sent[1]["Time"].as_i64()), (Some(1), Some(1_700_000_060_000)));
+    assert_eq!(sent[2]["TimeInForce"], 2);
+}
+
+#[tokio::test]
+async fn test_base_urls_fail_over_to_next_endpoint() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Nothing listens on the first endpoint
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let unreachable = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+
+    // The second answers every request with a 503
+    let failing_requests = Arc::new(AtomicUsize::new(0));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let failing = format!("http://{}", listener.local_addr().unwrap());
+    let requests = failing_requests.clone();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            read_request(&mut socket).await;
+            requests.fetch_add(1, Ordering::SeqCst);
+            let body = r#"{"code":503,"message":"unavailable"}"#;
+            let response = format!(
+                "HTTP/1.1 503 Service Unavailable\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+
+    let healthy = mock_server(|request| {
+        assert!(request.starts_with("GET / "));
+        r#"{"status":200,"network_id":1}"#.to_string()
+    })
+    .await;
+
+    let client = LighterClient::builder(unreachable.clone(), &test_key_hex(), 1, 0)
+        .base_urls(vec![unreachable.clone(), failing.clone(), healthy.clone()])
+        .failover_threshold(2)
+        .build()
+        .unwrap();
+    assert_eq!(client.active_base_url(), unreachable);
+
+    // Refused connection: moves on at once; the first 5xx is returned as is
+    let response = client.send_json(client.client.get(format!("{}/", unreachable))).await.unwrap();
+    assert_eq!(response["code"], 503);
+    assert_eq!(client.active_base_url(), failing);
+
+    // A 5xx is never resent; the second in a row reaches the threshold and the next request moves on
+    let response = client.send_json(client.client.get(format!("{}/", unreachable))).await.unwrap();
+    assert_eq!(response["code"], 503);
+    assert_eq!(failing_requests.load(Ordering::SeqCst), 2);
+    assert_eq!(client.active_base_url(), healthy);
+    let response = client.send_json(client.client.get(format!("{}/", unreachable))).await.unwrap();
+    assert_eq!(response["status"], 200);
+
+    // A transaction may have been executed despite the 5xx: returned, and the next request moves on at once
+    let client = LighterClient::builder(failing.clone(), &test_key_hex(), 1, 0)
+        .base_urls(vec![failing.clone(), healthy.clone()])
+        .failover_threshold(2)
+        .build()
+        .unwrap();
+    let response = client.send_json(client.client.post(format!("{}/api/v1/sendTx", failing))).await.unwrap();
+    assert_eq!(response["code"], 503);
+    assert_eq!(failing_requests.load(Ordering::SeqCst), 3);
+    assert_eq!(client.active_base_url(), healthy);
+
+    let result = LighterClient::builder(TESTNET_URL.to_string(), &test_key_hex(), 1, 0)
+        .base_urls(Vec::new())
+        .build();
+    assert!(matches!(result, Err(ApiError::InvalidConfig(_))));
+    let result = LighterClient::builder(TESTNET_URL.to_string(), &test_key_hex(), 1, 0)
+        .base_urls(vec![TESTNET_URL.to_string(), "https://mainnet.zklighter.elliot.ai".to_string()])
+        .build();
+    assert!(matches!(result, Err(ApiError::InvalidConfig(_))));
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_opens_and_recovers_through_half_open() {
+    use crate::CircuitState;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    // Answers 503 while `down` is set, counting the requests that reach it
+    let down = Arc::new(AtomicBool::new(true));
+    let requests = Arc::new(AtomicUsize::new(0));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    {
+        let (down, requests) = (down.clone(), requests.clone());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                read_request(&mut socket).await;
+                requests.fetch_add(1, Ordering::SeqCst);
+                let (status, body) = if down.load(Ordering::SeqCst) {
+                    ("503 Service Unavailable", r#"{"code":503,"message":"unavailable"}"#)
+                } else {
+                    ("200 OK", r#"{"code":200}"#)
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+    }
+    let cooldown = Duration::from_millis(200);
+    let client = LighterClient::builder(base_url.clone(), &test_key_hex(), 1, 0)
+        .circuit_breaker(3, Duration::from_secs(10), cooldown)
+        .build()
+        .unwrap();
+    let get = || client.send_json(client.client.get(format!("{}/", base_url)));
+
+    // 5xx responses are still returned until the third in a row opens the breaker
+    for _ in 0..3 {
+        assert_eq!(get().await.unwrap()["code"], 503);
+    }
+    assert!(matches!(client.circuit_state(), CircuitState::Open { .. }));
+    assert!(matches!(get().await, Err(ApiError::CircuitOpen { retry_after }) if retry_after <= cooldown));
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+    // after the cooldown a failing trial request opens it again
+    tokio::time::sleep(cooldown).await;
+    assert_eq!(client.circuit_state(), CircuitState::HalfOpen);
+    assert_eq!(get().await.unwrap()["code"], 503);
+    assert!(matches!(client.circuit_state(), CircuitState::Open { .. }));
+    assert!(matches!(get().await, Err(ApiError::CircuitOpen { .. })));
+    assert_eq!(requests.load(Ordering::SeqCst), 4);
+
+    // a successful trial closes it
+    down.store(false, Ordering::SeqCst);
+    tokio::time::sleep(cooldown).await;
+    assert_eq!(get().await.unwrap()["code"], 200);
+    assert_eq!(client.circuit_state(), CircuitState::Closed);
+    assert_eq!(get().await.unwrap()["code"], 200);
+
+    // a success resets the count of consecutive failures
+    for healthy in [false, false, true, false, false] {
+        down.store(!healthy, Ordering::SeqCst);
+        get().await.unwrap();
+    }
+    assert_eq!(client.circuit_state(), CircuitState::Closed);
+
+    let result = LighterClient::builder(TESTNET_URL.to_string(), &test_key_hex(), 1, 0)
+        .circuit_breaker(0, Duration::from_secs(1), Duration::from_secs(1))
+        .build();
+    assert!(matches!(result, Err(ApiError::InvalidConfig(_))));
+    let client = LighterClient::new(TESTNET_URL.to_string(), &test_key_hex(), 1, 0).unwrap();
+    assert_eq!(client.circuit_state(), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn test_rate_limit_status_from_headers_paces_requests() {
+    use crate::RateLimitStatus;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // 1st response: budget 3 of 10; 2nd: no headers; 3rd: exhausted for a second
+    let requests = Arc::new(AtomicUsize::new(0));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    {
+        let requests = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                read_request(&mut socket).await;
+                let headers = match requests.fetch_add(1, Ordering::SeqCst) {
+                    0 => "X-RateLimit-Limit: 10\r\nX-RateLimit-Remaining: 3\r\nX-RateLimit-Reset: 60\r\n",
+                    1 => "",
+                    2 => "X-RateLimit-Remaining: 0\r\nX-RateLimit-Reset: 1\r\n",
+                    _ => "X-RateLimit-Limit: 10\r\nX-RateLimit-Remaining: 9\r\n",
+                };
+                let body = r#"{"code":200}"#;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\n{}Content-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    headers,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+    }
+    let client = LighterClient::new(base_url.clone(), &test_key_hex(), 1, 0).unwrap();
+    let get = || client.send_json(client.client.get(format!("{}/", base_url)));
+    assert_eq!(client.rate_limit_status(), None);
+
+    get().await.unwrap();
+    let status = client.rate_limit_status().unwrap();
+    assert_eq!((status.limit, status.remaining), (Some(10), Some(3)));
+    assert!(status.reset_after.is_some_and(|reset| reset > Duration::from_secs(58)));
+
+    // a response without the headers keeps the last report
+    get().await.unwrap();
+    assert_eq!(client.rate_limit_status().unwrap().remaining, Some(3));
+
+    // an exhausted budget holds the next request until the reset
+    get().await.unwrap();
+    let status = client.rate_limit_status().unwrap();
+    assert_eq!((status.limit, status.remaining), (Some(10), Some(0)));
+    let started = std::time::Instant::now();
+    get().await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(900));
+    assert_eq!(
+        client.rate_limit_status(),
+        Some(RateLimitStatus { limit: Some(10), remaining: Some(9), reset_after: None })
+    );
+}
+
+#[test]
+fn test_account_events_from_messages() {
+    use crate::{AccountEvent, Side};
+    use serde_json::json;
+
+    let events = AccountEvent::from_message(&json!({
+        "type": "update/account_all",
+        "channel": "account_all:7",
+        "orders": {"0": [{"order_index": 10, "client_order_index": 42, "market_index": 0, "status": "open",
+            "price": "3100.50", "remaining_base_amount": "0.5", "is_ask": true}]},
+        "trades": {"0": [{"trade_id": 5, "market_id": 0, "timestamp": 1000, "price": "3100.50", "size": "0.1",
+            "ask_account_id": 7, "bid_account_id": 8, "is_maker_ask": true, "maker_fee": "0.02"}]},
+        "positions": {"0": {"market_id": 0, "symbol": "ETH", "sign": -1, "position": "0.1",
+            "avg_entry_price": "3100.50", "position_value": "300"}, "1": {"market_id": 1, "symbol": "BTC"}}
+    }), 7)
+    .unwrap();
+    assert_eq!(events.len(), 4);
+    let AccountEvent::OrderUpdate(order) = &events[0] else { panic!("{:?}", events[0]) };
+    assert_eq!((order.client_order_index, order.side, order.price.to_string()), (42, Side::Sell, "3100.50".to_string()));
+    let AccountEvent::Fill(fill) = &events[1] else { panic!("{:?}", events[1]) };
+    assert_eq!((fill.trade_id, fill.side, fill.is_maker, fill.fee.to_string()), (5, Side::Sell, true, "0.02".to_string()));
+    let AccountEvent::PositionUpdate(position) = &events[2] else { panic!("{:?}", events[2]) };
+    assert_eq!((position.size.to_string(), position.mark_price.to_string()), ("-0.1".to_string(), "3000".to_string()));
+    let AccountEvent::PositionUpdate(closed) = &events[3] else { panic!("{:?}", events[3]) };
+    assert_eq!((closed.market_index, closed.size), (1, rust_decimal::Decimal::ZERO));
+
+    let events = AccountEvent::from_message(&json!({
+        "type": "update/user_stats",
+        "stats": {"collateral": "1000.5", "portfolio_value": "1010", "available_balance": "800", "margin_usage": "12.5"}
+    }), 7)
+    .unwrap();
+    let [AccountEvent::BalanceUpdate(balance)] = events.as_slice() else { panic!("{:?}", events) };
+    assert_eq!((balance.collateral.to_string(), balance.margin_usage.to_string()), ("1000.5".to_string(), "12.5".to_string()));
+
+    let reward = json!({"id": "n2", "kind": "referral_reward", "created_at": 2000});
+    let events = AccountEvent::from_message(&json!({
+        "type": "update/notification",
+        "notifs": [
+            {"id": "n1", "kind": "liquidation", "created_at": 1500, "content": {"market_index": 0, "message": "margin below maintenance"}},
+            reward.clone()
+        ]
+    }), 7)
+    .unwrap();
+    let [AccountEvent::LiquidationWarning(warning), AccountEvent::Unknown(other)] = events.as_slice() else { panic!("{:?}", events) };
+    assert_eq!((warning.id.as_str(), warning.market_index, warning.timestamp), ("n1", Some(0), 1500));
+    assert_eq!(warning.message, "margin below maintenance");
+    assert_eq!(other, &reward);
+
+    // Unmodeled messages are passed through, broken modeled ones fail
+    let unknown = json!({"type": "update/account_tx", "txs": []});
+    assert_eq!(AccountEvent::from_message(&unknown, 7).unwrap(), vec![AccountEvent::Unknown(unknown)]);
+    assert!(AccountEvent::from_message(&json!({"type": "update/user_stats", "stats": {"collateral": []}}), 7).is_err());
+    assert!(AccountEvent::from_message(&json!({"type": "error", "message": "bad channel"}), 7).is_err());
+}
+
+#[tokio::test]
+async fn test_subscribe_account_events_merges_account_and_stats_channels() {
+    use crate::AccountEvent;
+    use futures::StreamExt;
+
+    let (base_url, received) = mock_ws_sessions(vec![
+        vec![r#"{"type":"subscribed/account_all","positions":{"0":{"market_id":0,"symbol":"ETH","sign":1,"position":"1","position_value":"3000"}}}"#.to_string(), STALL.to_string()],
+        vec![r#"{"type":"subscribed/user_stats","stats":{"collateral":"500"}}"#.to_string(), STALL.to_string()],
+    ])
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 7, 0).unwrap();
+
+    let mut events = client.subscribe_account_events(7).await.unwrap();
+    let mut kinds = Vec::new();
+    for _ in 0..2 {
+        match events.next().await.unwrap().unwrap() {
+            AccountEvent::PositionUpdate(position) => kinds.push(format!("position {}", position.size)),
+            AccountEvent::BalanceUpdate(balance) => kinds.push(format!("balance {}", balance.collateral)),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    kinds.sort();
+    assert_eq!(kinds, vec!["balance 500", "position 1"]);
+
+    let received = received.lock().unwrap().clone();
+    assert!(received.iter().any(|message| message.contains("account_all/7")));
+    assert!(received.iter().any(|message| message.contains("user_stats/7")));
+}
+
+#[tokio::test]
+async fn test_subscribe_account_from_replays_rest_fills_then_dedupes_live() {
+    use crate::AccountEvent;
+    use futures::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message;
+
+    // One port serves the REST trades page and both account channels
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let trade = |id: i64, timestamp: i64| {
+        format!(
+            r#"{{"trade_id":{},"timestamp":{},"price":"100","size":"1","ask_account_id":9,"bid_account_id":7,"is_maker_ask":true}}"#,
+            id, timestamp
+        )
+    };
+    let rest_body = format!(r#"{{"code":200,"trades":[{},{}]}}"#, trade(2, 2000), trade(3, 3000));
+    // The snapshot repeats fill 3, and carries fill 1 from before `since`
+    let account_all = format!(
+        r#"{{"type":"subscribed/account_all","trades":{{"0":[{},{},{}]}},"positions":{{"0":{{"market_id":0,"symbol":"ETH","sign":1,"position":"3","position_value":"300"}}}}}}"#,
+        trade(1, 1000),
+        trade(3, 3000),
+        trade(4, 4000)
+    );
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut head = [0u8; 1024];
+            let n = socket.peek(&mut head).await.unwrap();
+            if String::from_utf8_lossy(&head[..n]).contains("/api/v1/trades") {
+                read_request(&mut socket).await;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    rest_body.len(),
+                    rest_body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+                continue;
+            }
+            let account_all = account_all.clone();
+            tokio::spawn(async move {
+                let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+                let log = std::sync::Mutex::new(Vec::new());
+                record_text(&mut ws, &log).await;
+                let frame = if log.lock().unwrap()[0].contains("account_all") {
+                    account_all
+                } else {
+                    r#"{"type":"subscribed/user_stats","stats":{"collateral":"500"}}"#.to_string()
+                };
+                ws.send(Message::Text(frame)).await.unwrap();
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            });
+        }
+    });
+    let client = LighterClient::new(base_url, &test_key_hex(), 7, 0).unwrap();
+
+    let mut events = client.subscribe_account_from(7, 1500).await.unwrap();
+    let mut fills = Vec::new();
+    let mut others = Vec::new();
+    while fills.len() + others.len() < 5 {
+        match events.next().await.unwrap().unwrap() {
+            AccountEvent::Fill(fill) => fills.push(fill.trade_id),
+            AccountEvent::PositionUpdate(position) => others.push(format!("position {}", position.size)),
+            AccountEvent::BalanceUpdate(balance) => others.push(format!("balance {}", balance.collateral)),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    // REST fills first and in order, then only the live fill not seen yet
+    assert_eq!(fills, vec![2, 3, 4]);
+    others.sort();
+    assert_eq!(others, vec!["balance 500", "position 3"]);
+    assert!(tokio::time::timeout(Duration::from_millis(200), events.next()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_price_protection_precedence() {
+    use crate::{CreateOrderRequest, DEFAULT_PRICE_PROTECTION};
+    use std::sync::Mutex;
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let base_url = {
+        let sent = sent.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":3}"#.to_string();
+            }
+            let body = request.split("\r\n\r\n").nth(1).unwrap_or_default();
+            let (_, flag) = url_decoded_form(body).into_iter().find(|(key, _)| key == "price_protection").unwrap();
+            sent.lock().unwrap().push(flag);
+            r#"{"code":200,"tx_hash":"abc"}"#.to_string()
+        })
+        .await
+    };
+    let order = |price_protection| CreateOrderRequest {
+        account_index: 1,
+        order_book_index: 0,
+        client_order_index: 7,
+        base_amount: 100,
+        price: 300_000,
+        side: Side::Buy,
+        order_type: 0,
+        time_in_force: 1,
+        reduce_only: false,
+        trigger_price: 0,
+        order_expiry: OrderExpiry::None,
+        price_protection,
+    };
+    let last = || sent.lock().unwrap().pop().unwrap();
+
+    // Hardcoded default, then per-order override
+    let client = LighterClient::new(base_url.clone(), &test_key_hex(), 1, 0).unwrap();
+    assert_eq!(client.price_protection(), DEFAULT_PRICE_PROTECTION);
+    client.create_order(order(None)).await.unwrap();
+    assert_eq!(last(), "true");
+    client.create_order(order(Some(false))).await.unwrap();
+    assert_eq!(last(), "false");
+
+    // Client default off, per-order override back on
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .price_protection(false)
+        .build()
+        .unwrap();
+    client.create_order(order(None)).await.unwrap();
+    assert_eq!(last(), "false");
+    client.create_order(order(Some(true))).await.unwrap();
+    assert_eq!(last(), "true");
+    client.cancel_order(0, 7).await.unwrap();
+    assert_eq!(last(), "false");
+}
+
+#[tokio::test]
+async fn test_insufficient_margin_is_typed_error() {
+    use crate::CreateOrderRequest;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let submissions = Arc::new(AtomicUsize::new(0));
+    let base_url = {
+        let submissions = submissions.clone();
+        mock_server(move |request| {
+            if request.contains("/api/v1/nextNonce") {
+                return r#"{"code":200,"nonce":1}"#.to_string();
+            }
+            if submissions.fetch_add(1, Ordering::SeqCst) == 0 {
+                r#"{"code":21739,"message":"not enough margin to create the order"}"#
+            } else {
+                r#"{"code":21706,"message":"invalid order base or quote amount, insufficient margin"}"#
+            }
+            .to_string()
+        })
+        .await
+    };
+    let client = LighterClient::builder(base_url, &test_key_hex(), 1, 0)
+        .validate_orders(false)
+        .build()
+        .unwrap();
+    let order = || CreateOrderRequest {
+        account_index: 1,
+        order_book_index: 0,
+        client_order_index: 1,
+        base_amount: 100,
+        price: 300_000,
+        side: Side::Buy,
+        order_type: 0,
+        time_in_force: 1,
+        reduce_only: false,
+        trigger_price: 0,
+        order_expiry: OrderExpiry::default(),
+        price_protection: None,
+    };
+
+    match client.create_order(order()).await {
+        Err(ApiError::InsufficientMargin { code, message }) => {
+            assert_eq!(code, Some(21739));
+            assert_eq!(message, "not enough margin to create the order");
+        }
+        other => panic!("expected InsufficientMargin, got {:?}", other),
+    }
+    assert_eq!(submissions.load(Ordering::SeqCst), 1, "margin rejections are not retried");
+
+    // Another code is a plain rejection, whatever its message says
+    let result = client.create_order(order()).await;
+    assert!(matches!(result, Err(ApiError::Rejected { code: 21706, .. })), "{:?}", result);
+}
+
+#[test]
+fn test_parse_send_tx_response_maps_codes_to_errors() {
+    use crate::{check_send_tx_response, parse_send_tx_response};
+    use crate::response::send_tx_result;
+
+    let accepted = parse_send_tx_response(r#"{"code":200,"message":"{\"ratelimit\": \"didn't use volume quota\"}","tx_hash":"0xabc","predicted_execution_time_ms":1762241985117}"#).unwrap();
+    assert_eq!((accepted.tx_hash.as_str(), accepted.code), ("0xabc", 200));
+    assert_eq!(accepted.message, r#"{"ratelimit": "didn't use volume quota"}"#);
+
+    match parse_send_tx_response(r#"{"code":21120,"message":"invalid signature"}"#) {
+        Err(ApiError::Rejected { code, message }) => assert_eq!((code, message.as_str()), (21120, "invalid signature")),
+        other => panic!("expected Rejected, got {:?}", other),
+    }
+    match parse_send_tx_response(r#"{"code":21738,"message":"post only order would cross"}"#) {
+        Err(ApiError::PostOnlyWouldCross { market_index: None, code: Some(21738), .. }) => {}
+        other => panic!("expected PostOnlyWouldCross, got {:?}", other),
+    }
+    // A flagged price is not a post-only rejection, whatever the order was
+    assert!(matches!(
+        parse_send_tx_response(r#"{"code":21733,"message":"post only order price flagged"}"#),
+        Err(ApiError::Rejected { code: 21733, .. })
+    ));
+    assert!(matches!(
+        parse_send_tx_response(r#"{"code":21739,"message":"Insufficient margin"}"#),
+        Err(ApiError::InsufficientMargin { code: Some(21739), .. })
+    ));
+    assert!(matches!(
+        parse_send_tx_response(r#"{"code":21501,"message":"exchange under maintenance"}"#),
+        Err(ApiError::InMaintenance { code: Some(21501), .. })
+    ));
+    assert!(matches!(parse_send_tx_response(r#"{"tx_hash":"0xabc"}"#), Err(ApiError::Api(_))));
+    assert!(matches!(parse_send_tx_response("<html>502 Bad Gateway</html>"), Err(ApiError::Json(_))));
+    assert!(check_send_tx_response(serde_json::json!({"code": 200})).is_ok());
+
+    // Given the signed transaction, the error names its market and typed nonce errors carry its nonce
+    let tx_info = serde_json::json!({"MarketIndex": 3, "Nonce": 41});
+    let closed = serde_json::json!({"code": 21500, "message": "market is closed"});
+    assert!(matches!(send_tx_result(closed, Some(&tx_info)), Err(ApiError::MarketClosed { market_index: Some(3), .. })));
+    let too_low = serde_json::json!({"code": 21105, "message": "invalid nonce"});
+    assert!(matches!(
+        send_tx_result(too_low.clone(), Some(&tx_info)),
+        Err(ApiError::NonceTooLow { nonce: 41, code: Some(21105), .. })
+    ));
+    assert!(matches!(send_tx_result(too_low, None), Err(ApiError::Rejected { code: 21105, .. })));
+    // The generic nonce code falls back to the message for the direction
+    let too_high = serde_json::json!({"code": 21104, "message": "nonce too high"});
+    assert!(matches!(send_tx_result(too_high, Some(&tx_info)), Err(ApiError::NonceTooHigh { nonce: 41, .. })));
+}
+
+#[tokio::test]
+async fn test_get_account_index_resolves_every_account_of_an_address() {
+    let base_url = mock_server(|request| {
+        if request.contains("l1_address=0xabc") {
+            r#"{"code":200,"l1_address":"0xabc","sub_accounts":[
+                {"index":1,"account_type":0,"l1_address":"0xabc"},
+                {"index":7,"account_type":1,"l1_address":"0xabc"},
+                {"index":8,"account_type":1,"l1_address":"0xabc"}
+            ]}"#
+        } else if request.contains("l1_address=0xempty") {
+            r#"{"code":200,"l1_address":"0xempty","sub_accounts":[]}"#
+        } else if request.contains("l1_address=0xdead") {
+            r#"{"code":21100,"message":"account not found"}"#
+        } else {
+            r#"{"code":20001,"message":"invalid param"}"#
+        }
+        .to_string()
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    // Main account and sub-accounts in the API's order, with or without the 0x prefix
+    assert_eq!(client.get_account_index("0xabc").await.unwrap(), vec![1, 7, 8]);
+    assert_eq!(client.get_account_index("abc").await.unwrap(), vec![1, 7, 8]);
+
+    for unknown in ["0xdead", "0xempty"] {
+        let err = client.get_account_index(unknown).await.unwrap_err();
+        assert!(matches!(&err, ApiError::NotFound(message) if message.contains(unknown)), "{:?}", err);
+    }
+    assert!(matches!(client.get_account_index("0xbad").await, Err(ApiError::Api(message)) if message.starts_with("code 20001")));
+}
+
+#[tokio::test]
+async fn test_get_sub_accounts_lists_children_with_balances() {
+    let base_url = mock_server(|request| {
+        if request.contains("/api/v1/accountsByL1Address") {
+            assert!(request.contains("l1_address=0xabc"));
+            r#"{"code":200,"l1_address":"0xabc","sub_accounts":[
+                {"index":1,"account_type":0,"l1_address":"0xabc","collateral":"5000"},
+                {"index":7,"account_type":1,"l1_address":"0xabc","name":"mm-eth","collateral":"1200.50",
+                    "available_balance":"800.25","total_asset_value":"1210.75"},
+                {"index":8,"account_type":1,"l1_address":"0xabc","collateral":"0"}
+            ]}"#
+        } else if request.contains("value=7") {
+            r#"{"code":200,"accounts":[{"index":7,"account_type":1,"l1_address":"0xabc","name":"mm-eth"}]}"#
+        } else if request.contains("value=1") {
+            r#"{"code":200,"accounts":[{"index":1,"account_type":0,"l1_address":"0xabc"}]}"#
+        } else {
+            r#"{"code":200,"accounts":[]}"#
+        }
+        .to_string()
+    })
+    .await;
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+
+    let subs = client.get_sub_accounts(1).await.unwrap();
+    assert_eq!(subs.iter().map(|sub| sub.account_index).collect::<Vec<_>>(), vec![7, 8]);
+    assert_eq!(subs[0].label, "mm-eth");
+    assert_eq!(subs[0].collateral.to_string(), "1200.50");
+    assert_eq!(subs[0].available_balance.to_string(), "800.25");
+    assert_eq!(subs[0].total_asset_value.to_string(), "1210.75");
+    assert_eq!((subs[1].label.as_str(), subs[1].collateral.to_string()), ("", "0".to_string()));
+
+    // A sub-account has no children
+    assert!(client.get_sub_accounts(7).await.unwrap().is_empty());
+    assert!(matches!(client.get_sub_accounts(99).await, Err(ApiError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn test_market_refresh_swaps_the_metadata_cache() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let refreshes = Arc::new(AtomicUsize::new(0));
+    let base_url = {
+        let refreshes = refreshes.clone();
+        mock_server(move |request| {
+            assert!(request.contains("/api/v1/orderBooks"));
+            // The minimum size is raised after the first refresh
+            if refreshes.fetch_add(1, Ordering::SeqCst) == 0 {
+                ETH_MARKET_JSON.to_string()
+            } else {
+                ETH_MARKET_JSON.replace(r#""min_base_amount":"0.0050""#, r#""min_base_amount":"0.0100""#)
+            }
+        })
+        .await
+    };
+    let client = LighterClient::new(base_url, &test_key_hex(), 1, 0).unwrap();
+    assert_eq!(client.last_market_refresh(), None);
+
+    client.refresh_markets().await.unwrap();
+    let first = client.last_market_refresh().unwrap();
+    assert_eq!(client.cached_market_info(0).await.unwrap().min_base_amount.to_string(), "0.0050");
+
+    assert!(matches!(client.spawn_market_refresh(Duration::ZERO), Err(ApiError::InvalidConfig(_))));
+    let refresh = client.spawn_market_refresh(Duration::from_millis(20)).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while client.cached_market_info(0).await.unwrap().min_base_amount.to_string() != "0.0100" {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("background refresh did not pick up the new metadata");
+    assert!(client.last_market_refresh().unwrap() >= first);
+
+    // Dropping the handle stops the refreshes
+    drop(refresh);
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    let count = refreshes.load(Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(refreshes.load(Ordering::SeqCst), count);
+}
@@ -0,0 +1,137 @@
+use crate::{ApiError, MarketInfo, Result, Side};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// Direction to round a price that is not on the tick grid
+///
+/// Rounding a limit price the wrong way can turn a resting order into one that
+/// takes liquidity: a bid rounded up may reach the best ask. `TowardPassive`
+/// picks the safe direction for an order's side.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TickRounding {
+    /// Towards the lower tick (e.g. bids that must not pay more)
+    Down,
+    /// Towards the higher tick (e.g. asks that must not sell for less)
+    Up,
+    /// To the closer tick, halfway prices up
+    #[default]
+    Nearest,
+    /// Away from the opposite side of the book: down for buys, up for sells
+    TowardPassive(Side),
+}
+
+impl TickRounding {
+    /// Whether a price strictly between two ticks goes to the higher one;
+    /// `above_half` tells if it lies at or past the midpoint
+    fn rounds_up(self, above_half: bool) -> bool {
+        match self {
+            TickRounding::Down | TickRounding::TowardPassive(Side::Buy) => false,
+            TickRounding::Up | TickRounding::TowardPassive(Side::Sell) => true,
+            TickRounding::Nearest => above_half,
+        }
+    }
+}
+
+/// Price grid step, in the market's integer price units
+///
+/// The integer `Price` of an order counts the market's minimum increments
+/// (10^-price_decimals), so every integer price is on the market's own grid.
+/// Quoting ladders often use a coarser step; a `TickSize` converts between
+/// integer prices and whole steps of that grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TickSize(i64);
+
+impl TickSize {
+    /// A step of `raw` integer price units; must be positive
+    pub fn new(raw: i64) -> Result<Self> {
+        if raw <= 0 {
+            return Err(ApiError::InvalidConfig(format!("tick size must be positive, got {}", raw)));
+        }
+        Ok(Self(raw))
+    }
+
+    /// Step size in integer price units
+    pub fn raw(&self) -> i64 {
+        self.0
+    }
+
+    /// Number of whole ticks in `price`, rounded in `rounding` direction
+    pub fn price_to_ticks(&self, price: i64, rounding: TickRounding) -> i64 {
+        let (ticks, remainder) = (price.div_euclid(self.0), price.rem_euclid(self.0));
+        if remainder != 0 && rounding.rounds_up(remainder * 2 >= self.0) {
+            ticks + 1
+        } else {
+            ticks
+        }
+    }
+
+    /// Integer price of `ticks` whole ticks
+    pub fn ticks_to_price(&self, ticks: i64) -> i64 {
+        ticks * self.0
+    }
+
+    /// `price` moved onto the grid in `rounding` direction
+    pub fn round_price(&self, price: i64, rounding: TickRounding) -> i64 {
+        self.ticks_to_price(self.price_to_ticks(price, rounding))
+    }
+}
+
+impl MarketInfo {
+    /// The market's minimum price increment: one integer price unit
+    pub fn min_tick(&self) -> TickSize {
+        TickSize(1)
+    }
+
+    /// Integer `Price` of a decimal price, rounded onto the minimum tick in `rounding` direction
+    ///
+    /// ETH with 2 price decimals turns 3496.594 into `349659` with
+    /// `TickRounding::Nearest` (the default) and into `349660` with
+    /// `TickRounding::TowardPassive(Side::Sell)`. Round the result onto a
+    /// coarser grid with [`TickSize::round_price`]. Fails with
+    /// `ApiError::InvalidOrderParams` if the price is not positive or does not
+    /// fit an integer price.
+    pub fn to_price_units(&self, price: Decimal, rounding: TickRounding) -> Result<i64> {
+        let invalid = || {
+            ApiError::InvalidOrderParams(format!(
+                "price {} is not a valid {} price with {} decimals",
+                price, self.symbol, self.price_decimals
+            ))
+        };
+        if price <= Decimal::ZERO {
+            return Err(invalid());
+        }
+        let units = price
+            .checked_mul(Decimal::from(10u64.pow(self.price_decimals)))
+            .ok_or_else(invalid)?;
+        let strategy = if units.fract().is_zero() {
+            RoundingStrategy::ToZero
+        } else if rounding.rounds_up(units.fract() >= Decimal::new(5, 1)) {
+            RoundingStrategy::ToPositiveInfinity
+        } else {
+            RoundingStrategy::ToNegativeInfinity
+        };
+        units
+            .round_dp_with_strategy(0, strategy)
+            .to_i64()
+            .filter(|units| *units > 0)
+            .ok_or_else(invalid)
+    }
+
+    /// A grid step given as a price (e.g. `0.05` USDC) for this market
+    ///
+    /// Fails if `step` is not a positive multiple of the market's minimum
+    /// increment (10^-price_decimals).
+    pub fn tick_size(&self, step: Decimal) -> Result<TickSize> {
+        let raw = step * Decimal::from(10u64.pow(self.price_decimals));
+        if raw.fract() != Decimal::ZERO {
+            return Err(ApiError::InvalidConfig(format!(
+                "tick size {} is finer than {} price decimals of {}",
+                step, self.price_decimals, self.symbol
+            )));
+        }
+        let raw = raw
+            .to_i64()
+            .ok_or_else(|| ApiError::InvalidConfig(format!("tick size {} out of range", step)))?;
+        TickSize::new(raw)
+    }
+}
@@ -0,0 +1,145 @@
+use serde::{Deserialize, Serialize};
+
+/// A typed `tx_info` payload for the `sendTx` endpoint
+///
+/// Serializing an implementor produces the exact JSON the server expects,
+/// apart from the `Sig` field that signing adds. Every integer field is a JSON
+/// number, never a quoted string, including 64-bit values such as `Nonce` and
+/// `ExpiredAt` (serde_json writes them exactly); the only string fields are
+/// `PubKey` (hex) and `Sig` (base64).
+pub trait Transaction: Serialize {
+    /// Transaction type code sent alongside `tx_info`
+    const TX_TYPE: u32;
+
+    fn header(&self) -> &TxHeader;
+
+    fn header_mut(&mut self) -> &mut TxHeader;
+}
+
+/// Fields shared by every transaction type
+///
+/// The client's submit methods fill these in (see [`crate::LighterClient::tx_header`]);
+/// set them yourself only when signing with [`crate::LighterClient::sign_transaction`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TxHeader {
+    pub account_index: i64,
+    pub api_key_index: u8,
+    /// Unix time in milliseconds after which the transaction is rejected
+    pub expired_at: i64,
+    pub nonce: i64,
+}
+
+macro_rules! impl_transaction {
+    ($tx:ty, $tx_type:expr) => {
+        impl Transaction for $tx {
+            const TX_TYPE: u32 = $tx_type;
+
+            fn header(&self) -> &TxHeader {
+                &self.header
+            }
+
+            fn header_mut(&mut self) -> &mut TxHeader {
+                &mut self.header
+            }
+        }
+    };
+}
+
+/// CHANGE_PUB_KEY (8): register a new API public key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChangePubKeyTx {
+    #[serde(flatten)]
+    pub header: TxHeader,
+    /// 40-byte public key, hex-encoded
+    pub pub_key: String,
+}
+
+/// CREATE_ORDER (14)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CreateOrderTx {
+    #[serde(flatten)]
+    pub header: TxHeader,
+    pub market_index: u8,
+    pub client_order_index: u64,
+    pub base_amount: i64,
+    pub price: i64,
+    /// 1 = sell, 0 = buy
+    pub is_ask: u8,
+    #[serde(rename = "Type")]
+    pub order_type: u8,
+    pub time_in_force: u8,
+    pub reduce_only: u8,
+    pub trigger_price: i64,
+    pub order_expiry: i64,
+}
+
+/// CANCEL_ORDER (15)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CancelOrderTx {
+    #[serde(flatten)]
+    pub header: TxHeader,
+    pub market_index: u8,
+    pub index: i64,
+}
+
+/// CANCEL_ALL_ORDERS (16)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CancelAllOrdersTx {
+    #[serde(flatten)]
+    pub header: TxHeader,
+    pub time_in_force: u8,
+    pub time: i64,
+}
+
+/// MODIFY_ORDER (17)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ModifyOrderTx {
+    #[serde(flatten)]
+    pub header: TxHeader,
+    pub market_index: u8,
+    pub index: i64,
+    pub base_amount: i64,
+    pub price: i64,
+    pub trigger_price: i64,
+}
+
+/// UPDATE_LEVERAGE (20)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdateLeverageTx {
+    #[serde(flatten)]
+    pub header: TxHeader,
+    pub market_index: u8,
+    /// Initial margin fraction in basis points (10,000 / leverage)
+    pub initial_margin_fraction: u16,
+    /// 0 = cross, 1 = isolated
+    pub margin_mode: u8,
+}
+
+/// UPDATE_MARGIN (29): move collateral into or out of an isolated position
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdateMarginTx {
+    #[serde(flatten)]
+    pub header: TxHeader,
+    pub market_index: u8,
+    /// USDC with 6 decimals
+    #[serde(rename = "USDCAmount")]
+    pub usdc_amount: i64,
+    /// 0 = remove, 1 = add
+    pub direction: u8,
+}
+
+impl_transaction!(ChangePubKeyTx, 8);
+impl_transaction!(CreateOrderTx, 14);
+impl_transaction!(CancelOrderTx, 15);
+impl_transaction!(CancelAllOrdersTx, 16);
+impl_transaction!(ModifyOrderTx, 17);
+impl_transaction!(UpdateLeverageTx, 20);
+impl_transaction!(UpdateMarginTx, 29);
@@ -0,0 +1,71 @@
+use crate::order_rules::{ORDER_TYPE_STOP_LOSS, ORDER_TYPE_STOP_LOSS_LIMIT, ORDER_TYPE_TAKE_PROFIT, ORDER_TYPE_TAKE_PROFIT_LIMIT};
+use crate::{ApiError, CreateOrderRequest, Result, Side};
+
+/// Which way the price must cross a conditional order's trigger price
+///
+/// The CREATE_ORDER transaction has no direction field: the exchange derives
+/// it from the order type and side, as [`Self::of`] does. A stop-loss closes a
+/// position moving against it and a take-profit one moving in its favor, so a
+/// sell (closing a long) stop-loss fires on a fall and a sell take-profit on a
+/// rise; buys (closing a short) are the other way round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerDirection {
+    /// Fires once the price rises to or above the trigger price
+    Above,
+    /// Fires once the price falls to or below the trigger price
+    Below,
+}
+
+impl TriggerDirection {
+    /// Direction of a conditional order, `None` for order types without a trigger
+    pub fn of(order_type: u8, side: Side) -> Option<Self> {
+        let stop_loss = match order_type {
+            ORDER_TYPE_STOP_LOSS | ORDER_TYPE_STOP_LOSS_LIMIT => true,
+            ORDER_TYPE_TAKE_PROFIT | ORDER_TYPE_TAKE_PROFIT_LIMIT => false,
+            _ => return None,
+        };
+        Some(match (stop_loss, side) {
+            (true, Side::Sell) | (false, Side::Buy) => TriggerDirection::Below,
+            (true, Side::Buy) | (false, Side::Sell) => TriggerDirection::Above,
+        })
+    }
+}
+
+impl CreateOrderRequest {
+    /// Which way the price must move to trigger this order, `None` if it has no trigger
+    pub fn trigger_direction(&self) -> Option<TriggerDirection> {
+        TriggerDirection::of(self.order_type, self.side)
+    }
+
+    /// Check that the trigger price lies in the trigger direction from `reference_price`
+    ///
+    /// `reference_price` is the price the order protects: the position's entry
+    /// price, or the current price for an order placed against a live market.
+    /// A `Below` trigger must be strictly below it and an `Above` one strictly
+    /// above, otherwise the order would fire at once or never make sense; for
+    /// example a stop-loss on a long (a sell) must trigger below entry and a
+    /// take-profit above. The trigger price must also be positive. Fails with
+    /// `ApiError::InvalidOrderParams`, also for order types without a trigger.
+    pub fn validate_trigger(&self, reference_price: i64) -> Result<()> {
+        let direction = self.trigger_direction().ok_or_else(|| {
+            ApiError::InvalidOrderParams(format!("order type {} has no trigger price", self.order_type))
+        })?;
+        if self.trigger_price <= 0 {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "order type {} needs a positive trigger price, got {}",
+                self.order_type, self.trigger_price
+            )));
+        }
+        let (valid, relation) = match direction {
+            TriggerDirection::Above => (self.trigger_price > reference_price, "above"),
+            TriggerDirection::Below => (self.trigger_price < reference_price, "below"),
+        };
+        if !valid {
+            return Err(ApiError::InvalidOrderParams(format!(
+                "a {:?} order of type {} triggers {:?}, so its trigger price {} must be {} {}",
+                self.side, self.order_type, direction, self.trigger_price, relation, reference_price
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{ApiError, Result};
+use poseidon_hash::{hash_to_quintic_extension, Goldilocks};
+use serde_json::Value;
+
+/// Chain id hashed into every transaction: 304 on mainnet, 300 elsewhere
+pub(crate) fn chain_id(base_url: &str) -> u32 {
+    if base_url.contains("mainnet") {
+        304
+    } else {
+        300
+    }
+}
+
+/// How a `tx_info` field is encoded into Goldilocks elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FieldKind {
+    /// Signed 64-bit integer, sign-extended into the field
+    Int,
+    /// Unsigned integer truncated to 32 bits (indices, prices, flags)
+    Uint,
+    /// 40-byte hex public key, as 5 little-endian 8-byte limbs
+    PubKey,
+}
+
+use FieldKind::{Int, PubKey, Uint};
+
+/// Fields hashed before the type-specific body, for every transaction type
+///
+/// The chain id and tx type come first and are not `tx_info` fields.
+const HEADER_FIELDS: &[(&str, FieldKind)] = &[
+    ("Nonce", Int),
+    ("ExpiredAt", Int),
+    ("AccountIndex", Int),
+    ("ApiKeyIndex", Uint),
+];
+
+/// Type-specific `tx_info` fields in the order the exchange hashes them
+pub(crate) fn body_fields(tx_type: u32) -> Result<&'static [(&'static str, FieldKind)]> {
+    let fields: &[(&str, FieldKind)] = match tx_type {
+        // CHANGE_PUB_KEY
+        8 => &[("PubKey", PubKey)],
+        // CREATE_ORDER
+        14 => &[
+            ("MarketIndex", Uint),
+            ("ClientOrderIndex", Int),
+            ("BaseAmount", Int),
+            ("Price", Uint),
+            ("IsAsk", Uint),
+            ("Type", Uint),
+            ("TimeInForce", Uint),
+            ("ReduceOnly", Uint),
+            ("TriggerPrice", Uint),
+            ("OrderExpiry", Int),
+        ],
+        // CANCEL_ORDER
+        15 => &[("MarketIndex", Uint), ("Index", Int)],
+        // CANCEL_ALL_ORDERS
+        16 => &[("TimeInForce", Uint), ("Time", Int)],
+        // MODIFY_ORDER
+        17 => &[
+            ("MarketIndex", Uint),
+            ("Index", Int),
+            ("BaseAmount", Int),
+            ("Price", Uint),
+            ("TriggerPrice", Uint),
+        ],
+        // UPDATE_LEVERAGE
+        20 => &[
+            ("MarketIndex", Uint),
+            ("InitialMarginFraction", Uint),
+            ("MarginMode", Uint),
+        ],
+        // UPDATE_MARGIN
+        29 => &[("MarketIndex", Uint), ("USDCAmount", Int), ("Direction", Uint)],
+        _ => return Err(ApiError::Api(format!("Unsupported transaction type: {}", tx_type))),
+    };
+    Ok(fields)
+}
+
+/// Goldilocks elements of a transaction's signing message, in canonical order
+///
+/// `chainId, txType, Nonce, ExpiredAt, AccountIndex, ApiKeyIndex`, then the
+/// body fields of `tx_type`. The JSON key order of `tx_info` is irrelevant;
+/// a missing field is an error rather than a silent zero.
+pub(crate) fn message_elements(chain_id: u32, tx_type: u32, tx_info: &Value) -> Result<Vec<Goldilocks>> {
+    let mut elements = vec![
+        Goldilocks::from_canonical_u64(chain_id as u64),
+        Goldilocks::from_canonical_u64(tx_type as u64),
+    ];
+    for &(name, kind) in HEADER_FIELDS.iter().chain(body_fields(tx_type)?) {
+        encode_field(&mut elements, tx_type, name, kind, &tx_info[name])?;
+    }
+    Ok(elements)
+}
+
+/// Poseidon2 hash of the signing message: the 40 bytes that get signed
+pub(crate) fn message_hash(chain_id: u32, tx_type: u32, tx_info: &Value) -> Result<[u8; 40]> {
+    let elements = message_elements(chain_id, tx_type, tx_info)?;
+    let hash = hash_to_quintic_extension(&elements).to_bytes_le();
+
+    let mut bytes = [0u8; 40];
+    bytes.copy_from_slice(&hash[..40]);
+    Ok(bytes)
+}
+
+fn encode_field(
+    elements: &mut Vec<Goldilocks>,
+    tx_type: u32,
+    name: &str,
+    kind: FieldKind,
+    value: &Value,
+) -> Result<()> {
+    let missing = || ApiError::Api(format!("tx type {}: missing or invalid field {}", tx_type, name));
+    // The exchange reads integers as JSON numbers; a quoted one would hash differently there
+    if kind != PubKey && value.is_string() {
+        return Err(ApiError::Api(format!(
+            "tx type {}: field {} must be a JSON number, not the string {}",
+            tx_type, name, value
+        )));
+    }
+
+    match kind {
+        Int => {
+            let v = value.as_i64().ok_or_else(missing)?;
+            elements.push(Goldilocks::from_i64(v));
+        }
+        Uint => {
+            let v = value
+                .as_u64()
+                .or_else(|| value.as_i64().map(|v| v as u64))
+                .ok_or_else(missing)?;
+            elements.push(Goldilocks::from_canonical_u64(v as u32 as u64));
+        }
+        PubKey => {
+            let bytes = hex::decode(value.as_str().ok_or_else(missing)?)
+                .map_err(|e| ApiError::Api(format!("Invalid PubKey hex: {}", e)))?;
+            if bytes.len() != 40 {
+                return Err(ApiError::Api("PubKey must be 40 bytes".to_string()));
+            }
+            for chunk in bytes.chunks_exact(8) {
+                let limb = u64::from_le_bytes(chunk.try_into().expect("8-byte chunk"));
+                elements.push(Goldilocks::from_canonical_u64(limb));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use crate::account::{check_response_code, decode};
+use crate::{LighterClient, Result};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+/// Maximum page size accepted by the withdrawal history endpoint
+const WITHDRAW_PAGE_LIMIT: u32 = 100;
+
+/// Withdrawal to look up with [`LighterClient::get_withdrawal_status`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WithdrawalRef {
+    /// Identifier the exchange assigned to the withdrawal
+    Id(String),
+    /// Nonce of the withdraw transaction (tx type 19)
+    Nonce(i64),
+}
+
+/// Stage of a withdrawal on its way from L2 to L1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WithdrawalState {
+    /// Accepted on L2 and waiting for its batch, or not indexed yet
+    Pending,
+    /// Being proven and relayed to L1
+    Processing,
+    /// Arrived on L1 and ready to be claimed from the contract
+    Claimable,
+    /// Funds delivered on L1
+    Completed,
+    /// Failed or refunded to the L2 account
+    Failed,
+    /// A status this client does not know, as reported
+    Other(String),
+}
+
+impl WithdrawalState {
+    fn from_api(status: &str) -> Self {
+        match status.to_ascii_lowercase().as_str() {
+            "pending" | "queued" => WithdrawalState::Pending,
+            "processing" | "in_progress" => WithdrawalState::Processing,
+            "claimable" => WithdrawalState::Claimable,
+            "completed" | "success" => WithdrawalState::Completed,
+            "failed" | "refunded" => WithdrawalState::Failed,
+            _ => WithdrawalState::Other(status.to_string()),
+        }
+    }
+}
+
+/// Where a withdrawal is, see [`LighterClient::get_withdrawal_status`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WithdrawalStatus {
+    pub state: WithdrawalState,
+    /// `false` while the exchange does not list the withdrawal yet; `state` is then `Pending`
+    pub indexed: bool,
+    pub id: Option<String>,
+    /// "secure" (claimed on L1) or "fast"
+    pub kind: Option<String>,
+    /// USDC
+    pub amount: Option<Decimal>,
+    /// Submission time, unix ms
+    pub timestamp: Option<i64>,
+    /// Hash of the L1 transaction, once there is one
+    pub l1_tx_hash: Option<String>,
+    /// Expected completion time (unix ms), if the exchange gives one
+    pub estimated_completion: Option<i64>,
+}
+
+impl WithdrawalStatus {
+    fn not_indexed() -> Self {
+        Self {
+            state: WithdrawalState::Pending,
+            indexed: false,
+            id: None,
+            kind: None,
+            amount: None,
+            timestamp: None,
+            l1_tx_hash: None,
+            estimated_completion: None,
+        }
+    }
+
+    /// Whether the withdrawal will not change state any more by itself
+    ///
+    /// `Claimable` is not final: the funds still have to be claimed on L1.
+    pub fn is_final(&self) -> bool {
+        matches!(self.state, WithdrawalState::Completed | WithdrawalState::Failed)
+    }
+}
+
+/// Raw record from the withdrawal history endpoint
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+struct WithdrawalRecord {
+    id: String,
+    nonce: Option<i64>,
+    amount: Decimal,
+    timestamp: i64,
+    status: String,
+    /// "secure" or "fast"
+    #[serde(rename = "type")]
+    kind: String,
+    l1_tx_hash: String,
+    estimated_completion_time: Option<i64>,
+}
+
+impl WithdrawalRecord {
+    fn matches(&self, withdrawal: &WithdrawalRef) -> bool {
+        match withdrawal {
+            WithdrawalRef::Id(id) => &self.id == id,
+            WithdrawalRef::Nonce(nonce) => self.nonce == Some(*nonce),
+        }
+    }
+}
+
+impl From<WithdrawalRecord> for WithdrawalStatus {
+    fn from(record: WithdrawalRecord) -> Self {
+        Self {
+            state: WithdrawalState::from_api(&record.status),
+            indexed: true,
+            id: Some(record.id),
+            kind: Some(record.kind).filter(|kind| !kind.is_empty()),
+            amount: Some(record.amount),
+            timestamp: Some(record.timestamp),
+            l1_tx_hash: Some(record.l1_tx_hash).filter(|hash| !hash.is_empty()),
+            estimated_completion: record.estimated_completion_time,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+struct WithdrawalPage {
+    withdraws: Vec<WithdrawalRecord>,
+    cursor: Option<String>,
+}
+
+impl LighterClient {
+    /// Look up how far a withdrawal has got on its way to L1 (authenticated)
+    ///
+    /// Searches the account's withdrawal history, following pages until the
+    /// withdrawal is found. A withdrawal the exchange does not list yet, e.g.
+    /// one submitted moments ago, is reported as `Pending` with `indexed` set to
+    /// `false` rather than as an error, so the call can be polled until
+    /// [`WithdrawalStatus::is_final`].
+    pub async fn get_withdrawal_status(&self, account_index: i64, withdrawal: WithdrawalRef) -> Result<WithdrawalStatus> {
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let mut query = vec![
+                ("account_index", account_index.to_string()),
+                ("limit", WITHDRAW_PAGE_LIMIT.to_string()),
+            ];
+            if let Some(cursor) = &cursor {
+                query.push(("cursor", cursor.clone()));
+            }
+
+            let response = self.get_json_auth("/api/v1/withdraw_history", &query).await?;
+            check_response_code(&response)?;
+            let page: WithdrawalPage = decode(response)?;
+            let exhausted = page.withdraws.is_empty();
+
+            if let Some(record) = page.withdraws.into_iter().find(|record| record.matches(&withdrawal)) {
+                return Ok(record.into());
+            }
+
+            cursor = match page.cursor {
+                Some(next) if !next.is_empty() && !exhausted && Some(&next) != cursor.as_ref() => Some(next),
+                _ => return Ok(WithdrawalStatus::not_indexed()),
+            };
+        }
+    }
+}
@@ -0,0 +1,549 @@
+use crate::{ApiError, LighterClient, Result};
+use futures::stream::{self, BoxStream};
+use futures::{future, SinkExt, StreamExt};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::{interval_at, sleep_until, Instant, Interval, MissedTickBehavior};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+pub(crate) type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Callback receiving every raw WebSocket text frame, see [`LighterClientBuilder::on_ws_frame`](crate::LighterClientBuilder::on_ws_frame)
+pub type WsFrameHandler = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Aggregated size resting at one price
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceLevel {
+    pub price: Decimal,
+    pub size: Decimal,
+}
+
+/// Best bid and best offer of one market
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bbo {
+    pub market_index: u8,
+    /// `None` while the bid side is empty
+    pub bid: Option<PriceLevel>,
+    /// `None` while the ask side is empty
+    pub ask: Option<PriceLevel>,
+}
+
+/// Change to a locally maintained order book
+#[derive(Debug, Clone)]
+pub enum BookEvent {
+    /// The book after applying a snapshot or update
+    Updated(Arc<OrderBook>),
+    /// The book failed its consistency check, or a book message could not be
+    /// read, and the book was discarded
+    ///
+    /// A fresh snapshot has been requested; `Updated` resumes once it arrives.
+    DesyncDetected { market_index: u8, reason: String },
+    /// The heartbeat failed and the subscription moved to a new connection
+    ///
+    /// The book is rebuilt from the snapshot the new subscription receives.
+    Reconnected { market_index: u8 },
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+struct WsLevel {
+    price: Decimal,
+    size: Decimal,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+#[cfg_attr(feature = "strict-json", serde(deny_unknown_fields))]
+struct WsBook {
+    asks: Vec<WsLevel>,
+    bids: Vec<WsLevel>,
+    offset: u64,
+}
+
+/// One order book message of the WebSocket feed, typed
+///
+/// Levels are `(price, size)`; a size of zero removes the level. A snapshot
+/// replaces the whole book, any other update changes only the levels it lists.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BookUpdate {
+    pub bids: Vec<(Decimal, Decimal)>,
+    pub asks: Vec<(Decimal, Decimal)>,
+    /// Server offset of the message, increasing along the feed (0 if absent)
+    pub sequence: u64,
+    pub is_snapshot: bool,
+}
+
+impl BookUpdate {
+    /// Read an order book message; `None` for messages of other types
+    ///
+    /// Fails if a book message's levels cannot be read.
+    pub fn from_message(message: &Value) -> Result<Option<Self>> {
+        let is_snapshot = match message["type"].as_str().unwrap_or_default() {
+            "subscribed/order_book" => true,
+            "update/order_book" => false,
+            _ => return Ok(None),
+        };
+        let book: WsBook = serde_json::from_value(message["order_book"].clone())?;
+        let levels = |levels: Vec<WsLevel>| levels.into_iter().map(|level| (level.price, level.size)).collect();
+        Ok(Some(Self {
+            bids: levels(book.bids),
+            asks: levels(book.asks),
+            sequence: book.offset,
+            is_snapshot,
+        }))
+    }
+}
+
+/// Order book rebuilt from a snapshot plus incremental updates
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderBook {
+    market_index: u8,
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+}
+
+impl OrderBook {
+    pub fn new(market_index: u8) -> Self {
+        Self { market_index, ..Self::default() }
+    }
+
+    pub fn market_index(&self) -> u8 {
+        self.market_index
+    }
+
+    /// Bid levels, best (highest) first
+    pub fn bids(&self) -> impl Iterator<Item = PriceLevel> + '_ {
+        self.bids.iter().rev().map(|(&price, &size)| PriceLevel { price, size })
+    }
+
+    /// Ask levels, best (lowest) first
+    pub fn asks(&self) -> impl Iterator<Item = PriceLevel> + '_ {
+        self.asks.iter().map(|(&price, &size)| PriceLevel { price, size })
+    }
+
+    pub fn best_bid(&self) -> Option<PriceLevel> {
+        self.bids().next()
+    }
+
+    pub fn best_ask(&self) -> Option<PriceLevel> {
+        self.asks().next()
+    }
+
+    pub fn bbo(&self) -> Bbo {
+        Bbo {
+            market_index: self.market_index,
+            bid: self.best_bid(),
+            ask: self.best_ask(),
+        }
+    }
+
+    /// Volume imbalance of the best `levels` levels on each side, in [-1, 1]
+    ///
+    /// `(bid_volume - ask_volume) / (bid_volume + ask_volume)`, where each
+    /// volume is the summed size of that side's best `levels` levels. Positive
+    /// when bids outweigh asks. `None` if both sides are empty within `levels`.
+    pub fn imbalance(&self, levels: usize) -> Option<Decimal> {
+        let bid_volume: Decimal = self.bids().take(levels).map(|level| level.size).sum();
+        let ask_volume: Decimal = self.asks().take(levels).map(|level| level.size).sum();
+        let total = bid_volume + ask_volume;
+        if total.is_zero() {
+            return None;
+        }
+        Some((bid_volume - ask_volume) / total)
+    }
+
+    /// Mid price weighted by the sizes at the top of the book
+    ///
+    /// `(best_bid * ask_size + best_ask * bid_size) / (bid_size + ask_size)`:
+    /// each price is weighted by the size on the opposite side, so the result
+    /// leans toward the side with less size, where the next trade is more
+    /// likely to move the price. `None` unless both sides have a level.
+    pub fn microprice(&self) -> Option<Decimal> {
+        let (bid, ask) = (self.best_bid()?, self.best_ask()?);
+        let total = bid.size + ask.size;
+        if total.is_zero() {
+            return None;
+        }
+        Some((bid.price * ask.size + ask.price * bid.size) / total)
+    }
+
+    /// Why the book cannot be a real exchange book, if it cannot
+    ///
+    /// Levels are kept sorted by construction, so this checks what a missed or
+    /// misapplied update leaves behind: non-positive prices or sizes, and a best
+    /// bid at or above the best ask.
+    pub fn consistency_error(&self) -> Option<String> {
+        let mut levels = self.bids.iter().chain(&self.asks);
+        if let Some((price, size)) = levels.find(|(price, size)| **price <= Decimal::ZERO || **size <= Decimal::ZERO) {
+            return Some(format!("invalid level {} @ {}", size, price));
+        }
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) if bid.price >= ask.price => {
+                Some(format!("crossed book: best bid {} >= best ask {}", bid.price, ask.price))
+            }
+            _ => None,
+        }
+    }
+
+    /// Copy of the book keeping only the best `depth` levels on each side
+    pub fn truncated(&self, depth: usize) -> OrderBook {
+        OrderBook {
+            market_index: self.market_index,
+            bids: self.bids.iter().rev().take(depth).map(|(&price, &size)| (price, size)).collect(),
+            asks: self.asks.iter().take(depth).map(|(&price, &size)| (price, size)).collect(),
+        }
+    }
+
+    /// Apply a snapshot or an update; a size of zero removes the level
+    pub fn apply_update(&mut self, update: &BookUpdate) {
+        if update.is_snapshot {
+            self.bids.clear();
+            self.asks.clear();
+        }
+        for (side, levels) in [(&mut self.bids, &update.bids), (&mut self.asks, &update.asks)] {
+            for &(price, size) in levels {
+                if size.is_zero() {
+                    side.remove(&price);
+                } else {
+                    side.insert(price, size);
+                }
+            }
+        }
+    }
+}
+
+/// WebSocket endpoint for a REST base URL (`https://host` -> `wss://host/stream`)
+pub(crate) fn ws_url(base_url: &str) -> String {
+    let base_url = base_url.trim_end_matches('/');
+    let url = if let Some(rest) = base_url.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = base_url.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else {
+        base_url.to_string()
+    };
+    format!("{}/stream", url)
+}
+
+/// Settings shared by every WebSocket subscription of a client
+#[derive(Clone)]
+pub(crate) struct WsConfig {
+    url: String,
+    connect_timeout: Duration,
+    idle_timeout: Option<Duration>,
+    /// Ping interval and pong deadline, if the heartbeat is enabled
+    heartbeat: Option<(Duration, Duration)>,
+    /// Sees every text frame before it is parsed
+    frame_handler: Option<WsFrameHandler>,
+}
+
+/// What a subscription produced next
+pub(crate) enum WsFrame {
+    /// An application message (server pings are answered internally)
+    Json(Value),
+    /// The heartbeat failed and the channel was resubscribed on a new connection
+    Reconnected,
+}
+
+/// A subscription to one channel, kept alive by a ping/pong heartbeat
+pub(crate) struct WsSubscription {
+    ws: WsStream,
+    config: WsConfig,
+    channel: String,
+    ping_timer: Option<Interval>,
+    /// When the pong for the last ping must have arrived
+    pong_due: Option<Instant>,
+}
+
+impl WsSubscription {
+    pub(crate) async fn open(config: WsConfig, channel: String) -> Result<Self> {
+        let ws = connect(&config, &channel).await?;
+        let ping_timer = config.heartbeat.map(|(interval, _)| {
+            let mut timer = interval_at(Instant::now() + interval, interval);
+            timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            timer
+        });
+        Ok(Self { ws, config, channel, ping_timer, pong_due: None })
+    }
+
+    /// Wait for the next application message
+    ///
+    /// Sends a ping every heartbeat interval; a pong that does not arrive within
+    /// the deadline is treated as a dead connection, which is replaced and
+    /// resubscribed. Returns `None` once the server closes the connection.
+    pub(crate) async fn next(&mut self) -> Option<Result<WsFrame>> {
+        loop {
+            let pong_due = self.pong_due;
+            let wake = tokio::select! {
+                frame = read_frame(&mut self.ws, self.config.idle_timeout) => Wake::Frame(frame),
+                _ = tick(&mut self.ping_timer) => Wake::Ping,
+                _ = deadline(pong_due) => Wake::PongMissed,
+            };
+
+            match wake {
+                Wake::Frame(Err(e)) => return Some(Err(e)),
+                Wake::Frame(Ok(None)) | Wake::Frame(Ok(Some(Message::Close(_)))) => return None,
+                Wake::Frame(Ok(Some(Message::Pong(_)))) => self.pong_due = None,
+                Wake::Frame(Ok(Some(Message::Text(text)))) => {
+                    if let Some(handler) = &self.config.frame_handler {
+                        handler(&text);
+                    }
+                    // Not JSON: nothing typed can use it, so skip it (the handler saw it)
+                    let Ok(message) = serde_json::from_str::<Value>(&text) else {
+                        continue;
+                    };
+                    if message["type"] != "ping" {
+                        return Some(Ok(WsFrame::Json(message)));
+                    }
+                    if let Err(e) = self.send(json!({"type": "pong"})).await {
+                        return Some(Err(e));
+                    }
+                }
+                Wake::Frame(Ok(Some(_))) => {}
+                Wake::Ping => {
+                    if self.pong_due.is_none() {
+                        if let Err(e) = self.ws.send(Message::Ping(Vec::new())).await {
+                            return Some(Err(e.into()));
+                        }
+                        let (_, pong_timeout) = self.config.heartbeat.unwrap_or_default();
+                        self.pong_due = Some(Instant::now() + pong_timeout);
+                    }
+                }
+                Wake::PongMissed => {
+                    self.ws = match connect(&self.config, &self.channel).await {
+                        Ok(ws) => ws,
+                        Err(e) => return Some(Err(e)),
+                    };
+                    self.pong_due = None;
+                    if let Some(timer) = &mut self.ping_timer {
+                        timer.reset();
+                    }
+                    return Some(Ok(WsFrame::Reconnected));
+                }
+            }
+        }
+    }
+
+    pub(crate) async fn send(&mut self, message: Value) -> Result<()> {
+        self.ws.send(Message::Text(message.to_string())).await?;
+        Ok(())
+    }
+}
+
+enum Wake {
+    Frame(Result<Option<Message>>),
+    Ping,
+    PongMissed,
+}
+
+/// Open a connection to `config.url` and subscribe to `channel`
+async fn connect(config: &WsConfig, channel: &str) -> Result<WsStream> {
+    let (mut ws, _) = tokio::time::timeout(config.connect_timeout, connect_async(config.url.as_str()))
+        .await
+        .map_err(|_| ApiError::ConnectTimeout(config.connect_timeout))??;
+    let subscribe = json!({"type": "subscribe", "channel": channel});
+    ws.send(Message::Text(subscribe.to_string())).await?;
+    Ok(ws)
+}
+
+/// Next frame, `None` once the stream has ended
+async fn read_frame(ws: &mut WsStream, idle_timeout: Option<Duration>) -> Result<Option<Message>> {
+    let frame = match idle_timeout {
+        Some(idle) => tokio::time::timeout(idle, ws.next())
+            .await
+            .map_err(|_| ApiError::WebSocketIdle(idle))?,
+        None => ws.next().await,
+    };
+    Ok(frame.transpose()?)
+}
+
+async fn tick(timer: &mut Option<Interval>) {
+    match timer {
+        Some(timer) => {
+            timer.tick().await;
+        }
+        None => future::pending().await,
+    }
+}
+
+async fn deadline(due: Option<Instant>) {
+    match due {
+        Some(due) => sleep_until(due).await,
+        None => future::pending().await,
+    }
+}
+
+/// An `order_book/{market}` subscription maintaining the local book
+struct BookFeed {
+    subscription: WsSubscription,
+    book: Arc<OrderBook>,
+    /// False until the first snapshot and again after a desync or reconnect
+    synced: bool,
+    check_consistency: bool,
+}
+
+impl BookFeed {
+    /// Read messages until the book changes, a desync is detected or the stream ends
+    async fn next_event(&mut self) -> Option<Result<BookEvent>> {
+        loop {
+            let message = match self.subscription.next().await? {
+                Ok(WsFrame::Json(message)) => message,
+                Ok(WsFrame::Reconnected) => {
+                    let market_index = self.book.market_index;
+                    self.book = Arc::new(OrderBook::new(market_index));
+                    self.synced = false;
+                    return Some(Ok(BookEvent::Reconnected { market_index }));
+                }
+                Err(e) => return Some(Err(e)),
+            };
+
+            let update = match BookUpdate::from_message(&message) {
+                Ok(Some(update)) => update,
+                Ok(None) if message["type"] == "error" => {
+                    return Some(Err(ApiError::Api(format!("WebSocket: {}", message["message"]))));
+                }
+                Ok(None) => continue,
+                // A book message whose levels cannot be read leaves the book unknown: resync
+                Err(e) => {
+                    let kind = if message["type"] == "subscribed/order_book" { "snapshot" } else { "update" };
+                    return Some(self.resync(format!("unreadable {}: {}", kind, e)).await);
+                }
+            };
+            // Deltas before the (re)subscription snapshot belong to a stale book
+            if !update.is_snapshot && !self.synced {
+                continue;
+            }
+            Arc::make_mut(&mut self.book).apply_update(&update);
+            self.synced = true;
+
+            if self.check_consistency {
+                if let Some(reason) = self.book.consistency_error() {
+                    return Some(self.resync(reason).await);
+                }
+            }
+            return Some(Ok(BookEvent::Updated(self.book.clone())));
+        }
+    }
+
+    /// Drop the local book and request a fresh snapshot
+    async fn resync(&mut self, reason: String) -> Result<BookEvent> {
+        let market_index = self.book.market_index;
+        self.book = Arc::new(OrderBook::new(market_index));
+        self.synced = false;
+        let channel = self.subscription.channel.clone();
+        self.subscription.send(json!({"type": "unsubscribe", "channel": channel})).await?;
+        self.subscription.send(json!({"type": "subscribe", "channel": channel})).await?;
+        Ok(BookEvent::DesyncDetected { market_index, reason })
+    }
+}
+
+impl LighterClient {
+    pub(crate) fn ws_config(&self) -> WsConfig {
+        WsConfig {
+            url: ws_url(&self.active_base_url()),
+            connect_timeout: self.connect_timeout,
+            idle_timeout: self.ws_idle_timeout,
+            heartbeat: self.ws_heartbeat,
+            frame_handler: self.ws_frame_handler.clone(),
+        }
+    }
+
+    async fn book_feed(&self, market_index: u8) -> Result<BookFeed> {
+        let channel = format!("order_book/{}", market_index);
+        Ok(BookFeed {
+            subscription: WsSubscription::open(self.ws_config(), channel).await?,
+            book: Arc::new(OrderBook::new(market_index)),
+            synced: false,
+            check_consistency: self.check_book_consistency,
+        })
+    }
+
+    /// Stream the order book of one market, maintained locally from the WebSocket feed
+    ///
+    /// Every snapshot and update yields the resulting [`OrderBook`]. Unless
+    /// disabled with `LighterClientBuilder::check_book_consistency`, each new
+    /// state is checked with [`OrderBook::consistency_error`]; a corrupt book is
+    /// discarded, a fresh snapshot is requested and [`BookEvent::DesyncDetected`]
+    /// is yielded instead. A missed heartbeat pong reconnects and yields
+    /// [`BookEvent::Reconnected`]. Messages of unknown types are skipped, and a
+    /// book message whose levels cannot be read is handled like a desync; see
+    /// `LighterClientBuilder::on_ws_frame` to inspect them. The stream ends when
+    /// the server closes the connection and after yielding the first error.
+    ///
+    /// With `depth`, each yielded book holds only the best `depth` levels per
+    /// side (see [`OrderBook::truncated`]), and updates that leave those levels
+    /// unchanged yield nothing. The limit is applied by the client: the server
+    /// always streams the full book, so it saves processing downstream but not
+    /// bandwidth. `None` yields the full book on every change.
+    pub async fn subscribe_order_book(
+        &self,
+        market_index: u8,
+        depth: Option<usize>,
+    ) -> Result<BoxStream<'static, Result<BookEvent>>> {
+        let feed = self.book_feed(market_index).await?;
+
+        Ok(stream::unfold(Some((feed, None)), move |state| async move {
+            let (mut feed, mut last) = state?;
+            loop {
+                match feed.next_event().await? {
+                    Ok(BookEvent::Updated(book)) => {
+                        let Some(depth) = depth else {
+                            return Some((Ok(BookEvent::Updated(book)), Some((feed, last))));
+                        };
+                        let view = book.truncated(depth);
+                        if last.as_ref() != Some(&view) {
+                            let view = Arc::new(view);
+                            last = Some(view.as_ref().clone());
+                            return Some((Ok(BookEvent::Updated(view)), Some((feed, last))));
+                        }
+                    }
+                    Ok(event) => return Some((Ok(event), Some((feed, None)))),
+                    Err(e) => return Some((Err(e), None)),
+                }
+            }
+        })
+        .boxed())
+    }
+
+    /// Stream best bid / best offer changes for one market
+    ///
+    /// The server has no top-of-book channel, so this subscribes to the order
+    /// book channel, maintains the book locally and yields a [`Bbo`] only when
+    /// the best price or size on either side changes. Updates deeper in the book
+    /// are absorbed without producing an item, and so are desyncs and heartbeat
+    /// reconnects: the book is rebuilt silently (use [`Self::subscribe_order_book`]
+    /// to observe them).
+    /// The stream ends when the server closes the connection and after yielding
+    /// the first error.
+    pub async fn subscribe_bbo(
+        &self,
+        market_index: u8,
+    ) -> Result<BoxStream<'static, Result<Bbo>>> {
+        let feed = self.book_feed(market_index).await?;
+
+        Ok(stream::unfold(Some((feed, None)), |state| async move {
+            let (mut feed, mut last) = state?;
+            loop {
+                match feed.next_event().await? {
+                    Ok(BookEvent::Updated(book)) => {
+                        let bbo = book.bbo();
+                        if last != Some(bbo) {
+                            last = Some(bbo);
+                            return Some((Ok(bbo), Some((feed, last))));
+                        }
+                    }
+                    Ok(BookEvent::DesyncDetected { .. } | BookEvent::Reconnected { .. }) => last = None,
+                    Err(e) => return Some((Err(e), None)),
+                }
+            }
+        })
+        .boxed())
+    }
+}
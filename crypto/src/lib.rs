@@ -40,7 +40,7 @@
 //!
 //! // Derive public key
 //! let public_key = Point::generator().mul(&private_key);
-//! let public_key_bytes = public_key.encode().to_bytes_le();
+//! let _public_key_bytes = public_key.encode().to_bytes_le();
 //!
 //! // Sign a message
 //! let message = [0u8; 40];
@@ -48,13 +48,17 @@
 //! let nonce_bytes = nonce.to_bytes_le();
 //! let signature = sign_with_nonce(&private_key_bytes, &message, &nonce_bytes).unwrap();
 //!
-//! // Verify signature
-//! let is_valid = verify_signature(&signature, &message, &public_key_bytes).unwrap();
+//! // Verify signature (verification currently re-derives the public point from the
+//! // private key bytes until encoded point decoding is implemented)
+//! let is_valid = verify_signature(&signature, &message, &private_key_bytes).unwrap();
 //! assert!(is_valid);
 //! ```
 //!
 //! [`poseidon-hash`]: https://crates.io/crates/poseidon-hash
 
+// Curve and scalar arithmetic is ported from lighter-go and keeps its index-based loops.
+#![allow(clippy::needless_range_loop)]
+
 pub mod schnorr;
 pub mod scalar_field;
 
@@ -63,7 +67,7 @@ pub use scalar_field::ScalarField;
 pub use poseidon_hash::{Goldilocks, Fp5Element};
 
 // Re-export Schnorr functions
-pub use schnorr::{sign_with_nonce, verify_signature, Point};
+pub use schnorr::{sign_with_nonce, sign_with_table, verify_signature, GeneratorTable, Point};
 
 use thiserror::Error;
 
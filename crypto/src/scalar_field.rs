@@ -10,7 +10,7 @@ use num_bigint::BigUint;
 /// # Example
 ///
 /// ```rust
-/// use crypto::ScalarField;
+/// use goldilocks_crypto::ScalarField;
 ///
 /// // Generate a random scalar (cryptographically secure)
 /// let scalar = ScalarField::sample_crypto();
@@ -141,7 +141,7 @@ impl ScalarField {
     /// # Example
     ///
     /// ```rust
-    /// use crypto::ScalarField;
+    /// use goldilocks_crypto::ScalarField;
     ///
     /// let a = ScalarField::ONE;
     /// let b = ScalarField::TWO;
@@ -200,15 +200,14 @@ impl ScalarField {
     /// # Example
     ///
     /// ```rust
-    /// use crypto::ScalarField;
+    /// use goldilocks_crypto::ScalarField;
     ///
     /// let a = ScalarField::TWO;
     /// let b = ScalarField::TWO;
     /// let product = a.mul(&b);
     /// ```
     pub fn mul(&self, rhs: &ScalarField) -> ScalarField {
-        let res = self.monty_mul(&Self::R2).monty_mul(rhs);
-        res
+        self.monty_mul(&Self::R2).monty_mul(rhs)
     }
     
     /// Computes the square of this scalar.
@@ -382,7 +381,7 @@ impl ScalarField {
     /// # Example
     ///
     /// ```rust
-    /// use crypto::ScalarField;
+    /// use goldilocks_crypto::ScalarField;
     ///
     /// let private_key = ScalarField::sample_crypto();
     /// ```
@@ -391,7 +390,7 @@ impl ScalarField {
         
         // Generate random big int in range [0, ORDER)
         // ORDER = 1067993516717146951041484916571792702745057740581727230159139685185762082554198619328292418486241
-        let order_bytes = hex::decode("e80fd996948bffe1e8885c39d724a09c7fffffe6cfb806397ffffff1000000167ffffffd80000007")
+        let order_bytes = hex::decode("7ffffffd800000077ffffff1000000167fffffe6cfb80639e8885c39d724a09ce80fd996948bffe1")
             .expect("invalid ORDER hex");
         
         let order_big = BigUint::from_bytes_be(&order_bytes);
@@ -462,3 +461,17 @@ impl fmt::Display for ScalarField {
                self.0[4], self.0[3], self.0[2], self.0[1], self.0[0])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ScalarField;
+
+    #[test]
+    fn test_sample_crypto_is_below_the_group_order() {
+        // Against a limb-reversed ORDER about 45% of samples were >= N
+        for _ in 0..256 {
+            let (_, borrow) = ScalarField::sample_crypto().sub_inner(&ScalarField::N);
+            assert_ne!(borrow, 0, "sampled scalar is not below N");
+        }
+    }
+}
@@ -53,7 +53,7 @@ const GENERATOR_ECG_FP5_POINT: Point = Point {
     ])
 };
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Scalar([u64; 4]);
 
 impl Scalar {
@@ -171,12 +171,6 @@ impl Scalar {
     }
 }
 
-impl Default for Scalar {
-    fn default() -> Self {
-        Scalar([0, 0, 0, 0])
-    }
-}
-
 #[derive(Debug, Clone, Copy)]
 pub struct AffinePoint {
     pub x: Fp5Element,
@@ -248,7 +242,7 @@ impl AffinePoint {
 /// # Example
 ///
 /// ```rust
-/// use crypto::{Point, ScalarField};
+/// use goldilocks_crypto::{Point, ScalarField};
 ///
 /// // Get the generator point
 /// let generator = Point::generator();
@@ -365,7 +359,7 @@ impl Point {
     /// # Example
     ///
     /// ```rust
-    /// use crypto::{Point, ScalarField};
+    /// use goldilocks_crypto::{Point, ScalarField};
     ///
     /// let generator = Point::generator();
     /// let scalar = ScalarField::sample_crypto();
@@ -378,7 +372,7 @@ impl Point {
         
         // Special case for scalar 1
         if scalar.0 == [1, 0, 0, 0, 0] {
-            return self.clone();
+            return *self;
         }
         
         // Windowed multiplication algorithm (optimized)
@@ -410,7 +404,7 @@ impl Point {
     pub fn make_window_affine(&self) -> Vec<AffinePoint> {
         const WIN_SIZE: usize = 16; // 2^(5-1)
         let mut tmp = vec![Point::neutral(); WIN_SIZE];
-        tmp[0] = self.clone();
+        tmp[0] = *self;
         
         for i in 1..WIN_SIZE {
             if i & 1 == 0 {
@@ -475,9 +469,9 @@ impl Point {
         if k == 0 {
             AffinePoint::neutral()
         } else if k > 0 {
-            win[k as usize - 1].clone()
+            win[k as usize - 1]
         } else {
-            let mut res = win[(-k) as usize - 1].clone();
+            let mut res = win[(-k) as usize - 1];
             res.set_neg();
             res
         }
@@ -493,7 +487,7 @@ impl Point {
     // Multiple doublings - optimized point multiplication by 2^n
     pub fn set_m_double(&self, n: u32) -> Point {
         if n == 0 {
-            return self.clone();
+            return *self;
         }
         if n == 1 {
             return self.double();
@@ -667,6 +661,68 @@ impl Point {
     }
 }
 
+/// Precomputed multiples of the generator point for fast `k * G`.
+///
+/// [`Point::mul`] builds a window of 16 multiples of its point and then runs
+/// five doublings per 5-bit digit of the scalar. For the fixed generator all of
+/// that can be done ahead of time: the table holds the window of `32^i * G`
+/// for every digit position `i`, so a multiplication is one constant-time
+/// lookup and one affine addition per digit, with no doublings.
+///
+/// Building the table costs roughly as much as a few dozen multiplications;
+/// [`Self::shared`] builds it once per process.
+///
+/// # Example
+///
+/// ```rust
+/// use goldilocks_crypto::{GeneratorTable, ScalarField};
+///
+/// let nonce = ScalarField::sample_crypto();
+/// let r = GeneratorTable::shared().mul(&nonce); // same point as Point::generator().mul(&nonce)
+/// ```
+pub struct GeneratorTable {
+    windows: Vec<Vec<AffinePoint>>,
+}
+
+impl GeneratorTable {
+    /// Window width of the signed digit recoding, as in [`Point::mul`]
+    const WINDOW: usize = 5;
+
+    /// Build the table
+    pub fn new() -> Self {
+        let digits = (319 + Self::WINDOW) / Self::WINDOW;
+        let mut windows = Vec::with_capacity(digits);
+        let mut base = Point::generator();
+        for _ in 0..digits {
+            windows.push(base.make_window_affine());
+            base = base.set_m_double(Self::WINDOW as u32);
+        }
+        GeneratorTable { windows }
+    }
+
+    /// The process-wide table, built on first use
+    pub fn shared() -> &'static GeneratorTable {
+        static TABLE: std::sync::OnceLock<GeneratorTable> = std::sync::OnceLock::new();
+        TABLE.get_or_init(GeneratorTable::new)
+    }
+
+    /// Compute `scalar * G`
+    pub fn mul(&self, scalar: &ScalarField) -> Point {
+        let digits = scalar.recode_signed(Self::WINDOW);
+        let mut result = Point::neutral();
+        for (win, &digit) in self.windows.iter().zip(&digits) {
+            result = result.add_affine(&Point::lookup(win, digit));
+        }
+        result
+    }
+}
+
+impl Default for GeneratorTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Signs a message using Schnorr signature scheme with a given nonce.
 /// 
 /// This function implements the Schnorr signature algorithm:
@@ -702,6 +758,27 @@ pub fn sign_with_nonce(private_key: &[u8], message: &[u8], nonce_bytes: &[u8]) -
     let nonce_scalar = ScalarField::from_bytes_le(&nonce_bytes_40)
         .map_err(|_| CryptoError::InvalidPrivateKeyLength(nonce_bytes.len()))?;
     
+    Ok(schnorr_sign(&private_scalar, message, &nonce_scalar, Point::generator().mul(&nonce_scalar)).to_vec())
+}
+
+/// Signs a message with a parsed private key, taking `nonce * G` from a [`GeneratorTable`].
+///
+/// Produces exactly the signature of [`sign_with_nonce`] for the same key,
+/// message and nonce, but computes R from the precomputed table instead of a
+/// fresh windowed multiplication, which is the bulk of the signing cost. Use
+/// [`GeneratorTable::shared`] to build the table once per process.
+///
+/// # Arguments
+/// * `table` - Multiples of the generator point
+/// * `private_key` - Private key scalar
+/// * `message` - Message to sign (typically 40 bytes, representing a hash)
+/// * `nonce` - Nonce scalar; must be fresh and random for every signature
+pub fn sign_with_table(table: &GeneratorTable, private_key: &ScalarField, message: &[u8], nonce: &ScalarField) -> [u8; 80] {
+    schnorr_sign(private_key, message, nonce, table.mul(nonce))
+}
+
+/// Schnorr signature (s || e) of `message` given R = nonce * G
+fn schnorr_sign(private_scalar: &ScalarField, message: &[u8], nonce_scalar: &ScalarField, r_point: Point) -> [u8; 80] {
     // Convert message to Fp5Element (quintic extension field element)
     let mut message_elements = [Goldilocks::zero(); 5];
     for (i, chunk) in message.chunks(8).enumerate().take(5) {
@@ -711,12 +788,9 @@ pub fn sign_with_nonce(private_key: &[u8], message: &[u8], nonce_bytes: &[u8]) -
     }
     let message_fp5 = Fp5Element(message_elements);
     
-    // Step 1: Compute R = nonce * generator_point
-    let generator = Point::generator();
-    let r_point = generator.mul(&nonce_scalar);
     let r_encoded = r_point.encode();
     
-    // Step 2: Compute challenge e = H(R || message)
+    // Compute challenge e = H(R || message)
     use poseidon_hash::hash_to_quintic_extension;
     let mut pre_image = [Goldilocks::zero(); 10];
     pre_image[..5].copy_from_slice(&r_encoded.0);
@@ -726,11 +800,11 @@ pub fn sign_with_nonce(private_key: &[u8], message: &[u8], nonce_bytes: &[u8]) -
     let e_fp5 = hash_to_quintic_extension(&pre_image);
     let e_scalar = ScalarField::from_fp5_element(&e_fp5);
     
-    // Step 3: Compute response s = nonce - e * private_key
-    let e_times_private = e_scalar.mul(&private_scalar);
+    // Compute response s = nonce - e * private_key
+    let e_times_private = e_scalar.mul(private_scalar);
     let s = nonce_scalar.sub(e_times_private);
     
-    // Step 4: Assemble signature as (s || e)
+    // Assemble signature as (s || e)
     let mut signature = [0u8; 80];
     let s_bytes = s.to_bytes_le();
     signature[..40].copy_from_slice(&s_bytes);
@@ -738,7 +812,7 @@ pub fn sign_with_nonce(private_key: &[u8], message: &[u8], nonce_bytes: &[u8]) -
     let e_bytes = e_scalar.to_bytes_le();
     signature[40..].copy_from_slice(&e_bytes);
     
-    Ok(signature.to_vec())
+    signature
 }
 
 /// Verifies a Schnorr signature.
@@ -760,7 +834,7 @@ pub fn sign_with_nonce(private_key: &[u8], message: &[u8], nonce_bytes: &[u8]) -
 /// # Example
 ///
 /// ```rust
-/// use crypto::{sign_with_nonce, verify_signature, ScalarField};
+/// use goldilocks_crypto::{sign_with_nonce, verify_signature, ScalarField};
 ///
 /// let private_key = ScalarField::sample_crypto();
 /// let private_key_bytes = private_key.to_bytes_le();
@@ -856,7 +930,7 @@ fn monty_mul(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
     
     let mut temp = 0u128;
     for i in 0..4 {
-        temp += (q as u128) * (N[i] as u128) + (result[i] as u128);
+        temp += q * (N[i] as u128) + (result[i] as u128);
         if i < 3 {
             result[i] = (temp & 0xFFFFFFFFFFFFFFFF) as u64;
             temp >>= 64;
@@ -922,3 +996,27 @@ impl PartialEq for Scalar {
 }
 
 impl Eq for Scalar {}
+
+#[cfg(test)]
+mod tests {
+    use super::{sign_with_nonce, sign_with_table, GeneratorTable, Point};
+    use crate::ScalarField;
+
+    #[test]
+    fn test_generator_table_matches_cold_signing() {
+        // Limbs may hold p + x instead of x, so compare reduced encodings
+        let canonical = |point: Point| point.encode().0.map(|limb| limb.to_canonical_u64());
+        let table = GeneratorTable::shared();
+        for scalar in [ScalarField::ZERO, ScalarField::ONE, ScalarField::sample_crypto(), ScalarField::sample_crypto()] {
+            assert_eq!(canonical(table.mul(&scalar)), canonical(Point::generator().mul(&scalar)));
+        }
+
+        let private_key = ScalarField::sample_crypto();
+        let nonce = ScalarField::sample_crypto();
+        let message: [u8; 40] = std::array::from_fn(|i| i as u8);
+        assert_eq!(
+            sign_with_table(table, &private_key, &message, &nonce).to_vec(),
+            sign_with_nonce(&private_key.to_bytes_le(), &message, &nonce.to_bytes_le()).unwrap()
+        );
+    }
+}
@@ -24,7 +24,7 @@ fn main() {
         Goldilocks::from_canonical_u64(2),
         Goldilocks::from_canonical_u64(3),
     ];
-    let hash = hash_to_quintic_extension(&elements);
+    let _hash = hash_to_quintic_extension(&elements);
     println!("Poseidon2 hash computed successfully");
     
     println!("All poseidon-hash exports work correctly!");
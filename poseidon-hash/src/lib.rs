@@ -47,6 +47,9 @@
 //! let hash = hash_to_quintic_extension(&elements);
 //! ```
 
+// Field arithmetic is ported from lighter-go and keeps its index-based loops.
+#![allow(clippy::needless_range_loop)]
+
 /// Goldilocks field element.
 ///
 /// The Goldilocks field uses prime modulus p = 2^64 - 2^32 + 1, which is optimized for:
@@ -1,6 +1,7 @@
-use goldilocks_crypto::{schnorr::{sign_with_nonce}, ScalarField, Goldilocks};
+use goldilocks_crypto::{schnorr::{sign_with_nonce}, sign_with_table, GeneratorTable, ScalarField, Goldilocks};
+use std::fmt;
 use thiserror::Error;
-use hex;
+use zeroize::{Zeroize, Zeroizing};
 
 #[derive(Error, Debug)]
 pub enum SignerError {
@@ -12,12 +13,46 @@ pub enum SignerError {
     SystemTime(#[from] std::time::SystemTimeError),
     #[error("Hex decode error: {0}")]
     HexDecode(#[from] hex::FromHexError),
+    // Reports only the offset so a mistyped key never leaks a character into logs
+    #[error("Invalid hex character in private key at position {0}")]
+    InvalidKeyHex(usize),
 }
 
 pub type Result<T> = std::result::Result<T, SignerError>;
 
+/// Version of the signature scheme implemented by [`KeyManager::sign`]
+///
+/// Schnorr signatures over ECgFp5 of Poseidon2 (Goldilocks) message hashes.
+/// Bumped whenever the exchange changes how transactions must be signed.
+pub const SCHEME_VERSION: u32 = 1;
+
+/// The API private key scalar, wiped on drop
+///
+/// Not `Copy`, unlike `ScalarField`: every copy of the key is one of these and
+/// wipes itself, so dropping the signers leaves no key limbs behind.
+#[derive(Clone)]
+struct PrivateKey(ScalarField);
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        self.0 .0.zeroize();
+    }
+}
+
+/// Holds the API private key used for signing.
+///
+/// The key limbs are wiped when the manager is dropped, and `Debug` output never
+/// includes the key.
 pub struct KeyManager {
-    private_key: ScalarField,
+    private_key: PrivateKey,
+}
+
+impl fmt::Debug for KeyManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyManager")
+            .field("private_key", &"<redacted>")
+            .finish()
+    }
 }
 
 impl KeyManager {
@@ -28,24 +63,22 @@ impl KeyManager {
         // Use all 40 bytes for 5-limb scalar
         let private_key = ScalarField::from_bytes_le(private_key_bytes)
             .map_err(|_| SignerError::Crypto(goldilocks_crypto::CryptoError::InvalidPrivateKeyLength(private_key_bytes.len())))?;
-        Ok(Self { private_key })
+        Ok(Self { private_key: PrivateKey(private_key) })
     }
     
         pub fn from_hex(hex_str: &str) -> Result<Self> {
-        let hex_str = if hex_str.starts_with("0x") {
-            &hex_str[2..]
-        } else {
-            hex_str
-        };
+        let hex_str = hex_str.strip_prefix("0x").unwrap_or(hex_str);
         
-        let bytes = hex::decode(hex_str)?;
+        let bytes = Zeroizing::new(hex::decode(hex_str).map_err(|e| match e {
+            hex::FromHexError::InvalidHexCharacter { index, .. } => SignerError::InvalidKeyHex(index),
+            other => SignerError::HexDecode(other),
+        })?);
         Self::new(&bytes)
     }
 
     /// Generate a new random key pair
     pub fn generate() -> Self {
-        let random_scalar = ScalarField::sample_crypto();
-        Self { private_key: random_scalar }
+        Self { private_key: PrivateKey(ScalarField::sample_crypto()) }
     }
 
     /// Get the public key as bytes (40 bytes)
@@ -53,14 +86,24 @@ impl KeyManager {
         use goldilocks_crypto::schnorr::Point;
         // Public key = generator * private_key, encoded as Fp5Element
         let generator = Point::generator();
-        let public_point = generator.mul(&self.private_key);
+        let public_point = generator.mul(&self.private_key.0);
         let public_fp5 = public_point.encode();
         public_fp5.to_bytes_le()
     }
 
     /// Get the private key as bytes (40 bytes)
     pub fn private_key_bytes(&self) -> [u8; 40] {
-        self.private_key.to_bytes_le()
+        self.private_key.0.to_bytes_le()
+    }
+
+    /// A signer for this key that reuses precomputed generator multiples
+    ///
+    /// Signs exactly like this manager, several times faster; see [`PreparedSigner`].
+    pub fn prepare(&self) -> PreparedSigner {
+        PreparedSigner {
+            private_key: self.private_key.clone(),
+            table: GeneratorTable::shared(),
+        }
     }
 
     pub fn sign(&self, message: &[u8; 40]) -> Result<[u8; 80]> {
@@ -69,9 +112,14 @@ impl KeyManager {
         self.sign_with_fixed_nonce(message, &nonce_bytes)
     }
     
-    fn sign_with_fixed_nonce(&self, message: &[u8; 40], nonce_bytes: &[u8]) -> Result<[u8; 80]> {
-        let pk_bytes = self.private_key.to_bytes_le();
-        let signature = sign_with_nonce(&pk_bytes, message, nonce_bytes)?;
+    /// Sign with a caller-chosen nonce scalar (40 bytes, little-endian), deterministically
+    ///
+    /// For reproducible test vectors only: [`Self::sign`] draws a fresh random
+    /// nonce for each signature, and signing two messages with the same nonce
+    /// reveals the private key.
+    pub fn sign_with_fixed_nonce(&self, message: &[u8; 40], nonce_bytes: &[u8; 40]) -> Result<[u8; 80]> {
+        let pk_bytes = Zeroizing::new(self.private_key.0.to_bytes_le());
+        let signature = sign_with_nonce(&pk_bytes[..], message, nonce_bytes)?;
         
         let mut result = [0u8; 80];
         result.copy_from_slice(&signature);
@@ -126,8 +174,96 @@ impl KeyManager {
         
         // Sign the hash
         let signature = self.sign(&message_bytes)?;
-        let signature_hex = hex::encode(&signature);
+        let signature_hex = hex::encode(signature);
         
         Ok(format!("{}:{}", auth_data, signature_hex))
     }
 }
+
+/// Signs with the API private key using precomputed multiples of the generator.
+///
+/// Most of a cold [`KeyManager::sign`] is spent computing `nonce * G`. The
+/// generator never changes, so a prepared signer takes that product from the
+/// process-wide [`GeneratorTable`] (built on the first [`KeyManager::prepare`])
+/// and keeps the key parsed, which makes each signature several times cheaper.
+/// Signatures are identical to the manager's for the same nonce; a fresh
+/// random nonce is still drawn for every [`Self::sign`].
+///
+/// Like [`KeyManager`], the key limbs are wiped on drop and never printed.
+pub struct PreparedSigner {
+    private_key: PrivateKey,
+    table: &'static GeneratorTable,
+}
+
+impl fmt::Debug for PreparedSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PreparedSigner")
+            .field("private_key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl PreparedSigner {
+    pub fn sign(&self, message: &[u8; 40]) -> Result<[u8; 80]> {
+        let nonce = ScalarField::sample_crypto();
+        Ok(sign_with_table(self.table, &self.private_key.0, message, &nonce))
+    }
+
+    /// Sign with a caller-chosen nonce scalar (40 bytes, little-endian), deterministically
+    ///
+    /// For reproducible test vectors only, see [`KeyManager::sign_with_fixed_nonce`].
+    pub fn sign_with_fixed_nonce(&self, message: &[u8; 40], nonce_bytes: &[u8; 40]) -> Result<[u8; 80]> {
+        let nonce = ScalarField::from_bytes_le(nonce_bytes)
+            .map_err(|_| SignerError::Crypto(goldilocks_crypto::CryptoError::InvalidPrivateKeyLength(nonce_bytes.len())))?;
+        Ok(sign_with_table(self.table, &self.private_key.0, message, &nonce))
+    }
+}
+
+// Signers are shared across threads behind an `Arc`; fail the build if one stops being Send + Sync
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<KeyManager>();
+    assert_send_sync::<PreparedSigner>();
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::mem::ManuallyDrop;
+
+    const KEY_HEX: &str = "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f200000000000000000";
+
+    #[test]
+    fn test_debug_never_prints_the_key() {
+        let key_manager = KeyManager::from_hex(KEY_HEX).unwrap();
+        let prepared = key_manager.prepare();
+        let limbs = format!("{:?}", key_manager.private_key.0 .0);
+
+        for debug in [format!("{:?}", key_manager), format!("{:?}", prepared)] {
+            assert!(debug.contains("<redacted>"), "{}", debug);
+            assert!(!debug.contains(&KEY_HEX[..16]) && !debug.contains(&limbs), "{}", debug);
+        }
+    }
+
+    #[test]
+    fn test_dropping_a_key_wipes_its_limbs() {
+        let mut key = ManuallyDrop::new(PrivateKey(ScalarField::sample_crypto()));
+        assert_ne!(key.0 .0, [0; 5]);
+        // SAFETY: the limbs are plain integers, so reading them after the drop ran reads initialized memory
+        unsafe { ManuallyDrop::drop(&mut key) };
+        assert_eq!(key.0 .0, [0; 5]);
+    }
+
+    #[test]
+    fn test_prepared_signer_owns_its_copy_of_the_key() {
+        let key_manager = KeyManager::from_hex(KEY_HEX).unwrap();
+        let prepared = key_manager.prepare();
+        let message = [7; 40];
+        let nonce = ScalarField::sample_crypto().to_bytes_le();
+        let expected = key_manager.sign_with_fixed_nonce(&message, &nonce).unwrap();
+
+        // Wiping the manager's key leaves the prepared signer's untouched
+        drop(key_manager);
+        assert_eq!(prepared.sign_with_fixed_nonce(&message, &nonce).unwrap(), expected);
+    }
+}